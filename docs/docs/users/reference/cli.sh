@@ -34,6 +34,14 @@ generate_markdown_section "forest-wallet" "validate-address"
 generate_markdown_section "forest-wallet" "verify"
 generate_markdown_section "forest-wallet" "delete"
 generate_markdown_section "forest-wallet" "send"
+generate_markdown_section "forest-wallet" "msig"
+generate_markdown_section "forest-wallet" "msig create"
+generate_markdown_section "forest-wallet" "msig propose"
+generate_markdown_section "forest-wallet" "msig approve"
+generate_markdown_section "forest-wallet" "msig cancel"
+generate_markdown_section "forest-wallet" "msig swap-propose"
+generate_markdown_section "forest-wallet" "msig swap-approve"
+generate_markdown_section "forest-wallet" "msig swap-cancel"
 
 generate_markdown_section "forest-cli"
 
@@ -42,9 +50,14 @@ generate_markdown_section "forest-cli" "chain block"
 generate_markdown_section "forest-cli" "chain message"
 generate_markdown_section "forest-cli" "chain read-obj"
 generate_markdown_section "forest-cli" "chain set-head"
+generate_markdown_section "forest-cli" "chain prune"
+generate_markdown_section "forest-cli" "chain pin"
+generate_markdown_section "forest-cli" "chain unpin"
+generate_markdown_section "forest-cli" "chain pinned"
 
 generate_markdown_section "forest-cli" "auth"
 generate_markdown_section "forest-cli" "auth create-token"
+generate_markdown_section "forest-cli" "auth revoke"
 generate_markdown_section "forest-cli" "auth api-info"
 
 generate_markdown_section "forest-cli" "net"
@@ -60,6 +73,11 @@ generate_markdown_section "forest-cli" "sync mark-bad"
 generate_markdown_section "forest-cli" "mpool"
 generate_markdown_section "forest-cli" "mpool pending"
 generate_markdown_section "forest-cli" "mpool stat"
+generate_markdown_section "forest-cli" "mpool replace"
+generate_markdown_section "forest-cli" "mpool find-stuck"
+generate_markdown_section "forest-cli" "mpool config"
+generate_markdown_section "forest-cli" "mpool config get"
+generate_markdown_section "forest-cli" "mpool config set"
 
 generate_markdown_section "forest-cli" "state"
 generate_markdown_section "forest-cli" "state fetch"
@@ -67,9 +85,26 @@ generate_markdown_section "forest-cli" "state compute"
 
 generate_markdown_section "forest-cli" "config"
 
+generate_markdown_section "forest-cli" "log"
+generate_markdown_section "forest-cli" "log list"
+generate_markdown_section "forest-cli" "log set-level"
+
 generate_markdown_section "forest-cli" "snapshot"
 generate_markdown_section "forest-cli" "snapshot export"
 
+generate_markdown_section "forest-cli" "paych"
+generate_markdown_section "forest-cli" "paych add-funds"
+generate_markdown_section "forest-cli" "paych list"
+generate_markdown_section "forest-cli" "paych status"
+generate_markdown_section "forest-cli" "paych voucher"
+generate_markdown_section "forest-cli" "paych voucher create"
+generate_markdown_section "forest-cli" "paych voucher check"
+generate_markdown_section "forest-cli" "paych voucher add"
+generate_markdown_section "forest-cli" "paych voucher list"
+generate_markdown_section "forest-cli" "paych voucher submit"
+generate_markdown_section "forest-cli" "paych settle"
+generate_markdown_section "forest-cli" "paych collect"
+
 generate_markdown_section "forest-cli" "send"
 generate_markdown_section "forest-cli" "info"
 generate_markdown_section "forest-cli" "shutdown"
@@ -137,6 +172,12 @@ generate_markdown_section "forest-tool" "api test"
 
 generate_markdown_section "forest-tool" "net ping"
 
+generate_markdown_section "forest-tool" "chain-spec"
+generate_markdown_section "forest-tool" "chain-spec dump"
+
+generate_markdown_section "forest-tool" "genesis"
+generate_markdown_section "forest-tool" "genesis create"
+
 generate_markdown_section "forest-tool" "shed"
 generate_markdown_section "forest-tool" "shed summarize-tipsets"
 generate_markdown_section "forest-tool" "shed peer-id-from-key-pair"
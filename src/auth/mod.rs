@@ -1,11 +1,13 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use crate::key_management::KeyInfo;
+use crate::db::{SettingsExt as _, SettingsStore};
+use crate::key_management::{KeyInfo, KeyStore};
 use crate::shim::crypto::SignatureType;
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, errors::Result as JWTResult, DecodingKey, EncodingKey, Header};
 use rand::Rng;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -38,28 +40,124 @@ pub enum Error {
 
 /// Claim structure for JWT Tokens
 #[derive(Debug, Serialize, Deserialize)]
-struct Claims {
+pub struct Claims {
     #[serde(rename = "Allow")]
-    allow: Vec<String>,
+    pub allow: Vec<String>,
     // Expiration time (as UTC timestamp)
-    exp: usize,
+    pub exp: usize,
+    /// Client the token was issued to, used to apply per-client RPC limits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    /// Token ID, used to revoke the token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    /// Methods the token is restricted to, see [`method_matches`]. Empty means all methods.
+    #[serde(
+        rename = "AllowMethods",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub allow_methods: Vec<String>,
+    /// Methods the token can't call, even if allowed by `allow_methods`
+    #[serde(rename = "DenyMethods", default, skip_serializing_if = "Vec::is_empty")]
+    pub deny_methods: Vec<String>,
 }
 
-/// Create a new JWT Token
+impl Claims {
+    /// Returns whether the method lists of the token allow calling a method, known by any of `names`
+    pub fn allows_method<'a>(&self, names: impl IntoIterator<Item = &'a str> + Clone) -> bool {
+        let any_match = |patterns: &[String]| {
+            names
+                .clone()
+                .into_iter()
+                .any(|name| patterns.iter().any(|pattern| method_matches(pattern, name)))
+        };
+        (self.allow_methods.is_empty() || any_match(&self.allow_methods))
+            && !any_match(&self.deny_methods)
+    }
+}
+
+/// Restrictions applied to a JWT Token on top of its permissions
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct TokenScope {
+    /// Token ID, generated if not set
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Client the token is issued to, defaults to the token ID
+    #[serde(default)]
+    pub subject: Option<String>,
+    /// Methods the token is restricted to
+    #[serde(default)]
+    pub allow_methods: Vec<String>,
+    /// Methods the token can't call
+    #[serde(default)]
+    pub deny_methods: Vec<String>,
+}
+
+/// Returns whether a method name matches a pattern of a token method list. A pattern is either a
+/// method name, e.g. `Filecoin.StateWaitMsg`, or a prefix followed by `*`, e.g. `Filecoin.Eth*`.
+pub fn method_matches(pattern: &str, method: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => method.starts_with(prefix),
+        None => pattern == method,
+    }
+}
+
+/// Create a new JWT Token, see [`create_scoped_token`]
 pub fn create_token(perms: Vec<String>, key: &[u8], token_exp: Duration) -> JWTResult<String> {
+    create_scoped_token(perms, TokenScope::default(), key, token_exp)
+}
+
+/// Create a new JWT Token restricted by `scope`. Tokens always have an ID so that they can be
+/// revoked with [`SettingsExt::revoke_token`](crate::db::SettingsExt::revoke_token), and a subject, which defaults to the ID, so that the RPC limits
+/// apply to each client separately.
+pub fn create_scoped_token(
+    perms: Vec<String>,
+    scope: TokenScope,
+    key: &[u8],
+    token_exp: Duration,
+) -> JWTResult<String> {
     let exp_time = Utc::now() + token_exp;
+    let id = scope
+        .id
+        .unwrap_or_else(|| crate::utils::rand::new_uuid_v4().simple().to_string());
     let payload = Claims {
         allow: perms,
         exp: exp_time.timestamp() as usize,
+        sub: Some(scope.subject.unwrap_or_else(|| id.clone())),
+        jti: Some(id),
+        allow_methods: scope.allow_methods,
+        deny_methods: scope.deny_methods,
     };
     encode(&Header::default(), &payload, &EncodingKey::from_secret(key))
 }
 
 /// Verify JWT Token and return the allowed permissions from token
 pub fn verify_token(token: &str, key: &[u8]) -> JWTResult<Vec<String>> {
+    Ok(verify_token_claims(token, key)?.allow)
+}
+
+/// Verify JWT Token and return its claims
+pub fn verify_token_claims(token: &str, key: &[u8]) -> JWTResult<Claims> {
     let validation = jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::default());
     let token = decode::<Claims>(token, &DecodingKey::from_secret(key), &validation)?;
-    Ok(token.claims.allow)
+    Ok(token.claims)
+}
+
+/// Verify JWT Token with the secret of the `KeyStore` and return its claims. Fails if the token was
+/// revoked in the settings store.
+pub fn verify_keystore_token(
+    keystore: &KeyStore,
+    settings: &(impl SettingsStore + ?Sized),
+    token: &str,
+) -> anyhow::Result<Claims> {
+    let ki = keystore.get(JWT_IDENTIFIER)?;
+    let claims = verify_token_claims(token, ki.private_key())?;
+    if let Some(id) = &claims.jti {
+        anyhow::ensure!(!settings.is_token_revoked(id)?, "token {id} was revoked");
+    }
+    Ok(claims)
 }
 
 pub fn generate_priv_key() -> KeyInfo {
@@ -91,6 +189,11 @@ mod tests {
         let perms = verify_token(&token, key.private_key()).unwrap();
         assert_eq!(perms_expected, perms);
 
+        // The subject of the token defaults to its ID.
+        let claims = verify_token_claims(&token, key.private_key()).unwrap();
+        assert!(claims.jti.is_some());
+        assert_eq!(claims.sub, claims.jti);
+
         // Token duration of -1 hour (already expired). Validation must fail.
         let token = create_token(
             perms_expected.clone(),
@@ -111,4 +214,68 @@ mod tests {
         let perms = verify_token(&token, key.private_key()).unwrap();
         assert_eq!(perms_expected, perms);
     }
+
+    #[test]
+    fn method_lists() {
+        let claims = Claims {
+            allow: vec!["read".into()],
+            exp: 0,
+            sub: None,
+            jti: None,
+            allow_methods: vec!["Filecoin.Eth*".into(), "Filecoin.StateWaitMsg".into()],
+            deny_methods: vec!["Filecoin.EthSendRawTransaction".into()],
+        };
+        assert!(claims.allows_method(["Filecoin.EthCall"]));
+        assert!(claims.allows_method(["eth_call", "Filecoin.EthCall"]));
+        assert!(claims.allows_method(["Filecoin.StateWaitMsg"]));
+        assert!(!claims.allows_method(["Filecoin.StateWaitMsgLimited"]));
+        assert!(!claims.allows_method(["Filecoin.ChainHead"]));
+        assert!(!claims.allows_method(["Filecoin.EthSendRawTransaction"]));
+        assert!(!claims.allows_method(["eth_sendRawTransaction", "Filecoin.EthSendRawTransaction"]));
+
+        let claims = Claims {
+            allow_methods: vec![],
+            ..claims
+        };
+        assert!(claims.allows_method(["Filecoin.ChainHead"]));
+        assert!(!claims.allows_method(["Filecoin.EthSendRawTransaction"]));
+    }
+
+    #[test]
+    fn revoke_scoped_token() {
+        let mut keystore = KeyStore::new(crate::KeyStoreConfig::Memory).unwrap();
+        keystore.put(JWT_IDENTIFIER, generate_priv_key()).unwrap();
+        let settings = crate::db::MemoryDB::default();
+        let key = keystore.get(JWT_IDENTIFIER).unwrap();
+        let scope = TokenScope {
+            subject: Some("partner".into()),
+            allow_methods: vec!["Filecoin.Eth*".into()],
+            ..Default::default()
+        };
+        let token = create_scoped_token(
+            vec!["read".into()],
+            scope.clone(),
+            key.private_key(),
+            Duration::try_hours(1).expect("Infallible"),
+        )
+        .unwrap();
+        let other = create_scoped_token(
+            vec!["read".into()],
+            scope,
+            key.private_key(),
+            Duration::try_hours(1).expect("Infallible"),
+        )
+        .unwrap();
+
+        let claims = verify_keystore_token(&keystore, &settings, &token).unwrap();
+        assert_eq!(claims.sub.as_deref(), Some("partner"));
+        assert_eq!(claims.allow_methods, ["Filecoin.Eth*"]);
+        let id = claims.jti.unwrap();
+
+        assert!(settings.revoke_token(&id).unwrap());
+        assert!(!settings.revoke_token(&id).unwrap());
+        assert!(verify_keystore_token(&keystore, &settings, &token).is_err());
+        // Tokens have distinct IDs
+        verify_keystore_token(&keystore, &settings, &other).unwrap();
+    }
 }
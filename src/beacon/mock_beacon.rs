@@ -8,6 +8,8 @@ use crate::utils::encoding::blake2b_256;
 use async_trait::async_trait;
 use byteorder::{BigEndian, ByteOrder};
 
+/// Beacon whose entries are derived from their round, for networks that do not use `drand`
+/// such as solo devnets.
 #[derive(Default)]
 pub struct MockBeacon {}
 
@@ -7,7 +7,6 @@ pub mod signatures;
 pub use beacon_entries::*;
 pub use drand::*;
 
-#[cfg(test)]
 pub mod mock_beacon;
 #[cfg(test)]
 mod tests {
@@ -4,35 +4,50 @@ pub mod store;
 mod weight;
 use crate::blocks::Tipset;
 use crate::cid_collections::CidHashSet;
-use crate::db::car::forest;
+use crate::db::car::{carv2, forest};
 use crate::ipld::stream_chain;
+use crate::utils::db::car_stream::CarBlock;
 use crate::utils::io::{AsyncWriterWithChecksum, Checksum};
 use crate::utils::stream::par_buffer;
 use anyhow::Context as _;
 use digest::Digest;
 use fvm_ipld_blockstore::Blockstore;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
+use tokio::io::{AsyncSeek, AsyncWrite, AsyncWriteExt, BufWriter};
 
 pub use self::{store::*, weight::*};
 
-pub async fn export<D: Digest>(
+/// Archive format of exported snapshots.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, clap::ValueEnum,
+)]
+pub enum ExportFormat {
+    /// `.forest.car.zst`, a zstd compressed CARv1 with a skippable index.
+    #[default]
+    ForestCar,
+    /// `.car`, an uncompressed CARv2 with a `MultihashIndexSorted` index.
+    CarV2,
+}
+
+impl ExportFormat {
+    /// Whether the snapshot file name should have the `.forest.car.zst` extension.
+    pub fn is_forest_car(&self) -> bool {
+        matches!(self, ExportFormat::ForestCar)
+    }
+}
+
+/// Stream state-roots in range `(stateroot_lookup_limit+1)..=tipset.epoch()`, as well as all
+/// block headers until genesis.
+fn export_blocks(
     db: Arc<impl Blockstore + Send + Sync + 'static>,
     tipset: &Tipset,
     lookup_depth: ChainEpochDelta,
-    writer: impl AsyncWrite + Unpin,
     seen: CidHashSet,
-    skip_checksum: bool,
-) -> anyhow::Result<Option<digest::Output<D>>, Error> {
+) -> impl futures::TryStream<Ok = CarBlock, Error = anyhow::Error> + Unpin {
     let stateroot_lookup_limit = tipset.epoch() - lookup_depth;
-    let roots = tipset.key().to_cids();
-
-    // Wrap writer in optional checksum calculator
-    let mut writer = AsyncWriterWithChecksum::<D, _>::new(BufWriter::new(writer), !skip_checksum);
-
-    // Stream stateroots in range (stateroot_lookup_limit+1)..=tipset.epoch(). Also
-    // stream all block headers until genesis.
-    let blocks = par_buffer(
+    par_buffer(
         // Queue 1k blocks. This is enough to saturate the compressor and blocks
         // are small enough that keeping 1k in memory isn't a problem. Average
         // block size is between 1kb and 2kb.
@@ -43,7 +58,23 @@ pub async fn export<D: Digest>(
             stateroot_lookup_limit,
         )
         .with_seen(seen),
-    );
+    )
+}
+
+pub async fn export<D: Digest>(
+    db: Arc<impl Blockstore + Send + Sync + 'static>,
+    tipset: &Tipset,
+    lookup_depth: ChainEpochDelta,
+    writer: impl AsyncWrite + Unpin,
+    seen: CidHashSet,
+    skip_checksum: bool,
+) -> anyhow::Result<Option<digest::Output<D>>, Error> {
+    let roots = tipset.key().to_cids();
+
+    // Wrap writer in optional checksum calculator
+    let mut writer = AsyncWriterWithChecksum::<D, _>::new(BufWriter::new(writer), !skip_checksum);
+
+    let blocks = export_blocks(db, tipset, lookup_depth, seen);
 
     // Encode Ipld key-value pairs in zstd frames
     let frames = forest::Encoder::compress_stream_default(blocks);
@@ -58,3 +89,20 @@ pub async fn export<D: Digest>(
 
     Ok(digest)
 }
+
+/// Exports the chain as a CARv2 archive with a `MultihashIndexSorted` index. Unlike [`export`],
+/// the writer has to be seekable, as the CARv2 header is written last.
+pub async fn export_car_v2(
+    db: Arc<impl Blockstore + Send + Sync + 'static>,
+    tipset: &Tipset,
+    lookup_depth: ChainEpochDelta,
+    writer: impl AsyncWrite + AsyncSeek + Unpin,
+    seen: CidHashSet,
+) -> anyhow::Result<()> {
+    let roots = tipset.key().to_cids();
+    let mut writer = BufWriter::new(writer);
+    let blocks = export_blocks(db, tipset, lookup_depth, seen);
+    carv2::Encoder::write(&mut writer, roots, blocks).await?;
+    writer.flush().await.context("failed to flush")?;
+    Ok(())
+}
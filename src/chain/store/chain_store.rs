@@ -25,7 +25,7 @@ use crate::{
 };
 use crate::{
     chain_sync::metrics,
-    db::{EthMappingsStore, EthMappingsStoreExt},
+    db::{EthMappingsStore, EthMappingsStoreExt, HeightIndexStore},
 };
 use ahash::{HashMap, HashMapExt, HashSet};
 use anyhow::Context as _;
@@ -140,6 +140,17 @@ where
         Ok(cs)
     }
 
+    /// Persists a height index of the canonical chain in `store`, so that tipsets can be
+    /// looked up by height without walking the chain. See [`ChainIndex::with_height_index`].
+    pub fn with_height_index(
+        mut self,
+        store: Arc<dyn HeightIndexStore + Sync + Send>,
+    ) -> anyhow::Result<Self> {
+        self.chain_index =
+            Arc::new(ChainIndex::new(Arc::clone(&self.db)).with_height_index(store)?);
+        Ok(self)
+    }
+
     /// Sets heaviest tipset
     pub fn set_heaviest_tipset(&self, ts: Arc<Tipset>) -> Result<(), Error> {
         metrics::HEAD_EPOCH.set(ts.epoch());
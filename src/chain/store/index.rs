@@ -5,6 +5,7 @@ use std::{num::NonZeroUsize, sync::Arc};
 
 use crate::beacon::{BeaconEntry, IGNORE_DRAND_VAR};
 use crate::blocks::{Tipset, TipsetKey};
+use crate::db::HeightIndexStore;
 use crate::metrics;
 use crate::shim::clock::ChainEpoch;
 use crate::utils::misc::env::is_env_truthy;
@@ -13,11 +14,21 @@ use itertools::Itertools;
 use lru::LruCache;
 use nonzero_ext::nonzero;
 use parking_lot::Mutex;
+use serde_tuple::{Deserialize_tuple, Serialize_tuple};
 
 use crate::chain::Error;
 
 const DEFAULT_TIPSET_CACHE_SIZE: NonZeroUsize = nonzero!(131072_usize);
 
+/// Interval, in epochs, between two checkpoints of the [`HeightIndex`].
+const HEIGHT_INDEX_SKIP_LENGTH: ChainEpoch = 20;
+
+/// Maximum number of tipsets walked back from a new head to connect it to the
+/// [`HeightIndex`]. Older tipsets are indexed by [`ChainIndex::backfill_height_index`].
+const HEIGHT_INDEX_MAX_HEAD_WALK: usize = 2000;
+
+const HEIGHT_INDEX_RANGE_KEY: &[u8] = b"range";
+
 type TipsetCache = Mutex<LruCache<TipsetKey, Arc<Tipset>>>;
 
 /// Keeps look-back tipsets in cache at a given interval `skip_length` and can
@@ -26,10 +37,100 @@ pub struct ChainIndex<DB> {
     /// `Arc` reference tipset cache.
     ts_cache: TipsetCache,
 
+    /// Optional persisted checkpoints used to speed up [`ChainIndex::tipset_by_height`].
+    height_index: Option<HeightIndex>,
+
     /// `Blockstore` pointer needed to load tipsets from cold storage.
     pub db: DB,
 }
 
+/// Persisted skip-list of the canonical chain, used to look up old tipsets by
+/// height without walking the whole chain.
+///
+/// Every epoch that is a multiple of [`HEIGHT_INDEX_SKIP_LENGTH`] is a checkpoint,
+/// which maps to the key of the lowest tipset at or above that epoch. Checkpoints
+/// are only trusted within the indexed range, which always describes a single
+/// chain ending at the last head. Stale entries of abandoned forks, or from before
+/// the range was reset, are thus ignored and eventually overwritten.
+struct HeightIndex {
+    store: Arc<dyn HeightIndexStore + Sync + Send>,
+    /// Cached indexed range. The lock also serializes updates of the index.
+    range: Mutex<Option<IndexedRange>>,
+}
+
+/// Inclusive range of indexed checkpoint epochs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+struct IndexedRange {
+    min: ChainEpoch,
+    max: ChainEpoch,
+}
+
+impl IndexedRange {
+    fn contains(&self, epoch: ChainEpoch) -> bool {
+        self.min <= epoch && epoch <= self.max
+    }
+}
+
+impl HeightIndex {
+    fn new(store: Arc<dyn HeightIndexStore + Sync + Send>) -> anyhow::Result<Self> {
+        let range = store
+            .read_bin(HEIGHT_INDEX_RANGE_KEY)?
+            .map(|bytes| fvm_ipld_encoding::from_slice(&bytes))
+            .transpose()?;
+        Ok(Self {
+            store,
+            range: Mutex::new(range),
+        })
+    }
+
+    fn range(&self) -> Option<IndexedRange> {
+        *self.range.lock()
+    }
+
+    /// Returns the key of the tipset at the given checkpoint, if it is indexed.
+    fn checkpoint(
+        &self,
+        range: &IndexedRange,
+        epoch: ChainEpoch,
+    ) -> anyhow::Result<Option<TipsetKey>> {
+        if !range.contains(epoch) {
+            return Ok(None);
+        }
+        self.store
+            .read_bin(&epoch.to_be_bytes())?
+            .map(|bytes| fvm_ipld_encoding::from_slice(&bytes))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    fn write(
+        &self,
+        checkpoints: Vec<(ChainEpoch, TipsetKey)>,
+        range: IndexedRange,
+    ) -> anyhow::Result<()> {
+        let mut entries = checkpoints
+            .into_iter()
+            .map(|(epoch, tsk)| {
+                Ok((
+                    epoch.to_be_bytes().to_vec(),
+                    fvm_ipld_encoding::to_vec(&tsk)?,
+                ))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        entries.push((
+            HEIGHT_INDEX_RANGE_KEY.to_vec(),
+            fvm_ipld_encoding::to_vec(&range)?,
+        ));
+        self.store.write_batch(entries)
+    }
+}
+
+/// Returns the checkpoint crossed when walking from `child` to `parent`, if any.
+fn crossed_checkpoint(child: &Tipset, parent: &Tipset) -> Option<ChainEpoch> {
+    let checkpoint = child.epoch() - child.epoch().rem_euclid(HEIGHT_INDEX_SKIP_LENGTH);
+    (parent.epoch() < checkpoint).then_some(checkpoint)
+}
+
 #[derive(Debug, Clone, Copy)]
 /// Methods for resolving fetches of null tipsets.
 /// Imagine epoch 10 is null but epoch 9 and 11 exist. If epoch we request epoch
@@ -42,7 +143,22 @@ pub enum ResolveNullTipset {
 impl<DB: Blockstore> ChainIndex<DB> {
     pub fn new(db: DB) -> Self {
         let ts_cache = Mutex::new(LruCache::new(DEFAULT_TIPSET_CACHE_SIZE));
-        Self { ts_cache, db }
+        Self {
+            ts_cache,
+            height_index: None,
+            db,
+        }
+    }
+
+    /// Uses a persisted height index to speed up [`ChainIndex::tipset_by_height`]. The index
+    /// is maintained with [`ChainIndex::update_height_index`] and
+    /// [`ChainIndex::backfill_height_index`].
+    pub fn with_height_index(
+        mut self,
+        store: Arc<dyn HeightIndexStore + Sync + Send>,
+    ) -> anyhow::Result<Self> {
+        self.height_index = Some(HeightIndex::new(store)?);
+        Ok(self)
     }
 
     /// Loads a tipset from memory given the tipset keys and cache. Semantically
@@ -131,6 +247,21 @@ impl<DB: Blockstore> ChainIndex<DB> {
             )));
         }
 
+        match &self.height_index {
+            Some(height_index) if from.epoch() - to > HEIGHT_INDEX_SKIP_LENGTH => {
+                self.tipset_by_height_indexed(height_index, to, from, resolve)
+            }
+            _ => self.walk_to_height(to, from, resolve),
+        }
+    }
+
+    /// Walks the chain of ancestors of `from` until reaching epoch `to`.
+    fn walk_to_height(
+        &self,
+        to: ChainEpoch,
+        from: Arc<Tipset>,
+        resolve: ResolveNullTipset,
+    ) -> Result<Arc<Tipset>, Error> {
         for (child, parent) in self.chain(from).tuple_windows() {
             if to == child.epoch() {
                 return Ok(child);
@@ -148,6 +279,136 @@ impl<DB: Blockstore> ChainIndex<DB> {
         )))
     }
 
+    /// Walks the chain of ancestors of `from` until it joins the indexed chain, then
+    /// jumps to the checkpoint closest to `to` and walks the remaining few epochs.
+    fn tipset_by_height_indexed(
+        &self,
+        height_index: &HeightIndex,
+        to: ChainEpoch,
+        from: Arc<Tipset>,
+        resolve: ResolveNullTipset,
+    ) -> Result<Arc<Tipset>, Error> {
+        let Some(range) = height_index.range() else {
+            return self.walk_to_height(to, from, resolve);
+        };
+        for (child, parent) in self.chain(from).tuple_windows() {
+            if to == child.epoch() {
+                return Ok(child);
+            }
+            if to > parent.epoch() {
+                match resolve {
+                    ResolveNullTipset::TakeOlder => return Ok(parent),
+                    ResolveNullTipset::TakeNewer => return Ok(child),
+                }
+            }
+            let Some(checkpoint) = crossed_checkpoint(&child, &parent) else {
+                continue;
+            };
+            if height_index.checkpoint(&range, checkpoint)?.as_ref() == Some(child.key()) {
+                // The ancestors of `child` are indexed. The closest checkpoint at or
+                // above `to` is at most `checkpoint` since `to <= parent.epoch()`.
+                let target = (to + HEIGHT_INDEX_SKIP_LENGTH - 1)
+                    .div_euclid(HEIGHT_INDEX_SKIP_LENGTH)
+                    * HEIGHT_INDEX_SKIP_LENGTH;
+                let start = match height_index.checkpoint(&range, target.max(range.min))? {
+                    Some(tsk) => self.load_required_tipset(&tsk)?,
+                    None => parent,
+                };
+                return self.walk_to_height(to, start, resolve);
+            }
+        }
+        Err(Error::Other(format!(
+            "Tipset with epoch={to} does not exist"
+        )))
+    }
+
+    /// Indexes the checkpoints between `head` and the indexed chain. If `head` cannot be
+    /// connected to the indexed chain within a bounded number of tipsets, the index is
+    /// restarted from `head` and older tipsets are left to [`ChainIndex::backfill_height_index`].
+    /// Returns `true` if the index was restarted.
+    pub fn update_height_index(&self, head: &Arc<Tipset>) -> anyhow::Result<bool> {
+        let Some(height_index) = &self.height_index else {
+            return Ok(false);
+        };
+        let mut range = height_index.range.lock();
+        let mut checkpoints = vec![];
+        let mut connected = false;
+        for (child, parent) in self
+            .chain(head.clone())
+            .tuple_windows()
+            .take(HEIGHT_INDEX_MAX_HEAD_WALK)
+        {
+            let Some(checkpoint) = crossed_checkpoint(&child, &parent) else {
+                continue;
+            };
+            if let Some(current) = range.as_ref() {
+                if height_index.checkpoint(current, checkpoint)?.as_ref() == Some(child.key()) {
+                    connected = true;
+                    break;
+                }
+            }
+            checkpoints.push((checkpoint, child.key().clone()));
+        }
+        let min = match (connected, range.as_ref(), checkpoints.last()) {
+            (true, Some(current), _) => current.min,
+            (_, _, Some((lowest, _))) => *lowest,
+            // Nothing to index yet
+            (_, _, None) => return Ok(false),
+        };
+        let new_range = IndexedRange {
+            min,
+            max: head.epoch(),
+        };
+        height_index.write(checkpoints, new_range)?;
+        *range = Some(new_range);
+        Ok(!connected)
+    }
+
+    /// Extends the height index towards genesis, walking at most `max_tipsets` tipsets.
+    /// Returns `false` once there is nothing left to index, i.e., when genesis or a
+    /// missing tipset has been reached.
+    pub fn backfill_height_index(&self, max_tipsets: usize) -> anyhow::Result<bool> {
+        let Some(height_index) = &self.height_index else {
+            return Ok(false);
+        };
+        let Some(range) = height_index.range() else {
+            return Ok(false);
+        };
+        if range.min <= HEIGHT_INDEX_SKIP_LENGTH {
+            return Ok(false);
+        }
+        let Some(start) = height_index.checkpoint(&range, range.min)? else {
+            return Ok(false);
+        };
+        let mut checkpoints = vec![];
+        let mut walked = 0;
+        for (child, parent) in self
+            .chain(self.load_required_tipset(&start)?)
+            .tuple_windows()
+            .take(max_tipsets)
+        {
+            walked += 1;
+            if let Some(checkpoint) = crossed_checkpoint(&child, &parent) {
+                checkpoints.push((checkpoint, child.key().clone()));
+            }
+        }
+        let Some((lowest, _)) = checkpoints.last() else {
+            return Ok(walked == max_tipsets);
+        };
+        let mut current = height_index.range.lock();
+        // The index might have been restarted by a head change in the meantime
+        if *current != Some(range) {
+            return Ok(true);
+        }
+        let new_range = IndexedRange {
+            min: *lowest,
+            ..range
+        };
+        height_index.write(checkpoints, new_range)?;
+        *current = Some(new_range);
+        Ok(walked == max_tipsets)
+    }
+
     /// Iterate from the given tipset to genesis. Missing tipsets cut the chain
     /// short. Semantically identical to [`Tipset::chain`] but the results are
     /// cached.
@@ -283,4 +544,121 @@ mod tests {
             &epoch2b
         );
     }
+
+    /// Persists a chain of tipsets on top of `parent`, one for each of the given epochs.
+    fn persist_chain(
+        parent: &Tipset,
+        epochs: impl IntoIterator<Item = ChainEpoch>,
+        db: &impl Blockstore,
+    ) -> Vec<Arc<Tipset>> {
+        let mut parent = parent.clone();
+        let mut chain = vec![];
+        for epoch in epochs {
+            let child = tipset_child(&parent, epoch);
+            persist_tipset(&child, db);
+            chain.push(Arc::new(child.clone()));
+            parent = child;
+        }
+        chain
+    }
+
+    fn assert_same_as_walk(
+        index: &ChainIndex<Arc<MemoryDB>>,
+        from: &Arc<Tipset>,
+        heights: impl IntoIterator<Item = ChainEpoch>,
+    ) {
+        for to in heights {
+            for resolve in [ResolveNullTipset::TakeOlder, ResolveNullTipset::TakeNewer] {
+                assert_eq!(
+                    index
+                        .tipset_by_height(to, from.clone(), resolve)
+                        .unwrap()
+                        .key(),
+                    index
+                        .walk_to_height(to, from.clone(), resolve)
+                        .unwrap()
+                        .key(),
+                    "epoch {to}, {resolve:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn height_index_with_null_tipsets() {
+        let db = Arc::new(MemoryDB::default());
+        let gen = genesis_tipset();
+        persist_tipset(&gen, &db);
+        // Every 7th epoch is null, as well as a long streak spanning a few checkpoints.
+        let epochs = (1..300).filter(|epoch| epoch % 7 != 0 && !(110..170).contains(epoch));
+        let chain = persist_chain(&gen, epochs, &db);
+        let head = chain.last().unwrap();
+
+        let index = ChainIndex::new(db.clone())
+            .with_height_index(db.clone())
+            .unwrap();
+        index.update_height_index(head).unwrap();
+        assert_same_as_walk(&index, head, 1..=head.epoch());
+    }
+
+    #[test]
+    fn height_index_with_forks() {
+        let db = Arc::new(MemoryDB::default());
+        let gen = genesis_tipset();
+        persist_tipset(&gen, &db);
+        let common = persist_chain(&gen, 1..100, &db);
+        let fork_point = common.last().unwrap();
+        let branch_a = persist_chain(fork_point, 100..200, &db);
+        let branch_b = persist_chain(fork_point, (100..180).filter(|epoch| epoch % 3 != 0), &db);
+        let head_a = branch_a.last().unwrap();
+        let head_b = branch_b.last().unwrap();
+
+        let index = ChainIndex::new(db.clone())
+            .with_height_index(db.clone())
+            .unwrap();
+        assert!(index.update_height_index(head_a).unwrap());
+        assert_same_as_walk(&index, head_a, 1..=head_a.epoch());
+        assert_same_as_walk(&index, head_b, 1..=head_b.epoch());
+
+        // Re-org to a shorter branch, connected to the indexed chain
+        assert!(!index.update_height_index(head_b).unwrap());
+        assert_same_as_walk(&index, head_a, 1..=head_a.epoch());
+        assert_same_as_walk(&index, head_b, 1..=head_b.epoch());
+    }
+
+    #[test]
+    fn height_index_backfill() {
+        let db = Arc::new(MemoryDB::default());
+        let gen = genesis_tipset();
+        persist_tipset(&gen, &db);
+        let chain = persist_chain(
+            &gen,
+            1..(HEIGHT_INDEX_MAX_HEAD_WALK as ChainEpoch + 500),
+            &db,
+        );
+        let head = chain.last().unwrap();
+
+        let index = ChainIndex::new(db.clone())
+            .with_height_index(db.clone())
+            .unwrap();
+        // Too far from genesis to be connected to it, the index is restarted from the head
+        assert!(index.update_height_index(head).unwrap());
+        let range = index.height_index.as_ref().unwrap().range().unwrap();
+        assert!(range.min > HEIGHT_INDEX_SKIP_LENGTH);
+        assert_eq!(range.max, head.epoch());
+        // Lookups below the indexed range are still correct
+        assert_same_as_walk(&index, head, (1..range.min).step_by(97));
+
+        while index.backfill_height_index(100).unwrap() {}
+        let range = index.height_index.as_ref().unwrap().range().unwrap();
+        assert_eq!(range.min, HEIGHT_INDEX_SKIP_LENGTH);
+        assert_same_as_walk(&index, head, (1..=head.epoch()).step_by(13));
+
+        // The index is persisted
+        let index = ChainIndex::new(db.clone())
+            .with_height_index(db.clone())
+            .unwrap();
+        assert_eq!(index.height_index.as_ref().unwrap().range(), Some(range));
+        assert_same_as_walk(&index, head, (1..=head.epoch()).step_by(13));
+    }
 }
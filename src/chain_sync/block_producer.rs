@@ -0,0 +1,400 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Block production for local networks that have no external miner, such as a devnet run with
+//! `forest --devnet-solo`.
+//!
+//! Produced blocks do not go through the validation of the blocks received from the network:
+//! their tickets, election proofs and winning PoSt proofs are made up, and they are not signed.
+//! Such blocks are only valid on the node that produced them.
+//!
+//! Messages are still executed by the FVM, which verifies the seal and Window PoSt proofs
+//! submitted to miner actors. Sectors made up at genesis can't be proven, so they stay active
+//! only because genesis miners are not enrolled in the deadline cron, and new sectors need real
+//! proofs.
+
+use std::sync::Arc;
+
+use crate::blocks::{ElectionProof, Ticket, Tipset, VRFProof};
+use crate::message_pool::{MessagePool, MpoolRpcProvider};
+use crate::rpc::miner::{create_unsigned_block, BlockTemplate};
+use crate::shim::actors::{miner, MinerActorStateLoad as _};
+use crate::shim::{address::Address, clock::ChainEpoch};
+use crate::state_manager::StateManager;
+use crate::utils::encoding::blake2b_256;
+use anyhow::{ensure, Context as _};
+use fvm_ipld_blockstore::Blockstore;
+use tracing::info;
+
+/// Ticket quality used to select the messages of produced blocks. There is no competing block
+/// at the same epoch, so messages are selected greedily.
+const TICKET_QUALITY: f64 = 1.0;
+
+pub struct BlockProducer<DB> {
+    state_manager: Arc<StateManager<DB>>,
+    mpool: Arc<MessagePool<MpoolRpcProvider<DB>>>,
+    miner: Address,
+    /// Held while producing a tipset, so that concurrently produced blocks do not end up in the
+    /// same tipset
+    producing: tokio::sync::Mutex<()>,
+}
+
+impl<DB: Blockstore + Send + Sync + 'static> BlockProducer<DB> {
+    /// Creates a producer of blocks mined by `miner`, which must be a miner actor of the
+    /// heaviest tipset.
+    pub fn new(
+        state_manager: Arc<StateManager<DB>>,
+        mpool: Arc<MessagePool<MpoolRpcProvider<DB>>>,
+        miner: Address,
+    ) -> anyhow::Result<Self> {
+        let head = state_manager.chain_store().heaviest_tipset();
+        let actor = state_manager
+            .get_actor(&miner, *head.parent_state())?
+            .with_context(|| format!("miner {miner} not found"))?;
+        miner::State::load(state_manager.blockstore(), actor.code, actor.state)
+            .with_context(|| format!("{miner} is not a miner actor"))?;
+        Ok(Self {
+            state_manager,
+            mpool,
+            miner,
+            producing: Default::default(),
+        })
+    }
+
+    pub fn miner(&self) -> &Address {
+        &self.miner
+    }
+
+    /// Epoch of the next tipset produced at the given time: the expected epoch at that time, or
+    /// the epoch after the heaviest tipset if the chain is ahead of the clock.
+    pub fn next_epoch(&self, now: u64) -> ChainEpoch {
+        let chain_store = self.state_manager.chain_store();
+        let expected = crate::networks::calculate_expected_epoch(
+            now,
+            chain_store.genesis_block_header().timestamp,
+            self.state_manager.chain_config().block_delay_secs,
+        );
+        (chain_store.heaviest_tipset().epoch() + 1).max(expected as ChainEpoch)
+    }
+
+    /// Produces a tipset of a single block at `epoch` on top of the heaviest tipset, with the
+    /// messages selected from the message pool, and makes it the heaviest tipset. Epochs
+    /// between the heaviest tipset and `epoch` are null rounds.
+    pub async fn produce_tipset(&self, epoch: ChainEpoch) -> anyhow::Result<Arc<Tipset>> {
+        let _producing = self.producing.lock().await;
+        let chain_store = self.state_manager.chain_store();
+        let parent = chain_store.heaviest_tipset();
+        ensure!(
+            epoch > parent.epoch(),
+            "cannot produce a tipset at epoch {epoch}, the heaviest tipset is at epoch {}",
+            parent.epoch()
+        );
+
+        let prev_beacon = chain_store
+            .chain_index
+            .latest_beacon_entry(parent.clone())?;
+        let beacon_values = self
+            .state_manager
+            .beacon_schedule()
+            .beacon_entries_for_block(
+                self.state_manager.get_network_version(epoch),
+                epoch,
+                parent.epoch(),
+                &prev_beacon,
+            )
+            .await?;
+        let beacon = beacon_values.last().unwrap_or(&prev_beacon);
+        let made_up_proof = |domain: &str| {
+            VRFProof::new(
+                blake2b_256(
+                    &[
+                        domain.as_bytes(),
+                        &self.miner.to_bytes(),
+                        &epoch.to_be_bytes(),
+                        beacon.signature(),
+                    ]
+                    .concat(),
+                )
+                .to_vec(),
+            )
+        };
+
+        let messages = self.mpool.select_messages(&parent, TICKET_QUALITY)?;
+        let message_count = messages.len();
+        let block_delay = self.state_manager.chain_config().block_delay_secs as u64;
+        let block = create_unsigned_block(
+            &self.state_manager,
+            BlockTemplate {
+                miner: self.miner,
+                parents: parent.key().clone(),
+                ticket: Ticket::new(made_up_proof("ticket")),
+                eproof: ElectionProof {
+                    win_count: 1,
+                    vrfproof: made_up_proof("election"),
+                },
+                beacon_values,
+                messages,
+                epoch,
+                timestamp: parent.min_timestamp() + block_delay * (epoch - parent.epoch()) as u64,
+                winning_post_proof: vec![],
+            },
+        )
+        .await?;
+
+        let header = block.header().clone();
+        chain_store.add_to_tipset_tracker(&header);
+        let tipset = Arc::new(Tipset::from(header));
+        chain_store.put_tipset(&tipset)?;
+        ensure!(
+            chain_store.heaviest_tipset().key() == tipset.key(),
+            "produced tipset {} is not heavier than its parent, the network has no power",
+            tipset.key()
+        );
+        info!("Produced tipset at epoch {epoch} with {message_count} messages");
+        Ok(tipset)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::beacon::{mock_beacon::MockBeacon, BeaconPoint, BeaconSchedule};
+    use crate::chain::ChainStore;
+    use crate::chain_sync::network_context::SyncNetworkContext;
+    use crate::daemon::bundle::load_actor_bundles_from_server;
+    use crate::db::MemoryDB;
+    use crate::genesis::{
+        test_utils::{solo_genesis, SOLO_MINER},
+        GENESIS_ACTORS_VERSION,
+    };
+    use crate::key_management::{generate_key, Key, KeyStore, KeyStoreConfig};
+    use crate::libp2p::{NetworkMessage, PeerManager};
+    use crate::message::SignedMessage;
+    use crate::networks::{ActorBundleInfo, ChainConfig, ACTOR_BUNDLES};
+    use crate::paychmgr::PaychStore;
+    use crate::rpc::eth::filter::EthEventHandler;
+    use crate::rpc::RPCState;
+    use crate::shim::{crypto::SignatureType, econ::TokenAmount, message::Message};
+    use tokio::sync::{mpsc, RwLock};
+    use tokio::task::JoinSet;
+
+    /// A devnet whose heaviest tipset is the genesis of [`solo_genesis`], with a block producer
+    /// mining with [`SOLO_MINER`].
+    pub struct SoloDevnet {
+        pub ctx: Arc<RPCState<MemoryDB>>,
+        /// Owner and worker of [`SOLO_MINER`], whose key is in the keystore.
+        pub worker: Key,
+        /// Account funded at genesis.
+        pub account: Key,
+        _network_rx: flume::Receiver<NetworkMessage>,
+        _services: JoinSet<anyhow::Result<()>>,
+    }
+
+    impl SoloDevnet {
+        pub fn new() -> Self {
+            Self::with_db(Default::default())
+        }
+
+        /// Builds the devnet with the devnet actor bundle, downloading it, so that messages can be
+        /// executed.
+        pub async fn with_actor_bundle() -> Self {
+            let db = Arc::new(MemoryDB::default());
+            let bundle = ACTOR_BUNDLES
+                .iter()
+                .find(|bundle| {
+                    bundle.network.is_devnet()
+                        && bundle
+                            .version
+                            .starts_with(&format!("v{GENESIS_ACTORS_VERSION}."))
+                })
+                .unwrap();
+            load_actor_bundles_from_server(
+                &db,
+                &bundle.network,
+                &[ActorBundleInfo {
+                    manifest: bundle.manifest,
+                    url: bundle.url.clone(),
+                    alt_url: bundle.alt_url.clone(),
+                    network: bundle.network.clone(),
+                    version: bundle.version.clone(),
+                }],
+            )
+            .await
+            .unwrap();
+            Self::with_db(db)
+        }
+
+        /// Builds the devnet in `db`, which may already hold the actor bundle.
+        pub fn with_db(db: Arc<MemoryDB>) -> Self {
+            let (network_send, network_rx) = flume::bounded(5);
+            let mut services = JoinSet::new();
+            let chain_config = Arc::new(ChainConfig::devnet());
+            let worker = generate_key(SignatureType::Bls).unwrap();
+            let account = generate_key(SignatureType::Secp256k1).unwrap();
+            let genesis = solo_genesis(&db, &chain_config, &worker.address, &account.address);
+
+            let chain_store = Arc::new(
+                ChainStore::new(
+                    db.clone(),
+                    db.clone(),
+                    db.clone(),
+                    chain_config.clone(),
+                    genesis,
+                )
+                .unwrap(),
+            );
+            let state_manager = Arc::new(
+                StateManager::new(chain_store.clone(), chain_config.clone())
+                    .unwrap()
+                    .with_beacon_schedule(Arc::new(BeaconSchedule(vec![BeaconPoint {
+                        height: 0,
+                        beacon: Box::<MockBeacon>::default(),
+                    }]))),
+            );
+            let mpool = Arc::new(
+                MessagePool::new(
+                    MpoolRpcProvider::new(chain_store.publisher().clone(), state_manager.clone()),
+                    chain_config.network.to_string(),
+                    network_send.clone(),
+                    Default::default(),
+                    chain_config,
+                    &mut services,
+                )
+                .unwrap(),
+            );
+            let block_producer =
+                BlockProducer::new(state_manager.clone(), mpool.clone(), SOLO_MINER).unwrap();
+
+            let mut keystore = KeyStore::new(KeyStoreConfig::Memory).unwrap();
+            keystore
+                .put(
+                    &format!("wallet-{}", worker.address),
+                    worker.key_info.clone(),
+                )
+                .unwrap();
+            let sync_network_context = SyncNetworkContext::new(
+                network_send,
+                Arc::new(PeerManager::default()),
+                state_manager.blockstore_owned(),
+            );
+            let ctx = Arc::new(RPCState {
+                state_manager,
+                keystore: Arc::new(RwLock::new(keystore)),
+                mpool,
+                bad_blocks: Default::default(),
+                msgs_in_tipset: Default::default(),
+                sync_states: Arc::new(parking_lot::RwLock::new(nunny::vec![Default::default()])),
+                eth_event_handler: Arc::new(EthEventHandler::new()),
+                paych_store: Arc::new(PaychStore::new(db.clone())),
+                settings_store: db,
+                chain_pruner: None,
+                block_producer: Some(Arc::new(block_producer)),
+                sync_network_context,
+                network_name: "devnet".into(),
+                start_time: chrono::Utc::now(),
+                shutdown: mpsc::channel(1).0,
+                tipset_send: flume::bounded(5).0,
+                snapshot_progress_tracker: Default::default(),
+            });
+            Self {
+                ctx,
+                worker,
+                account,
+                _network_rx: network_rx,
+                _services: services,
+            }
+        }
+
+        pub fn producer(&self) -> &BlockProducer<MemoryDB> {
+            self.ctx.block_producer.as_deref().unwrap()
+        }
+
+        /// Signs a transfer of `1 FIL` from the funded account to the worker.
+        pub fn transfer(&self, sequence: u64) -> SignedMessage {
+            sign_transfer(&self.account, self.worker.address, sequence)
+        }
+    }
+
+    /// Signs a transfer of `1 FIL` from the secp256k1 account `from` to `to`.
+    pub fn sign_transfer(from: &Key, to: Address, sequence: u64) -> SignedMessage {
+        let message = Message {
+            from: from.address,
+            to,
+            sequence,
+            value: TokenAmount::from_whole(1),
+            gas_limit: 1_000_000,
+            gas_fee_cap: TokenAmount::from_nano(1),
+            gas_premium: TokenAmount::from_atto(100_000),
+            ..Default::default()
+        };
+        let signature = crate::key_management::sign(
+            SignatureType::Secp256k1,
+            from.key_info.private_key(),
+            message.cid().to_bytes().as_slice(),
+        )
+        .unwrap();
+        SignedMessage::new_from_parts(message, signature).unwrap()
+    }
+
+    #[tokio::test]
+    async fn produce_tipset_on_genesis() {
+        let devnet = SoloDevnet::new();
+        let chain_store = devnet.ctx.chain_store();
+        let genesis = chain_store.genesis_block_header().clone();
+        assert_eq!(devnet.producer().next_epoch(genesis.timestamp), 1);
+
+        let tipset = devnet.producer().produce_tipset(1).await.unwrap();
+        assert_eq!(tipset.epoch(), 1);
+        assert_eq!(tipset.parents(), chain_store.genesis_tipset().key());
+        assert_eq!(tipset.min_ticket_block().miner_address, SOLO_MINER);
+        assert_eq!(
+            tipset.min_timestamp(),
+            genesis.timestamp + devnet.ctx.chain_config().block_delay_secs as u64
+        );
+        assert_eq!(chain_store.heaviest_tipset().key(), tipset.key());
+        assert!(chain_store.messages_for_tipset(&tipset).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn produce_tipset_with_mpool_messages() {
+        let devnet = SoloDevnet::new();
+        let message = devnet.transfer(0);
+        devnet.ctx.mpool.push(message.clone()).await.unwrap();
+
+        let tipset = devnet.producer().produce_tipset(1).await.unwrap();
+        let messages = devnet
+            .ctx
+            .chain_store()
+            .messages_for_tipset(&tipset)
+            .unwrap();
+        assert_eq!(
+            messages.iter().map(|m| m.cid()).collect::<Vec<_>>(),
+            vec![message.cid()]
+        );
+    }
+
+    #[tokio::test]
+    async fn produce_tipset_after_null_rounds() {
+        let devnet = SoloDevnet::new();
+        let chain_store = devnet.ctx.chain_store();
+        let genesis = chain_store.genesis_block_header().clone();
+        let block_delay = devnet.ctx.chain_config().block_delay_secs as u64;
+        assert_eq!(
+            devnet
+                .producer()
+                .next_epoch(genesis.timestamp + 3 * block_delay),
+            3
+        );
+
+        let tipset = devnet.producer().produce_tipset(3).await.unwrap();
+        assert_eq!(tipset.epoch(), 3);
+        assert_eq!(tipset.parents(), chain_store.genesis_tipset().key());
+        assert_eq!(tipset.min_timestamp(), genesis.timestamp + 3 * block_delay);
+        assert!(!tipset.min_ticket_block().beacon_entries.is_empty());
+        assert_eq!(chain_store.heaviest_tipset().key(), tipset.key());
+
+        // Tipsets can only be produced after the heaviest one
+        devnet.producer().produce_tipset(3).await.unwrap_err();
+        devnet.producer().produce_tipset(2).await.unwrap_err();
+    }
+}
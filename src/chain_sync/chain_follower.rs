@@ -797,6 +797,7 @@ impl std::fmt::Display for SyncTask {
 }
 
 impl SyncTask {
+    #[tracing::instrument(skip_all, fields(task = %self))]
     async fn execute<DB: Blockstore + Sync + Send + 'static>(
         self,
         network: SyncNetworkContext<DB>,
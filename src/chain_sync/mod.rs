@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 mod bad_block_cache;
+pub mod block_producer;
 mod chain_follower;
 mod chain_muxer;
 pub mod consensus;
@@ -13,6 +14,7 @@ mod validation;
 
 pub use self::{
     bad_block_cache::BadBlockCache,
+    block_producer::BlockProducer,
     chain_follower::ChainFollower,
     chain_muxer::SyncConfig,
     consensus::collect_errs,
@@ -31,7 +31,7 @@ use fvm_ipld_encoding::to_vec;
 use itertools::Itertools;
 use nunny::Vec as NonEmpty;
 use thiserror::Error;
-use tracing::{error, trace, warn};
+use tracing::{error, instrument, trace, warn};
 
 use crate::chain_sync::{consensus::collect_errs, metrics, validation::TipsetValidator};
 
@@ -95,6 +95,7 @@ impl TipsetSyncerError {
 /// executed), adding the successful ones to the tipset tracker, and the failed
 /// ones to the bad block cache, depending on strategy. Any bad block fails
 /// validation.
+#[instrument(skip_all, fields(epoch = full_tipset.epoch()))]
 pub async fn validate_tipset<DB: Blockstore + Send + Sync + 'static>(
     state_manager: Arc<StateManager<DB>>,
     chainstore: &ChainStore<DB>,
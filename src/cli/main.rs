@@ -24,7 +24,10 @@ where
         .build()
         .unwrap()
         .block_on(async {
-            logger::setup_logger(&crate::cli_shared::cli::CliOpts::default());
+            logger::setup_logger(
+                &crate::cli_shared::cli::CliOpts::default(),
+                &Default::default(),
+            );
 
             if let Ok(name) = StateNetworkName::call(&client, ()).await {
                 if get_actual_chain_name(&name) != "mainnet" {
@@ -44,7 +47,9 @@ where
                 Subcommand::Send(cmd) => cmd.run(client).await,
                 Subcommand::Info(cmd) => cmd.run(client).await,
                 Subcommand::Snapshot(cmd) => cmd.run(client).await,
+                Subcommand::Paych(cmd) => cmd.run(client).await,
                 Subcommand::Shutdown(cmd) => cmd.run(client).await,
+                Subcommand::Log(cmd) => cmd.run(client).await,
                 Subcommand::Healthcheck(cmd) => cmd.run(client).await,
                 Subcommand::F3(cmd) => cmd.run(client).await,
                 Subcommand::WaitApi(cmd) => cmd.run(client).await,
@@ -1,6 +1,7 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use crate::auth::{Claims, TokenScope};
 use crate::rpc::{self, auth::AuthNewParams, prelude::*};
 use chrono::Duration;
 use clap::Subcommand;
@@ -17,6 +18,26 @@ pub enum AuthCommands {
         /// Token is revoked after this duration
         #[arg(long, default_value = "2 months")]
         expire_in: humantime::Duration,
+        /// Restrict the token to a method, or to the methods starting with a prefix if it ends
+        /// with `*`, e.g. `Filecoin.Eth*`. Can be repeated.
+        #[arg(long)]
+        allow_method: Vec<String>,
+        /// Forbid the token from calling a method, or the methods starting with a prefix if it ends
+        /// with `*`. Can be repeated.
+        #[arg(long)]
+        deny_method: Vec<String>,
+        /// Client the token is issued to, used for per-client RPC rate limits. Defaults to the
+        /// token ID.
+        #[arg(long)]
+        subject: Option<String>,
+        /// ID of the token, used to revoke it. Generated if not set.
+        #[arg(long)]
+        id: Option<String>,
+    },
+    /// Revoke the tokens with a given ID
+    Revoke {
+        /// Token ID, or a token whose ID is revoked
+        token_or_id: String,
     },
     /// Get RPC API Information
     ApiInfo {
@@ -32,13 +53,40 @@ pub enum AuthCommands {
 impl AuthCommands {
     pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
         match self {
-            Self::CreateToken { perm, expire_in } => {
+            Self::CreateToken {
+                perm,
+                expire_in,
+                allow_method,
+                deny_method,
+                subject,
+                id,
+            } => {
                 let perm: String = perm.parse()?;
                 let perms = AuthNewParams::process_perms(perm)?;
                 let token_exp = Duration::from_std(expire_in.into())?;
-                let res = AuthNew::call(&client, AuthNewParams { perms, token_exp }.into()).await?;
+                let scope = TokenScope {
+                    id,
+                    subject,
+                    allow_methods: allow_method,
+                    deny_methods: deny_method,
+                };
+                let res = if scope == TokenScope::default() {
+                    AuthNew::call(&client, AuthNewParams { perms, token_exp }.into()).await?
+                } else {
+                    AuthNewScoped::call(&client, (perms, scope, Some(token_exp.num_seconds())))
+                        .await?
+                };
                 print_rpc_res_bytes(res)
             }
+            Self::Revoke { token_or_id } => {
+                let id = token_id(&token_or_id)?.unwrap_or(token_or_id);
+                if AuthRevoke::call(&client, (id.clone(),)).await? {
+                    println!("Revoked tokens with ID {id}");
+                } else {
+                    println!("Tokens with ID {id} were already revoked");
+                }
+                Ok(())
+            }
             Self::ApiInfo { perm, expire_in } => {
                 let perm: String = perm.parse()?;
                 let perms = AuthNewParams::process_perms(perm)?;
@@ -53,3 +101,23 @@ impl AuthCommands {
         }
     }
 }
+
+/// Returns the ID of `token` if it is a JWT token, without verifying it.
+fn token_id(token: &str) -> anyhow::Result<Option<String>> {
+    if token.split('.').count() != 3 {
+        return Ok(None);
+    }
+    let mut validation = jsonwebtoken::Validation::default();
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+    validation.required_spec_claims.clear();
+    let claims = jsonwebtoken::decode::<Claims>(
+        token,
+        &jsonwebtoken::DecodingKey::from_secret(&[]),
+        &validation,
+    )?
+    .claims;
+    Ok(Some(claims.jti.ok_or_else(|| {
+        anyhow::anyhow!("token has no ID and can't be revoked, rotate the JWT secret instead")
+    })?))
+}
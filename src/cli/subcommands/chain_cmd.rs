@@ -2,13 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::blocks::{Tipset, TipsetKey};
+use crate::chain::ChainEpochDelta;
+use crate::db::PruneOptions;
 use crate::lotus_json::HasLotusJson;
 use crate::message::ChainMessage;
 use crate::rpc::{self, prelude::*};
+use crate::shim::message::Message;
 use anyhow::{bail, ensure};
 use cid::Cid;
 use clap::Subcommand;
+use human_repr::HumanCount as _;
 use nunny::Vec as NonEmpty;
+use std::time::Duration;
 
 use super::print_pretty_lotus_json;
 
@@ -68,6 +73,36 @@ pub enum ChainCommands {
         #[arg(short, long, aliases = ["yes", "no-confirm"], short_alias = 'y')]
         force: bool,
     },
+
+    /// Remove the data that is neither reachable within the state retention nor from the pinned
+    /// tipsets. Waits for chain finality before removing anything
+    Prune {
+        /// Number of most recent epochs to keep the state for, defaults to the node's configured
+        /// retention
+        #[arg(long)]
+        retain_state: Option<ChainEpochDelta>,
+        /// Only report the reclaimable space without removing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Pin a tipset so that its block headers and state are never garbage collected. The ancestors
+    /// of the tipset are not retained
+    Pin {
+        /// CIDs of the blocks of the tipset
+        #[arg(num_args = 1.., required = true)]
+        cids: Vec<Cid>,
+    },
+
+    /// Unpin a previously pinned tipset
+    Unpin {
+        /// CIDs of the blocks of the tipset
+        #[arg(num_args = 1.., required = true)]
+        cids: Vec<Cid>,
+    },
+
+    /// Prints out the pinned tipsets
+    Pinned,
 }
 
 impl ChainCommands {
@@ -80,17 +115,22 @@ impl ChainCommands {
             Self::Head { tipsets, format } => print_chain_head(&client, tipsets, format).await,
             Self::Message { cid } => {
                 let bytes = ChainReadObj::call(&client, (cid,)).await?;
-                match fvm_ipld_encoding::from_slice::<ChainMessage>(&bytes)? {
-                    ChainMessage::Unsigned(m) => print_pretty_lotus_json(m),
+                let message = match fvm_ipld_encoding::from_slice::<ChainMessage>(&bytes)? {
+                    ChainMessage::Unsigned(m) => {
+                        print_pretty_lotus_json(m.clone())?;
+                        m
+                    }
                     ChainMessage::Signed(m) => {
                         let cid = m.cid();
+                        let message = m.message.clone();
                         println!(
                             "{}",
                             serde_json::to_string_pretty(&m.into_lotus_json().with_cid(cid))?
                         );
-                        Ok(())
+                        message
                     }
-                }
+                };
+                print_decoded_params(&client, &message).await
             }
             Self::ReadObj { cid } => {
                 let bytes = ChainReadObj::call(&client, (cid,)).await?;
@@ -123,10 +163,47 @@ impl ChainCommands {
                 .await?;
                 Ok(())
             }
+            Self::Prune {
+                retain_state,
+                dry_run,
+            } => {
+                let options = PruneOptions {
+                    retain_state,
+                    dry_run,
+                };
+                let report = client
+                    .call(ChainPrune::request((options,))?.with_timeout(Duration::MAX))
+                    .await?;
+                let action = if dry_run { "Reclaimable" } else { "Removed" };
+                println!(
+                    "{action}: {} records, {}",
+                    report.records,
+                    report.bytes.human_count_bytes()
+                );
+                Ok(())
+            }
+            Self::Pin { cids } => {
+                ChainPinTipset::call(&client, (tipset_key(cids),)).await?;
+                Ok(())
+            }
+            Self::Unpin { cids } => {
+                ChainUnpinTipset::call(&client, (tipset_key(cids),)).await?;
+                Ok(())
+            }
+            Self::Pinned => {
+                for tsk in ChainPinnedTipsets::call(&client, ()).await? {
+                    println!("{tsk}");
+                }
+                Ok(())
+            }
         }
     }
 }
 
+fn tipset_key(cids: Vec<Cid>) -> TipsetKey {
+    TipsetKey::from(NonEmpty::new(cids).expect("empty vec disallowed by clap"))
+}
+
 /// If `epoch_or_offset` is negative, get the tipset that many blocks before the
 /// current head. Else treat `epoch_or_offset` as an epoch, and get that tipset.
 async fn tipset_by_epoch_or_offset(
@@ -182,6 +259,32 @@ async fn collect_n_tipsets(client: &rpc::Client, n: u64) -> anyhow::Result<Vec<T
     Ok(tipsets)
 }
 
+/// Print the parameters of a message decoded according to the actor it is sent to, if it has
+/// any. Decoding failures are reported without failing the command.
+async fn print_decoded_params(client: &rpc::Client, message: &Message) -> anyhow::Result<()> {
+    if message.params.is_empty() {
+        return Ok(());
+    }
+    match StateDecodeParams::call(
+        client,
+        (
+            message.to,
+            message.method_num,
+            message.params.to_vec(),
+            None.into(),
+        ),
+    )
+    .await
+    {
+        Ok(params) => {
+            println!("Decoded params:");
+            println!("{}", serde_json::to_string_pretty(&params)?);
+        }
+        Err(e) => eprintln!("Failed to decode params: {e}"),
+    }
+    Ok(())
+}
+
 /// Print the first `n` tipsets from the head (inclusive).
 async fn print_chain_head(client: &rpc::Client, n: u64, format: Format) -> anyhow::Result<()> {
     let tipsets = collect_n_tipsets(client, n).await?;
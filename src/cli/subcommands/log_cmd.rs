@@ -0,0 +1,43 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use crate::rpc::{self, prelude::*};
+use clap::Subcommand;
+
+#[derive(Debug, Subcommand)]
+pub enum LogCommands {
+    /// List the directives of the node log filter
+    List,
+    /// Set the log level of one or more targets at runtime
+    SetLevel {
+        /// Log level, one of: trace, debug, info, warn, error, off
+        level: String,
+        /// Targets to set the level of, e.g. `forest::chain_sync`. Sets the default level if
+        /// omitted
+        #[arg(long)]
+        target: Vec<String>,
+    },
+}
+
+impl LogCommands {
+    pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
+        match self {
+            Self::List => {
+                for directive in LogList::call(&client, ()).await? {
+                    println!("{directive}");
+                }
+                Ok(())
+            }
+            Self::SetLevel { level, target } => {
+                let targets = match target.is_empty() {
+                    true => vec!["*".to_string()],
+                    false => target,
+                };
+                for target in targets {
+                    LogSetLevel::call(&client, (target, level.clone())).await?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -12,8 +12,10 @@ mod config_cmd;
 mod f3_cmd;
 mod healthcheck_cmd;
 mod info_cmd;
+mod log_cmd;
 mod mpool_cmd;
 mod net_cmd;
+mod paych_cmd;
 pub(crate) mod send_cmd;
 mod shutdown_cmd;
 mod snapshot_cmd;
@@ -31,10 +33,10 @@ use tracing::error;
 
 pub(super) use self::{
     auth_cmd::AuthCommands, chain_cmd::ChainCommands, config_cmd::ConfigCommands,
-    f3_cmd::F3Commands, healthcheck_cmd::HealthcheckCommand, mpool_cmd::MpoolCommands,
-    net_cmd::NetCommands, send_cmd::SendCommand, shutdown_cmd::ShutdownCommand,
-    snapshot_cmd::SnapshotCommands, state_cmd::StateCommands, sync_cmd::SyncCommands,
-    wait_api_cmd::WaitApiCommand,
+    f3_cmd::F3Commands, healthcheck_cmd::HealthcheckCommand, log_cmd::LogCommands,
+    mpool_cmd::MpoolCommands, net_cmd::NetCommands, paych_cmd::PaychCommands,
+    send_cmd::SendCommand, shutdown_cmd::ShutdownCommand, snapshot_cmd::SnapshotCommands,
+    state_cmd::StateCommands, sync_cmd::SyncCommands, wait_api_cmd::WaitApiCommand,
 };
 use crate::cli::subcommands::info_cmd::InfoCommand;
 
@@ -86,6 +88,10 @@ pub enum Subcommand {
     #[command(subcommand)]
     Snapshot(SnapshotCommands),
 
+    /// Manage payment channels
+    #[command(subcommand)]
+    Paych(PaychCommands),
+
     /// Send funds between accounts
     Send(SendCommand),
 
@@ -96,6 +102,10 @@ pub enum Subcommand {
     /// Shutdown Forest
     Shutdown(ShutdownCommand),
 
+    /// Inspect or change the node log levels
+    #[command(subcommand)]
+    Log(LogCommands),
+
     /// Print healthcheck info
     #[command(subcommand)]
     Healthcheck(HealthcheckCommand),
@@ -3,15 +3,20 @@
 
 use std::str::FromStr as _;
 
+use super::format_vec_pretty;
 use crate::blocks::Tipset;
+use crate::cli::humantoken::{self, TokenAmountPretty as _};
 use crate::lotus_json::{HasLotusJson as _, NotNullVec};
 use crate::message::SignedMessage;
+use crate::rpc::mpool::MpoolReplaceSpec;
 use crate::rpc::{self, prelude::*, types::ApiTipsetKey};
 use crate::shim::address::StrictAddress;
 use crate::shim::message::Message;
-use crate::shim::{address::Address, econ::TokenAmount};
+use crate::shim::{address::Address, clock::ChainEpoch, econ::TokenAmount};
 
 use ahash::{HashMap, HashSet};
+use anyhow::{bail, Context as _};
+use cid::Cid;
 use clap::Subcommand;
 use num::BigInt;
 
@@ -41,6 +46,66 @@ pub enum MpoolCommands {
         #[arg(long)]
         local: bool,
     },
+    /// Replace a pending message by a copy paying higher gas fees. Unless given, the fees are
+    /// estimated and raised to the minimum required to replace the message
+    Replace {
+        /// CID of the pending message
+        #[arg(long, required_unless_present = "from", conflicts_with_all = ["from", "nonce"])]
+        cid: Option<Cid>,
+        /// Sender of the pending message
+        #[arg(long, requires = "nonce")]
+        from: Option<String>,
+        /// Nonce of the pending message
+        #[arg(long, requires = "from")]
+        nonce: Option<u64>,
+        /// Gas premium of the replacement
+        #[arg(long, value_parser = humantoken::parse)]
+        gas_premium: Option<TokenAmount>,
+        /// Gas fee cap of the replacement
+        #[arg(long, value_parser = humantoken::parse)]
+        gas_feecap: Option<TokenAmount>,
+        /// Gas limit of the replacement, defaults to the gas limit of the pending message
+        #[arg(long)]
+        gas_limit: Option<u64>,
+    },
+    /// List nonce gaps and long-pending messages of the addresses that pushed messages through
+    /// the node
+    FindStuck {
+        /// Number of epochs after which a pending message is reported
+        #[arg(long, default_value_t = 10)]
+        min_age: ChainEpoch,
+    },
+    /// Get or set the message pool configuration
+    #[command(subcommand)]
+    Config(MpoolConfigCommands),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MpoolConfigCommands {
+    /// Print the message pool configuration
+    Get,
+    /// Update the message pool configuration. Settings that are not given are left unchanged
+    Set {
+        /// Addresses whose messages are selected first, comma separated. Pass no value to clear
+        /// the list
+        #[arg(long, value_delimiter = ',', num_args = 0..)]
+        priority_addrs: Option<Vec<String>>,
+        /// Number of pending messages above which the message pool is pruned
+        #[arg(long)]
+        size_limit_high: Option<i64>,
+        /// Number of pending messages kept when the message pool is pruned
+        #[arg(long)]
+        size_limit_low: Option<i64>,
+        /// Minimum ratio between the gas premium of a replacing message and the one it replaces
+        #[arg(long)]
+        replace_by_fee_ratio: Option<f64>,
+        /// Minimum time between two prunings of the message pool, e.g. `1m`
+        #[arg(long)]
+        prune_cooldown: Option<humantime::Duration>,
+        /// Factor applied to the estimated gas limit of messages
+        #[arg(long)]
+        gas_limit_overestimation: Option<f64>,
+    },
 }
 
 fn to_addr(value: &Option<String>) -> anyhow::Result<Option<StrictAddress>> {
@@ -275,6 +340,112 @@ impl MpoolCommands {
 
                 Ok(())
             }
+            Self::Replace {
+                cid,
+                from,
+                nonce,
+                gas_premium,
+                gas_feecap,
+                gas_limit,
+            } => {
+                let (from, nonce) = match (cid, from, nonce) {
+                    (Some(cid), _, _) => {
+                        let NotNullVec(pending) =
+                            MpoolPending::call(&client, (ApiTipsetKey(None),)).await?;
+                        let smsg = pending
+                            .into_iter()
+                            .find(|smsg| smsg.cid() == cid)
+                            .with_context(|| format!("message {cid} is not pending"))?;
+                        (smsg.message.from, smsg.message.sequence)
+                    }
+                    (None, Some(from), Some(nonce)) => {
+                        (StrictAddress::from_str(&from)?.into(), nonce)
+                    }
+                    _ => {
+                        bail!("either the CID or the sender and nonce of the message are required")
+                    }
+                };
+                let spec = MpoolReplaceSpec {
+                    gas_premium,
+                    gas_fee_cap: gas_feecap,
+                    gas_limit,
+                };
+                let smsg = MpoolReplace::call(&client, (from, nonce, Some(spec))).await?;
+                println!("new message cid: {}", smsg.cid());
+                Ok(())
+            }
+            Self::FindStuck { min_age } => {
+                let head = ChainHead::call(&client, ()).await?.epoch();
+                for stuck in MpoolFindStuck::call(&client, (min_age,)).await? {
+                    println!("{}: state nonce {}", stuck.address, stuck.state_nonce);
+                    if !stuck.nonce_gaps.is_empty() {
+                        println!(
+                            "  missing nonces: {}",
+                            format_vec_pretty(
+                                stuck.nonce_gaps.iter().map(u64::to_string).collect()
+                            )
+                        );
+                    }
+                    for msg in stuck.long_pending {
+                        println!(
+                            "  {} nonce {}: pending for {} epochs, premium {}, fee cap {}",
+                            msg.cid,
+                            msg.nonce,
+                            head - msg.pending_since,
+                            msg.gas_premium.pretty(),
+                            msg.gas_fee_cap.pretty()
+                        );
+                    }
+                }
+                Ok(())
+            }
+            Self::Config(cmd) => cmd.run(client).await,
+        }
+    }
+}
+
+impl MpoolConfigCommands {
+    pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
+        match self {
+            Self::Get => {
+                let config = MpoolGetConfig::call(&client, ()).await?;
+                println!("{}", config.into_lotus_json_string_pretty()?);
+                Ok(())
+            }
+            Self::Set {
+                priority_addrs,
+                size_limit_high,
+                size_limit_low,
+                replace_by_fee_ratio,
+                prune_cooldown,
+                gas_limit_overestimation,
+            } => {
+                let mut config = MpoolGetConfig::call(&client, ()).await?;
+                if let Some(priority_addrs) = priority_addrs {
+                    config.priority_addrs = priority_addrs
+                        .iter()
+                        .map(|addr| Ok(StrictAddress::from_str(addr)?.into()))
+                        .collect::<anyhow::Result<_>>()?;
+                }
+                if let Some(size_limit_high) = size_limit_high {
+                    config.size_limit_high = size_limit_high;
+                }
+                if let Some(size_limit_low) = size_limit_low {
+                    config.size_limit_low = size_limit_low;
+                }
+                if let Some(replace_by_fee_ratio) = replace_by_fee_ratio {
+                    config.replace_by_fee_ratio = replace_by_fee_ratio;
+                }
+                if let Some(prune_cooldown) = prune_cooldown {
+                    config.prune_cooldown = prune_cooldown.into();
+                }
+                if let Some(gas_limit_overestimation) = gas_limit_overestimation {
+                    config.gas_limit_overestimation = gas_limit_overestimation;
+                }
+                config.validate()?;
+                MpoolSetConfig::call(&client, (config,)).await?;
+                Ok(())
+            }
         }
     }
 }
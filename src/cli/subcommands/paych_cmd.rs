@@ -0,0 +1,196 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::str::FromStr as _;
+use std::time::Duration;
+
+use crate::cli::humantoken::{self, TokenAmountPretty as _};
+use crate::rpc::{self, prelude::*};
+use crate::shim::actors::paych::SignedVoucher;
+use crate::shim::address::{Address, StrictAddress};
+use crate::shim::econ::TokenAmount;
+use anyhow::bail;
+use clap::Subcommand;
+
+#[derive(Debug, Subcommand)]
+pub enum PaychCommands {
+    /// Add funds to the payment channel between the two addresses, creating the channel if it
+    /// does not exist yet, and print the channel address
+    AddFunds {
+        /// Address of the payer, a wallet of this node
+        from: String,
+        /// Address of the payee
+        to: String,
+        #[arg(value_parser = humantoken::parse)]
+        amount: TokenAmount,
+    },
+    /// List the payment channels tracked by the node
+    List,
+    /// Print the funds available in a payment channel
+    Status { channel: String },
+    /// Create, check and submit payment channel vouchers
+    #[command(subcommand)]
+    Voucher(VoucherCommands),
+    /// Settle a payment channel, starting the settlement period
+    Settle { channel: String },
+    /// Collect the funds of a settled payment channel
+    Collect { channel: String },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum VoucherCommands {
+    /// Create a signed voucher for an outbound payment channel and print it encoded
+    Create {
+        channel: String,
+        /// Total amount redeemable in the lane
+        #[arg(value_parser = humantoken::parse)]
+        amount: TokenAmount,
+        #[arg(long, default_value_t = 0)]
+        lane: u64,
+    },
+    /// Check that an encoded voucher is valid for the payment channel
+    Check { channel: String, voucher: String },
+    /// Check and store an encoded voucher received for an inbound payment channel
+    Add { channel: String, voucher: String },
+    /// List the vouchers stored for a payment channel
+    List { channel: String },
+    /// Submit an encoded voucher to the payment channel actor
+    Submit { channel: String, voucher: String },
+}
+
+impl PaychCommands {
+    pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
+        match self {
+            Self::AddFunds { from, to, amount } => {
+                let info = PaychGet::call(
+                    &client,
+                    (parse_address(&from)?, parse_address(&to)?, amount, None),
+                )
+                .await?;
+                let channel = match (info.channel, info.wait_sentinel) {
+                    (Some(channel), None) => channel,
+                    (_, Some(sentinel)) => {
+                        client
+                            .call(
+                                PaychGetWaitReady::request((sentinel,))?
+                                    .with_timeout(Duration::MAX),
+                            )
+                            .await?
+                    }
+                    (None, None) => bail!("the node did not return a payment channel"),
+                };
+                println!("{channel}");
+                Ok(())
+            }
+            Self::List => {
+                for channel in PaychList::call(&client, ()).await? {
+                    println!("{channel}");
+                }
+                Ok(())
+            }
+            Self::Status { channel } => {
+                let funds = PaychAvailableFunds::call(&client, (parse_address(&channel)?,)).await?;
+                if let Some(channel) = funds.channel {
+                    println!("Channel:            {channel}");
+                }
+                println!("From:               {}", funds.from);
+                println!("To:                 {}", funds.to);
+                println!("Confirmed:          {}", funds.confirmed_amt.pretty());
+                println!("Pending:            {}", funds.pending_amt.pretty());
+                println!(
+                    "Vouchers redeemed:  {}",
+                    funds.voucher_redeemed_amt.pretty()
+                );
+                println!("Available:          {}", funds.non_reserved_amt.pretty());
+                Ok(())
+            }
+            Self::Voucher(cmd) => cmd.run(client).await,
+            Self::Settle { channel } => {
+                let cid = PaychSettle::call(&client, (parse_address(&channel)?,)).await?;
+                println!("{cid}");
+                Ok(())
+            }
+            Self::Collect { channel } => {
+                let cid = PaychCollect::call(&client, (parse_address(&channel)?,)).await?;
+                println!("{cid}");
+                Ok(())
+            }
+        }
+    }
+}
+
+impl VoucherCommands {
+    pub async fn run(self, client: rpc::Client) -> anyhow::Result<()> {
+        match self {
+            Self::Create {
+                channel,
+                amount,
+                lane,
+            } => {
+                let result =
+                    PaychVoucherCreate::call(&client, (parse_address(&channel)?, amount, lane))
+                        .await?;
+                match result.voucher {
+                    Some(voucher) => println!("{}", voucher.encode()?),
+                    None => bail!(
+                        "not enough funds in the channel to create the voucher, shortfall: {}",
+                        result.shortfall.pretty()
+                    ),
+                }
+                Ok(())
+            }
+            Self::Check { channel, voucher } => {
+                PaychVoucherCheckValid::call(
+                    &client,
+                    (parse_address(&channel)?, SignedVoucher::decode(&voucher)?),
+                )
+                .await?;
+                println!("voucher is valid");
+                Ok(())
+            }
+            Self::Add { channel, voucher } => {
+                PaychVoucherAdd::call(
+                    &client,
+                    (
+                        parse_address(&channel)?,
+                        SignedVoucher::decode(&voucher)?,
+                        vec![],
+                        TokenAmount::default(),
+                    ),
+                )
+                .await?;
+                Ok(())
+            }
+            Self::List { channel } => {
+                for voucher in PaychVoucherList::call(&client, (parse_address(&channel)?,)).await? {
+                    println!(
+                        "Lane {}, Nonce {}: {}; {}",
+                        voucher.lane,
+                        voucher.nonce,
+                        voucher.amount.pretty(),
+                        voucher.encode()?
+                    );
+                }
+                Ok(())
+            }
+            Self::Submit { channel, voucher } => {
+                let cid = PaychVoucherSubmit::call(
+                    &client,
+                    (
+                        parse_address(&channel)?,
+                        SignedVoucher::decode(&voucher)?,
+                        vec![],
+                        vec![],
+                    ),
+                )
+                .await?;
+                println!("{cid}");
+                Ok(())
+            }
+        }
+    }
+}
+
+fn parse_address(s: &str) -> anyhow::Result<Address> {
+    Ok(StrictAddress::from_str(s)?.into())
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::*;
+use crate::chain::ExportFormat;
 use crate::chain_sync::SyncConfig;
 use crate::cli_shared::snapshot::{self, TrustedVendor};
 use crate::rpc::types::ApiTipsetKey;
@@ -34,6 +35,9 @@ pub enum SnapshotCommands {
         /// How many state-roots to include. Lower limit is 900 for `calibnet` and `mainnet`.
         #[arg(short, long)]
         depth: Option<crate::chain::ChainEpochDelta>,
+        /// Archive format of the snapshot.
+        #[arg(long, value_enum, default_value_t = ExportFormat::ForestCar)]
+        format: ExportFormat,
     },
 }
 
@@ -46,6 +50,7 @@ impl SnapshotCommands {
                 dry_run,
                 tipset,
                 depth,
+                format,
             } => {
                 let chain_head = ChainHead::call(&client, ()).await?;
 
@@ -67,7 +72,7 @@ impl SnapshotCommands {
                             .naive_utc()
                             .date(),
                         epoch,
-                        true,
+                        format.is_forest_car(),
                     )),
                     false => output_path.clone(),
                 };
@@ -82,6 +87,7 @@ impl SnapshotCommands {
                     tipset_keys: ApiTipsetKey(Some(chain_head.key().clone())),
                     skip_checksum,
                     dry_run,
+                    format,
                 };
 
                 let handle = tokio::spawn({
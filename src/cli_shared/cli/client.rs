@@ -40,6 +40,8 @@ impl Default for BufferSize {
 pub struct Client {
     pub data_dir: PathBuf,
     pub genesis_file: Option<PathBuf>,
+    /// Chain specification file overriding the parameters of the built-in networks
+    pub chain_spec: Option<PathBuf>,
     pub enable_rpc: bool,
     pub enable_metrics_endpoint: bool,
     pub enable_health_check: bool,
@@ -75,6 +77,7 @@ impl Default for Client {
         Self {
             data_dir: dir.data_dir().to_path_buf(),
             genesis_file: None,
+            chain_spec: None,
             enable_rpc: true,
             enable_metrics_endpoint: true,
             enable_health_check: true,
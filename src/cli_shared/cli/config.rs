@@ -7,6 +7,7 @@ use crate::shim::clock::ChainEpoch;
 use crate::utils::misc::env::is_env_set_and_truthy;
 use crate::{chain_sync::SyncConfig, networks::NetworkChain};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use super::client::Client;
@@ -92,6 +93,119 @@ impl Default for ChainIndexerConfig {
     }
 }
 
+/// Structure that defines the export of tracing spans to an `OpenTelemetry` collector
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct OtlpConfig {
+    /// Export spans to the collector. Requires the `tracing-opentelemetry` feature.
+    pub enabled: bool,
+    /// Traces endpoint of the collector, using the OTLP/HTTP protocol
+    pub endpoint: String,
+    /// Service name the spans are reported under
+    pub service_name: String,
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "http://127.0.0.1:4318/v1/traces".into(),
+            service_name: "forest".into(),
+        }
+    }
+}
+
+/// Structure that defines the throttling of RPC calls. Limits apply to each client, identified by
+/// the `sub` claim of its JWT or by its IP address.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct RpcRateLimitConfig {
+    pub enabled: bool,
+    /// Sustained request weight allowed per second. Set to `0` to disable rate limiting.
+    pub requests_per_second: u32,
+    /// Request weight a client can spend at once, after being idle
+    pub burst: u32,
+    /// Maximum number of calls a client can have in progress. Set to `0` to disable the limit.
+    pub max_concurrent_requests: u32,
+    /// Weights of the expensive methods, other methods have a weight of `1`
+    pub method_weights: BTreeMap<String, u32>,
+}
+
+impl Default for RpcRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            requests_per_second: 100,
+            burst: 200,
+            max_concurrent_requests: 32,
+            method_weights: [
+                ("Filecoin.ChainExport", 200),
+                ("Filecoin.EthGetLogs", 20),
+                ("Filecoin.EthTraceFilter", 20),
+                ("Filecoin.StateMarketDeals", 200),
+                ("Filecoin.StateMinerActiveSectors", 20),
+                ("Filecoin.StateMinerSectors", 20),
+                ("eth_getLogs", 20),
+                ("trace_filter", 20),
+            ]
+            .into_iter()
+            .map(|(method, weight)| (method.into(), weight))
+            .collect(),
+        }
+    }
+}
+
+/// Structure that defines the cache of RPC responses. Only the results of methods that declare
+/// themselves cacheable are stored, and only once the data they refer to is final.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct RpcCacheConfig {
+    pub enabled: bool,
+    /// Maximum number of cached responses
+    #[cfg_attr(test, arbitrary(gen(|g| u32::arbitrary(g) as _)))]
+    pub max_entries: usize,
+    /// Maximum total size of the cached responses, in bytes
+    #[cfg_attr(test, arbitrary(gen(|g| u32::arbitrary(g) as _)))]
+    pub max_size_bytes: usize,
+}
+
+impl Default for RpcCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_entries: 100_000,
+            max_size_bytes: 256 * 1024 * 1024,
+        }
+    }
+}
+
+/// Structure that defines the handling of JSON-RPC batch requests
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
+#[serde(default)]
+pub struct RpcBatchConfig {
+    /// Maximum number of calls in a batch. Larger batches are rejected.
+    pub max_batch_size: u32,
+    /// Maximum number of calls of a batch executed concurrently over HTTP
+    pub max_concurrent_calls: u32,
+    /// Time after which the calls of a batch still in progress over HTTP fail. Set to `0` to
+    /// disable the timeout.
+    pub timeout_secs: u32,
+}
+
+impl Default for RpcBatchConfig {
+    fn default() -> Self {
+        Self {
+            max_batch_size: 1000,
+            max_concurrent_calls: 16,
+            timeout_secs: 60,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Default, Debug, Clone)]
 #[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
 #[serde(default)]
@@ -105,6 +219,10 @@ pub struct Config {
     pub events: EventsConfig,
     pub fevm: FevmConfig,
     pub chain_indexer: ChainIndexerConfig,
+    pub otlp: OtlpConfig,
+    pub rpc_rate_limit: RpcRateLimitConfig,
+    pub rpc_cache: RpcCacheConfig,
+    pub rpc_batch: RpcBatchConfig,
 }
 
 impl Config {
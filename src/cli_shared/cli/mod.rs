@@ -10,7 +10,8 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::networks::NetworkChain;
+use crate::networks::{ChainConfig, NetworkChain};
+use crate::shim::address::Address;
 use crate::utils::misc::LoggingColor;
 use crate::{cli_shared::read_config, daemon::db_util::ImportMode};
 use ahash::HashSet;
@@ -114,6 +115,10 @@ pub struct CliOpts {
     /// Choose network chain to sync to
     #[arg(long)]
     pub chain: Option<NetworkChain>,
+    /// A TOML or JSON file specifying the parameters of the network chain, e.g., as dumped by
+    /// `forest-tool chain-spec dump`
+    #[arg(long)]
+    pub chain_spec: Option<PathBuf>,
     /// Daemonize Forest process
     #[arg(long)]
     pub detach: bool,
@@ -158,6 +163,17 @@ pub struct CliOpts {
     /// Skip loading actors from the actors bundle.
     #[arg(long)]
     pub skip_load_actors: bool,
+    /// Run a single-node devnet, whose blocks are produced by this node every block delay, with
+    /// a mock beacon and made-up proofs
+    #[arg(long)]
+    pub devnet_solo: bool,
+    /// Miner actor of the blocks produced in `--devnet-solo` mode (default: `t01000`)
+    #[arg(long, requires = "devnet_solo")]
+    pub devnet_solo_miner: Option<Address>,
+    /// Also produce a block as soon as a message is pushed to the message pool in
+    /// `--devnet-solo` mode
+    #[arg(long, requires = "devnet_solo")]
+    pub devnet_solo_instant_seal: bool,
 }
 
 impl CliOpts {
@@ -167,6 +183,22 @@ impl CliOpts {
         if let Some(genesis_file) = &self.genesis {
             cfg.client.genesis_file = Some(genesis_file.to_owned());
         }
+        if let Some(chain_spec) = &self.chain_spec {
+            cfg.client.chain_spec = Some(chain_spec.to_owned());
+        }
+        if let Some(chain_spec) = &cfg.client.chain_spec {
+            let network = ChainConfig::from_spec_file(chain_spec)?.network;
+            if let Some(chain) = self.chain.as_ref().filter(|&chain| chain != &network) {
+                anyhow::bail!(
+                    "--chain {chain} conflicts with the {network} network of {}",
+                    chain_spec.display()
+                );
+            }
+            cfg.chain = network;
+        }
+        if self.devnet_solo && !cfg.chain.is_devnet() {
+            anyhow::bail!("--devnet-solo requires a devnet, not {}", cfg.chain);
+        }
         if self.rpc.unwrap_or(cfg.client.enable_rpc) {
             cfg.client.enable_rpc = true;
             cfg.client.rpc_filter_list = self.rpc_filter_list.clone();
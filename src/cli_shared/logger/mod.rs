@@ -2,31 +2,66 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use std::pin::Pin;
+use std::sync::OnceLock;
 
+use anyhow::Context as _;
 use futures::Future;
-use tracing_subscriber::{prelude::*, EnvFilter, Registry};
+use tracing_subscriber::{filter::LevelFilter, prelude::*, reload, EnvFilter, Registry};
 
-use crate::cli_shared::cli::CliOpts;
+use crate::cli_shared::cli::{CliOpts, OtlpConfig};
 use crate::utils::misc::LoggingColor;
 
+#[cfg(feature = "tracing-opentelemetry")]
+mod otlp;
+#[cfg(feature = "tracing-opentelemetry")]
+pub use otlp::set_remote_parent;
+
+/// Sets the parent of `span` to the trace context propagated in the HTTP `headers`, if any.
+#[cfg(not(feature = "tracing-opentelemetry"))]
+pub fn set_remote_parent(_span: &tracing::Span, _headers: &http::HeaderMap) {}
+
 type BackgroundTask = Pin<Box<dyn Future<Output = ()> + Send>>;
 
+type FilterHandle = reload::Handle<EnvFilter, Registry>;
+
+/// Handles of the console and file log filters, which can be changed at runtime with
+/// [`set_log_level`].
+static FILTER_HANDLES: OnceLock<Vec<FilterHandle>> = OnceLock::new();
+
 #[derive(Default)]
 pub struct Guards {
     #[cfg(feature = "tracing-chrome")]
     tracing_chrome: Option<tracing_chrome::FlushGuard>,
+    #[cfg(feature = "tracing-opentelemetry")]
+    tracer_provider: Option<opentelemetry_sdk::trace::TracerProvider>,
+}
+
+impl Drop for Guards {
+    fn drop(&mut self) {
+        // Export the remaining spans
+        #[cfg(feature = "tracing-opentelemetry")]
+        if let Some(provider) = self.tracer_provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to shut down the OpenTelemetry tracer provider: {e}");
+            }
+        }
+    }
 }
 
 #[allow(unused_mut)]
-pub fn setup_logger(opts: &CliOpts) -> (Vec<BackgroundTask>, Guards) {
+pub fn setup_logger(opts: &CliOpts, otlp: &OtlpConfig) -> (Vec<BackgroundTask>, Guards) {
     let mut background_tasks: Vec<BackgroundTask> = vec![];
     let mut guards = Guards::default();
+    let mut filter_handles = vec![];
     let mut layers: Vec<Box<dyn tracing_subscriber::layer::Layer<Registry> + Send + Sync>> =
         // console logger
         vec![Box::new(
             tracing_subscriber::fmt::Layer::new()
                 .with_ansi(opts.color.coloring_enabled())
-                .with_filter(get_env_filter(default_env_filter())),
+                .with_filter(reloadable_filter(
+                    get_env_filter(default_env_filter()),
+                    &mut filter_handles,
+                )),
         )];
 
     // file logger
@@ -36,7 +71,10 @@ pub fn setup_logger(opts: &CliOpts) -> (Vec<BackgroundTask>, Guards) {
             tracing_subscriber::fmt::Layer::new()
                 .with_ansi(false)
                 .with_writer(file_appender)
-                .with_filter(get_env_filter(default_env_filter())),
+                .with_filter(reloadable_filter(
+                    get_env_filter(default_env_filter()),
+                    &mut filter_handles,
+                )),
         ));
     }
 
@@ -84,6 +122,23 @@ pub fn setup_logger(opts: &CliOpts) -> (Vec<BackgroundTask>, Guards) {
         }
     }
 
+    if otlp.enabled {
+        #[cfg(not(feature = "tracing-opentelemetry"))]
+        tracing::warn!("`tracing-opentelemetry` is unavailable, forest binaries need to be recompiled with `tracing-opentelemetry` feature");
+
+        #[cfg(feature = "tracing-opentelemetry")]
+        match otlp::layer(otlp) {
+            Ok((layer, provider)) => {
+                guards.tracer_provider = Some(provider);
+                layers.push(Box::new(layer.with_filter(reloadable_filter(
+                    get_env_filter(default_env_filter()),
+                    &mut filter_handles,
+                ))));
+            }
+            Err(e) => eprintln!("Unable to create OpenTelemetry layer: {e}"),
+        }
+    }
+
     // Go to <https://ui.perfetto.dev> to browse trace files.
     // You may want to call ChromeLayerBuilder::trace_style as appropriate
     if let Some(_chrome_trace_file) = std::env::var_os("CHROME_TRACE_FILE") {
@@ -105,9 +160,71 @@ pub fn setup_logger(opts: &CliOpts) -> (Vec<BackgroundTask>, Guards) {
     }
 
     tracing_subscriber::registry().with(layers).init();
+    FILTER_HANDLES.set(filter_handles).ok();
     (background_tasks, guards)
 }
 
+fn reloadable_filter(
+    filter: EnvFilter,
+    handles: &mut Vec<FilterHandle>,
+) -> reload::Layer<EnvFilter, Registry> {
+    let (filter, handle) = reload::Layer::new(filter);
+    handles.push(handle);
+    filter
+}
+
+fn filter_handles() -> anyhow::Result<&'static [FilterHandle]> {
+    FILTER_HANDLES
+        .get()
+        .map(Vec::as_slice)
+        .filter(|it| !it.is_empty())
+        .context("log filters are not reloadable in this process")
+}
+
+/// Returns the directives of the active log filter, e.g. `info` or `libp2p_kad=error`.
+pub fn log_directives() -> anyhow::Result<Vec<String>> {
+    let handle = filter_handles()?
+        .first()
+        .context("log filters are not reloadable in this process")?;
+    let filter = handle.with_current(ToString::to_string)?;
+    // Directives are separated by commas, see `tracing_subscriber::filter::Builder::parse`.
+    Ok(filter
+        .split(',')
+        .filter(|it| !it.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Sets the log level of `target`, replacing any existing directive for it. The `*` target sets
+/// the default level.
+pub fn set_log_level(target: &str, level: &str) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !target.is_empty() && !target.contains([',', '=', '[', ']', '{', '}']),
+        "invalid log target: {target}"
+    );
+    let level: LevelFilter = level
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid log level: {level}"))?;
+    let directives = with_level(log_directives()?, target, level).join(",");
+    for handle in filter_handles()? {
+        handle.reload(EnvFilter::try_new(&directives)?)?;
+    }
+    Ok(())
+}
+
+/// Replaces the directives targeting exactly `target` with one setting `level`.
+fn with_level(directives: Vec<String>, target: &str, level: LevelFilter) -> Vec<String> {
+    let mut directives: Vec<_> = directives
+        .into_iter()
+        .filter(|it| it.rsplit_once('=').map_or("*", |(selector, _)| selector) != target)
+        .collect();
+    directives.push(match target {
+        "*" => level.to_string(),
+        _ => format!("{target}={level}"),
+    });
+    directives
+}
+
 // Log warnings to stderr
 pub fn setup_minimal_logger() {
     tracing_subscriber::registry()
@@ -171,3 +288,32 @@ fn default_tool_filter() -> EnvFilter {
 fn test_default_env_filter() {
     let _did_not_panic = default_env_filter();
 }
+
+#[test]
+fn test_with_level() {
+    let directives = vec![
+        "info".to_string(),
+        "axum=warn".to_string(),
+        "axum[request]=debug".to_string(),
+    ];
+    assert_eq!(
+        with_level(directives.clone(), "axum", LevelFilter::TRACE),
+        ["info", "axum[request]=debug", "axum=trace"]
+    );
+    assert_eq!(
+        with_level(directives, "*", LevelFilter::OFF),
+        ["axum=warn", "axum[request]=debug", "off"]
+    );
+}
+
+#[test]
+fn test_with_level_roundtrip() {
+    let directives = default_env_filter()
+        .to_string()
+        .split(',')
+        .map(String::from)
+        .collect();
+    let directives = with_level(directives, "forest::chain_sync", LevelFilter::DEBUG).join(",");
+    let filter = EnvFilter::try_new(&directives).unwrap();
+    assert!(filter.to_string().contains("forest::chain_sync=debug"));
+}
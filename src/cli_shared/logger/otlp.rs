@@ -0,0 +1,91 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Export of tracing spans to an `OpenTelemetry` collector, e.g. Tempo or Jaeger, over OTLP/HTTP.
+
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig as _;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+use opentelemetry_sdk::{runtime, Resource};
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt as _};
+use tracing_subscriber::Registry;
+
+use crate::cli_shared::cli::OtlpConfig;
+use crate::utils::version::FOREST_VERSION_STRING;
+
+/// Creates a layer exporting spans to the configured collector. Spans are exported in batches
+/// from a dedicated thread, so this doesn't require a Tokio runtime.
+pub(super) fn layer(
+    config: &OtlpConfig,
+) -> anyhow::Result<(OpenTelemetryLayer<Registry, Tracer>, TracerProvider)> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(&config.endpoint)
+        .build()?;
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::TokioCurrentThread)
+        .with_resource(Resource::new([
+            KeyValue::new("service.name", config.service_name.clone()),
+            KeyValue::new("service.version", FOREST_VERSION_STRING.clone()),
+        ]))
+        .build();
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    let tracer = provider.tracer(env!("CARGO_PKG_NAME"));
+    Ok((tracing_opentelemetry::layer().with_tracer(tracer), provider))
+}
+
+/// Sets the parent of `span` to the [trace context](https://www.w3.org/TR/trace-context/)
+/// propagated in the HTTP `headers`, if any.
+pub fn set_remote_parent(span: &tracing::Span, headers: &http::HeaderMap) {
+    let context = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(headers))
+    });
+    span.set_parent(context);
+}
+
+struct HeaderExtractor<'a>(&'a http::HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(http::HeaderName::as_str).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::propagation::TextMapPropagator as _;
+    use opentelemetry::trace::TraceContextExt as _;
+
+    #[test]
+    fn layer_does_not_require_a_runtime() {
+        let (_layer, provider) = layer(&OtlpConfig::default()).unwrap();
+        provider.shutdown().unwrap();
+    }
+
+    #[test]
+    fn extract_trace_context_from_headers() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert(
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+                .parse()
+                .unwrap(),
+        );
+        let context = TraceContextPropagator::new().extract(&HeaderExtractor(&headers));
+        let span_context = context.span().span_context().clone();
+        assert!(span_context.is_remote());
+        assert_eq!(
+            span_context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(span_context.span_id().to_string(), "00f067aa0ba902b7");
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 use crate::auth::{create_token, generate_priv_key, ADMIN};
+use crate::beacon::{mock_beacon::MockBeacon, BeaconPoint, BeaconSchedule};
 use crate::chain::ChainStore;
 use crate::cli_shared::chain_path;
 use crate::cli_shared::cli::CliOpts;
@@ -44,10 +45,10 @@ pub struct AppContext {
 
 impl AppContext {
     pub async fn init(opts: &CliOpts, cfg: &Config) -> anyhow::Result<AppContext> {
-        let chain_cfg = get_chain_config_and_set_network(cfg);
+        let chain_cfg = get_chain_config_and_set_network(cfg)?;
         let (net_keypair, p2p_peer_id) = get_or_create_p2p_keypair_and_peer_id(cfg)?;
         let (db, db_meta_data) = setup_db(opts, cfg).await?;
-        let state_manager = create_state_manager(cfg, &db, &chain_cfg).await?;
+        let state_manager = create_state_manager(opts, cfg, &db, &chain_cfg).await?;
         let (keystore, admin_jwt) = load_or_create_keystore_and_configure_jwt(opts, cfg).await?;
         let network_name = state_manager.get_network_name_from_genesis()?;
         let snapshot_progress_tracker = SnapshotProgressTracker::default();
@@ -65,12 +66,15 @@ impl AppContext {
     }
 }
 
-fn get_chain_config_and_set_network(config: &Config) -> Arc<ChainConfig> {
-    let chain_config = Arc::new(ChainConfig::from_chain(config.chain()));
+fn get_chain_config_and_set_network(config: &Config) -> anyhow::Result<Arc<ChainConfig>> {
+    let chain_config = Arc::new(match &config.client.chain_spec {
+        Some(path) => ChainConfig::from_spec_file(path)?,
+        None => ChainConfig::from_chain(config.chain()),
+    });
     if chain_config.is_testnet() {
         CurrentNetwork::set_global(Network::Testnet);
     }
-    chain_config
+    Ok(chain_config)
 }
 
 fn get_or_create_p2p_keypair_and_peer_id(config: &Config) -> anyhow::Result<(Keypair, PeerId)> {
@@ -211,6 +215,7 @@ async fn setup_db(opts: &CliOpts, config: &Config) -> anyhow::Result<(Arc<DbType
 }
 
 async fn create_state_manager(
+    opts: &CliOpts,
     config: &Config,
     db: &Arc<DbType>,
     chain_config: &Arc<ChainConfig>,
@@ -224,6 +229,17 @@ async fn create_state_manager(
         db,
     )
     .await?;
+    if let Some(genesis_cid) = chain_config
+        .genesis_cid
+        .as_ref()
+        .filter(|_| config.client.chain_spec.is_some())
+    {
+        anyhow::ensure!(
+            genesis_cid == &genesis_header.cid().to_string(),
+            "genesis {} doesn't match the genesis_cid {genesis_cid} of the chain specification",
+            genesis_header.cid()
+        );
+    }
 
     let eth_mappings: Arc<dyn EthMappingsStore + Sync + Send> =
         if config.chain_indexer.enable_indexer {
@@ -231,19 +247,28 @@ async fn create_state_manager(
         } else {
             Arc::new(DummyStore {})
         };
-    let chain_store = Arc::new(ChainStore::new(
-        Arc::clone(db),
-        Arc::new(db.clone()),
-        eth_mappings,
-        chain_config.clone(),
-        genesis_header.clone(),
-    )?);
+    let chain_store = Arc::new(
+        ChainStore::new(
+            Arc::clone(db),
+            Arc::new(db.clone()),
+            eth_mappings,
+            chain_config.clone(),
+            genesis_header.clone(),
+        )?
+        .with_height_index(db.writer().clone())?,
+    );
 
     // Initialize StateManager
-    let state_manager = Arc::new(StateManager::new(
-        Arc::clone(&chain_store),
-        Arc::clone(chain_config),
-    )?);
+    let mut state_manager = StateManager::new(Arc::clone(&chain_store), Arc::clone(chain_config))?;
+    if opts.devnet_solo {
+        // Solo devnets do not depend on `drand`
+        state_manager =
+            state_manager.with_beacon_schedule(Arc::new(BeaconSchedule(vec![BeaconPoint {
+                height: 0,
+                beacon: Box::<MockBeacon>::default(),
+            }])));
+    }
+    let state_manager = Arc::new(state_manager);
 
     Ok(state_manager)
 }
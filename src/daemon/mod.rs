@@ -7,9 +7,9 @@ pub mod db_util;
 pub mod main;
 
 use crate::blocks::Tipset;
-use crate::chain::HeadChange;
+use crate::chain::{ChainStore, HeadChange};
 use crate::chain_sync::network_context::SyncNetworkContext;
-use crate::chain_sync::ChainFollower;
+use crate::chain_sync::{BlockProducer, ChainFollower, SyncStage};
 use crate::cli_shared::{car_db_path, snapshot};
 use crate::cli_shared::{
     chain_path,
@@ -21,13 +21,17 @@ use crate::daemon::db_util::{
 };
 use crate::db::car::ManyCar;
 use crate::db::SettingsStore;
-use crate::db::{ttl::EthMappingCollector, MarkAndSweep, MemoryDB, SettingsExt};
+use crate::db::{
+    ttl::EthMappingCollector, ChainPruner, MarkAndSweep, MemoryDB, Prune, SettingsExt,
+};
 use crate::libp2p::{Libp2pService, PeerManager};
 use crate::message_pool::{MessagePool, MpoolConfig, MpoolRpcProvider};
 use crate::networks::{self, ChainConfig};
-use crate::rpc::eth::filter::EthEventHandler;
+use crate::paychmgr::PaychStore;
+use crate::rpc::eth::filter::{index::EventIndex, EthEventHandler};
 use crate::rpc::start_rpc;
 use crate::rpc::RPCState;
+use crate::shim::address::Address;
 use crate::shim::clock::ChainEpoch;
 use crate::shim::version::NetworkVersion;
 use crate::state_manager::StateManager;
@@ -53,7 +57,7 @@ use tokio::{
         ctrl_c,
         unix::{signal, SignalKind},
     },
-    sync::mpsc,
+    sync::{broadcast::error::RecvError, mpsc, watch},
     task::JoinSet,
 };
 use tracing::{debug, info, warn};
@@ -113,9 +117,10 @@ pub async fn start_interruptable(opts: CliOpts, config: Config) -> anyhow::Resul
     let start_time = chrono::Utc::now();
     let mut terminate = signal(SignalKind::terminate())?;
     let (shutdown_send, mut shutdown_recv) = mpsc::channel(1);
+    let mut peer_manager = None;
 
     let result = tokio::select! {
-        ret = start(start_time, opts, config, shutdown_send) => ret,
+        ret = start(start_time, opts, config, shutdown_send, &mut peer_manager) => ret,
         _ = ctrl_c() => {
             info!("Keyboard interrupt.");
             Ok(())
@@ -129,6 +134,12 @@ pub async fn start_interruptable(opts: CliOpts, config: Config) -> anyhow::Resul
             Ok(())
         },
     };
+    // The peer set is otherwise only saved periodically
+    if let Some(peer_manager) = peer_manager {
+        if let Err(e) = peer_manager.save_peer_store().await {
+            warn!("failed to save the peer store: {e:#}");
+        }
+    }
     crate::utils::io::terminal_cleanup();
     result
 }
@@ -136,6 +147,12 @@ pub async fn start_interruptable(opts: CliOpts, config: Config) -> anyhow::Resul
 // Garbage collection interval, currently set at 10 hours.
 const GC_INTERVAL: Duration = Duration::from_secs(60 * 60 * 10);
 
+// Default miner of solo devnets, the first miner created by `forest-tool genesis create`.
+const DEVNET_SOLO_MINER_ID: u64 = 1000;
+
+// Number of tipsets walked per height index backfill step.
+const HEIGHT_INDEX_BACKFILL_BATCH_SIZE: usize = 10_000;
+
 /// This function initialize Forest with below steps
 /// - increase file descriptor limit (for parity-db)
 /// - setup proofs parameter cache directory
@@ -305,8 +322,9 @@ async fn create_p2p_service(
         config.network.bootstrap_peers = ctx.state_manager.chain_config().bootstrap_peers.clone();
     }
 
-    let peer_manager = Arc::new(PeerManager::default());
+    let peer_manager = Arc::new(PeerManager::with_peer_store(ctx.db.writer().clone())?);
     services.spawn(peer_manager.clone().peer_operation_event_loop_task());
+    services.spawn(peer_manager.clone().peer_store_task());
     // Libp2p service setup
     let p2p_service = Libp2pService::new(
         config.network.clone(),
@@ -406,6 +424,8 @@ fn maybe_start_rpc_service(
     chain_follower: &ChainFollower<DbType>,
     start_time: chrono::DateTime<chrono::Utc>,
     shutdown: mpsc::Sender<()>,
+    event_index: Option<Arc<EventIndex>>,
+    block_producer: Option<Arc<BlockProducer<DbType>>>,
     ctx: &AppContext,
 ) -> anyhow::Result<()> {
     if config.client.enable_rpc {
@@ -416,8 +436,23 @@ fn maybe_start_rpc_service(
             .as_ref()
             .map(|path| crate::rpc::FilterList::new_from_file(path))
             .transpose()?;
+        let rate_limiter = config
+            .rpc_rate_limit
+            .enabled
+            .then(|| crate::rpc::RateLimiter::new(config.rpc_rate_limit.clone()));
+        let response_cache = config
+            .rpc_cache
+            .enabled
+            .then(|| crate::rpc::ResponseCache::new(config.rpc_cache.clone()));
+        let rpc_batch = config.rpc_batch.clone();
         info!("JSON-RPC endpoint will listen at {rpc_address}");
-        let eth_event_handler = Arc::new(EthEventHandler::from_config(&config.events));
+        let eth_event_handler = {
+            let handler = EthEventHandler::from_config(&config.events);
+            Arc::new(match event_index {
+                Some(event_index) => handler.with_event_index(event_index),
+                None => handler,
+            })
+        };
         services.spawn({
             let state_manager = ctx.state_manager.clone();
             let bad_blocks = chain_follower.bad_blocks.clone();
@@ -425,6 +460,22 @@ fn maybe_start_rpc_service(
             let sync_network_context = chain_follower.network.clone();
             let tipset_send = chain_follower.tipset_sender.clone();
             let keystore = ctx.keystore.clone();
+            let paych_store = Arc::new(PaychStore::new(ctx.db.writer().clone()));
+            let settings_store = ctx.db.writer().clone();
+            let chain_pruner = {
+                let chain_store = ctx.state_manager.chain_store().clone();
+                let chain_finality = ctx.state_manager.chain_config().policy.chain_finality;
+                let depth = cmp::max(chain_finality * 2, config.sync.recent_state_roots);
+                let get_heaviest_tipset = Box::new(move || chain_store.heaviest_tipset());
+                let pruner: Arc<dyn Prune> = Arc::new(ChainPruner::new(
+                    ctx.db.writer().clone(),
+                    get_heaviest_tipset,
+                    depth,
+                    chain_finality,
+                    Duration::from_secs(ctx.state_manager.chain_config().block_delay_secs as u64),
+                ));
+                Some(pruner)
+            };
             let network_name = ctx.network_name.clone();
             let snapshot_progress_tracker = ctx.snapshot_progress_tracker.clone();
             let msgs_in_tipset = Arc::new(crate::chain::MsgsInTipsetCache::default());
@@ -438,6 +489,10 @@ fn maybe_start_rpc_service(
                         msgs_in_tipset,
                         sync_states,
                         eth_event_handler,
+                        paych_store,
+                        settings_store,
+                        chain_pruner,
+                        block_producer,
                         sync_network_context,
                         network_name,
                         start_time,
@@ -447,6 +502,9 @@ fn maybe_start_rpc_service(
                     },
                     rpc_address,
                     filter_list,
+                    rate_limiter,
+                    response_cache,
+                    rpc_batch,
                 )
                 .await
             }
@@ -533,10 +591,83 @@ fn maybe_populate_eth_mappings_in_background(
     }
 }
 
+/// Keeps the height index of the chain store up to date with the head, off the head change path,
+/// and extends it down to genesis whenever it is (re)started, so that old tipsets can quickly be
+/// looked up by height.
+fn maybe_start_height_indexer(
+    services: &mut JoinSet<anyhow::Result<()>>,
+    opts: &CliOpts,
+    ctx: &AppContext,
+) {
+    if !opts.stateless {
+        let chain_store = ctx.state_manager.chain_store().clone();
+        let mut head_changes = chain_store.publisher().subscribe();
+        services.spawn(async move {
+            // The index is backfilled at startup, in case a previous backfill was interrupted
+            let mut backfilling = true;
+            loop {
+                let chain_store = chain_store.clone();
+                backfilling = tokio::task::spawn_blocking(move || {
+                    update_height_index(&chain_store, backfilling)
+                })
+                .await?;
+                if backfilling {
+                    // Only the latest head matters, the next step indexes it
+                    head_changes = head_changes.resubscribe();
+                } else if let Err(RecvError::Closed) = head_changes.recv().await {
+                    break Ok(());
+                }
+            }
+        });
+    }
+}
+
+/// Indexes the current head of `chain_store` and, if `backfilling` or if the index has been
+/// restarted from the head, a batch of older tipsets. Returns whether there are older tipsets left
+/// to index.
+fn update_height_index(chain_store: &ChainStore<DbType>, backfilling: bool) -> bool {
+    let chain_index = &chain_store.chain_index;
+    let restarted = match chain_index.update_height_index(&chain_store.heaviest_tipset()) {
+        Ok(restarted) => restarted,
+        Err(e) => {
+            warn!("Failed to update the height index: {e:#}");
+            false
+        }
+    };
+    if !(backfilling || restarted) {
+        return false;
+    }
+    match chain_index.backfill_height_index(HEIGHT_INDEX_BACKFILL_BATCH_SIZE) {
+        Ok(true) => true,
+        Ok(false) => {
+            debug!("Height index backfill completed");
+            false
+        }
+        Err(e) => {
+            warn!("Height index backfill failed: {e:#}");
+            false
+        }
+    }
+}
+
+/// Creates the persistent actor event index. It is maintained by the indexer service, see
+/// [`maybe_start_indexer_service`].
+fn create_event_index(
+    opts: &CliOpts,
+    config: &Config,
+    ctx: &AppContext,
+) -> Option<Arc<EventIndex>> {
+    (config.chain_indexer.enable_indexer
+        && !opts.stateless
+        && !ctx.state_manager.chain_config().is_devnet())
+    .then(|| Arc::new(EventIndex::new(ctx.db.writer().clone())))
+}
+
 fn maybe_start_indexer_service(
     services: &mut JoinSet<anyhow::Result<()>>,
     opts: &CliOpts,
     config: &Config,
+    event_index: Option<Arc<EventIndex>>,
     ctx: &AppContext,
 ) {
     if config.chain_indexer.enable_indexer
@@ -545,22 +676,58 @@ fn maybe_start_indexer_service(
     {
         let mut receiver = ctx.state_manager.chain_store().publisher().subscribe();
         let chain_store = ctx.state_manager.chain_store().clone();
+        // Re-executing tipsets to index their events is slow, so it is done by a separate task
+        // that always catches up with the latest head. This keeps the head change subscriber
+        // below from lagging behind.
+        let (head_tx, head_rx) = watch::channel(None);
+        if let Some(event_index) = event_index {
+            let state_manager = ctx.state_manager.clone();
+            let event_lookback = config.events.max_filter_height_range;
+            let retention_epochs = config.chain_indexer.gc_retention_epochs;
+            services.spawn(run_event_indexer(
+                event_index,
+                state_manager,
+                head_rx,
+                event_lookback,
+                retention_epochs,
+            ));
+        }
         services.spawn(async move {
             tracing::info!("Starting indexer service");
 
-            // Continuously listen for head changes
-            loop {
-                let msg = receiver.recv().await?;
-
-                let HeadChange::Apply(ts) = msg;
+            let index_tipset = |ts: &Tipset| -> anyhow::Result<()> {
                 tracing::debug!("Indexing tipset {}", ts.key());
-
                 chain_store.put_tipset_key(ts.key())?;
-
                 let delegated_messages =
                     chain_store.headers_delegated_messages(ts.block_headers().iter())?;
+                chain_store.process_signed_messages(&delegated_messages)
+            };
 
-                chain_store.process_signed_messages(&delegated_messages)?;
+            // Continuously listen for head changes
+            let mut last_epoch = None;
+            loop {
+                let head = match receiver.recv().await {
+                    Ok(HeadChange::Apply(ts)) => {
+                        index_tipset(&ts)?;
+                        ts
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Indexer head change subscriber lagged, skipped {skipped} events");
+                        // Re-sync from the current head, down to the last indexed epoch
+                        let head = chain_store.heaviest_tipset();
+                        let missed: Vec<_> = Tipset::clone(&head)
+                            .chain(chain_store.blockstore())
+                            .take_while(|ts| ts.epoch() > last_epoch.unwrap_or(head.epoch() - 1))
+                            .collect();
+                        for ts in missed.iter().rev() {
+                            index_tipset(ts)?;
+                        }
+                        head
+                    }
+                    Err(RecvError::Closed) => break Ok(()),
+                };
+                last_epoch = Some(head.epoch());
+                head_tx.send_replace(Some(head));
             }
         });
 
@@ -581,12 +748,112 @@ fn maybe_start_indexer_service(
     }
 }
 
-/// Starts daemon process
+/// Keeps `event_index` up to date with the latest head sent on `head_rx`. Heads that are replaced
+/// while a previous one is being indexed are skipped, [`EventIndex::apply`] indexes all the
+/// tipsets up to the latest head anyway.
+async fn run_event_indexer(
+    event_index: Arc<EventIndex>,
+    state_manager: Arc<StateManager<DbType>>,
+    mut head_rx: watch::Receiver<Option<Arc<Tipset>>>,
+    event_lookback: ChainEpoch,
+    retention_epochs: Option<u32>,
+) -> anyhow::Result<()> {
+    while head_rx.changed().await.is_ok() {
+        let Some(ts) = head_rx.borrow_and_update().clone() else {
+            continue;
+        };
+        if let Err(e) = event_index.apply(&state_manager, &ts, event_lookback).await {
+            tracing::warn!("Failed to index events of tipset {}: {e:#}", ts.key());
+        }
+        if let Some(retention_epochs) = retention_epochs {
+            let retain_from = ts.epoch() - ChainEpoch::from(retention_epochs);
+            if let Err(e) = event_index.prune_before(retain_from) {
+                tracing::warn!("Failed to prune event index: {e:#}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Creates the block producer of a solo devnet, see [`maybe_start_block_producer_service`].
+fn create_block_producer(
+    opts: &CliOpts,
+    mpool: Arc<MessagePool<MpoolRpcProvider<DbType>>>,
+    ctx: &AppContext,
+) -> anyhow::Result<Option<Arc<BlockProducer<DbType>>>> {
+    opts.devnet_solo
+        .then(|| {
+            let miner = opts
+                .devnet_solo_miner
+                .unwrap_or(Address::new_id(DEVNET_SOLO_MINER_ID));
+            BlockProducer::new(ctx.state_manager.clone(), mpool, miner).map(Arc::new)
+        })
+        .transpose()
+}
+
+/// Produces the blocks of a solo devnet, every block delay and, with instant sealing, whenever a
+/// message is added to the message pool.
+fn maybe_start_block_producer_service(
+    services: &mut JoinSet<anyhow::Result<()>>,
+    opts: &CliOpts,
+    block_producer: Option<Arc<BlockProducer<DbType>>>,
+    mpool: &MessagePool<MpoolRpcProvider<DbType>>,
+    chain_follower: &ChainFollower<DbType>,
+    ctx: &AppContext,
+) {
+    let Some(producer) = block_producer else {
+        return;
+    };
+    let mut pending = opts
+        .devnet_solo_instant_seal
+        .then(|| mpool.subscribe_pending());
+    let block_delay = Duration::from_secs(ctx.state_manager.chain_config().block_delay_secs as u64);
+    let sync_states = chain_follower.sync_states.clone();
+    services.spawn(async move {
+        info!("Producing the blocks of miner {}", producer.miner());
+        let mut interval = tokio::time::interval(block_delay);
+        loop {
+            match &mut pending {
+                Some(pending) => {
+                    tokio::select! {
+                        _ = interval.tick() => {}
+                        received = pending.recv() => {
+                            if let Err(RecvError::Closed) = received {
+                                anyhow::bail!("message pool closed");
+                            }
+                            // Messages pushed in the meantime are included in the same block
+                            while pending.try_recv().is_ok() {}
+                        }
+                    }
+                }
+                None => {
+                    interval.tick().await;
+                }
+            }
+            let epoch = producer.next_epoch(chrono::Utc::now().timestamp() as u64);
+            match producer.produce_tipset(epoch).await {
+                Ok(tipset) => {
+                    // There is nothing to sync from in a solo devnet
+                    let mut sync_states = sync_states.write();
+                    let sync_state = sync_states.first_mut();
+                    sync_state.set_epoch(tipset.epoch());
+                    sync_state.set_target(Some(tipset));
+                    sync_state.set_stage(SyncStage::Complete);
+                }
+                Err(e) => warn!("Failed to produce a tipset at epoch {epoch}: {e:#}"),
+            }
+        }
+    });
+}
+
+/// Starts daemon process. `peer_manager` is set once the peer manager is created, so that its peer
+/// set can be saved at shutdown.
 pub(super) async fn start(
     start_time: chrono::DateTime<chrono::Utc>,
     opts: CliOpts,
     mut config: Config,
     shutdown_send: mpsc::Sender<()>,
+    peer_manager: &mut Option<Arc<PeerManager>>,
 ) -> anyhow::Result<()> {
     startup_init(&opts, &config)?;
     let mut services = JoinSet::new();
@@ -601,11 +868,16 @@ pub(super) async fn start(
         return Ok(());
     }
     let p2p_service = create_p2p_service(&mut services, &mut config, &ctx).await?;
+    *peer_manager = Some(p2p_service.peer_manager().clone());
 
     let mpool = create_mpool(&mut services, &p2p_service, &ctx)?;
 
     let chain_follower = create_chain_follower(&opts, &p2p_service, mpool.clone(), &ctx)?;
 
+    let event_index = create_event_index(&opts, &config, &ctx);
+
+    let block_producer = create_block_producer(&opts, mpool.clone(), &ctx)?;
+
     info!(
         "Starting network:: {}",
         get_actual_chain_name(&ctx.network_name)
@@ -618,6 +890,8 @@ pub(super) async fn start(
         &chain_follower,
         start_time,
         shutdown_send.clone(),
+        event_index.clone(),
+        block_producer.clone(),
         &ctx,
     )?;
 
@@ -633,7 +907,16 @@ pub(super) async fn start(
     maybe_start_health_check_service(&mut services, &config, &p2p_service, &chain_follower, &ctx)
         .await?;
     maybe_populate_eth_mappings_in_background(&mut services, &opts, config.clone(), &ctx);
-    maybe_start_indexer_service(&mut services, &opts, &config, &ctx);
+    maybe_start_height_indexer(&mut services, &opts, &ctx);
+    maybe_start_indexer_service(&mut services, &opts, &config, event_index, &ctx);
+    maybe_start_block_producer_service(
+        &mut services,
+        &opts,
+        block_producer,
+        &mpool,
+        &chain_follower,
+        &ctx,
+    );
     if !opts.stateless {
         ensure_proof_params_downloaded().await?;
     }
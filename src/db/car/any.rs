@@ -6,7 +6,9 @@
 //! header and the first key-value block, and picks the appropriate block store
 //! (either [`super::ForestCar`] or [`super::PlainCar`]).
 //!
-//! CARv2 is not supported yet.
+//! Plain `.car` files may be either CARv1 or CARv2, CARv2 files are read in
+//! place, as written by [`super::carv2::Encoder`]. Their embedded index is
+//! not used as it does not record the codecs of the CIDs.
 
 use super::{CacheKey, RandomAccessFileReader, ZstdFrameCache};
 use crate::blocks::{Tipset, TipsetKey};
@@ -28,7 +30,7 @@ pub enum AnyCar<ReaderT> {
 }
 
 impl<ReaderT: RandomAccessFileReader> AnyCar<ReaderT> {
-    /// Open an archive. May be formatted as `.car`, `.car.zst` or
+    /// Open an archive. May be formatted as `.car` (CARv1 or CARv2), `.car.zst` or
     /// `.forest.car.zst`. This call may block for an indeterminate amount of
     /// time while data is decoded and indexed.
     pub fn new(reader: ReaderT) -> Result<Self> {
@@ -70,7 +72,7 @@ impl<ReaderT: RandomAccessFileReader> AnyCar<ReaderT> {
         }
     }
 
-    /// Return the identified CAR format variant. There are three variants:
+    /// Return the identified CAR format variant. There are five variants:
     /// `CARv1`, `CARv2`, `CARv1.zst`, `CARv2.zst` and `ForestCARv1.zst`.
     pub fn variant(&self) -> Cow<'static, str> {
         match self {
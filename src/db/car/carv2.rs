@@ -0,0 +1,284 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! # CARv2 layout
+//!
+//! A [CARv2](https://ipld.io/specs/transport/car/carv2/) archive wraps a plain CARv1 payload
+//! between a fixed-size header and an optional index.
+//!
+//! ```text
+//! ├──────┬─────────┬─────────────────────────────────┬──────────────────────┤
+//! │pragma│v2 header│CARv1 payload (header and blocks)│MultihashIndexSorted  │
+//! └──────┴─────────┼─────────────────────────────────┼──────────────────────┘
+//!                  │◄──────────── data_size ────────►│
+//!      data_offset►│                    index_offset►│
+//! ```
+//!
+//! The header carries the offsets of the payload and the index, which are only known once the
+//! payload has been written, so the [`Encoder`] requires a seekable sink to fill it in.
+//!
+//! # Index
+//!
+//! The index follows the [`MultihashIndexSorted`](https://ipld.io/specs/transport/car/carv2/#format-0x0401-multihashindexsorted)
+//! format, which is what `go-car` and hence Boost and other IPFS tooling produce by default. It
+//! is a varint codec followed by little-endian encoded buckets:
+//!
+//! ```text
+//! i32 number of multihash codes
+//! for each multihash code, ascending:
+//!     u64 multihash code
+//!     i32 number of digest widths
+//!     for each width (digest length + 8), ascending:
+//!         u32 width
+//!         i64 byte length of the entries
+//!         entries sorted by digest: digest || u64 block frame offset in the payload
+//! ```
+//!
+//! Identity CIDs are not indexed, as their data is inlined in the CID.
+
+use crate::utils::db::car_stream::{CarBlock, CarV1Header};
+use bytes::{Bytes, BytesMut};
+use cid::Cid;
+use futures::{TryStream, TryStreamExt as _};
+use fvm_ipld_encoding::to_vec;
+use integer_encoding::VarInt as _;
+use nunny::Vec as NonEmpty;
+use std::collections::BTreeMap;
+use std::io::SeekFrom;
+use tokio::io::{AsyncSeek, AsyncSeekExt as _, AsyncWrite, AsyncWriteExt as _};
+use tokio_util::codec::Encoder as _;
+use unsigned_varint::codec::UviBytes;
+
+/// <https://ipld.io/specs/transport/car/carv2/#pragma>
+pub const CAR_V2_PRAGMA: [u8; 11] = [
+    0x0a, 0xa1, 0x67, 0x76, 0x65, 0x72, 0x73, 0x69, 0x6f, 0x6e, 0x02,
+];
+
+/// Length of the fixed-size header following the pragma.
+const CAR_V2_HEADER_LEN: u64 = 40;
+
+/// <https://github.com/multiformats/multicodec/blob/master/table.csv>
+const MULTIHASH_INDEX_SORTED_CODEC: u64 = 0x0401;
+
+/// Multihash code of identity hashes.
+const IDENTITY_CODE: u64 = 0x00;
+
+pub struct Encoder {}
+
+impl Encoder {
+    /// Writes a CARv2 archive with a `MultihashIndexSorted` index. Blocks are written in the
+    /// order of the stream, uncompressed.
+    pub async fn write(
+        mut sink: impl AsyncWrite + AsyncSeek + Unpin,
+        roots: NonEmpty<Cid>,
+        mut stream: impl TryStream<Ok = CarBlock, Error = anyhow::Error> + Unpin,
+    ) -> anyhow::Result<()> {
+        let start = sink.stream_position().await?;
+        let data_offset = CAR_V2_PRAGMA.len() as u64 + CAR_V2_HEADER_LEN;
+
+        // Reserve space for the header, it is written once the payload and index offsets are known.
+        sink.write_all(&CAR_V2_PRAGMA).await?;
+        sink.write_all(&[0; CAR_V2_HEADER_LEN as usize]).await?;
+
+        // Write CARv1 payload and collect the offsets of the block frames.
+        let header = CarV1Header { roots, version: 1 };
+        let mut header_uvi_frame = BytesMut::new();
+        UviBytes::default().encode(Bytes::from(to_vec(&header)?), &mut header_uvi_frame)?;
+        sink.write_all(&header_uvi_frame).await?;
+        let mut offset = header_uvi_frame.len() as u64;
+
+        let mut index = IndexBuilder::default();
+        let mut frame = vec![];
+        while let Some(block) = stream.try_next().await? {
+            index.insert(&block.cid, offset);
+            frame.clear();
+            block.write(&mut frame)?;
+            sink.write_all(&frame).await?;
+            offset += frame.len() as u64;
+        }
+        let data_size = offset;
+        let index_offset = data_offset + data_size;
+
+        index.write_into(&mut sink).await?;
+        let end = sink.stream_position().await?;
+
+        let mut header = Vec::with_capacity(CAR_V2_HEADER_LEN as usize);
+        // Characteristics, the index is not marked as fully indexed as identity CIDs are skipped.
+        header.extend_from_slice(&[0; 16]);
+        header.extend_from_slice(&data_offset.to_le_bytes());
+        header.extend_from_slice(&data_size.to_le_bytes());
+        header.extend_from_slice(&index_offset.to_le_bytes());
+        sink.seek(SeekFrom::Start(start + CAR_V2_PRAGMA.len() as u64))
+            .await?;
+        sink.write_all(&header).await?;
+        sink.seek(SeekFrom::Start(end)).await?;
+        Ok(())
+    }
+}
+
+/// Index entries grouped by multihash code and digest width. Each entry is the digest followed by
+/// the little-endian frame offset.
+#[derive(Default)]
+struct IndexBuilder {
+    buckets: BTreeMap<u64, BTreeMap<u32, Vec<u8>>>,
+}
+
+impl IndexBuilder {
+    fn insert(&mut self, cid: &Cid, offset: u64) {
+        let hash = cid.hash();
+        if hash.code() == IDENTITY_CODE {
+            return;
+        }
+        let digest = hash.digest();
+        let width = digest.len() as u32 + 8;
+        let entries = self
+            .buckets
+            .entry(hash.code())
+            .or_default()
+            .entry(width)
+            .or_default();
+        entries.extend_from_slice(digest);
+        entries.extend_from_slice(&offset.to_le_bytes());
+    }
+
+    async fn write_into(self, sink: &mut (impl AsyncWrite + Unpin)) -> std::io::Result<()> {
+        sink.write_all(&MULTIHASH_INDEX_SORTED_CODEC.encode_var_vec())
+            .await?;
+        sink.write_all(&(self.buckets.len() as i32).to_le_bytes())
+            .await?;
+        for (code, widths) in self.buckets {
+            sink.write_all(&code.to_le_bytes()).await?;
+            sink.write_all(&(widths.len() as i32).to_le_bytes()).await?;
+            for (width, entries) in widths {
+                let mut sorted: Vec<_> = entries.chunks_exact(width as usize).collect();
+                sorted.sort_unstable();
+                sink.write_all(&width.to_le_bytes()).await?;
+                sink.write_all(&(entries.len() as i64).to_le_bytes())
+                    .await?;
+                for entry in sorted {
+                    sink.write_all(entry).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::car::{AnyCar, PlainCar};
+    use crate::utils::db::car_stream::CarStream;
+    use crate::utils::multihash::prelude::*;
+    use ahash::HashMap;
+    use fvm_ipld_blockstore::Blockstore as _;
+    use integer_encoding::{FixedIntReader as _, VarIntReader as _};
+    use quickcheck_macros::quickcheck;
+    use std::io::{Cursor, Read as _};
+
+    /// Reads a `MultihashIndexSorted` index into a map of `(multihash code, digest)` to frame offsets.
+    fn read_index(mut index: &[u8]) -> anyhow::Result<HashMap<(u64, Vec<u8>), u64>> {
+        let codec: u64 = index.read_varint()?;
+        anyhow::ensure!(codec == MULTIHASH_INDEX_SORTED_CODEC, "unexpected codec");
+        let mut offsets = HashMap::default();
+        let codes: i32 = index.read_fixedint()?;
+        for _ in 0..codes {
+            let code: u64 = index.read_fixedint()?;
+            let widths: i32 = index.read_fixedint()?;
+            for _ in 0..widths {
+                let width: u32 = index.read_fixedint()?;
+                let len: i64 = index.read_fixedint()?;
+                let mut entries = vec![0; len as usize];
+                index.read_exact(&mut entries)?;
+                let entries: Vec<_> = entries.chunks_exact(width as usize).collect();
+                anyhow::ensure!(entries.is_sorted(), "index entries are not sorted");
+                for entry in entries {
+                    let (digest, offset) = entry.split_at(width as usize - 8);
+                    let offset = u64::from_le_bytes(offset.try_into()?);
+                    offsets.insert((code, digest.to_vec()), offset);
+                }
+            }
+        }
+        Ok(offsets)
+    }
+
+    fn encode(blocks: Vec<CarBlock>) -> Vec<u8> {
+        let roots = NonEmpty::new(vec![blocks.first().unwrap().cid]).unwrap();
+        let mut car = Cursor::new(vec![]);
+        crate::block_on(Encoder::write(
+            &mut car,
+            roots,
+            futures::stream::iter(blocks.into_iter().map(Ok)),
+        ))
+        .unwrap();
+        car.into_inner()
+    }
+
+    #[quickcheck]
+    fn carv2_roundtrip(head: CarBlock, tail: Vec<CarBlock>) {
+        let mut blocks = vec![head];
+        blocks.extend(tail);
+        let car = encode(blocks.clone());
+
+        let plain = PlainCar::new(car.clone()).unwrap();
+        assert_eq!(plain.version(), 2);
+        for block in &blocks {
+            assert_eq!(plain.get(&block.cid).unwrap(), Some(block.data.clone()));
+        }
+
+        let any = AnyCar::new(car.clone()).unwrap();
+        assert_eq!(any.variant(), "CARv2");
+
+        let streamed: Vec<_> = crate::block_on(async {
+            CarStream::new(Cursor::new(car))
+                .await
+                .unwrap()
+                .try_collect()
+                .await
+                .unwrap()
+        });
+        assert_eq!(streamed, blocks);
+    }
+
+    #[quickcheck]
+    fn carv2_index_points_at_blocks(head: CarBlock, tail: Vec<CarBlock>) {
+        let mut blocks = vec![head];
+        blocks.extend(tail);
+        let car = encode(blocks.clone());
+
+        let header = crate::db::car::plain::read_v2_header(car.as_slice())
+            .unwrap()
+            .unwrap();
+        let (_, index) = car.split_at(header.index_offset as usize);
+        let (_, payload) = car.split_at(header.data_offset as usize);
+        let index = read_index(index).unwrap();
+        for block in blocks {
+            let hash = block.cid.hash();
+            let offset = index[&(hash.code(), hash.digest().to_vec())];
+            let (_, mut frame) = payload.split_at(offset as usize);
+            let len: usize = frame.read_varint().unwrap();
+            let (frame, _) = frame.split_at(len);
+            let frame = CarBlock::from_bytes(frame.to_vec()).unwrap();
+            // Identical digests may be shared by blocks with different codecs.
+            assert_eq!(frame.cid.hash(), block.cid.hash());
+        }
+    }
+
+    #[test]
+    fn carv2_skips_identity_cids() {
+        let data = b"inlined".to_vec();
+        let identity = CarBlock {
+            cid: Cid::new_v1(
+                fvm_ipld_encoding::IPLD_RAW,
+                MultihashCode::Identity.digest(&data),
+            ),
+            data,
+        };
+        let car = encode(vec![identity]);
+        let header = crate::db::car::plain::read_v2_header(car.as_slice())
+            .unwrap()
+            .unwrap();
+        let (_, index) = car.split_at(header.index_offset as usize);
+        assert!(read_index(index).unwrap().is_empty());
+    }
+}
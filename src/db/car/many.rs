@@ -10,7 +10,10 @@
 
 use super::{AnyCar, ZstdFrameCache};
 use crate::blocks::TipsetKey;
-use crate::db::{EthMappingsStore, MemoryDB, PersistentStore, SettingsStore, SettingsStoreExt};
+use crate::db::{
+    EthMappingsStore, EventIndexStore, HeightIndexStore, MemoryDB, PersistentStore, SettingsStore,
+    SettingsStoreExt,
+};
 use crate::libp2p_bitswap::BitswapStoreReadWrite;
 use crate::rpc::eth::types::EthHash;
 use crate::shim::clock::ChainEpoch;
@@ -243,6 +246,30 @@ impl<WriterT: EthMappingsStore> EthMappingsStore for ManyCar<WriterT> {
     }
 }
 
+impl<WriterT: EventIndexStore> EventIndexStore for ManyCar<WriterT> {
+    fn read_bin(&self, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        EventIndexStore::read_bin(self.writer(), key)
+    }
+
+    fn write_batch(&self, ops: Vec<(Vec<u8>, Option<Vec<u8>>)>) -> anyhow::Result<()> {
+        EventIndexStore::write_batch(self.writer(), ops)
+    }
+
+    fn keys_in_range(&self, from: &[u8], to: &[u8]) -> anyhow::Result<Vec<Vec<u8>>> {
+        EventIndexStore::keys_in_range(self.writer(), from, to)
+    }
+}
+
+impl<WriterT: HeightIndexStore> HeightIndexStore for ManyCar<WriterT> {
+    fn read_bin(&self, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        HeightIndexStore::read_bin(self.writer(), key)
+    }
+
+    fn write_batch(&self, entries: Vec<(Vec<u8>, Vec<u8>)>) -> anyhow::Result<()> {
+        HeightIndexStore::write_batch(self.writer(), entries)
+    }
+}
+
 impl<T: Blockstore + SettingsStore> super::super::HeaviestTipsetKeyProvider for ManyCar<T> {
     fn heaviest_tipset_key(&self) -> anyhow::Result<TipsetKey> {
         match SettingsStoreExt::read_obj::<TipsetKey>(self, crate::db::setting_keys::HEAD_KEY)? {
@@ -1,6 +1,7 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 mod any;
+pub mod carv2;
 pub mod forest;
 mod many;
 pub mod plain;
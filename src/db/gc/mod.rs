@@ -73,7 +73,7 @@ use crate::blocks::Tipset;
 use crate::chain::ChainEpochDelta;
 
 use crate::cid_collections::CidHashSet;
-use crate::db::{GarbageCollectable, SettingsStore};
+use crate::db::{GarbageCollectable, SettingsExt as _, SettingsStore};
 use crate::ipld::stream_graph;
 use crate::shim::clock::ChainEpoch;
 use futures::StreamExt;
@@ -82,7 +82,10 @@ use std::mem;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+
+mod prune;
+pub use prune::{ChainPruner, Prune, PruneOptions, PruneReport};
 
 const SETTINGS_KEY: &str = "LAST_GC_RUN";
 
@@ -142,6 +145,7 @@ impl<DB: Blockstore + SettingsStore + GarbageCollectable<CidHashSet> + Sync + Se
             let block = block?;
             self.marked.remove(&block.cid);
         }
+        unmark_pinned(&self.db, &mut self.marked).await?;
 
         anyhow::Ok(())
     }
@@ -232,11 +236,34 @@ impl<DB: Blockstore + SettingsStore + GarbageCollectable<CidHashSet> + Sync + Se
         anyhow::Ok(())
     }
 }
+
+// Remove the state of the pinned tipsets from the marked set, so that it is never collected.
+async fn unmark_pinned<DB: Blockstore + SettingsStore + Sync + Send + 'static>(
+    db: &Arc<DB>,
+    marked: &mut CidHashSet,
+) -> anyhow::Result<()> {
+    for tsk in db.gc_pinned_tipsets()? {
+        let Some(tipset) = Tipset::load(db, &tsk)? else {
+            warn!("pinned tipset {tsk} not found");
+            continue;
+        };
+        let stateroot_limit = tipset.epoch() - 1;
+        let mut stream = stream_graph(db.clone(), std::iter::once(tipset), stateroot_limit);
+        while let Some(block) = stream.next().await {
+            marked.remove(&block?.cid);
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use crate::blocks::{CachingBlockHeader, Tipset};
     use crate::chain::{ChainEpochDelta, ChainStore};
-    use crate::db::{GarbageCollectable, MarkAndSweep, MemoryDB, PersistentStore};
+    use crate::db::{
+        ChainPruner, GarbageCollectable, MarkAndSweep, MemoryDB, PersistentStore, Prune,
+        PruneOptions, SettingsExt as _,
+    };
     use crate::message_pool::test_provider::{mock_block, mock_block_with_parents};
     use crate::networks::ChainConfig;
     use crate::shim::clock::ChainEpoch;
@@ -511,4 +538,73 @@ mod test {
             Some(persistent_data.to_vec())
         );
     }
+
+    #[tokio::test]
+    async fn prune_keeps_pinned_tipsets() {
+        let depth = 5 as ChainEpochDelta;
+        let unreachable_nodes = 3;
+
+        let tester = GCTester::new();
+        let store = tester.store.clone();
+        let pruner = Arc::new(ChainPruner::new(
+            tester.db.clone(),
+            Box::new(move || store.heaviest_tipset()),
+            depth,
+            depth,
+            Duration::from_millis(10),
+        ));
+
+        tester.run_epochs(depth * 2);
+        tester.insert_unreachable(unreachable_nodes);
+        let pinned = Tipset::from(mock_block(1, 1 + unreachable_nodes as u64));
+        tester
+            .db
+            .set_gc_pinned_tipsets(&[pinned.key().clone()])
+            .unwrap();
+        let keys = tester.db.get_keys().unwrap().len() as i64;
+
+        // Retaining less than the minimum depth is rejected.
+        let options = PruneOptions {
+            retain_state: Some(depth - 1),
+            dry_run: false,
+        };
+        assert!(pruner.prune(options).await.is_err());
+
+        // A dry run reports the unreachable nodes that are not pinned, leaving them in place.
+        let options = PruneOptions {
+            retain_state: None,
+            dry_run: true,
+        };
+        let report = pruner.prune(options).await.unwrap();
+        assert_eq!(report.records as i64, unreachable_nodes - 1);
+        assert_eq!(tester.db.get_keys().unwrap().len() as i64, keys);
+
+        // A prune waits for `depth` epochs after marking before removing anything.
+        let options = PruneOptions {
+            retain_state: None,
+            dry_run: false,
+        };
+        let prune = tokio::spawn({
+            let pruner = pruner.clone();
+            async move { pruner.prune(options).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!prune.is_finished());
+        assert_eq!(tester.db.get_keys().unwrap().len() as i64, keys);
+
+        // Unreachable data written after the mark is kept.
+        let young: CachingBlockHeader = mock_block(1 + unreachable_nodes as u64, 1);
+        let young_cid = tester.db.put_cbor_default(&young).unwrap();
+        tester.run_epochs(depth);
+
+        // The prune then removes the unreachable nodes, keeping the pinned tipset.
+        let report = prune.await.unwrap().unwrap();
+        assert_eq!(report.records as i64, unreachable_nodes - 1);
+        assert!(tester.db.has(pinned.block_headers().first().cid()).unwrap());
+        assert!(tester.db.has(&young_cid).unwrap());
+        for idx in 1..unreachable_nodes as u64 {
+            let block: CachingBlockHeader = mock_block(1 + idx, 1 + unreachable_nodes as u64);
+            assert!(!tester.db.has(block.cid()).unwrap());
+        }
+    }
 }
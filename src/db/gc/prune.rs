@@ -0,0 +1,194 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! On-demand pruning of the database with an operator-defined retention policy.
+//!
+//! A prune follows the same workflow as [`super::MarkAndSweep`]: it marks all the database keys,
+//! waits for `chain finality` epochs, then unmarks everything reachable from the heaviest tipset
+//! and from the pinned tipsets and sweeps the rest. The wait makes sure that data written shortly
+//! before the mark, such as tipsets being validated or messages not yet included in a block, is
+//! never removed while it could still become reachable. Data written after the mark is not marked,
+//! and hence never removed either. A dry run skips the wait, so its report may include such recent
+//! data.
+//!
+//! The retained state is never shallower than `chain finality`, and all the block headers reachable
+//! from the heaviest tipset are kept. A pinned tipset only retains its own block headers and state,
+//! not those of its ancestors.
+
+use super::unmark_pinned;
+use crate::blocks::Tipset;
+use crate::chain::ChainEpochDelta;
+use crate::cid_collections::CidHashSet;
+use crate::db::{GarbageCollectable, SettingsStore};
+use crate::ipld::stream_graph;
+use crate::lotus_json::lotus_json_with_self;
+use crate::shim::clock::ChainEpoch;
+use anyhow::Context as _;
+use futures::StreamExt;
+use fvm_ipld_blockstore::Blockstore;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+/// Retention policy of a single prune run.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct PruneOptions {
+    /// Number of most recent epochs to keep the state for. Defaults to the node's configured
+    /// retention. Values below `chain finality` are rejected.
+    pub retain_state: Option<ChainEpochDelta>,
+    /// Only report what would be removed, leaving the database intact.
+    pub dry_run: bool,
+}
+lotus_json_with_self!(PruneOptions);
+
+/// Outcome of a prune run.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct PruneReport {
+    /// Number of records removed, or reclaimable in a dry run.
+    pub records: u64,
+    /// Total size of the records removed, or reclaimable in a dry run, in bytes.
+    pub bytes: u64,
+}
+lotus_json_with_self!(PruneReport);
+
+/// A database that can be pruned on demand.
+#[async_trait::async_trait]
+pub trait Prune: Send + Sync {
+    async fn prune(&self, options: PruneOptions) -> anyhow::Result<PruneReport>;
+}
+
+/// [`Prune`] implementation removing everything that is not reachable from the heaviest tipset
+/// within the requested state retention, nor from the pinned tipsets.
+pub struct ChainPruner<DB> {
+    db: Arc<DB>,
+    get_heaviest_tipset: Box<dyn Fn() -> Arc<Tipset> + Send + Sync>,
+    default_depth: ChainEpochDelta,
+    min_depth: ChainEpochDelta,
+    block_time: Duration,
+    running: tokio::sync::Mutex<()>,
+}
+
+impl<DB> ChainPruner<DB> {
+    /// Creates a new pruner.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database instance.
+    /// * `get_heaviest_tipset` - A function that facilitates heaviest tipset retrieval.
+    /// * `default_depth` - The number of state-roots to retain when none is requested.
+    /// * `min_depth` - The smallest accepted number of state-roots to retain, i.e. `chain finality`.
+    ///   It is also the number of epochs to wait between the `mark` and the `filter` steps.
+    /// * `block_time` - An average block production time.
+    pub fn new(
+        db: Arc<DB>,
+        get_heaviest_tipset: Box<dyn Fn() -> Arc<Tipset> + Send + Sync>,
+        default_depth: ChainEpochDelta,
+        min_depth: ChainEpochDelta,
+        block_time: Duration,
+    ) -> Self {
+        Self {
+            db,
+            get_heaviest_tipset,
+            default_depth: default_depth.max(min_depth),
+            min_depth,
+            block_time,
+            running: tokio::sync::Mutex::new(()),
+        }
+    }
+}
+
+impl<DB: Blockstore + SettingsStore + GarbageCollectable<CidHashSet> + Sync + Send + 'static>
+    ChainPruner<DB>
+{
+    // Traverse the graph reachable from the heaviest tipset, unmarking the visited entries.
+    // Returns the set of visited entries so that subsequent traversals can skip them.
+    async fn unmark_reachable(
+        &self,
+        marked: &mut CidHashSet,
+        depth: ChainEpochDelta,
+        seen: CidHashSet,
+    ) -> anyhow::Result<CidHashSet> {
+        let head = (self.get_heaviest_tipset)();
+        let stateroot_limit = head.epoch() - depth;
+        let mut stream = stream_graph(self.db.clone(), head.chain_arc(&self.db), stateroot_limit)
+            .with_seen(seen);
+        while let Some(block) = stream.next().await {
+            marked.remove(&block?.cid);
+        }
+        Ok(stream.into_seen())
+    }
+
+    // Wait until the heaviest tipset is at least `min_depth` epochs ahead of `epoch_marked`.
+    async fn wait_for_finality(&self, epoch_marked: ChainEpoch) {
+        loop {
+            let epochs_since_marked = (self.get_heaviest_tipset)().epoch() - epoch_marked;
+            if epochs_since_marked >= self.min_depth {
+                return;
+            }
+            let remaining = self.min_depth - epochs_since_marked;
+            info!("prune waiting for {remaining} epochs");
+            tokio::time::sleep(self.block_time * remaining as u32).await;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<DB: Blockstore + SettingsStore + GarbageCollectable<CidHashSet> + Sync + Send + 'static> Prune
+    for ChainPruner<DB>
+{
+    async fn prune(&self, options: PruneOptions) -> anyhow::Result<PruneReport> {
+        let _guard = self
+            .running
+            .try_lock()
+            .ok()
+            .context("a prune is already running")?;
+        let depth = options.retain_state.unwrap_or(self.default_depth);
+        anyhow::ensure!(
+            depth >= self.min_depth,
+            "state has to be retained for at least {} epochs",
+            self.min_depth
+        );
+
+        info!("populate keys for prune");
+        let epoch_marked = (self.get_heaviest_tipset)().epoch();
+        let db = self.db.clone();
+        let mut marked = tokio::task::spawn_blocking(move || db.get_keys()).await??;
+        if !options.dry_run {
+            self.wait_for_finality(epoch_marked).await;
+        }
+
+        info!("filter keys for prune");
+        let seen = self
+            .unmark_reachable(&mut marked, depth, CidHashSet::new())
+            .await?;
+        unmark_pinned(&self.db, &mut marked).await?;
+        // The head might have moved on to a fork while traversing, make sure it's retained too.
+        self.unmark_reachable(&mut marked, depth, seen).await?;
+
+        let mut report = PruneReport::default();
+        let mut removable = CidHashSet::new();
+        for cid in marked {
+            if let Some(data) = self.db.get(&cid)? {
+                report.records += 1;
+                report.bytes += data.len() as u64;
+                removable.insert(cid);
+            }
+        }
+
+        if options.dry_run {
+            info!(
+                "prune dry run: {} reclaimable records, {} bytes",
+                report.records, report.bytes
+            );
+        } else {
+            let db = self.db.clone();
+            let deleted = tokio::task::spawn_blocking(move || db.remove_keys(removable)).await??;
+            info!("prune finished: {} deleted records", deleted);
+        }
+        Ok(report)
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::{EthMappingsStore, SettingsStore, SettingsStoreExt};
+use super::{EthMappingsStore, EventIndexStore, HeightIndexStore, SettingsStore, SettingsStoreExt};
 use crate::blocks::TipsetKey;
 use crate::cid_collections::CidHashSet;
 use crate::db::{GarbageCollectable, PersistentStore};
@@ -15,6 +15,7 @@ use cid::Cid;
 use fvm_ipld_blockstore::Blockstore;
 use itertools::Itertools;
 use parking_lot::RwLock;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Default)]
 pub struct MemoryDB {
@@ -22,6 +23,8 @@ pub struct MemoryDB {
     blockchain_persistent_db: RwLock<HashMap<Cid, Vec<u8>>>,
     settings_db: RwLock<HashMap<String, Vec<u8>>>,
     pub eth_mappings_db: RwLock<HashMap<EthHash, Vec<u8>>>,
+    event_index_db: RwLock<BTreeMap<Vec<u8>, Vec<u8>>>,
+    height_index_db: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
 }
 
 impl MemoryDB {
@@ -133,6 +136,46 @@ impl EthMappingsStore for MemoryDB {
     }
 }
 
+impl EventIndexStore for MemoryDB {
+    fn read_bin(&self, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self.event_index_db.read().get(key).cloned())
+    }
+
+    fn write_batch(&self, ops: Vec<(Vec<u8>, Option<Vec<u8>>)>) -> anyhow::Result<()> {
+        let mut lock = self.event_index_db.write();
+        for (key, value) in ops {
+            match value {
+                Some(value) => lock.insert(key, value),
+                None => lock.remove(&key),
+            };
+        }
+        Ok(())
+    }
+
+    fn keys_in_range(&self, from: &[u8], to: &[u8]) -> anyhow::Result<Vec<Vec<u8>>> {
+        if from > to {
+            return Ok(vec![]);
+        }
+        Ok(self
+            .event_index_db
+            .read()
+            .range(from.to_vec()..=to.to_vec())
+            .map(|(k, _)| k.clone())
+            .collect())
+    }
+}
+
+impl HeightIndexStore for MemoryDB {
+    fn read_bin(&self, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self.height_index_db.read().get(key).cloned())
+    }
+
+    fn write_batch(&self, entries: Vec<(Vec<u8>, Vec<u8>)>) -> anyhow::Result<()> {
+        self.height_index_db.write().extend(entries);
+        Ok(())
+    }
+}
+
 impl Blockstore for MemoryDB {
     fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
         Ok(self.blockchain_db.read().get(k).cloned().or(self
@@ -3,14 +3,16 @@
 
 pub mod car;
 mod memory;
+mod overlay;
 pub mod parity_db;
 pub mod parity_db_config;
 
 mod gc;
 pub mod ttl;
-pub use gc::MarkAndSweep;
+pub use gc::{ChainPruner, MarkAndSweep, Prune, PruneOptions, PruneReport};
 pub use memory::MemoryDB;
-use setting_keys::ETH_MAPPING_UP_TO_DATE_KEY;
+pub use overlay::MemoryOverlay;
+use setting_keys::{ETH_MAPPING_UP_TO_DATE_KEY, GC_PINNED_TIPSETS_KEY, JWT_REVOKED_PREFIX};
 mod db_mode;
 pub mod migration;
 
@@ -32,6 +34,14 @@ pub mod setting_keys {
     pub const MPOOL_CONFIG_KEY: &str = "/mpool/config";
     /// Key used to store the state of the Ethereum mapping. This is expected to be a [`bool`].
     pub const ETH_MAPPING_UP_TO_DATE_KEY: &str = "eth_mapping_up_to_date";
+    /// Key used to store the payment channels tracked by the node. This is expected to be a list of [`crate::paychmgr::TrackedChannel`]s
+    pub const PAYCH_CHANNELS_KEY: &str = "/paych/channels";
+    /// Key used to store the tipsets whose state is never garbage collected. This is expected to be a list of [`crate::blocks::TipsetKey`]s
+    pub const GC_PINNED_TIPSETS_KEY: &str = "/gc/pinned_tipsets";
+    /// Prefix of the keys marking JWT tokens as revoked, followed by the token ID. This is expected to be a [`bool`].
+    pub const JWT_REVOKED_PREFIX: &str = "/auth/revoked/";
+    /// Key used to store the peers known to the node along with their reputation.
+    pub const PEER_STORE_KEY: &str = "/libp2p/peer_store";
 }
 
 /// Interface used to store and retrieve settings from the database.
@@ -107,6 +117,10 @@ impl<T: ?Sized + SettingsStore> SettingsStoreExt for T {
 pub trait SettingsExt {
     fn set_eth_mapping_up_to_date(&self) -> anyhow::Result<()>;
     fn eth_mapping_up_to_date(&self) -> anyhow::Result<Option<bool>>;
+    fn set_gc_pinned_tipsets(&self, tipsets: &[TipsetKey]) -> anyhow::Result<()>;
+    fn gc_pinned_tipsets(&self) -> anyhow::Result<Vec<TipsetKey>>;
+    fn revoke_token(&self, id: &str) -> anyhow::Result<bool>;
+    fn is_token_revoked(&self, id: &str) -> anyhow::Result<bool>;
 }
 
 impl<T: ?Sized + SettingsStoreExt> SettingsExt for T {
@@ -119,6 +133,32 @@ impl<T: ?Sized + SettingsStoreExt> SettingsExt for T {
     fn eth_mapping_up_to_date(&self) -> anyhow::Result<Option<bool>> {
         self.read_obj(ETH_MAPPING_UP_TO_DATE_KEY)
     }
+
+    /// Sets the tipsets whose state is never garbage collected.
+    fn set_gc_pinned_tipsets(&self, tipsets: &[TipsetKey]) -> anyhow::Result<()> {
+        self.write_obj(GC_PINNED_TIPSETS_KEY, &tipsets)
+    }
+
+    /// Returns the tipsets whose state is never garbage collected.
+    fn gc_pinned_tipsets(&self) -> anyhow::Result<Vec<TipsetKey>> {
+        Ok(self.read_obj(GC_PINNED_TIPSETS_KEY)?.unwrap_or_default())
+    }
+
+    /// Marks the JWT tokens with the given ID as revoked. Returns `false` if they already were.
+    fn revoke_token(&self, id: &str) -> anyhow::Result<bool> {
+        if self.is_token_revoked(id)? {
+            return Ok(false);
+        }
+        self.write_obj(&format!("{JWT_REVOKED_PREFIX}{id}"), &true)?;
+        Ok(true)
+    }
+
+    /// Returns whether the JWT tokens with the given ID were revoked.
+    fn is_token_revoked(&self, id: &str) -> anyhow::Result<bool> {
+        Ok(self
+            .read_obj(&format!("{JWT_REVOKED_PREFIX}{id}"))?
+            .unwrap_or_default())
+    }
 }
 
 /// Interface used to store and retrieve Ethereum mappings from the database.
@@ -209,6 +249,52 @@ impl<T: ?Sized + EthMappingsStore> EthMappingsStoreExt for T {
     }
 }
 
+/// Interface used to store and retrieve the actor event index from the database.
+/// Keys are ordered byte-wise so that range scans can be used for epoch lookups.
+pub trait EventIndexStore {
+    /// Reads binary field from the event index.
+    fn read_bin(&self, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>>;
+
+    /// Atomically applies a batch of writes. A [`None`] value deletes the key.
+    fn write_batch(&self, ops: Vec<(Vec<u8>, Option<Vec<u8>>)>) -> anyhow::Result<()>;
+
+    /// Returns all keys within the inclusive range `[from, to]`, in ascending order.
+    fn keys_in_range(&self, from: &[u8], to: &[u8]) -> anyhow::Result<Vec<Vec<u8>>>;
+}
+
+impl<T: EventIndexStore> EventIndexStore for Arc<T> {
+    fn read_bin(&self, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        EventIndexStore::read_bin(self.as_ref(), key)
+    }
+
+    fn write_batch(&self, ops: Vec<(Vec<u8>, Option<Vec<u8>>)>) -> anyhow::Result<()> {
+        EventIndexStore::write_batch(self.as_ref(), ops)
+    }
+
+    fn keys_in_range(&self, from: &[u8], to: &[u8]) -> anyhow::Result<Vec<Vec<u8>>> {
+        EventIndexStore::keys_in_range(self.as_ref(), from, to)
+    }
+}
+
+/// Interface used to store and retrieve the tipset height index from the database.
+pub trait HeightIndexStore {
+    /// Reads binary field from the height index.
+    fn read_bin(&self, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>>;
+
+    /// Atomically writes a batch of entries to the height index.
+    fn write_batch(&self, entries: Vec<(Vec<u8>, Vec<u8>)>) -> anyhow::Result<()>;
+}
+
+impl<T: HeightIndexStore> HeightIndexStore for Arc<T> {
+    fn read_bin(&self, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        HeightIndexStore::read_bin(self.as_ref(), key)
+    }
+
+    fn write_batch(&self, entries: Vec<(Vec<u8>, Vec<u8>)>) -> anyhow::Result<()> {
+        HeightIndexStore::write_batch(self.as_ref(), entries)
+    }
+}
+
 /// Traits for collecting DB stats
 pub trait DBStatistics {
     fn get_statistics(&self) -> Option<String> {
@@ -0,0 +1,60 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::MemoryDB;
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+
+/// A [`Blockstore`] that keeps the written blocks in memory, on top of a read-only base store.
+///
+/// This allows running computations, e.g. message simulations, on a modified copy of the state
+/// without persisting anything to the base store.
+pub struct MemoryOverlay<DB> {
+    base: DB,
+    overlay: MemoryDB,
+}
+
+impl<DB> MemoryOverlay<DB> {
+    pub fn new(base: DB) -> Self {
+        Self {
+            base,
+            overlay: MemoryDB::default(),
+        }
+    }
+}
+
+impl<DB: Blockstore> Blockstore for MemoryOverlay<DB> {
+    fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        match self.overlay.get(k)? {
+            Some(block) => Ok(Some(block)),
+            None => self.base.get(k),
+        }
+    }
+
+    fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
+        self.overlay.put_keyed(k, block)
+    }
+
+    fn has(&self, k: &Cid) -> anyhow::Result<bool> {
+        Ok(self.overlay.has(k)? || self.base.has(k)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::db::CborStoreExt as _;
+    use std::sync::Arc;
+
+    #[test]
+    fn writes_do_not_reach_the_base_store() {
+        let base = Arc::new(MemoryDB::default());
+        let existing = base.put_cbor_default(&"existing").unwrap();
+        let overlay = MemoryOverlay::new(base.clone());
+        let written = overlay.put_cbor_default(&"written").unwrap();
+
+        assert!(overlay.has(&existing).unwrap());
+        assert!(overlay.has(&written).unwrap());
+        assert!(!base.has(&written).unwrap());
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::{EthMappingsStore, PersistentStore, SettingsStore};
+use super::{EthMappingsStore, EventIndexStore, HeightIndexStore, PersistentStore, SettingsStore};
 use crate::cid_collections::CidHashSet;
 use crate::db::{parity_db_config::ParityDbConfig, DBStatistics, GarbageCollectable};
 use crate::libp2p_bitswap::{BitswapStoreRead, BitswapStoreReadWrite};
@@ -14,10 +14,13 @@ use fvm_ipld_encoding::DAG_CBOR;
 use parity_db::{CompressionType, Db, Operation, Options};
 use std::path::PathBuf;
 use strum::{Display, EnumIter, FromRepr, IntoEnumIterator};
-use tracing::warn;
+use tracing::{info, warn};
 
 /// This is specific to Forest's `ParityDb` usage.
 /// It is used to determine which column to use for a given entry type.
+///
+/// New columns must only ever be appended. Missing trailing columns are created
+/// when an existing database is opened, see [`ParityDb::open`].
 #[derive(Copy, Clone, Debug, Display, PartialEq, FromRepr, EnumIter)]
 #[repr(u8)]
 enum DbColumn {
@@ -37,6 +40,11 @@ enum DbColumn {
     /// Anything stored in this column can be considered permanent, unless manually
     /// deleted.
     PersistentGraph,
+    /// Column for storing the actor event index. Keys are ordered so that entries
+    /// can be looked up by epoch ranges.
+    EventIndex,
+    /// Column for storing the tipset height index.
+    HeightIndex,
 }
 
 impl DbColumn {
@@ -73,6 +81,19 @@ impl DbColumn {
                         compression,
                         ..Default::default()
                     },
+                    DbColumn::EventIndex => parity_db::ColumnOptions {
+                        preimage: false,
+                        // This is needed for range scans.
+                        btree_index: true,
+                        compression,
+                        ..Default::default()
+                    },
+                    DbColumn::HeightIndex => parity_db::ColumnOptions {
+                        preimage: false,
+                        btree_index: false,
+                        compression,
+                        ..Default::default()
+                    },
                 }
             })
             .collect()
@@ -101,6 +122,7 @@ impl ParityDb {
 
     pub fn open(path: impl Into<PathBuf>, config: &ParityDbConfig) -> anyhow::Result<Self> {
         let opts = Self::to_options(path.into(), config);
+        Self::add_missing_columns(&opts)?;
         Ok(Self {
             db: Db::open_or_create(&opts)?,
             statistics_enabled: opts.stats,
@@ -108,6 +130,37 @@ impl ParityDb {
         })
     }
 
+    /// Appends the columns that have been introduced after the database at `opts.path`
+    /// was created. Existing columns are left untouched.
+    fn add_missing_columns(opts: &Options) -> anyhow::Result<()> {
+        let Some(metadata) = Options::load_metadata(&opts.path)? else {
+            return Ok(());
+        };
+        let existing = metadata.columns.len();
+        let Some(existing_columns) = opts.columns.get(..existing) else {
+            return Ok(());
+        };
+        if existing_columns.len() == opts.columns.len() {
+            return Ok(());
+        }
+        let mut current = Options {
+            columns: existing_columns.to_vec(),
+            ..opts.clone()
+        };
+        for (idx, column) in opts.columns.iter().enumerate().skip(existing) {
+            info!(
+                "Adding column {} to database at {}",
+                DbColumn::from_repr(idx as u8)
+                    .map(|c| c.to_string())
+                    .unwrap_or_else(|| idx.to_string()),
+                opts.path.display()
+            );
+            Db::add_column(&mut current, column.clone())
+                .with_context(|| format!("failed to add column {idx}"))?;
+        }
+        Ok(())
+    }
+
     /// Returns an appropriate column variant based on the information
     /// in the Cid.
     fn choose_column(cid: &Cid) -> DbColumn {
@@ -204,6 +257,49 @@ impl EthMappingsStore for ParityDb {
     }
 }
 
+impl EventIndexStore for ParityDb {
+    fn read_bin(&self, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        self.read_from_column(key, DbColumn::EventIndex)
+    }
+
+    fn write_batch(&self, ops: Vec<(Vec<u8>, Option<Vec<u8>>)>) -> anyhow::Result<()> {
+        let tx = ops
+            .into_iter()
+            .map(|(key, value)| (DbColumn::EventIndex as u8, key, value));
+        self.db
+            .commit(tx)
+            .map_err(|e| anyhow!("error writing to column {}: {e}", DbColumn::EventIndex))
+    }
+
+    fn keys_in_range(&self, from: &[u8], to: &[u8]) -> anyhow::Result<Vec<Vec<u8>>> {
+        let mut iter = self.db.iter(DbColumn::EventIndex as u8)?;
+        iter.seek(from)?;
+        let mut keys = vec![];
+        while let Some((key, _)) = iter.next()? {
+            if key.as_slice() > to {
+                break;
+            }
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+}
+
+impl HeightIndexStore for ParityDb {
+    fn read_bin(&self, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        self.read_from_column(key, DbColumn::HeightIndex)
+    }
+
+    fn write_batch(&self, entries: Vec<(Vec<u8>, Vec<u8>)>) -> anyhow::Result<()> {
+        let tx = entries
+            .into_iter()
+            .map(|(key, value)| (DbColumn::HeightIndex as u8, key, Some(value)));
+        self.db
+            .commit(tx)
+            .map_err(|e| anyhow!("error writing to column {}: {e}", DbColumn::HeightIndex))
+    }
+}
+
 impl Blockstore for ParityDb {
     fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
         let column = Self::choose_column(k);
@@ -432,6 +528,8 @@ mod test {
                 DbColumn::Settings => panic!("invalid column for IPLD data"),
                 DbColumn::EthMappings => panic!("invalid column for IPLD data"),
                 DbColumn::PersistentGraph => panic!("invalid column for GC enabled IPLD data"),
+                DbColumn::EventIndex => panic!("invalid column for IPLD data"),
+                DbColumn::HeightIndex => panic!("invalid column for IPLD data"),
             };
             let actual = db.read_from_column(cid.to_bytes(), other_column).unwrap();
             assert!(actual.is_none());
@@ -568,4 +666,24 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn add_missing_columns_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paritydb");
+        let config = ParityDbConfig::default();
+
+        // Simulate a database created before the event index column was introduced.
+        let mut opts = ParityDb::to_options(path.clone(), &config);
+        opts.columns.truncate(DbColumn::EventIndex as usize);
+        drop(Db::open_or_create(&opts).unwrap());
+
+        let db = ParityDb::open(path, &config).unwrap();
+        EventIndexStore::write_batch(&db, vec![(b"key".to_vec(), Some(b"value".to_vec()))])
+            .unwrap();
+        assert_eq!(
+            EventIndexStore::read_bin(&db, b"key").unwrap(),
+            Some(b"value".to_vec())
+        );
+    }
 }
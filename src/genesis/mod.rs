@@ -1,15 +1,24 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::{io::Cursor, path::Path};
+mod template;
+#[cfg(test)]
+pub mod test_utils;
+
+pub use template::{GenesisTemplate, GENESIS_ACTORS_VERSION};
+
+use std::{io::Cursor, path::Path, sync::Arc};
 
-use crate::blocks::CachingBlockHeader;
+use crate::blocks::{CachingBlockHeader, Tipset};
+use crate::ipld::stream_chain;
+use crate::utils::db::car_stream::CarWriter;
 use crate::utils::db::car_util::load_car;
 use anyhow::Context as _;
+use futures::{StreamExt as _, TryStreamExt as _};
 use fvm_ipld_blockstore::Blockstore;
 use tokio::{
     fs::File,
-    io::{AsyncBufRead, AsyncSeek, BufReader},
+    io::{AsyncBufRead, AsyncSeek, AsyncWrite, BufReader},
 };
 use tracing::{debug, info};
 
@@ -43,6 +52,26 @@ where
     Ok(genesis)
 }
 
+/// Writes a genesis block and everything it links to as a CAR file, which can be loaded with
+/// [`read_genesis_header`].
+pub async fn export_genesis<DB>(
+    db: Arc<DB>,
+    genesis: &CachingBlockHeader,
+    writer: impl AsyncWrite + Unpin,
+) -> anyhow::Result<()>
+where
+    DB: Blockstore + Send + Sync + 'static,
+{
+    let tipset = Tipset::from(genesis);
+    // With a state root limit below the genesis epoch, the messages of the genesis block are
+    // exported along with its state
+    stream_chain(db.clone(), tipset.chain_owned(db), -1)
+        .map_err(std::io::Error::other)
+        .forward(CarWriter::new_carv1(nunny::vec![*genesis.cid()], writer)?)
+        .await?;
+    Ok(())
+}
+
 async fn process_car<R, BS>(reader: R, db: &BS) -> Result<CachingBlockHeader, anyhow::Error>
 where
     R: AsyncBufRead + AsyncSeek + Unpin,
@@ -84,6 +113,121 @@ mod tests {
         assert_eq!(header.cid(), &GENESIS_CID as &Cid);
     }
 
+    #[tokio::test]
+    async fn test_create_genesis() {
+        use crate::db::MemoryDB;
+        use crate::networks::ChainConfig;
+        use crate::shim::{
+            address::Address,
+            econ::{TokenAmount, TOTAL_FILECOIN},
+            machine::BuiltinActorManifest,
+            state_tree::StateTree,
+        };
+        use crate::utils::db::CborStoreExt as _;
+        use crate::utils::multihash::prelude::*;
+        use fvm_ipld_encoding::{CborStore as _, IPLD_RAW};
+
+        let db = Arc::new(MemoryDB::default());
+        let actor_list = [
+            "system",
+            "init",
+            "cron",
+            "account",
+            "storagepower",
+            "storageminer",
+            "storagemarket",
+            "paymentchannel",
+            "multisig",
+            "reward",
+            "verifiedregistry",
+            "datacap",
+            "placeholder",
+            "evm",
+            "eam",
+            "ethaccount",
+        ]
+        .map(|name| {
+            let hash = MultihashCode::Identity.digest(format!("fil/16/{name}").as_bytes());
+            (name, Cid::new_v1(IPLD_RAW, hash))
+        });
+        let actor_list = db.put_cbor_default(&actor_list).unwrap();
+        let manifest = BuiltinActorManifest::load_manifest(
+            &db,
+            &db.put_cbor_default(&(1, actor_list)).unwrap(),
+        )
+        .unwrap();
+
+        let owner = Address::new_bls(&[1; 48]).unwrap();
+        let miner_owner = Address::new_bls(&[3; 48]).unwrap();
+        let account = Address::new_secp256k1(&[2; 65]).unwrap();
+        let template: GenesisTemplate = toml::from_str(&format!(
+            r#"
+            network_name = "genesistest"
+            timestamp = 1700000000
+            verifreg_root = "{owner}"
+
+            [[accounts]]
+            address = "{account}"
+            balance = "1000 FIL"
+
+            [[miners]]
+            owner = "{miner_owner}"
+            fake_sectors = 4
+
+            [[verifiers]]
+            address = "{account}"
+            allowance = 1048576
+            "#
+        ))
+        .unwrap();
+        let genesis = template
+            .build(&db, &manifest, &ChainConfig::devnet())
+            .unwrap();
+        let mut car = vec![];
+        export_genesis(db, &genesis, &mut car).await.unwrap();
+
+        // The genesis is loaded like the ones of the built-in networks
+        let db = Arc::new(MemoryDB::default());
+        let header = read_genesis_header(None, Some(&car), &db).await.unwrap();
+        assert_eq!(header, genesis);
+        assert_eq!(header.epoch, 0);
+        assert_eq!(header.timestamp, 1700000000);
+        assert_eq!(header.election_proof, Some(Default::default()));
+        assert!(crate::chain::block_messages(&db, &header)
+            .unwrap()
+            .0
+            .is_empty());
+
+        let tree = StateTree::new_from_root(db.clone(), &header.state_root).unwrap();
+        let account = tree.get_actor(&Address::new_id(100)).unwrap().unwrap();
+        assert_eq!(
+            TokenAmount::from(&account.balance),
+            TokenAmount::from_whole(1000)
+        );
+        let mut total = TokenAmount::default();
+        tree.for_each(|_, actor| {
+            total += TokenAmount::from(&actor.balance);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(total, *TOTAL_FILECOIN);
+
+        let miner = Address::new_id(1000);
+        assert!(tree.get_actor(&miner).unwrap().is_some());
+        let power = tree.get_actor(&Address::POWER_ACTOR).unwrap().unwrap();
+        let power: fil_actor_power_state::v16::State = db.get_cbor(&power.state).unwrap().unwrap();
+        let claim = power
+            .load_claims(&db)
+            .unwrap()
+            .get(&miner)
+            .unwrap()
+            .cloned()
+            .unwrap();
+        assert_eq!(claim.raw_byte_power, (4 * 2048).into());
+        assert_eq!(power.total_quality_adj_power, (4 * 2048).into());
+        assert_eq!(power.miner_above_min_power_count, 1);
+    }
+
     async fn load_header_from_car(genesis_bytes: &[u8]) -> CachingBlockHeader {
         let db = crate::db::MemoryDB::default();
         process_car(Cursor::new(genesis_bytes), &db).await.unwrap()
@@ -0,0 +1,648 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Genesis templates describe the initial state of a private network: its accounts, pre-sealed
+//! miners and verifiers. [`GenesisTemplate::build`] turns a template into a genesis block, so that
+//! a devnet can be started without the Lotus genesis tooling.
+//!
+//! The actor states are written with the state types of actors `v16`, see
+//! [`GENESIS_ACTORS_VERSION`], so genesis states can only use a `v16` actor bundle, and the chain
+//! must run actors `v16` from its network version at epoch 0 on.
+
+use std::{path::Path, sync::Arc};
+
+use crate::beacon::BeaconEntry;
+use crate::blocks::{CachingBlockHeader, ElectionProof, RawBlockHeader, Ticket, TxMeta, VRFProof};
+use crate::cli::humantoken;
+use crate::networks::{ChainConfig, ChainSpecFormat};
+use crate::shim::actors::convert::from_policy_v13_to_v16;
+use crate::shim::{
+    address::{Address, Protocol},
+    econ::{TokenAmount, TOTAL_FILECOIN},
+    machine::{BuiltinActor, BuiltinActorManifest},
+    state_tree::{ActorState, StateTree, StateTreeVersion},
+    version::NetworkVersion,
+};
+use crate::utils::{db::CborStoreExt as _, encoding::blake2b_256};
+use anyhow::{ensure, Context as _};
+use cid::Cid;
+use fil_actor_miner_state::v16::{
+    assign_deadlines, CollisionPolicy, MinerInfo, SectorOnChainInfo, SectorOnChainInfoFlags,
+};
+use fil_actor_multisig_state::v16::{PendingTxnMap, PENDING_TXN_CONFIG};
+use fil_actor_power_state::v16::{consensus_miner_min_power, set_claim, Claim};
+use fil_actors_shared::fvm_ipld_amt::Amtv0 as Amt;
+use fil_actors_shared::fvm_ipld_bitfield::BitField;
+use fil_actors_shared::v16::runtime::Policy;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_shared4::{
+    bigint::BigInt,
+    commcid::replica_commitment_v1_to_cid,
+    sector::{RegisteredSealProof, SectorNumber, SectorSize, StoragePower},
+    ActorID,
+};
+use num::Zero as _;
+use serde::{Deserialize, Deserializer};
+use serde_with::{serde_as, DisplayFromStr};
+
+/// Version of the actors whose state types are used to build genesis states.
+pub const GENESIS_ACTORS_VERSION: u64 = 16;
+
+/// Default actor bundle of genesis states.
+pub const DEFAULT_ACTORS_BUNDLE: &str = "v16.0.0-rc3";
+
+/// Multisig holding the root key of the verified registry.
+const VERIFIED_REGISTRY_ROOT: Address = Address::new_id(80);
+
+/// Lowest ID of miner actors, as in the Lotus genesis.
+const FIRST_MINER_ID: ActorID = 1000;
+
+/// Balance of the reward actor, from which block rewards are paid.
+const STORAGE_MINING_ALLOCATION: u64 = 1_100_000_000;
+
+/// Base fee of the genesis block, in attoFIL.
+const INITIAL_BASE_FEE: u64 = 100_000_000;
+
+/// Initial state of a private network.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisTemplate {
+    /// Name of the network, stored in the init actor. Defaults to the name of the chain.
+    #[serde(default)]
+    pub network_name: Option<String>,
+    /// Unix timestamp of the genesis block. Defaults to the current time.
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+    /// Network version at genesis. Defaults to the network version of the chain at epoch 0.
+    #[serde(default)]
+    pub network_version: Option<NetworkVersion>,
+    /// Version of the actor bundle, e.g., `v16.0.0-rc3`. Only `v16` bundles are supported, as the
+    /// actor states are written with their state types.
+    #[serde(default = "default_actors_version")]
+    pub actors_version: String,
+    /// Signer of the multisig root of the verified registry. Without it, verifiers can only be
+    /// added at genesis.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub verifreg_root: Option<Address>,
+    #[serde(default)]
+    pub accounts: Vec<AccountTemplate>,
+    #[serde(default)]
+    pub miners: Vec<MinerTemplate>,
+    #[serde(default)]
+    pub verifiers: Vec<VerifierTemplate>,
+}
+
+/// Account actor funded at genesis.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountTemplate {
+    /// `secp256k1` or BLS address of the account.
+    #[serde_as(as = "DisplayFromStr")]
+    pub address: Address,
+    /// Balance of the account, e.g., `1000 FIL`.
+    #[serde(deserialize_with = "deserialize_token_amount")]
+    pub balance: TokenAmount,
+}
+
+/// Miner actor whose sectors are proven at genesis.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MinerTemplate {
+    /// ID address of the miner. Defaults to the next free ID, starting from `f01000`.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub id: Option<Address>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub owner: Address,
+    /// BLS address signing the blocks of the miner. Defaults to the owner.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub worker: Option<Address>,
+    /// Libp2p peer ID of the miner.
+    #[serde(default)]
+    pub peer_id: Option<String>,
+    /// Sector size in bytes.
+    #[serde(default = "default_sector_size")]
+    pub sector_size: u64,
+    /// Pre-sealed sectors.
+    #[serde(default)]
+    pub sectors: Vec<SectorTemplate>,
+    /// Number of sectors with made-up commitments, after the pre-sealed ones. Such sectors can
+    /// only be used on networks that do not verify proofs.
+    #[serde(default)]
+    pub fake_sectors: u64,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SectorTemplate {
+    pub number: SectorNumber,
+    /// Replica commitment of the sector.
+    #[serde_as(as = "DisplayFromStr")]
+    pub sealed_cid: Cid,
+}
+
+/// Verifier registered at genesis.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VerifierTemplate {
+    #[serde_as(as = "DisplayFromStr")]
+    pub address: Address,
+    /// Data cap the verifier can allocate to clients, in bytes.
+    pub allowance: u64,
+}
+
+fn default_actors_version() -> String {
+    DEFAULT_ACTORS_BUNDLE.into()
+}
+
+fn default_sector_size() -> u64 {
+    SectorSize::_2KiB as u64
+}
+
+fn deserialize_token_amount<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<TokenAmount, D::Error> {
+    humantoken::parse(&String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+}
+
+fn sector_size(bytes: u64) -> anyhow::Result<SectorSize> {
+    use SectorSize::*;
+    [_2KiB, _8MiB, _512MiB, _32GiB, _64GiB]
+        .into_iter()
+        .find(|size| *size as u64 == bytes)
+        .with_context(|| format!("unsupported sector size {bytes}"))
+}
+
+impl GenesisTemplate {
+    /// Reads a template from a TOML or JSON file, depending on its extension.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let template = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let template = match ChainSpecFormat::from_path(path) {
+            ChainSpecFormat::Toml => toml::from_str(&template)?,
+            ChainSpecFormat::Json => serde_json::from_str(&template)?,
+        };
+        Ok(template)
+    }
+
+    /// Network version at genesis on the given chain.
+    pub fn network_version(&self, chain_config: &ChainConfig) -> NetworkVersion {
+        self.network_version
+            .unwrap_or_else(|| chain_config.network_version(0))
+    }
+
+    /// Builds the genesis state with the actors of `manifest`, whose blocks must be in `db`, and
+    /// returns the genesis block header, stored in `db` along with everything it links to.
+    ///
+    /// Miners are not enrolled in the deadline cron of the power actor, so their sectors stay
+    /// active without any Window PoSt being submitted.
+    pub fn build<DB: Blockstore>(
+        &self,
+        db: &Arc<DB>,
+        manifest: &BuiltinActorManifest,
+        chain_config: &ChainConfig,
+    ) -> anyhow::Result<CachingBlockHeader> {
+        let state_root = GenesisBuilder::new(db, manifest, chain_config, self)?.build()?;
+
+        let empty_amt = Amt::<Cid, _>::new(db).flush()?;
+        let messages = db.put_cbor_default(&TxMeta {
+            bls_message_root: empty_amt,
+            secp_message_root: empty_amt,
+        })?;
+        // Like in Lotus, the parent of the genesis block is a dummy block.
+        let network_name = self.network_name(chain_config);
+        let parent = db.put_cbor_default(&format!("{network_name} genesis"))?;
+        let timestamp = self
+            .timestamp
+            .unwrap_or_else(|| chrono::Utc::now().timestamp() as u64);
+
+        let header = CachingBlockHeader::new(RawBlockHeader {
+            miner_address: Address::SYSTEM_ACTOR,
+            ticket: Some(Ticket::new(VRFProof::new(
+                blake2b_256(&[state_root.to_bytes(), timestamp.to_be_bytes().to_vec()].concat())
+                    .to_vec(),
+            ))),
+            // Like in Lotus, the genesis block has an empty election proof, which counts as no
+            // win in the weight of the genesis tipset
+            election_proof: Some(ElectionProof::default()),
+            beacon_entries: vec![BeaconEntry::new(0, vec![0; 32])],
+            winning_post_proof: vec![],
+            parents: nunny::vec![parent].into(),
+            weight: Default::default(),
+            epoch: 0,
+            state_root,
+            message_receipts: empty_amt,
+            messages,
+            bls_aggregate: None,
+            timestamp,
+            signature: None,
+            fork_signal: 0,
+            parent_base_fee: TokenAmount::from_atto(INITIAL_BASE_FEE),
+        });
+        db.put_cbor_default(&*header)?;
+        Ok(header)
+    }
+
+    fn network_name(&self, chain_config: &ChainConfig) -> String {
+        self.network_name
+            .clone()
+            .unwrap_or_else(|| chain_config.network.to_string())
+    }
+}
+
+/// Builds a genesis state tree, writing the actor states directly instead of running their
+/// constructors.
+struct GenesisBuilder<'a, DB> {
+    db: &'a Arc<DB>,
+    manifest: &'a BuiltinActorManifest,
+    policy: Policy,
+    network_version: NetworkVersion,
+    template: &'a GenesisTemplate,
+    tree: StateTree<DB>,
+    init: fil_actor_init_state::v16::State,
+    power: fil_actor_power_state::v16::State,
+    verifreg: fil_actor_verifreg_state::v16::State,
+}
+
+impl<'a, DB: Blockstore> GenesisBuilder<'a, DB> {
+    fn new(
+        db: &'a Arc<DB>,
+        manifest: &'a BuiltinActorManifest,
+        chain_config: &ChainConfig,
+        template: &'a GenesisTemplate,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            db,
+            manifest,
+            policy: from_policy_v13_to_v16(&chain_config.policy),
+            network_version: template.network_version(chain_config),
+            template,
+            tree: StateTree::new(db.clone(), StateTreeVersion::V5)?,
+            init: fil_actor_init_state::v16::State::new(db, template.network_name(chain_config))?,
+            power: fil_actor_power_state::v16::State::new(db)?,
+            verifreg: fil_actor_verifreg_state::v16::State::new(db, *VERIFIED_REGISTRY_ROOT)?,
+        })
+    }
+
+    fn build(mut self) -> anyhow::Result<Cid> {
+        let template = self.template;
+
+        // Singleton actors whose state does not depend on the other actors
+        ensure!(
+            self.db.has(&self.manifest.source_cid())?,
+            "actor list {} is missing from the blockstore",
+            self.manifest.source_cid()
+        );
+        let system = fil_actor_system_state::v16::State {
+            builtin_actors: self.manifest.source_cid(),
+        };
+        self.set_actor(BuiltinActor::System, Address::SYSTEM_ACTOR, &system)?;
+        let cron = fil_actor_cron_state::v16::State {
+            entries: vec![
+                fil_actor_cron_state::v16::Entry {
+                    receiver: *Address::POWER_ACTOR,
+                    method_num: fil_actor_power_state::v16::Method::OnEpochTickEnd as u64,
+                },
+                fil_actor_cron_state::v16::Entry {
+                    receiver: *Address::MARKET_ACTOR,
+                    method_num: fil_actor_market_state::v16::Method::CronTick as u64,
+                },
+            ],
+        };
+        self.set_actor(BuiltinActor::Cron, Address::CRON_ACTOR, &cron)?;
+        let market = fil_actor_market_state::v16::State::new(self.db)?;
+        self.set_actor(BuiltinActor::Market, Address::MARKET_ACTOR, &market)?;
+        let datacap =
+            fil_actor_datacap_state::v16::State::new(self.db, *Address::VERIFIED_REGISTRY_ACTOR)?;
+        self.set_actor(
+            BuiltinActor::DataCap,
+            Address::DATACAP_TOKEN_ACTOR,
+            &datacap,
+        )?;
+        let eam = self.db.put_cbor_default(&())?;
+        self.tree.set_actor(
+            &Address::ETHEREUM_ACCOUNT_MANAGER_ACTOR,
+            ActorState::new(
+                self.manifest.get(BuiltinActor::EAM)?,
+                eam,
+                TokenAmount::zero(),
+                0,
+                None,
+            ),
+        )?;
+        let burnt_funds = fil_actor_account_state::v16::State {
+            address: *Address::BURNT_FUNDS_ACTOR,
+        };
+        self.set_actor(
+            BuiltinActor::Account,
+            Address::BURNT_FUNDS_ACTOR,
+            &burnt_funds,
+        )?;
+
+        // Accounts, multisigs and miners, whose IDs are allocated by the init actor
+        for account in &template.accounts {
+            let id = self.create_account(&account.address)?;
+            let mut actor = self
+                .tree
+                .get_actor(&id)?
+                .context("account actor not found")?;
+            ensure!(
+                actor.balance.is_zero(),
+                "account {} is listed more than once",
+                account.address
+            );
+            actor.balance = account.balance.clone().into();
+            self.tree.set_actor(&id, actor)?;
+        }
+        let root_signers = match &template.verifreg_root {
+            Some(signer) => vec![self.create_account(signer)?],
+            None => vec![],
+        };
+        self.create_multisig(VERIFIED_REGISTRY_ROOT, root_signers, TokenAmount::zero())?;
+        // Like in Lotus, the accounts of miner owners and workers are allocated before the
+        // miners, so that the first miner is `f01000`
+        for miner in &template.miners {
+            self.create_account(&miner.owner)?;
+            self.create_account(&miner.worker.unwrap_or(miner.owner))?;
+        }
+        self.init.next_id = self.init.next_id.max(FIRST_MINER_ID);
+        for miner in &template.miners {
+            self.create_miner(miner)?;
+        }
+        for verifier in &template.verifiers {
+            let id = self.create_account(&verifier.address)?;
+            self.verifreg
+                .put_verifier(self.db, &id, &StoragePower::from(verifier.allowance))?;
+        }
+
+        // Singleton actors whose state depends on the other actors
+        let reward =
+            fil_actor_reward_state::v16::State::new(self.power.total_quality_adj_power.clone());
+        let reward = self.db.put_cbor_default(&reward)?;
+        self.tree.set_actor(
+            &Address::REWARD_ACTOR,
+            ActorState::new(
+                self.manifest.get(BuiltinActor::Reward)?,
+                reward,
+                TokenAmount::from_whole(STORAGE_MINING_ALLOCATION),
+                0,
+                None,
+            ),
+        )?;
+        let power = self.power.clone();
+        self.set_actor(BuiltinActor::Power, Address::POWER_ACTOR, &power)?;
+        let verifreg = self.verifreg.clone();
+        self.set_actor(
+            BuiltinActor::VerifiedRegistry,
+            Address::VERIFIED_REGISTRY_ACTOR,
+            &verifreg,
+        )?;
+
+        // Like in Lotus, the remaining FIL is held by the reserve
+        let mut allocated = TokenAmount::zero();
+        self.tree.flush()?;
+        self.tree.for_each(|_, actor| {
+            allocated += TokenAmount::from(&actor.balance);
+            Ok(())
+        })?;
+        ensure!(
+            allocated <= *TOTAL_FILECOIN,
+            "{allocated} allocated at genesis, more than the total supply of {}",
+            *TOTAL_FILECOIN
+        );
+        self.create_multisig(
+            Address::RESERVE_ACTOR,
+            vec![],
+            TOTAL_FILECOIN.clone() - &allocated,
+        )?;
+
+        let init = self.init.clone();
+        self.set_actor(BuiltinActor::Init, Address::INIT_ACTOR, &init)?;
+        self.tree.flush()
+    }
+
+    fn set_actor(
+        &mut self,
+        actor: BuiltinActor,
+        address: Address,
+        state: &impl serde::Serialize,
+    ) -> anyhow::Result<()> {
+        let state = self.db.put_cbor_default(state)?;
+        self.tree.set_actor(
+            &address,
+            ActorState::new(
+                self.manifest.get(actor)?,
+                state,
+                TokenAmount::zero(),
+                0,
+                None,
+            ),
+        )
+    }
+
+    /// Returns the ID address of an account, creating it if needed.
+    fn create_account(&mut self, address: &Address) -> anyhow::Result<Address> {
+        if address.protocol() == Protocol::ID {
+            ensure!(
+                self.tree.get_actor(address)?.is_some(),
+                "actor {address} does not exist"
+            );
+            return Ok(*address);
+        }
+        ensure!(
+            matches!(address.protocol(), Protocol::Secp256k1 | Protocol::BLS),
+            "account {address} must be a secp256k1 or BLS address"
+        );
+        if let Some(id) = self.init.resolve_address(self.db, address)? {
+            return Ok(id.into());
+        }
+        let (id, _) = self.init.map_addresses_to_id(self.db, address, None)?;
+        let id = Address::new_id(id);
+        let state = fil_actor_account_state::v16::State { address: **address };
+        self.set_actor(BuiltinActor::Account, id, &state)?;
+        Ok(id)
+    }
+
+    fn create_multisig(
+        &mut self,
+        address: Address,
+        signers: Vec<Address>,
+        balance: TokenAmount,
+    ) -> anyhow::Result<()> {
+        let pending_txs = PendingTxnMap::empty(self.db, PENDING_TXN_CONFIG, "pending").flush()?;
+        let state = fil_actor_multisig_state::v16::State {
+            signers: signers.into_iter().map(|signer| *signer).collect(),
+            num_approvals_threshold: 1,
+            next_tx_id: Default::default(),
+            initial_balance: TokenAmount::zero().into(),
+            start_epoch: 0,
+            unlock_duration: 0,
+            pending_txs,
+        };
+        let state = self.db.put_cbor_default(&state)?;
+        self.tree.set_actor(
+            &address,
+            ActorState::new(
+                self.manifest.get(BuiltinActor::Multisig)?,
+                state,
+                balance,
+                0,
+                None,
+            ),
+        )
+    }
+
+    fn create_miner(&mut self, miner: &MinerTemplate) -> anyhow::Result<()> {
+        let owner = self.create_account(&miner.owner)?;
+        let worker_key = miner.worker.unwrap_or(miner.owner);
+        ensure!(
+            worker_key.protocol() == Protocol::BLS,
+            "worker {worker_key} must be a BLS address"
+        );
+        let worker = self.create_account(&worker_key)?;
+
+        if let Some(id) = miner.id {
+            let id = id
+                .id()
+                .with_context(|| format!("{id} is not an ID address"))?;
+            ensure!(
+                id >= self.init.next_id,
+                "miner ID f0{id} is already allocated"
+            );
+            self.init.next_id = id;
+        }
+        // Robust address of the miner, normally derived from the message creating it
+        let robust = Address::new_actor(&self.init.next_id.to_be_bytes());
+        let (id, _) = self.init.map_addresses_to_id(self.db, &robust, None)?;
+        let address = Address::new_id(id);
+
+        let seal_proof = RegisteredSealProof::from_sector_size(
+            sector_size(miner.sector_size)?,
+            self.network_version.into(),
+        );
+        let window_post_proof = seal_proof
+            .registered_window_post_proof()
+            .map_err(anyhow::Error::msg)?;
+        let peer_id = match &miner.peer_id {
+            Some(peer_id) => peer_id.parse::<libp2p::PeerId>()?.to_bytes(),
+            None => vec![],
+        };
+        let info = MinerInfo::new(
+            owner.id()?,
+            worker.id()?,
+            vec![],
+            peer_id,
+            vec![],
+            window_post_proof,
+        )?;
+        let info = self.db.put_cbor_default(&info)?;
+        let mut state = fil_actor_miner_state::v16::State::new(&self.policy, self.db, info, 0, 0)?;
+
+        // Sectors
+        let mut sectors = miner
+            .sectors
+            .iter()
+            .map(|sector| (sector.number, sector.sealed_cid))
+            .collect::<Vec<_>>();
+        let first_fake_sector = sectors
+            .iter()
+            .map(|(number, _)| number + 1)
+            .max()
+            .unwrap_or_default();
+        for number in first_fake_sector..first_fake_sector + miner.fake_sectors {
+            let comm_r = blake2b_256(format!("{address} {number}").as_bytes());
+            let sealed_cid = replica_commitment_v1_to_cid(&comm_r).map_err(anyhow::Error::msg)?;
+            sectors.push((number, sealed_cid));
+        }
+        let sectors = sectors
+            .into_iter()
+            .map(|(sector_number, sealed_cid)| SectorOnChainInfo {
+                sector_number,
+                seal_proof,
+                sealed_cid,
+                deprecated_deal_ids: vec![],
+                activation: 0,
+                expiration: self.policy.max_sector_expiration_extension,
+                deal_weight: BigInt::zero(),
+                verified_deal_weight: BigInt::zero(),
+                initial_pledge: TokenAmount::zero().into(),
+                expected_day_reward: None,
+                expected_storage_pledge: None,
+                power_base_epoch: 0,
+                replaced_day_reward: None,
+                sector_key_cid: None,
+                flags: SectorOnChainInfoFlags::SIMPLE_QA_POWER,
+                daily_fee: TokenAmount::zero().into(),
+            })
+            .collect::<Vec<_>>();
+        let sector_numbers =
+            BitField::try_from_bits(sectors.iter().map(|sector| sector.sector_number))?;
+        ensure!(
+            sector_numbers.len() == sectors.len() as u64,
+            "duplicate sector numbers for miner {address}"
+        );
+        state.allocate_sector_numbers(self.db, &sector_numbers, CollisionPolicy::DenyCollisions)?;
+        state.put_sectors(self.db, sectors.clone())?;
+
+        // Sectors are assigned to deadlines as if they had already been proven
+        let partition_size = window_post_proof
+            .window_post_partition_sectors()
+            .map_err(anyhow::Error::msg)?;
+        let size = seal_proof.sector_size().map_err(anyhow::Error::msg)?;
+        let mut deadlines = state.load_deadlines(self.db)?;
+        let deadline_vec = (0..self.policy.wpost_period_deadlines)
+            .map(|idx| Ok(Some(deadlines.load_deadline(self.db, idx)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let assignment = assign_deadlines(
+            &self.policy,
+            self.policy.max_partitions_per_deadline,
+            partition_size,
+            &deadline_vec,
+            sectors,
+        )?;
+        let mut power = BigInt::zero();
+        for (idx, (deadline, sectors)) in deadline_vec.into_iter().zip(assignment).enumerate() {
+            let Some(mut deadline) = deadline.filter(|_| !sectors.is_empty()) else {
+                continue;
+            };
+            let quant = state.quant_spec_for_deadline(&self.policy, idx as u64);
+            let (added, _) =
+                deadline.add_sectors(self.db, partition_size, true, true, &sectors, size, quant)?;
+            power += added.raw;
+            deadlines.update_deadline(&self.policy, self.db, idx as u64, &deadline)?;
+        }
+        state.save_deadlines(self.db, deadlines)?;
+        self.set_actor(BuiltinActor::Miner, address, &state)?;
+
+        // Power claim, with quality-adjusted power equal to raw power as there are no deals
+        let mut claims = self.power.load_claims(self.db)?;
+        set_claim(
+            &mut claims,
+            &address,
+            Claim {
+                window_post_proof_type: window_post_proof,
+                raw_byte_power: power.clone(),
+                quality_adj_power: power.clone(),
+            },
+        )?;
+        self.power.save_claims(&mut claims)?;
+        self.power.miner_count += 1;
+        self.power.total_bytes_committed += &power;
+        self.power.total_qa_bytes_committed += &power;
+        if power >= consensus_miner_min_power(&self.policy, window_post_proof)? {
+            self.power.miner_above_min_power_count += 1;
+            self.power.total_raw_byte_power += &power;
+            self.power.total_quality_adj_power += &power;
+            self.power.this_epoch_raw_byte_power += &power;
+            self.power.this_epoch_quality_adj_power += &power;
+        }
+        Ok(())
+    }
+}
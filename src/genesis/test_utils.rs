@@ -0,0 +1,73 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Genesis of a devnet with a single miner, for the tests of block production.
+
+use std::{mem::discriminant, sync::Arc};
+
+use super::{GenesisTemplate, GENESIS_ACTORS_VERSION};
+use crate::blocks::CachingBlockHeader;
+use crate::networks::{ChainConfig, ACTOR_BUNDLES_METADATA};
+use crate::shim::{address::Address, machine::BuiltinActorManifest};
+use crate::utils::db::CborStoreExt as _;
+use fvm_ipld_blockstore::Blockstore;
+
+/// ID address of the miner of [`solo_genesis`], the first miner ID.
+pub const SOLO_MINER: Address = Address::new_id(1000);
+
+/// Allowance of the verifier of [`solo_genesis`], in bytes.
+pub const VERIFIER_ALLOWANCE: u64 = 3072;
+
+/// Builds the genesis of a devnet whose only miner, [`SOLO_MINER`], is owned by the BLS address
+/// `owner`, and where `account` holds `1000 FIL` and is a verifier with [`VERIFIER_ALLOWANCE`].
+///
+/// The actors have the code CIDs of the devnet actor bundle. Unless the bundle is in `db`, their
+/// code is replaced by empty blocks, so the genesis tipset can be exported and built upon but
+/// messages can't be executed.
+pub fn solo_genesis<DB: Blockstore>(
+    db: &Arc<DB>,
+    chain_config: &ChainConfig,
+    owner: &Address,
+    account: &Address,
+) -> CachingBlockHeader {
+    let bundle = ACTOR_BUNDLES_METADATA
+        .values()
+        .find(|bundle| {
+            discriminant(&bundle.network) == discriminant(&chain_config.network)
+                && bundle.actor_major_version().ok() == Some(GENESIS_ACTORS_VERSION)
+        })
+        .unwrap();
+    let actor_list = bundle
+        .manifest
+        .builtin_actors()
+        .map(|(actor, code)| (actor.name().to_owned(), code))
+        .collect::<Vec<_>>();
+    for (_, code) in &actor_list {
+        if !db.has(code).unwrap() {
+            db.put_keyed(code, &[]).unwrap();
+        }
+    }
+    let manifest =
+        BuiltinActorManifest::load_v1_actor_list(db, &db.put_cbor_default(&actor_list).unwrap())
+            .unwrap();
+
+    let template: GenesisTemplate = toml::from_str(&format!(
+        r#"
+        timestamp = 1700000000
+
+        [[accounts]]
+        address = "{account}"
+        balance = "1000 FIL"
+
+        [[miners]]
+        owner = "{owner}"
+        fake_sectors = 4
+
+        [[verifiers]]
+        address = "{account}"
+        allowance = {VERIFIER_ALLOWANCE}
+        "#
+    ))
+    .unwrap();
+    template.build(db, &manifest, chain_config).unwrap()
+}
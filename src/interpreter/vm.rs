@@ -355,6 +355,51 @@ where
         mut callback: Option<impl FnMut(MessageCallbackCtx<'_>) -> anyhow::Result<()>>,
         enable_event_pushing: VMEvent,
     ) -> ApplyBlockResult {
+        let (receipts, events, _) = self.apply_messages_until(
+            messages,
+            epoch,
+            None,
+            callback.as_mut(),
+            enable_event_pushing,
+        )?;
+
+        if let Err(e) = self.run_cron(epoch, callback.as_mut()) {
+            tracing::error!("End of epoch cron failed to run: {}", e);
+        }
+
+        Ok((receipts, events))
+    }
+
+    /// Applies the block messages from a Tipset that precede the message `cid`, along with the
+    /// rewards of the blocks before the one including it. This leaves the VM in the state `cid` is
+    /// applied on.
+    pub fn apply_block_messages_before(
+        &mut self,
+        messages: &[BlockMessages],
+        epoch: ChainEpoch,
+        cid: Cid,
+    ) -> anyhow::Result<()> {
+        let (_, _, found) = self.apply_messages_until(
+            messages,
+            epoch,
+            Some(cid),
+            None::<fn(MessageCallbackCtx<'_>) -> anyhow::Result<()>>,
+            VMEvent::NotPushed,
+        )?;
+        anyhow::ensure!(found, "message {cid} not found in the block messages");
+        Ok(())
+    }
+
+    /// Applies the block messages and the block rewards, stopping right before the message
+    /// `stop_before` if any. Returns the receipts, the events and whether it stopped.
+    fn apply_messages_until(
+        &mut self,
+        messages: &[BlockMessages],
+        epoch: ChainEpoch,
+        stop_before: Option<Cid>,
+        mut callback: Option<impl FnMut(MessageCallbackCtx<'_>) -> anyhow::Result<()>>,
+        enable_event_pushing: VMEvent,
+    ) -> anyhow::Result<(Vec<Receipt>, Vec<Vec<StampedEvent>>, bool)> {
         let mut receipts = Vec::new();
         let mut events = Vec::new();
         let mut processed = HashSet::<Cid>::default();
@@ -397,6 +442,9 @@ where
             };
 
             for msg in block.messages.iter() {
+                if stop_before == Some(msg.cid()) {
+                    return Ok((receipts, events, true));
+                }
                 process_msg(msg)?;
             }
 
@@ -432,11 +480,7 @@ where
             }
         }
 
-        if let Err(e) = self.run_cron(epoch, callback.as_mut()) {
-            tracing::error!("End of epoch cron failed to run: {}", e);
-        }
-
-        Ok((receipts, events))
+        Ok((receipts, events, false))
     }
 
     /// Applies single message through VM and returns result from execution.
@@ -123,7 +123,6 @@ impl<DB, T> ChainStream<DB, T> {
         ChainStream { seen, ..self }
     }
 
-    #[allow(dead_code)]
     pub fn into_seen(self) -> CidHashSet {
         self.seen
     }
@@ -63,6 +63,7 @@ mod message;
 mod message_pool;
 mod metrics;
 mod networks;
+mod paychmgr;
 mod rpc;
 mod shim;
 mod state_manager;
@@ -10,6 +10,7 @@ pub mod hello;
 pub mod keypair;
 pub mod metrics;
 mod peer_manager;
+mod peer_store;
 pub mod ping;
 pub mod rpc;
 mod service;
@@ -3,7 +3,7 @@
 
 use std::{
     sync::Arc,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
 use ahash::{HashMap, HashSet};
@@ -12,7 +12,8 @@ use parking_lot::RwLock;
 use rand::seq::SliceRandom;
 use tracing::{debug, trace, warn};
 
-use crate::libp2p::*;
+use crate::db::SettingsStore;
+use crate::libp2p::{peer_store::*, *};
 
 /// New peer multiplier slightly less than 1 to incentivize choosing new peers.
 const NEW_PEER_MUL: f64 = 0.9;
@@ -25,6 +26,13 @@ const LOCAL_INV_ALPHA: u32 = 5;
 /// Global duration multiplier, affects duration delta change.
 const GLOBAL_INV_ALPHA: u32 = 20;
 
+/// Interval between saving the peer set to the peer store.
+const PEER_STORE_SAVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Delay before first saving the peer set to the peer store, once the node has connected to
+/// its first peers.
+const PEER_STORE_FIRST_SAVE_DELAY: Duration = Duration::from_secs(30);
+
 #[derive(Debug, Default)]
 /// Contains info about the peer's head [Tipset], as well as the request stats.
 struct PeerInfo {
@@ -36,6 +44,16 @@ struct PeerInfo {
     average_time: Duration,
 }
 
+impl From<&PeerRecord> for PeerInfo {
+    fn from(record: &PeerRecord) -> Self {
+        Self {
+            successes: record.successes,
+            failures: record.failures,
+            average_time: record.average_time,
+        }
+    }
+}
+
 /// Peer tracking sets, these are handled together to avoid race conditions or
 /// deadlocks when updating state.
 #[derive(Default)]
@@ -62,6 +80,10 @@ pub struct PeerManager {
     peer_ban_list: tokio::sync::RwLock<HashMap<PeerId, Option<Instant>>>,
     /// A set of peers that won't be proactively banned or disconnected from
     protected_peers: RwLock<HashSet<PeerId>>,
+    /// Peers known from previous runs and the addresses of identified peers
+    known_peers: RwLock<HashMap<PeerId, PeerRecord>>,
+    /// Store the peer set is persisted to, if any
+    peer_store: Option<Arc<dyn SettingsStore + Sync + Send>>,
 }
 
 impl Default for PeerManager {
@@ -74,11 +96,50 @@ impl Default for PeerManager {
             peer_ops_rx,
             peer_ban_list: Default::default(),
             protected_peers: Default::default(),
+            known_peers: Default::default(),
+            peer_store: None,
         }
     }
 }
 
 impl PeerManager {
+    /// Creates a peer manager that persists the peer set to the given store, restoring the
+    /// peers, their statistics, bans and protection flags saved by previous runs.
+    pub fn with_peer_store(store: Arc<dyn SettingsStore + Sync + Send>) -> anyhow::Result<Self> {
+        let peer_store = PeerStore::load(store.as_ref())?;
+        let mut manager = Self {
+            avg_global_time: RwLock::new(peer_store.average_time),
+            peer_store: Some(store),
+            ..Default::default()
+        };
+        let known_peers = peer_store.into_peers();
+        let now = SystemTime::now();
+        let mut ban_list = HashMap::default();
+        for (&peer, record) in &known_peers {
+            if record.protected {
+                manager.protected_peers.write().insert(peer);
+            } else if record.is_banned(now) {
+                let expiration = record.ban_expiry.and_then(|expiry| {
+                    Instant::now().checked_add(expiry.duration_since(now).unwrap_or_default())
+                });
+                ban_list.insert(peer, expiration);
+                manager.peer_ops_tx.send(PeerOperation::Ban {
+                    peer,
+                    user_agent: None,
+                    reason: "banned in a previous run".into(),
+                })?;
+            }
+        }
+        debug!(
+            "restored {} peers from the peer store, {} banned",
+            known_peers.len(),
+            ban_list.len()
+        );
+        *manager.known_peers.write() = known_peers;
+        *manager.peer_ban_list.get_mut() = ban_list;
+        Ok(manager)
+    }
+
     /// Returns true if peer is not marked as bad or not already in set.
     pub fn is_peer_new(&self, peer_id: &PeerId) -> bool {
         let peers = self.peers.read();
@@ -159,7 +220,10 @@ impl PeerManager {
         if peers.bad_peers.remove(peer) {
             metrics::BAD_PEERS.set(peers.bad_peers.len() as _);
         };
-        let peer_stats = peers.full_peers.entry(*peer).or_default();
+        let peer_stats = peers
+            .full_peers
+            .entry(*peer)
+            .or_insert_with(|| self.restored_peer_info(peer));
         peer_stats.successes += 1;
         log_time(peer_stats, dur);
     }
@@ -171,7 +235,10 @@ impl PeerManager {
         let mut peers = self.peers.write();
         if !peers.bad_peers.contains(peer) {
             metrics::PEER_FAILURE_TOTAL.inc();
-            let peer_stats = peers.full_peers.entry(*peer).or_default();
+            let peer_stats = peers
+                .full_peers
+                .entry(*peer)
+                .or_insert_with(|| self.restored_peer_info(peer));
             peer_stats.failures += 1;
             log_time(peer_stats, dur);
         }
@@ -301,6 +368,101 @@ impl PeerManager {
     pub fn is_peer_protected(&self, peer_id: &PeerId) -> bool {
         self.protected_peers.read().contains(peer_id)
     }
+
+    /// Request statistics of the peer from previous runs, if any.
+    fn restored_peer_info(&self, peer_id: &PeerId) -> PeerInfo {
+        self.known_peers
+            .read()
+            .get(peer_id)
+            .map(PeerInfo::from)
+            .unwrap_or_default()
+    }
+
+    /// Records the addresses the peer advertised, so that it can be dialed after a restart.
+    pub fn record_peer_addresses(&self, peer_id: PeerId, addresses: Vec<Multiaddr>) {
+        let mut known_peers = self.known_peers.write();
+        let record = known_peers.entry(peer_id).or_default();
+        record.addresses = addresses;
+        record.addresses.truncate(MAX_STORED_PEER_ADDRESSES);
+        record.last_seen = Some(SystemTime::now());
+    }
+
+    /// Returns the known peers to dial at start-up along with their addresses, ordered by the
+    /// success rate and latency of previous requests.
+    pub(in crate::libp2p) fn known_peers_to_dial(&self) -> Vec<(PeerId, Vec<Multiaddr>)> {
+        let now = SystemTime::now();
+        let known_peers = self.known_peers.read();
+        let mut peers: Vec<_> = known_peers
+            .iter()
+            .filter(|(_, record)| !record.addresses.is_empty() && !record.is_banned(now))
+            .map(|(peer, record)| {
+                let info = PeerInfo::from(record);
+                // Peers without failures come first, then the fastest ones.
+                let fail_rate = f64::from(info.failures) / f64::from(info.successes.max(1));
+                (peer, record, fail_rate, info.average_time)
+            })
+            .collect();
+        peers.sort_by(|(_, _, f1, t1), (_, _, f2, t2)| f1.total_cmp(f2).then(t1.cmp(t2)));
+        peers
+            .into_iter()
+            .take(SHUFFLE_PEERS_PREFIX)
+            .map(|(peer, record, ..)| (*peer, record.addresses.clone()))
+            .collect()
+    }
+
+    /// Saves the peer set to the peer store, if any.
+    pub async fn save_peer_store(&self) -> anyhow::Result<()> {
+        let Some(store) = &self.peer_store else {
+            return Ok(());
+        };
+        let now = SystemTime::now();
+        let now_instant = Instant::now();
+        let ban_list = self.peer_ban_list.read().await.clone();
+        let mut records = self.known_peers.read().clone();
+        for (peer, info) in self.peers.read().full_peers.iter() {
+            let record = records.entry(*peer).or_default();
+            record.successes = info.successes;
+            record.failures = info.failures;
+            record.average_time = info.average_time;
+        }
+        for record in records.values_mut() {
+            record.banned = false;
+            record.ban_expiry = None;
+            record.protected = false;
+        }
+        for (peer, expiration) in ban_list {
+            let record = records.entry(peer).or_default();
+            record.banned = true;
+            record.ban_expiry = expiration
+                .map(|expiration| now + expiration.saturating_duration_since(now_instant));
+        }
+        for peer in self.list_protected_peers() {
+            records.entry(peer).or_default().protected = true;
+        }
+
+        let peer_store = PeerStore::new(*self.avg_global_time.read(), records);
+        peer_store.save(store.as_ref())?;
+        // Drop the records that did not make it to the store to bound memory usage.
+        let mut known_peers = self.known_peers.write();
+        let stored = peer_store.into_peers();
+        known_peers.retain(|peer, _| stored.contains_key(peer));
+        Ok(())
+    }
+
+    /// Periodically saves the peer set to the peer store, starting soon after startup. The peer
+    /// set should also be saved with [`PeerManager::save_peer_store`] at shutdown.
+    pub async fn peer_store_task(self: Arc<Self>) -> anyhow::Result<()> {
+        let mut interval = tokio::time::interval_at(
+            tokio::time::Instant::now() + PEER_STORE_FIRST_SAVE_DELAY,
+            PEER_STORE_SAVE_INTERVAL,
+        );
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.save_peer_store().await {
+                warn!("failed to save the peer store: {e}");
+            }
+        }
+    }
 }
 
 fn remove_peer(peers: &mut PeerSets, peer_id: &PeerId) {
@@ -333,3 +495,41 @@ pub enum PeerOperation {
     },
     Unban(PeerId),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDB;
+
+    #[tokio::test]
+    async fn peer_manager_restores_peer_store() {
+        let db = Arc::new(MemoryDB::default());
+        let peer = PeerId::random();
+        let banned = PeerId::random();
+        let protected = PeerId::random();
+
+        let manager = PeerManager::with_peer_store(db.clone()).unwrap();
+        manager.record_peer_addresses(peer, vec!["/ip4/127.0.0.1/tcp/1234".parse().unwrap()]);
+        manager.log_success(&peer, Duration::from_millis(100));
+        manager.protect_peer(protected);
+        manager
+            .ban_peer(banned, "test", Some(Duration::from_secs(60)), |_| None)
+            .await;
+        manager.save_peer_store().await.unwrap();
+
+        let manager = PeerManager::with_peer_store(db).unwrap();
+        assert!(manager.is_peer_protected(&protected));
+        assert!(manager.peer_ban_list.read().await.contains_key(&banned));
+        assert_eq!(
+            manager.known_peers_to_dial(),
+            vec![(peer, vec!["/ip4/127.0.0.1/tcp/1234".parse().unwrap()])]
+        );
+        // Statistics are restored once the peer is back.
+        manager.log_success(&peer, Duration::from_millis(100));
+        assert_eq!(manager.peers.read().full_peers[&peer].successes, 2);
+        assert!(matches!(
+            manager.peer_ops_rx().try_recv(),
+            Ok(PeerOperation::Ban { peer, .. }) if peer == banned
+        ));
+    }
+}
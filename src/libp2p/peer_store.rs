@@ -0,0 +1,197 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::time::{Duration, SystemTime};
+
+use ahash::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::db::{setting_keys::PEER_STORE_KEY, SettingsStore, SettingsStoreExt as _};
+use crate::libp2p::*;
+
+/// Maximum number of peers kept in the peer store.
+pub(in crate::libp2p) const MAX_STORED_PEERS: usize = 1000;
+
+/// Maximum number of addresses kept for each peer in the peer store.
+pub(in crate::libp2p) const MAX_STORED_PEER_ADDRESSES: usize = 10;
+
+/// Peer state kept across restarts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(in crate::libp2p) struct PeerRecord {
+    /// Addresses the peer advertised via `identify`.
+    pub addresses: Vec<Multiaddr>,
+    /// Number of successful requests.
+    pub successes: u32,
+    /// Number of failed requests.
+    pub failures: u32,
+    /// Average response time for the peer.
+    pub average_time: Duration,
+    /// Whether the peer is banned.
+    pub banned: bool,
+    /// Expiration time of the ban, `None` for permanent bans.
+    pub ban_expiry: Option<SystemTime>,
+    /// Whether the peer is protected from being banned or disconnected from.
+    pub protected: bool,
+    /// Last time the peer was seen.
+    pub last_seen: Option<SystemTime>,
+}
+
+impl PeerRecord {
+    /// Returns true if the record has a ban that has not expired yet.
+    pub fn is_banned(&self, now: SystemTime) -> bool {
+        self.banned && self.ban_expiry.is_none_or(|expiry| expiry > now)
+    }
+
+    /// Returns true if the record carries information worth dialing or remembering the peer for.
+    fn is_useful(&self, now: SystemTime) -> bool {
+        self.protected || self.is_banned(now) || !self.addresses.is_empty()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredPeer {
+    peer_id: PeerId,
+    #[serde(flatten)]
+    record: PeerRecord,
+}
+
+/// Snapshot of the peer manager state that is persisted in the settings store.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(in crate::libp2p) struct PeerStore {
+    /// Average response time from peers.
+    pub average_time: Duration,
+    /// Known peers.
+    peers: Vec<StoredPeer>,
+}
+
+impl PeerStore {
+    pub fn new(average_time: Duration, peers: HashMap<PeerId, PeerRecord>) -> Self {
+        let now = SystemTime::now();
+        let mut peers: Vec<_> = peers
+            .into_iter()
+            .filter(|(_, record)| record.is_useful(now))
+            .map(|(peer_id, mut record)| {
+                record.addresses.truncate(MAX_STORED_PEER_ADDRESSES);
+                StoredPeer { peer_id, record }
+            })
+            .collect();
+        // Keep protected and banned peers, followed by the most recently seen ones.
+        peers.sort_by_key(|it| {
+            (
+                !it.record.protected,
+                !it.record.is_banned(now),
+                std::cmp::Reverse(it.record.last_seen),
+            )
+        });
+        peers.truncate(MAX_STORED_PEERS);
+        Self {
+            average_time,
+            peers,
+        }
+    }
+
+    /// Loads the peer store, returning an empty one if none has been saved yet.
+    pub fn load(store: &(impl SettingsStore + ?Sized)) -> anyhow::Result<Self> {
+        Ok(store.read_obj(PEER_STORE_KEY)?.unwrap_or_default())
+    }
+
+    pub fn save(&self, store: &(impl SettingsStore + ?Sized)) -> anyhow::Result<()> {
+        store.write_obj(PEER_STORE_KEY, self)
+    }
+
+    /// Returns the stored peers, dropping expired bans.
+    pub fn into_peers(self) -> HashMap<PeerId, PeerRecord> {
+        let now = SystemTime::now();
+        self.peers
+            .into_iter()
+            .map(
+                |StoredPeer {
+                     peer_id,
+                     mut record,
+                 }| {
+                    if !record.is_banned(now) {
+                        record.banned = false;
+                        record.ban_expiry = None;
+                    }
+                    (peer_id, record)
+                },
+            )
+            .filter(|(_, record)| record.is_useful(now))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDB;
+
+    #[test]
+    fn peer_store_roundtrip() {
+        let db = MemoryDB::default();
+        let now = SystemTime::now();
+        let peer = PeerId::random();
+        let record = PeerRecord {
+            addresses: vec!["/ip4/127.0.0.1/tcp/1234".parse().unwrap()],
+            successes: 3,
+            failures: 1,
+            average_time: Duration::from_millis(250),
+            last_seen: Some(now),
+            ..Default::default()
+        };
+        let expired_ban = PeerRecord {
+            banned: true,
+            ban_expiry: Some(now - Duration::from_secs(1)),
+            ..Default::default()
+        };
+        let permanent_ban = PeerRecord {
+            banned: true,
+            ..Default::default()
+        };
+        let banned = PeerId::random();
+        let peers = HashMap::from_iter([
+            (peer, record.clone()),
+            (PeerId::random(), expired_ban),
+            (banned, permanent_ban.clone()),
+        ]);
+        PeerStore::new(Duration::from_secs(1), peers)
+            .save(&db)
+            .unwrap();
+
+        let store = PeerStore::load(&db).unwrap();
+        assert_eq!(store.average_time, Duration::from_secs(1));
+        let peers = store.into_peers();
+        assert_eq!(peers.len(), 2);
+        assert_eq!(peers.get(&peer), Some(&record));
+        assert_eq!(peers.get(&banned), Some(&permanent_ban));
+    }
+
+    #[test]
+    fn peer_store_is_bounded() {
+        let now = SystemTime::now();
+        let protected = PeerId::random();
+        let mut peers: HashMap<_, _> = (0..MAX_STORED_PEERS as u64 + 10)
+            .map(|i| {
+                let record = PeerRecord {
+                    addresses: vec!["/ip4/127.0.0.1/tcp/1234".parse().unwrap(); 20],
+                    last_seen: Some(now - Duration::from_secs(i)),
+                    ..Default::default()
+                };
+                (PeerId::random(), record)
+            })
+            .collect();
+        peers.insert(
+            protected,
+            PeerRecord {
+                protected: true,
+                ..Default::default()
+            },
+        );
+        let peers = PeerStore::new(Duration::ZERO, peers).into_peers();
+        assert_eq!(peers.len(), MAX_STORED_PEERS);
+        assert!(peers.contains_key(&protected));
+        assert!(peers
+            .values()
+            .all(|it| it.addresses.len() <= MAX_STORED_PEER_ADDRESSES));
+    }
+}
@@ -31,7 +31,10 @@ use libp2p::{
     metrics::{Metrics, Recorder},
     multiaddr::Protocol,
     noise, ping, request_response,
-    swarm::{DialError, SwarmEvent},
+    swarm::{
+        dial_opts::{DialOpts, PeerCondition},
+        DialError, SwarmEvent,
+    },
     tcp, yamux, PeerId, Swarm, SwarmBuilder,
 };
 use tokio_stream::wrappers::IntervalStream;
@@ -284,6 +287,7 @@ where
         if let Err(e) = self.swarm.behaviour_mut().bootstrap() {
             warn!("Failed to bootstrap with Kademlia: {e}");
         }
+        dial_known_peers(&mut self.swarm, &self.peer_manager);
 
         let bitswap_request_manager = self.swarm.behaviour().bitswap.request_manager();
         let mut swarm_stream = self.swarm.fuse();
@@ -395,6 +399,19 @@ where
     }
 }
 
+/// Dials the peers known from previous runs, best ones first.
+fn dial_known_peers(swarm: &mut Swarm<ForestBehaviour>, peer_manager: &PeerManager) {
+    for (peer, addresses) in peer_manager.known_peers_to_dial() {
+        let opts = DialOpts::peer_id(peer)
+            .condition(PeerCondition::Disconnected)
+            .addresses(addresses)
+            .build();
+        if let Err(e) = swarm.dial(opts) {
+            debug!("Failed to dial known peer {peer}: {e}");
+        }
+    }
+}
+
 fn dial_to_bootstrap_peers_if_needed(
     swarm: &mut Swarm<ForestBehaviour>,
     bootstrap_peers: &HashMap<PeerId, Multiaddr>,
@@ -612,6 +629,8 @@ async fn handle_discovery_event(
                             |p| get_user_agent(peer_info_map, p),
                         )
                         .await;
+                } else {
+                    peer_manager.record_peer_addresses(*peer_id, info.listen_addrs.clone());
                 }
             }
             DerivedDiscoveryBehaviourEvent::Identify(_) => {}
@@ -0,0 +1,455 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Lotus JSON of the parameters and return values of builtin actor methods, used to decode
+//! messages for humans, see [`crate::rpc::state::StateDecodeParams`].
+//!
+//! The types are the version-independent ones of the `params` module of each actor shim, see
+//! [`crate::shim::actors::miner::params`] for example.
+
+use super::*;
+use crate::shim::actors::{BatchReturn, FailCode};
+use crate::shim::address::Address;
+use crate::shim::econ::TokenAmount;
+use crate::shim::error::ExitCode;
+use fvm_ipld_encoding::{BytesDe, RawBytes};
+use fvm_shared4::sector::RegisteredAggregateProof;
+
+impl HasLotusJson for ExitCode {
+    type LotusJson = u32;
+
+    #[cfg(test)]
+    fn snapshots() -> Vec<(serde_json::Value, Self)> {
+        vec![(json!(16), ExitCode::from(16))]
+    }
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        self.value()
+    }
+
+    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+        Self::from(lotus_json)
+    }
+}
+
+impl HasLotusJson for BytesDe {
+    type LotusJson = <Vec<u8> as HasLotusJson>::LotusJson;
+
+    #[cfg(test)]
+    fn snapshots() -> Vec<(serde_json::Value, Self)> {
+        vec![(json!("aGVsbG8gd29ybGQh"), BytesDe(b"hello world!".to_vec()))]
+    }
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        self.0.into_lotus_json()
+    }
+
+    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+        Self(Vec::from_lotus_json(lotus_json))
+    }
+}
+
+impl HasLotusJson for RegisteredAggregateProof {
+    type LotusJson = i64;
+
+    #[cfg(test)]
+    fn snapshots() -> Vec<(serde_json::Value, Self)> {
+        vec![(json!(1), RegisteredAggregateProof::SnarkPackV2)]
+    }
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        i64::from(self)
+    }
+
+    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+        Self::from(lotus_json)
+    }
+}
+
+/// Declares the Lotus JSON of actor types whose fields all implement [`HasLotusJson`], i.e.,
+/// `Type` gets a `TypeLotusJson` with the same fields in `PascalCase`. The fields hold the Lotus
+/// JSON of the actor fields, as nested actor types do not implement [`Clone`].
+macro_rules! actor_types_lotus_json {
+    ($($(#[$attr:meta])* $ty:ident { $($field:ident: $field_ty:ty),* $(,)? })*) => {
+        $(
+            paste::paste! {
+                #[derive(Serialize, Deserialize)]
+                #[serde(rename_all = "PascalCase")]
+                $(#[$attr])*
+                pub struct [<$ty LotusJson>] {
+                    $(pub $field: <$field_ty as HasLotusJson>::LotusJson,)*
+                }
+
+                impl HasLotusJson for $ty {
+                    type LotusJson = [<$ty LotusJson>];
+
+                    #[cfg(test)]
+                    fn snapshots() -> Vec<(serde_json::Value, Self)> {
+                        vec![]
+                    }
+
+                    fn into_lotus_json(self) -> Self::LotusJson {
+                        let Self { $($field),* } = self;
+                        Self::LotusJson {
+                            $($field: $field.into_lotus_json(),)*
+                        }
+                    }
+
+                    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+                        let Self::LotusJson { $($field),* } = lotus_json;
+                        Self {
+                            $($field: HasLotusJson::from_lotus_json($field),)*
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+actor_types_lotus_json! {
+    BatchReturn { success_count: u32, fail_codes: Vec<FailCode> }
+    FailCode { idx: u32, code: ExitCode }
+}
+
+pub mod account {
+    use super::*;
+    use crate::shim::actors::account::params::*;
+
+    actor_types_lotus_json! {
+        #[serde(transparent)]
+        ConstructorParams { address: Address }
+        #[serde(transparent)]
+        PubkeyAddressReturn { address: Address }
+        AuthenticateMessageParams { signature: Vec<u8>, message: Vec<u8> }
+        #[serde(transparent)]
+        AuthenticateMessageReturn { authenticated: bool }
+    }
+}
+
+pub mod init {
+    use super::*;
+    use crate::shim::actors::init::params::*;
+
+    actor_types_lotus_json! {
+        ConstructorParams { network_name: String }
+        ExecParams { code_cid: ::cid::Cid, constructor_params: RawBytes }
+        ExecReturn { id_address: Address, robust_address: Address }
+        Exec4Params { code_cid: ::cid::Cid, constructor_params: RawBytes, subaddress: RawBytes }
+    }
+}
+
+pub mod multisig {
+    use super::*;
+    use crate::shim::actors::multisig::params::*;
+    use crate::shim::clock::ChainEpoch;
+
+    actor_types_lotus_json! {
+        ConstructorParams {
+            signers: Vec<Address>,
+            num_approvals_threshold: u64,
+            unlock_duration: ChainEpoch,
+            start_epoch: ChainEpoch,
+        }
+        ProposeParams { to: Address, value: TokenAmount, method: u64, params: RawBytes }
+        ProposeReturn { txn_id: TxnID, applied: bool, code: ExitCode, ret: RawBytes }
+        TxnIDParams { id: TxnID, proposal_hash: Vec<u8> }
+        ApproveReturn { applied: bool, code: ExitCode, ret: RawBytes }
+        AddSignerParams { signer: Address, increase: bool }
+        RemoveSignerParams { signer: Address, decrease: bool }
+        SwapSignerParams { from: Address, to: Address }
+        ChangeNumApprovalsThresholdParams { new_threshold: u64 }
+        LockBalanceParams {
+            start_epoch: ChainEpoch,
+            unlock_duration: ChainEpoch,
+            amount: TokenAmount,
+        }
+    }
+}
+
+pub mod miner {
+    use super::*;
+    use crate::shim::actors::miner::params::*;
+    use crate::shim::clock::ChainEpoch;
+    use crate::shim::sector::{PoStProof, RegisteredSealProof};
+    use fil_actors_shared::fvm_ipld_bitfield::BitField;
+
+    actor_types_lotus_json! {
+        GetControlAddressesReturn {
+            owner: Address,
+            worker: Address,
+            control_addresses: Vec<Address>,
+        }
+        ChangeWorkerAddressParams { new_worker: Address, new_control_addresses: Vec<Address> }
+        ChangePeerIDParams { new_id: Vec<u8> }
+        ChangeMultiaddrsParams { new_multi_addrs: Vec<BytesDe> }
+        #[serde(transparent)]
+        ChangeOwnerAddressParams { new_owner: Address }
+        SubmitWindowedPoStParams {
+            deadline: u64,
+            partitions: Vec<PoStPartition>,
+            proofs: Vec<PoStProof>,
+            chain_commit_epoch: ChainEpoch,
+            chain_commit_rand: Vec<u8>,
+        }
+        PoStPartition { index: u64, skipped: BitField }
+        DeclareFaultsParams { faults: Vec<FaultDeclaration> }
+        FaultDeclaration { deadline: u64, partition: u64, sectors: BitField }
+        DeclareFaultsRecoveredParams { recoveries: Vec<RecoveryDeclaration> }
+        RecoveryDeclaration { deadline: u64, partition: u64, sectors: BitField }
+        TerminateSectorsParams { terminations: Vec<TerminationDeclaration> }
+        TerminationDeclaration { deadline: u64, partition: u64, sectors: BitField }
+        TerminateSectorsReturn { done: bool }
+        WithdrawBalanceParams { amount_requested: TokenAmount }
+        #[serde(transparent)]
+        WithdrawBalanceReturn { amount_withdrawn: TokenAmount }
+        ProveCommitAggregateParams { sector_numbers: BitField, aggregate_proof: RawBytes }
+        PreCommitSectorBatchParams2 { sectors: Vec<SectorPreCommitInfo> }
+        SectorPreCommitInfo {
+            seal_proof: RegisteredSealProof,
+            sector_number: u64,
+            sealed_cid: ::cid::Cid,
+            seal_rand_epoch: ChainEpoch,
+            deal_ids: Vec<u64>,
+            expiration: ChainEpoch,
+            unsealed_cid: Option<::cid::Cid>,
+        }
+        ProveCommitSectors3Params {
+            sector_activations: Vec<SectorActivationManifest>,
+            sector_proofs: Vec<RawBytes>,
+            aggregate_proof: RawBytes,
+            aggregate_proof_type: Option<RegisteredAggregateProof>,
+            require_activation_success: bool,
+            require_notification_success: bool,
+        }
+        SectorActivationManifest { sector_number: u64, pieces: Vec<PieceActivationManifest> }
+        PieceActivationManifest {
+            cid: ::cid::Cid,
+            size: u64,
+            verified_allocation_key: Option<VerifiedAllocationKey>,
+            notify: Vec<DataActivationNotification>,
+        }
+        VerifiedAllocationKey { client: u64, id: u64 }
+        DataActivationNotification { address: Address, payload: RawBytes }
+        ProveCommitSectors3Return { activation_results: BatchReturn }
+        ExtendSectorExpiration2Params { extensions: Vec<ExpirationExtension2> }
+        ExpirationExtension2 {
+            deadline: u64,
+            partition: u64,
+            sectors: BitField,
+            sectors_with_claims: Vec<SectorClaim>,
+            new_expiration: ChainEpoch,
+        }
+        SectorClaim { sector_number: u64, maintain_claims: Vec<u64>, drop_claims: Vec<u64> }
+        ChangeBeneficiaryParams {
+            new_beneficiary: Address,
+            new_quota: TokenAmount,
+            new_expiration: ChainEpoch,
+        }
+    }
+}
+
+pub mod power {
+    use super::*;
+    use crate::shim::actors::power::params::*;
+    use crate::shim::sector::RegisteredPoStProof;
+
+    actor_types_lotus_json! {
+        CreateMinerParams {
+            owner: Address,
+            worker: Address,
+            window_post_proof_type: RegisteredPoStProof,
+            peer: Vec<u8>,
+            multiaddrs: Vec<BytesDe>,
+        }
+        CreateMinerReturn { id_address: Address, robust_address: Address }
+    }
+}
+
+pub mod market {
+    use super::*;
+    use crate::shim::actors::market::params::*;
+    use crate::shim::clock::ChainEpoch;
+    use crate::shim::crypto::Signature;
+    use fil_actors_shared::fvm_ipld_bitfield::BitField;
+
+    /// A string label is a JSON string, a bytes label is `{ "Bytes": <base64> }`.
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    pub enum LabelLotusJson {
+        String(String),
+        Bytes {
+            #[serde(rename = "Bytes")]
+            bytes: <Vec<u8> as HasLotusJson>::LotusJson,
+        },
+    }
+
+    impl HasLotusJson for Label {
+        type LotusJson = LabelLotusJson;
+
+        #[cfg(test)]
+        fn snapshots() -> Vec<(serde_json::Value, Self)> {
+            vec![
+                (json!("label"), Label::String("label".into())),
+                (
+                    json!({ "Bytes": "aGVsbG8gd29ybGQh" }),
+                    Label::Bytes(b"hello world!".to_vec()),
+                ),
+            ]
+        }
+
+        fn into_lotus_json(self) -> Self::LotusJson {
+            match self {
+                Label::String(s) => LabelLotusJson::String(s),
+                Label::Bytes(b) => LabelLotusJson::Bytes {
+                    bytes: b.into_lotus_json(),
+                },
+            }
+        }
+
+        fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+            match lotus_json {
+                LabelLotusJson::String(s) => Label::String(s),
+                LabelLotusJson::Bytes { bytes } => Label::Bytes(Vec::from_lotus_json(bytes)),
+            }
+        }
+    }
+
+    actor_types_lotus_json! {
+        #[serde(transparent)]
+        AddBalanceParams { provider_or_client: Address }
+        WithdrawBalanceParams { provider_or_client: Address, amount: TokenAmount }
+        #[serde(transparent)]
+        WithdrawBalanceReturn { amount_withdrawn: TokenAmount }
+        PublishStorageDealsParams { deals: Vec<ClientDealProposal> }
+        ClientDealProposal { proposal: DealProposal, client_signature: Signature }
+        DealProposal {
+            piece_cid: ::cid::Cid,
+            piece_size: u64,
+            verified_deal: bool,
+            client: Address,
+            provider: Address,
+            label: Label,
+            start_epoch: ChainEpoch,
+            end_epoch: ChainEpoch,
+            storage_price_per_epoch: TokenAmount,
+            provider_collateral: TokenAmount,
+            client_collateral: TokenAmount,
+        }
+        PublishStorageDealsReturn { ids: Vec<u64>, valid_deals: BitField }
+    }
+}
+
+pub mod verifreg {
+    use super::*;
+    use crate::shim::actors::verifreg::params::*;
+    use crate::shim::clock::ChainEpoch;
+
+    actor_types_lotus_json! {
+        VerifierParams { address: Address, allowance: num::BigInt }
+        ClaimAllocationsParams { sectors: Vec<SectorAllocationClaims>, all_or_nothing: bool }
+        SectorAllocationClaims { sector: u64, expiry: ChainEpoch, claims: Vec<AllocationClaim> }
+        AllocationClaim { client: u64, allocation_id: u64, data: ::cid::Cid, size: u64 }
+        ClaimAllocationsReturn {
+            sector_results: BatchReturn,
+            sector_claims: Vec<SectorClaimSummary>,
+        }
+        #[serde(transparent)]
+        SectorClaimSummary { claimed_space: num::BigInt }
+        ClaimAllocationsParamsV9 { sectors: Vec<SectorAllocationClaimV9>, all_or_nothing: bool }
+        SectorAllocationClaimV9 {
+            client: u64,
+            allocation_id: u64,
+            data: ::cid::Cid,
+            size: u64,
+            sector: u64,
+            sector_expiry: ChainEpoch,
+        }
+        ClaimAllocationsReturnV9 { batch_info: BatchReturn, claimed_space: num::BigInt }
+    }
+}
+
+pub mod datacap {
+    use super::*;
+    use crate::shim::actors::datacap::params::*;
+
+    actor_types_lotus_json! {
+        MintParams { to: Address, amount: TokenAmount, operators: Vec<Address> }
+        MintReturn { balance: TokenAmount, supply: TokenAmount, recipient_data: RawBytes }
+        DestroyParams { owner: Address, amount: TokenAmount }
+        TransferParams { to: Address, amount: TokenAmount, operator_data: RawBytes }
+        TransferReturn {
+            from_balance: TokenAmount,
+            to_balance: TokenAmount,
+            recipient_data: RawBytes,
+        }
+        TransferFromParams {
+            from: Address,
+            to: Address,
+            amount: TokenAmount,
+            operator_data: RawBytes,
+        }
+        TransferFromReturn {
+            from_balance: TokenAmount,
+            to_balance: TokenAmount,
+            allowance: TokenAmount,
+            recipient_data: RawBytes,
+        }
+        IncreaseAllowanceParams { operator: Address, increase: TokenAmount }
+        DecreaseAllowanceParams { operator: Address, decrease: TokenAmount }
+        RevokeAllowanceParams { operator: Address }
+        GetAllowanceParams { owner: Address, operator: Address }
+        BurnParams { amount: TokenAmount }
+        BurnReturn { balance: TokenAmount }
+        BurnFromParams { owner: Address, amount: TokenAmount }
+        BurnFromReturn { balance: TokenAmount, allowance: TokenAmount }
+    }
+}
+
+pub mod paych {
+    use super::*;
+    use crate::shim::actors::paych::*;
+
+    actor_types_lotus_json! {
+        ConstructorParams { from: Address, to: Address }
+        UpdateChannelStateParams { sv: SignedVoucher, secret: Vec<u8> }
+    }
+}
+
+pub mod evm {
+    use super::*;
+    use crate::shim::actors::evm::params::*;
+
+    actor_types_lotus_json! {
+        #[serde(transparent)]
+        InvokeContractParams { input_data: Vec<u8> }
+        #[serde(transparent)]
+        InvokeContractReturn { output_data: Vec<u8> }
+        #[serde(transparent)]
+        BytecodeReturn { code: Option<::cid::Cid> }
+        GetStorageAtParams { storage_key: Vec<u8> }
+        #[serde(transparent)]
+        GetStorageAtReturn { storage: Vec<u8> }
+    }
+}
+
+pub mod eam {
+    use super::*;
+    use crate::shim::actors::eam::params::*;
+
+    actor_types_lotus_json! {
+        CreateParams { initcode: Vec<u8>, nonce: u64 }
+        Create2Params { initcode: Vec<u8>, salt: Vec<u8> }
+        #[serde(transparent)]
+        CreateExternalParams { initcode: Vec<u8> }
+        CreateReturn { actor_id: u64, robust_address: Option<Address>, eth_address: Vec<u8> }
+    }
+}
+
+#[test]
+fn snapshots() {
+    assert_all_snapshots::<ExitCode>();
+    assert_all_snapshots::<BytesDe>();
+    assert_all_snapshots::<RegisteredAggregateProof>();
+    assert_all_snapshots::<crate::shim::actors::market::params::Label>();
+}
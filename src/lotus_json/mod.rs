@@ -219,6 +219,7 @@ decl_and_test!(
 
 // If a module cannot be tested normally above, you MAY declare it separately here
 // but you MUST document any tech debt - the reason WHY it cannot be tested above.
+mod actor_params; // actor method parameter types: !quickcheck::Arbitrary
 mod allocation;
 mod beneficiary_term; // fil_actor_miner_state::v12::BeneficiaryTerm: !quickcheck::Arbitrary
 mod bit_field; //  fil_actors_shared::fvm_ipld_bitfield::BitField: !quickcheck::Arbitrary
@@ -226,12 +227,14 @@ mod hash_map;
 mod ipld; // NaN != NaN
 mod miner_info; // fil_actor_miner_state::v12::MinerInfo: !quickcheck::Arbitrary
 mod miner_power; // actors::miner::MinerInfo: !quickcheck::Arbitrary
+mod mpool_config; // crate::message_pool::MpoolConfig: !quickcheck::Arbitrary
 mod nonempty; // can't make snapshots of generic type
 mod opt; // can't make snapshots of generic type
 mod pending_beneficiary_change; // fil_actor_miner_state::v12::PendingBeneficiaryChange: !quickcheck::Arbitrary
 mod power_claim; // actors::power::Claim: !quickcheck::Arbitrary
 mod raw_bytes; // fvm_ipld_encoding::RawBytes: !quickcheck::Arbitrary
 mod receipt; // shim type roundtrip is wrong - see module
+mod signed_voucher; // actors::paych::SignedVoucher: !quickcheck::Arbitrary
 mod vec; // can't make snapshots of generic type
 mod verifreg_claim;
 
@@ -605,6 +608,83 @@ mod fixme {
             )
         }
     }
+
+    impl<A: HasLotusJson, B: HasLotusJson, C: HasLotusJson, D: HasLotusJson, E: HasLotusJson>
+        HasLotusJson for (A, B, C, D, E)
+    {
+        type LotusJson = (
+            A::LotusJson,
+            B::LotusJson,
+            C::LotusJson,
+            D::LotusJson,
+            E::LotusJson,
+        );
+        #[cfg(test)]
+        fn snapshots() -> Vec<(serde_json::Value, Self)> {
+            unimplemented!("tests are trivial for HasLotusJson<LotusJson = Self>")
+        }
+        fn into_lotus_json(self) -> Self::LotusJson {
+            (
+                self.0.into_lotus_json(),
+                self.1.into_lotus_json(),
+                self.2.into_lotus_json(),
+                self.3.into_lotus_json(),
+                self.4.into_lotus_json(),
+            )
+        }
+        fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+            (
+                HasLotusJson::from_lotus_json(lotus_json.0),
+                HasLotusJson::from_lotus_json(lotus_json.1),
+                HasLotusJson::from_lotus_json(lotus_json.2),
+                HasLotusJson::from_lotus_json(lotus_json.3),
+                HasLotusJson::from_lotus_json(lotus_json.4),
+            )
+        }
+    }
+
+    impl<
+            A: HasLotusJson,
+            B: HasLotusJson,
+            C: HasLotusJson,
+            D: HasLotusJson,
+            E: HasLotusJson,
+            F: HasLotusJson,
+        > HasLotusJson for (A, B, C, D, E, F)
+    {
+        type LotusJson = (
+            A::LotusJson,
+            B::LotusJson,
+            C::LotusJson,
+            D::LotusJson,
+            E::LotusJson,
+            F::LotusJson,
+        );
+        #[cfg(test)]
+        fn snapshots() -> Vec<(serde_json::Value, Self)> {
+            unimplemented!("tests are trivial for HasLotusJson<LotusJson = Self>")
+        }
+        fn into_lotus_json(self) -> Self::LotusJson {
+            (
+                self.0.into_lotus_json(),
+                self.1.into_lotus_json(),
+                self.2.into_lotus_json(),
+                self.3.into_lotus_json(),
+                self.4.into_lotus_json(),
+                self.5.into_lotus_json(),
+            )
+        }
+        fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+            (
+                HasLotusJson::from_lotus_json(lotus_json.0),
+                HasLotusJson::from_lotus_json(lotus_json.1),
+                HasLotusJson::from_lotus_json(lotus_json.2),
+                HasLotusJson::from_lotus_json(lotus_json.3),
+                HasLotusJson::from_lotus_json(lotus_json.4),
+                HasLotusJson::from_lotus_json(lotus_json.5),
+            )
+        }
+    }
 }
 
 #[cfg(test)]
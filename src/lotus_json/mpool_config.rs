@@ -0,0 +1,86 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use crate::message_pool::MpoolConfig;
+use crate::shim::address::Address;
+use std::time::Duration;
+
+use super::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+#[schemars(rename = "MpoolConfig")]
+pub struct MpoolConfigLotusJson {
+    #[schemars(with = "LotusJson<Vec<Address>>")]
+    #[serde(with = "crate::lotus_json")]
+    pub priority_addrs: Vec<Address>,
+    pub size_limit_high: i64,
+    pub size_limit_low: i64,
+    pub replace_by_fee_ratio: f64,
+    #[schemars(with = "LotusJson<Duration>")]
+    #[serde(with = "crate::lotus_json")]
+    pub prune_cooldown: Duration,
+    pub gas_limit_overestimation: f64,
+}
+
+impl HasLotusJson for MpoolConfig {
+    type LotusJson = MpoolConfigLotusJson;
+    #[cfg(test)]
+    fn snapshots() -> Vec<(serde_json::Value, Self)> {
+        vec![(
+            json! {{
+                "PriorityAddrs": ["f01234"],
+                "SizeLimitHigh": 30000,
+                "SizeLimitLow": 20000,
+                "ReplaceByFeeRatio": 1.25,
+                "PruneCooldown": 60000000000_u64,
+                "GasLimitOverestimation": 1.25,
+            }},
+            MpoolConfig {
+                priority_addrs: vec![Address::new_id(1234)],
+                ..Default::default()
+            },
+        )]
+    }
+    fn into_lotus_json(self) -> Self::LotusJson {
+        let Self {
+            priority_addrs,
+            size_limit_high,
+            size_limit_low,
+            replace_by_fee_ratio,
+            prune_cooldown,
+            gas_limit_overestimation,
+        } = self;
+        MpoolConfigLotusJson {
+            priority_addrs,
+            size_limit_high,
+            size_limit_low,
+            replace_by_fee_ratio,
+            prune_cooldown,
+            gas_limit_overestimation,
+        }
+    }
+    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+        let MpoolConfigLotusJson {
+            priority_addrs,
+            size_limit_high,
+            size_limit_low,
+            replace_by_fee_ratio,
+            prune_cooldown,
+            gas_limit_overestimation,
+        } = lotus_json;
+        Self {
+            priority_addrs,
+            size_limit_high,
+            size_limit_low,
+            replace_by_fee_ratio,
+            prune_cooldown,
+            gas_limit_overestimation,
+        }
+    }
+}
+
+#[test]
+fn snapshots() {
+    assert_all_snapshots::<MpoolConfig>();
+}
@@ -0,0 +1,218 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use super::*;
+use crate::shim::actors::paych::{Merge, ModVerifyParams, SignedVoucher};
+use crate::shim::{address::Address, clock::ChainEpoch, crypto::Signature, econ::TokenAmount};
+use fvm_ipld_encoding::RawBytes;
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+#[schemars(rename = "SignedVoucher")]
+pub struct SignedVoucherLotusJson {
+    #[schemars(with = "LotusJson<Address>")]
+    #[serde(with = "crate::lotus_json")]
+    channel_addr: Address,
+    time_lock_min: ChainEpoch,
+    time_lock_max: ChainEpoch,
+    #[schemars(with = "LotusJson<Vec<u8>>")]
+    #[serde(with = "crate::lotus_json")]
+    secret_hash: Vec<u8>,
+    #[schemars(with = "LotusJson<Option<ModVerifyParams>>")]
+    #[serde(with = "crate::lotus_json")]
+    extra: Option<ModVerifyParams>,
+    lane: u64,
+    nonce: u64,
+    #[schemars(with = "LotusJson<TokenAmount>")]
+    #[serde(with = "crate::lotus_json")]
+    amount: TokenAmount,
+    min_settle_height: ChainEpoch,
+    #[schemars(with = "LotusJson<Vec<Merge>>")]
+    #[serde(with = "crate::lotus_json")]
+    merges: Vec<Merge>,
+    #[schemars(with = "LotusJson<Option<Signature>>")]
+    #[serde(with = "crate::lotus_json")]
+    signature: Option<Signature>,
+}
+
+impl HasLotusJson for SignedVoucher {
+    type LotusJson = SignedVoucherLotusJson;
+
+    #[cfg(test)]
+    fn snapshots() -> Vec<(serde_json::Value, Self)> {
+        vec![(
+            json!({
+                "ChannelAddr": "f01234",
+                "TimeLockMin": 0,
+                "TimeLockMax": 0,
+                "SecretHash": null,
+                "Extra": {
+                    "Actor": "f01000",
+                    "Method": 2,
+                    "Data": "aGVsbG8gd29ybGQh"
+                },
+                "Lane": 1,
+                "Nonce": 2,
+                "Amount": "100",
+                "MinSettleHeight": 0,
+                "Merges": [{"Lane": 3, "Nonce": 4}],
+                "Signature": {"Type": 1, "Data": "aGVsbG8gd29ybGQh"}
+            }),
+            SignedVoucher {
+                channel_addr: Address::new_id(1234),
+                time_lock_min: 0,
+                time_lock_max: 0,
+                secret_hash: vec![],
+                extra: Some(ModVerifyParams {
+                    actor: Address::new_id(1000),
+                    method: 2,
+                    data: RawBytes::new(Vec::from_iter(*b"hello world!")),
+                }),
+                lane: 1,
+                nonce: 2,
+                amount: TokenAmount::from_atto(100),
+                min_settle_height: 0,
+                merges: vec![Merge { lane: 3, nonce: 4 }],
+                signature: Some(Signature::new_secp256k1(Vec::from_iter(*b"hello world!"))),
+            },
+        )]
+    }
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        let Self {
+            channel_addr,
+            time_lock_min,
+            time_lock_max,
+            secret_hash,
+            extra,
+            lane,
+            nonce,
+            amount,
+            min_settle_height,
+            merges,
+            signature,
+        } = self;
+        Self::LotusJson {
+            channel_addr,
+            time_lock_min,
+            time_lock_max,
+            secret_hash,
+            extra,
+            lane,
+            nonce,
+            amount,
+            min_settle_height,
+            merges,
+            signature,
+        }
+    }
+
+    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+        let Self::LotusJson {
+            channel_addr,
+            time_lock_min,
+            time_lock_max,
+            secret_hash,
+            extra,
+            lane,
+            nonce,
+            amount,
+            min_settle_height,
+            merges,
+            signature,
+        } = lotus_json;
+        Self {
+            channel_addr,
+            time_lock_min,
+            time_lock_max,
+            secret_hash,
+            extra,
+            lane,
+            nonce,
+            amount,
+            min_settle_height,
+            merges,
+            signature,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+#[schemars(rename = "ModVerifyParams")]
+pub struct ModVerifyParamsLotusJson {
+    #[schemars(with = "LotusJson<Address>")]
+    #[serde(with = "crate::lotus_json")]
+    actor: Address,
+    method: u64,
+    #[schemars(with = "LotusJson<RawBytes>")]
+    #[serde(with = "crate::lotus_json")]
+    data: RawBytes,
+}
+
+impl HasLotusJson for ModVerifyParams {
+    type LotusJson = ModVerifyParamsLotusJson;
+
+    #[cfg(test)]
+    fn snapshots() -> Vec<(serde_json::Value, Self)> {
+        vec![]
+    }
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        let Self {
+            actor,
+            method,
+            data,
+        } = self;
+        Self::LotusJson {
+            actor,
+            method,
+            data,
+        }
+    }
+
+    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+        let Self::LotusJson {
+            actor,
+            method,
+            data,
+        } = lotus_json;
+        Self {
+            actor,
+            method,
+            data,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+#[schemars(rename = "Merge")]
+pub struct MergeLotusJson {
+    lane: u64,
+    nonce: u64,
+}
+
+impl HasLotusJson for Merge {
+    type LotusJson = MergeLotusJson;
+
+    #[cfg(test)]
+    fn snapshots() -> Vec<(serde_json::Value, Self)> {
+        vec![]
+    }
+
+    fn into_lotus_json(self) -> Self::LotusJson {
+        let Self { lane, nonce } = self;
+        Self::LotusJson { lane, nonce }
+    }
+
+    fn from_lotus_json(lotus_json: Self::LotusJson) -> Self {
+        let Self::LotusJson { lane, nonce } = lotus_json;
+        Self { lane, nonce }
+    }
+}
+
+#[test]
+fn snapshots() {
+    assert_all_snapshots::<SignedVoucher>();
+}
@@ -9,7 +9,7 @@ impl<T> HasLotusJson for Vec<T>
 //               the OUTER issue of serializing an empty Vec as null, and
 //               shouldn't be interested in the inner representation.
 where
-    T: HasLotusJson + Clone,
+    T: HasLotusJson,
 {
     type LotusJson = Option<Vec<T::LotusJson>>;
 
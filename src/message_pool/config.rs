@@ -8,6 +8,7 @@ use crate::{
     shim::address::Address,
     utils::encoding::from_slice_with_fallback,
 };
+use anyhow::ensure;
 use serde::{Deserialize, Serialize};
 
 const SIZE_LIMIT_LOW: i64 = 20000;
@@ -15,11 +16,13 @@ const SIZE_LIMIT_HIGH: i64 = 30000;
 const PRUNE_COOLDOWN: Duration = Duration::from_secs(60); // 1 minute
 const REPLACE_BY_FEE_RATIO: f64 = 1.25;
 const GAS_LIMIT_OVERESTIMATION: f64 = 1.25;
+/// Minimum ratio between the gas premium of a replacing message and the one it replaces.
+const MIN_REPLACE_BY_FEE_RATIO: f64 = 1.1;
 
 /// Configuration available for the [`crate::message_pool::MessagePool`].
 ///
 /// [MessagePool]: crate::message_pool::MessagePool
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MpoolConfig {
     pub priority_addrs: Vec<Address>,
     pub size_limit_high: i64,
@@ -43,12 +46,33 @@ impl Default for MpoolConfig {
 }
 
 impl MpoolConfig {
-    #[cfg(test)]
     /// Saves message pool `config` to the database, to easily reload.
-    pub fn save_config<DB: SettingsStore>(&self, store: &DB) -> Result<(), anyhow::Error> {
+    pub fn save_config<DB: SettingsStore + ?Sized>(&self, store: &DB) -> Result<(), anyhow::Error> {
         store.write_bin(MPOOL_CONFIG_KEY, &fvm_ipld_encoding::to_vec(&self)?)
     }
 
+    /// Checks that the configuration can be used by the message pool.
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        ensure!(
+            self.size_limit_low >= 0,
+            "'SizeLimitLow' cannot be negative"
+        );
+        ensure!(
+            self.size_limit_high >= self.size_limit_low,
+            "'SizeLimitHigh' cannot be less than 'SizeLimitLow'"
+        );
+        ensure!(
+            self.replace_by_fee_ratio.is_finite()
+                && self.replace_by_fee_ratio >= MIN_REPLACE_BY_FEE_RATIO,
+            "'ReplaceByFeeRatio' is less than required {MIN_REPLACE_BY_FEE_RATIO}"
+        );
+        ensure!(
+            self.gas_limit_overestimation.is_finite() && self.gas_limit_overestimation >= 1.0,
+            "'GasLimitOverestimation' cannot be less than 1"
+        );
+        Ok(())
+    }
+
     /// Returns the low limit capacity of messages to allocate.
     pub fn size_limit_low(&self) -> i64 {
         self.size_limit_low
@@ -70,3 +94,39 @@ impl MpoolConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(MpoolConfig::default().validate().is_ok());
+
+        let invalid = [
+            MpoolConfig {
+                size_limit_low: -1,
+                ..Default::default()
+            },
+            MpoolConfig {
+                size_limit_high: SIZE_LIMIT_LOW - 1,
+                ..Default::default()
+            },
+            MpoolConfig {
+                replace_by_fee_ratio: 1.05,
+                ..Default::default()
+            },
+            MpoolConfig {
+                replace_by_fee_ratio: f64::NAN,
+                ..Default::default()
+            },
+            MpoolConfig {
+                gas_limit_overestimation: 0.9,
+                ..Default::default()
+            },
+        ];
+        for config in invalid {
+            assert!(config.validate().is_err());
+        }
+    }
+}
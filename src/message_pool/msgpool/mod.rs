@@ -15,7 +15,7 @@ use crate::blocks::Tipset;
 use crate::libp2p::{NetworkMessage, Topic, PUBSUB_MSG_STR};
 use crate::message::{Message as MessageTrait, SignedMessage};
 use crate::networks::ChainConfig;
-use crate::shim::{address::Address, crypto::Signature};
+use crate::shim::{address::Address, crypto::Signature, econ::TokenAmount};
 use ahash::{HashMap, HashMapExt, HashSet, HashSetExt};
 use cid::Cid;
 use fvm_ipld_encoding::to_vec;
@@ -26,19 +26,25 @@ use utils::{get_base_fee_lower_bound, recover_sig};
 
 use super::errors::Error;
 use crate::message_pool::{
+    config::MpoolConfig,
     msg_chain::{create_message_chains, Chains},
     msg_pool::{add_helper, remove, MsgSet},
     provider::Provider,
 };
 
-const REPLACE_BY_FEE_RATIO: f32 = 1.25;
-const RBF_NUM: u64 = ((REPLACE_BY_FEE_RATIO - 1f32) * 256f32) as u64;
 const RBF_DENOM: u64 = 256;
 const BASE_FEE_LOWER_BOUND_FACTOR_CONSERVATIVE: i64 = 100;
 const BASE_FEE_LOWER_BOUND_FACTOR: i64 = 10;
 const REPUB_MSG_LIMIT: usize = 30;
 const MIN_GAS: u64 = 1298450;
 
+/// Returns the minimum gas premium a message must pay to replace a pending message with the given
+/// gas premium, given the `replace_by_fee_ratio` of the [`MpoolConfig`].
+pub fn min_rbf_premium(premium: &TokenAmount, replace_by_fee_ratio: f64) -> TokenAmount {
+    let rbf_num = ((replace_by_fee_ratio - 1.0) * RBF_DENOM as f64) as u64;
+    premium.clone() + (premium * rbf_num).div_floor(RBF_DENOM) + TokenAmount::from_atto(1u8)
+}
+
 /// Get the state of the `base_sequence` for a given address in the current
 /// Tipset
 fn get_state_sequence<T>(api: &T, addr: &Address, cur_ts: &Tipset) -> Result<u64, Error>
@@ -211,6 +217,7 @@ pub async fn head_change<T>(
     repub_trigger: Arc<flume::Sender<()>>,
     republished: &SyncRwLock<HashSet<Cid>>,
     pending: &SyncRwLock<HashMap<Address, MsgSet>>,
+    config: &SyncRwLock<MpoolConfig>,
     cur_tipset: &Mutex<Arc<Tipset>>,
     revert: Vec<Tipset>,
     apply: Vec<Tipset>,
@@ -272,7 +279,14 @@ where
     for (_, hm) in rmsgs {
         for (_, msg) in hm {
             let sequence = get_state_sequence(api, &msg.from(), &cur_tipset.lock().clone())?;
-            if let Err(e) = add_helper(api, bls_sig_cache, pending, msg, sequence) {
+            if let Err(e) = add_helper(
+                api,
+                bls_sig_cache,
+                pending,
+                msg,
+                sequence,
+                config.read().replace_by_fee_ratio,
+            ) {
                 error!("Failed to read message from reorg to mpool: {}", e);
             }
         }
@@ -330,7 +344,7 @@ pub mod tests {
         econ::TokenAmount,
         message::{Message, Message_v3},
     };
-    use num_traits::Zero;
+    use num_traits::{ToPrimitive as _, Zero};
     use test_provider::*;
     use tokio::task::JoinSet;
 
@@ -376,6 +390,65 @@ pub mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_replace_by_fee() {
+        let keystore = KeyStore::new(KeyStoreConfig::Memory).unwrap();
+        let mut wallet = Wallet::new(keystore);
+        let sender = wallet.generate_addr(SignatureType::Secp256k1).unwrap();
+        let target = wallet.generate_addr(SignatureType::Secp256k1).unwrap();
+        let tma = TestApi::default();
+        tma.set_state_sequence(&sender, 0);
+
+        let (tx, _rx) = flume::bounded(50);
+        let mut services = JoinSet::new();
+        let mpool = MessagePool::new(
+            tma,
+            "mptest".to_string(),
+            tx,
+            Default::default(),
+            Arc::default(),
+            &mut services,
+        )
+        .unwrap();
+
+        let original = create_smsg(&target, &sender, wallet.borrow_mut(), 0, 1000000, 1000);
+        mpool.add(original.clone()).unwrap();
+        let epoch = mpool.api.get_heaviest_tipset().epoch();
+        assert_eq!(mpool.pending_since(&original), Some(epoch));
+
+        let min_premium = min_rbf_premium(
+            &original.message().gas_premium,
+            mpool.get_config().replace_by_fee_ratio,
+        );
+        assert_eq!(min_premium, TokenAmount::from_atto(1251));
+        let min_premium = min_premium.atto().to_u64().unwrap();
+        let too_low = create_smsg(
+            &target,
+            &sender,
+            wallet.borrow_mut(),
+            0,
+            1000000,
+            min_premium - 1,
+        );
+        assert_eq!(mpool.add(too_low), Err(Error::GasPriceTooLow));
+
+        let replacement = create_smsg(
+            &target,
+            &sender,
+            wallet.borrow_mut(),
+            0,
+            1000000,
+            min_premium,
+        );
+        mpool.add(replacement.clone()).unwrap();
+        assert_eq!(
+            mpool.pending_for(&sender).unwrap(),
+            vec![replacement.clone()]
+        );
+        assert_eq!(mpool.pending_since(&original), None);
+        assert_eq!(mpool.pending_since(&replacement), Some(epoch));
+    }
+
     pub fn create_smsg(
         to: &Address,
         from: &Address,
@@ -475,6 +548,7 @@ pub mod tests {
             repub_trigger,
             republished.as_ref(),
             pending.as_ref(),
+            mpool.config.as_ref(),
             cur_tipset.as_ref(),
             Vec::new(),
             vec![Tipset::from(a)],
@@ -543,6 +617,7 @@ pub mod tests {
             repub_trigger.clone(),
             republished.as_ref(),
             pending.as_ref(),
+            mpool.config.as_ref(),
             cur_tipset.as_ref(),
             Vec::new(),
             vec![Tipset::from(a)],
@@ -565,6 +640,7 @@ pub mod tests {
             repub_trigger.clone(),
             republished.as_ref(),
             pending.as_ref(),
+            mpool.config.as_ref(),
             cur_tipset.as_ref(),
             Vec::new(),
             vec![Tipset::from(&b)],
@@ -582,6 +658,7 @@ pub mod tests {
             repub_trigger.clone(),
             republished.as_ref(),
             pending.as_ref(),
+            mpool.config.as_ref(),
             cur_tipset.as_ref(),
             vec![Tipset::from(b)],
             Vec::new(),
@@ -10,7 +10,6 @@ use std::{num::NonZeroUsize, sync::Arc, time::Duration};
 
 use crate::blocks::{CachingBlockHeader, Tipset};
 use crate::chain::{HeadChange, MINIMUM_BASE_FEE};
-#[cfg(test)]
 use crate::db::SettingsStore;
 use crate::eth::is_valid_eth_tx_for_sending;
 use crate::libp2p::{NetworkMessage, Topic, PUBSUB_MSG_STR};
@@ -18,6 +17,7 @@ use crate::message::{valid_for_block_inclusion, ChainMessage, Message, SignedMes
 use crate::networks::{ChainConfig, NEWEST_NETWORK_VERSION};
 use crate::shim::{
     address::Address,
+    clock::ChainEpoch,
     crypto::{Signature, SignatureType},
     econ::TokenAmount,
     gas::{price_list_by_network_version, Gas},
@@ -32,16 +32,20 @@ use itertools::Itertools;
 use lru::LruCache;
 use nonzero_ext::nonzero;
 use parking_lot::{Mutex, RwLock as SyncRwLock};
-use tokio::{sync::broadcast::error::RecvError, task::JoinSet, time::interval};
-use tracing::warn;
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    task::JoinSet,
+    time::interval,
+};
+use tracing::{instrument, warn};
 
 use crate::message_pool::{
     config::MpoolConfig,
     errors::Error,
     head_change, metrics,
     msgpool::{
-        recover_sig, republish_pending_messages, BASE_FEE_LOWER_BOUND_FACTOR_CONSERVATIVE,
-        RBF_DENOM, RBF_NUM,
+        min_rbf_premium, recover_sig, republish_pending_messages,
+        BASE_FEE_LOWER_BOUND_FACTOR_CONSERVATIVE,
     },
     provider::Provider,
     utils::get_base_fee_lower_bound,
@@ -51,6 +55,8 @@ use crate::message_pool::{
 const BLS_SIG_CACHE_SIZE: NonZeroUsize = nonzero!(40000usize);
 const SIG_VAL_CACHE_SIZE: NonZeroUsize = nonzero!(32000usize);
 
+const PENDING_PUBLISHER_CAPACITY: usize = 1024;
+
 pub const MAX_ACTOR_PENDING_MESSAGES: u64 = 1000;
 pub const MAX_UNTRUSTED_ACTOR_PENDING_MESSAGES: u64 = 10;
 
@@ -59,6 +65,8 @@ pub const MAX_UNTRUSTED_ACTOR_PENDING_MESSAGES: u64 = 10;
 #[derive(Clone, Default, Debug)]
 pub struct MsgSet {
     pub(in crate::message_pool) msgs: HashMap<u64, SignedMessage>,
+    /// Epoch at which each pending message was added, keyed by sequence
+    added_at: HashMap<u64, ChainEpoch>,
     next_sequence: u64,
 }
 
@@ -68,6 +76,7 @@ impl MsgSet {
     pub fn new(sequence: u64) -> Self {
         MsgSet {
             msgs: HashMap::new(),
+            added_at: HashMap::new(),
             next_sequence: sequence,
         }
     }
@@ -75,25 +84,41 @@ impl MsgSet {
     /// Add a signed message to the `MsgSet`. Increase `next_sequence` if the
     /// message has a sequence greater than any existing message sequence.
     /// Use this method when pushing a message coming from trusted sources.
-    pub fn add_trusted<T>(&mut self, api: &T, m: SignedMessage) -> Result<(), Error>
+    pub fn add_trusted<T>(
+        &mut self,
+        api: &T,
+        m: SignedMessage,
+        replace_by_fee_ratio: f64,
+    ) -> Result<(), Error>
     where
         T: Provider,
     {
-        self.add(api, m, true)
+        self.add(api, m, true, replace_by_fee_ratio)
     }
 
     /// Add a signed message to the `MsgSet`. Increase `next_sequence` if the
     /// message has a sequence greater than any existing message sequence.
     /// Use this method when pushing a message coming from untrusted sources.
     #[allow(dead_code)]
-    pub fn add_untrusted<T>(&mut self, api: &T, m: SignedMessage) -> Result<(), Error>
+    pub fn add_untrusted<T>(
+        &mut self,
+        api: &T,
+        m: SignedMessage,
+        replace_by_fee_ratio: f64,
+    ) -> Result<(), Error>
     where
         T: Provider,
     {
-        self.add(api, m, false)
+        self.add(api, m, false, replace_by_fee_ratio)
     }
 
-    fn add<T>(&mut self, api: &T, m: SignedMessage, trusted: bool) -> Result<(), Error>
+    fn add<T>(
+        &mut self,
+        api: &T,
+        m: SignedMessage,
+        trusted: bool,
+        replace_by_fee_ratio: f64,
+    ) -> Result<(), Error>
     where
         T: Provider,
     {
@@ -109,11 +134,8 @@ impl MsgSet {
 
         if let Some(exms) = self.msgs.get(&m.sequence()) {
             if m.cid() != exms.cid() {
-                let premium = &exms.message().gas_premium;
-                let min_price = premium.clone()
-                    + ((premium * RBF_NUM).div_floor(RBF_DENOM))
-                    + TokenAmount::from_atto(1u8);
-                if m.message().gas_premium <= min_price {
+                let min_price = min_rbf_premium(&exms.message().gas_premium, replace_by_fee_ratio);
+                if m.message().gas_premium < min_price {
                     return Err(Error::GasPriceTooLow);
                 }
             } else {
@@ -127,15 +149,23 @@ impl MsgSet {
                 trusted,
             ));
         }
+        self.added_at
+            .insert(m.sequence(), api.get_heaviest_tipset().epoch());
         if self.msgs.insert(m.sequence(), m).is_none() {
             metrics::MPOOL_MESSAGE_TOTAL.inc();
         }
         Ok(())
     }
 
+    /// Returns the epoch at which the pending message with the given sequence was added.
+    pub fn added_at(&self, sequence: u64) -> Option<ChainEpoch> {
+        self.added_at.get(&sequence).copied()
+    }
+
     /// Removes message with the given sequence. If applied, update the set's
     /// next sequence.
     pub fn rm(&mut self, sequence: u64, applied: bool) {
+        self.added_at.remove(&sequence);
         if self.msgs.remove(&sequence).is_none() {
             if applied && sequence >= self.next_sequence {
                 self.next_sequence = sequence + 1;
@@ -189,10 +219,12 @@ pub struct MessagePool<T> {
     /// messages
     pub repub_trigger: flume::Sender<()>,
     local_msgs: Arc<SyncRwLock<HashSet<SignedMessage>>>,
-    /// Configurable parameters of the message pool
-    pub config: MpoolConfig,
+    /// Configurable parameters of the message pool, can be changed at runtime
+    pub config: Arc<SyncRwLock<MpoolConfig>>,
     /// Chain configuration
     pub chain_config: Arc<ChainConfig>,
+    /// Publishes messages as they are added to the pending set
+    pending_publisher: broadcast::Sender<SignedMessage>,
 }
 
 impl<T> MessagePool<T>
@@ -208,6 +240,7 @@ where
 
     /// Push a signed message to the `MessagePool`. Additionally performs basic
     /// checks on the validity of a message.
+    #[instrument(skip_all, fields(cid = %msg.cid()))]
     pub async fn push(&self, msg: SignedMessage) -> Result<Cid, Error> {
         self.check_message(&msg)?;
         let cid = msg.cid();
@@ -243,6 +276,7 @@ where
 
     /// This is a helper to push that will help to make sure that the message
     /// fits the parameters to be pushed to the `MessagePool`.
+    #[instrument(skip_all, fields(cid = %msg.cid()))]
     pub fn add(&self, msg: SignedMessage) -> Result<(), Error> {
         self.check_message(&msg)?;
 
@@ -317,13 +351,25 @@ where
     fn add_helper(&self, msg: SignedMessage) -> Result<(), Error> {
         let from = msg.from();
         let cur_ts = self.cur_tipset.lock().clone();
+        let published = (self.pending_publisher.receiver_count() > 0).then(|| msg.clone());
         add_helper(
             self.api.as_ref(),
             self.bls_sig_cache.as_ref(),
             self.pending.as_ref(),
             msg,
             self.get_state_sequence(&from, &cur_ts)?,
-        )
+            self.config.read().replace_by_fee_ratio,
+        )?;
+        if let Some(msg) = published {
+            // Sending only fails when there are no subscribers left, which is fine.
+            let _ = self.pending_publisher.send(msg);
+        }
+        Ok(())
+    }
+
+    /// Subscribes to messages added to the pending set.
+    pub fn subscribe_pending(&self) -> broadcast::Receiver<SignedMessage> {
+        self.pending_publisher.subscribe()
     }
 
     /// Get the sequence for a given address, return Error if there is a failure
@@ -347,6 +393,13 @@ where
         }
     }
 
+    /// Get the sequence of the given address in the state of the current tipset, ignoring the
+    /// pending messages.
+    pub fn get_state_sequence_at_head(&self, addr: &Address) -> Result<u64, Error> {
+        let cur_ts = self.cur_tipset.lock().clone();
+        self.get_state_sequence(addr, &cur_ts)
+    }
+
     /// Get the state of the sequence for a given address in `cur_ts`.
     fn get_state_sequence(&self, addr: &Address, cur_ts: &Tipset) -> Result<u64, Error> {
         let actor = self.api.get_actor_after(addr, cur_ts)?;
@@ -398,6 +451,21 @@ where
         )
     }
 
+    /// Returns the epoch at which the message was added to the pool, if it is still pending.
+    pub fn pending_since(&self, msg: &SignedMessage) -> Option<ChainEpoch> {
+        let pending = self.pending.read();
+        let mset = pending.get(&msg.from())?;
+        if mset.msgs.get(&msg.sequence())?.cid() != msg.cid() {
+            return None;
+        }
+        mset.added_at(msg.sequence())
+    }
+
+    /// Returns the senders of the messages pushed to the pool through this node.
+    pub fn local_addrs(&self) -> Vec<Address> {
+        self.local_addrs.read().iter().copied().unique().collect()
+    }
+
     /// Return Vector of signed messages given a block header for self.
     pub fn messages_for_blocks<'a>(
         &self,
@@ -432,20 +500,21 @@ where
         Ok(())
     }
 
-    #[cfg(test)]
-    pub fn get_config(&self) -> &MpoolConfig {
-        &self.config
+    /// Returns a snapshot of the message pool configuration.
+    pub fn get_config(&self) -> MpoolConfig {
+        self.config.read().clone()
     }
 
-    #[cfg(test)]
-    pub fn set_config<DB: SettingsStore>(
-        &mut self,
+    /// Validates the configuration, persists it in `db` and applies it to the message pool.
+    pub fn set_config<DB: SettingsStore + ?Sized>(
+        &self,
         db: &DB,
         cfg: MpoolConfig,
     ) -> Result<(), Error> {
+        cfg.validate().map_err(|e| Error::Other(e.to_string()))?;
         cfg.save_config(db)
             .map_err(|e| Error::Other(e.to_string()))?;
-        self.config = cfg;
+        *self.config.write() = cfg;
         Ok(())
     }
 }
@@ -476,6 +545,7 @@ where
         let block_delay = chain_config.block_delay_secs;
 
         let (repub_trigger, repub_trigger_rx) = flume::bounded::<()>(4);
+        let (pending_publisher, _) = broadcast::channel(PENDING_PUBLISHER_CAPACITY);
         let mut mp = MessagePool {
             local_addrs,
             pending,
@@ -486,10 +556,11 @@ where
             sig_val_cache,
             local_msgs,
             republished,
-            config,
+            config: Arc::new(SyncRwLock::new(config)),
             network_sender,
             repub_trigger,
             chain_config: Arc::clone(&chain_config),
+            pending_publisher,
         };
 
         mp.load_local()?;
@@ -500,6 +571,7 @@ where
         let bls_sig_cache = mp.bls_sig_cache.clone();
         let pending = mp.pending.clone();
         let republished = mp.republished.clone();
+        let config = mp.config.clone();
 
         let cur_tipset = mp.cur_tipset.clone();
         let repub_trigger = Arc::new(mp.repub_trigger.clone());
@@ -522,6 +594,7 @@ where
                             repub_trigger.clone(),
                             republished.as_ref(),
                             pending.as_ref(),
+                            config.as_ref(),
                             cur.as_ref(),
                             rev,
                             app,
@@ -588,6 +661,7 @@ pub(in crate::message_pool) fn add_helper<T>(
     pending: &SyncRwLock<HashMap<Address, MsgSet>>,
     msg: SignedMessage,
     sequence: u64,
+    replace_by_fee_ratio: f64,
 ) -> Result<(), Error>
 where
     T: Provider,
@@ -608,11 +682,11 @@ where
     let mut pending = pending.write();
     let msett = pending.get_mut(&msg.from());
     match msett {
-        Some(mset) => mset.add_trusted(api, msg)?,
+        Some(mset) => mset.add_trusted(api, msg, replace_by_fee_ratio)?,
         None => {
             let mut mset = MsgSet::new(sequence);
             let from = msg.from();
-            mset.add_trusted(api, msg)?;
+            mset.add_trusted(api, msg, replace_by_fee_ratio)?;
             pending.insert(from, mset);
         }
     }
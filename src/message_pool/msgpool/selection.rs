@@ -37,6 +37,7 @@ where
     /// Forest employs a sophisticated algorithm for selecting messages
     /// for inclusion from the pool, given the ticket quality of a miner.
     /// This method selects messages for including in a block.
+    #[tracing::instrument(skip_all, fields(epoch = ts.epoch()))]
     pub fn select_messages(&self, ts: &Tipset, tq: f64) -> Result<Vec<SignedMessage>, Error> {
         let cur_ts = self.cur_tipset.lock().clone();
         // if the ticket quality is high enough that the first block has higher
@@ -498,12 +499,13 @@ where
         base_fee: &TokenAmount,
         ts: &Tipset,
     ) -> Result<(Vec<SignedMessage>, u64), Error> {
-        let result = Vec::with_capacity(self.config.size_limit_low() as usize);
+        let config = self.get_config();
+        let result = Vec::with_capacity(config.size_limit_low() as usize);
         let gas_limit = crate::shim::econ::BLOCK_GAS_LIMIT;
         let min_gas = 1298450;
 
         // 1. Get priority actor chains
-        let priority = self.config.priority_addrs();
+        let priority = config.priority_addrs();
         let mut chains = Chains::new();
         for actor in priority.iter() {
             // remove actor from pending set as we are processing these messages.
@@ -754,6 +756,7 @@ mod test_selection {
             repub_trigger.clone(),
             republished.as_ref(),
             pending.as_ref(),
+            mpool.config.as_ref(),
             cur_tipset.as_ref(),
             Vec::new(),
             vec![Tipset::from(b1)],
@@ -812,6 +815,7 @@ mod test_selection {
             repub_trigger.clone(),
             republished.as_ref(),
             pending.as_ref(),
+            mpool.config.as_ref(),
             cur_tipset.as_ref(),
             Vec::new(),
             vec![Tipset::from(b2)],
@@ -916,6 +920,7 @@ mod test_selection {
             repub_trigger.clone(),
             republished.as_ref(),
             pending.as_ref(),
+            mpool.config.as_ref(),
             cur_tipset.as_ref(),
             Vec::new(),
             vec![Tipset::from(b1)],
@@ -967,7 +972,7 @@ mod test_selection {
         let db = MemoryDB::default();
 
         let mut joinset = JoinSet::new();
-        let mut mpool = make_test_mpool(&mut joinset);
+        let mpool = make_test_mpool(&mut joinset);
 
         let ks1 = KeyStore::new(KeyStoreConfig::Memory).unwrap();
         let mut w1 = Wallet::new(ks1);
@@ -978,7 +983,7 @@ mod test_selection {
         let a2 = w2.generate_addr(SignatureType::Secp256k1).unwrap();
 
         // set priority addrs to a1
-        let mut mpool_cfg = mpool.get_config().clone();
+        let mut mpool_cfg = mpool.get_config();
         mpool_cfg.priority_addrs.push(a1);
         mpool.set_config(&db, mpool_cfg).unwrap();
 
@@ -996,6 +1001,7 @@ mod test_selection {
             repub_trigger.clone(),
             republished.as_ref(),
             pending.as_ref(),
+            mpool.config.as_ref(),
             cur_tipset.as_ref(),
             Vec::new(),
             vec![Tipset::from(b1)],
@@ -1090,6 +1096,7 @@ mod test_selection {
             repub_trigger.clone(),
             republished.as_ref(),
             pending.as_ref(),
+            mpool.config.as_ref(),
             cur_tipset.as_ref(),
             Vec::new(),
             vec![Tipset::from(b1)],
@@ -1169,6 +1176,7 @@ mod test_selection {
             repub_trigger.clone(),
             republished.as_ref(),
             pending.as_ref(),
+            mpool.config.as_ref(),
             cur_tipset.as_ref(),
             Vec::new(),
             vec![Tipset::from(b1)],
@@ -1286,6 +1294,7 @@ mod test_selection {
             repub_trigger.clone(),
             republished.as_ref(),
             pending.as_ref(),
+            mpool.config.as_ref(),
             cur_tipset.as_ref(),
             Vec::new(),
             vec![Tipset::from(block)],
@@ -12,6 +12,7 @@ use prometheus_client::{
     metrics::{
         counter::Counter,
         family::Family,
+        gauge::Gauge,
         histogram::{exponential_buckets, Histogram},
     },
 };
@@ -53,6 +54,77 @@ pub static RPC_METHOD_FAILURE: Lazy<Family<RpcMethodLabel, Counter>> = Lazy::new
     metric
 });
 
+pub static RPC_METHOD_THROTTLED: Lazy<Family<RpcThrottledLabel, Counter>> = Lazy::new(|| {
+    let metric = Family::default();
+    DEFAULT_REGISTRY.write().register(
+        "rpc_method_throttled",
+        "Number of RPC calls rejected by the rate or concurrency limits",
+        metric.clone(),
+    );
+    metric
+});
+
+pub static RPC_BATCH_SIZE: Lazy<Histogram> = Lazy::new(|| {
+    // 12 buckets from 1 call to 2048 calls, each bucket twice as big as the last.
+    let metric = Histogram::new(exponential_buckets(1., 2., 12));
+    DEFAULT_REGISTRY.write().register(
+        "rpc_batch_size",
+        "Number of calls in JSON-RPC batch requests",
+        metric.clone(),
+    );
+    metric
+});
+
+pub static RPC_BATCH_METHOD: Lazy<Family<RpcMethodLabel, Counter>> = Lazy::new(|| {
+    let metric = Family::default();
+    DEFAULT_REGISTRY.write().register(
+        "rpc_batch_method",
+        "Number of calls to each method in JSON-RPC batch requests",
+        metric.clone(),
+    );
+    metric
+});
+
+pub static RPC_BATCH_TIMEOUT: Lazy<Counter> = Lazy::new(|| {
+    let metric = Counter::default();
+    DEFAULT_REGISTRY.write().register(
+        "rpc_batch_timeout",
+        "Number of JSON-RPC batch requests that did not complete in time",
+        metric.clone(),
+    );
+    metric
+});
+
+pub static RPC_CACHE_HIT: Lazy<Family<RpcMethodLabel, Counter>> = Lazy::new(|| {
+    let metric = Family::default();
+    DEFAULT_REGISTRY.write().register(
+        "rpc_cache_hit",
+        "Number of RPC calls answered from the response cache",
+        metric.clone(),
+    );
+    metric
+});
+
+pub static RPC_CACHE_MISS: Lazy<Family<RpcMethodLabel, Counter>> = Lazy::new(|| {
+    let metric = Family::default();
+    DEFAULT_REGISTRY.write().register(
+        "rpc_cache_miss",
+        "Number of cacheable RPC calls not found in the response cache",
+        metric.clone(),
+    );
+    metric
+});
+
+pub static RPC_CACHE_SIZE: Lazy<Gauge> = Lazy::new(|| {
+    let metric = Gauge::default();
+    DEFAULT_REGISTRY.write().register(
+        "rpc_cache_size_bytes",
+        "Total size of the responses in the RPC response cache",
+        metric.clone(),
+    );
+    metric
+});
+
 pub static RPC_METHOD_TIME: Lazy<Family<RpcMethodLabel, Histogram>> = Lazy::new(|| {
     let metric = Family::<RpcMethodLabel, Histogram>::new_with_constructor(|| {
         // Histogram with 5 buckets starting from 0.1ms going to 1s, each bucket 10 times as big as the last.
@@ -137,6 +209,13 @@ pub struct RpcMethodLabel {
     pub method: String,
 }
 
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct RpcThrottledLabel {
+    pub method: String,
+    /// The limit that was exceeded, `rate` or `concurrency`.
+    pub reason: &'static str,
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
 pub struct KindLabel {
     kind: &'static str,
@@ -0,0 +1,174 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Chain specifications: [`ChainConfig`]s stored in TOML or JSON files, so that private networks
+//! can be configured without setting dozens of environment variables.
+
+use std::path::Path;
+
+use anyhow::Context as _;
+use serde_json::Value;
+
+use super::{ChainConfig, NetworkChain};
+
+/// Format of a chain specification file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ChainSpecFormat {
+    #[default]
+    Toml,
+    Json,
+}
+
+impl ChainSpecFormat {
+    /// Guesses the format of a file from its extension, defaulting to TOML.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Toml,
+        }
+    }
+}
+
+impl ChainConfig {
+    /// Parses a chain specification. The parameters it omits are those of the network it names:
+    /// the built-in network, or the devnet for any other name. Tables are merged with those of the
+    /// network, so that, e.g., a single policy parameter or network upgrade can be overridden.
+    pub fn from_spec(spec: &str, format: ChainSpecFormat) -> anyhow::Result<Self> {
+        let spec: Value = match format {
+            ChainSpecFormat::Toml => toml::from_str(spec)?,
+            ChainSpecFormat::Json => serde_json::from_str(spec)?,
+        };
+        let Value::Object(spec) = spec else {
+            anyhow::bail!("chain specification must be a table");
+        };
+        let network: NetworkChain = serde_json::from_value(
+            spec.get("network")
+                .context("chain specification must define `network`")?
+                .clone(),
+        )
+        .context("invalid `network`")?;
+
+        let mut config = serde_json::to_value(Self::from_chain(&network))?;
+        merge(&mut config, Value::Object(spec));
+        Ok(serde_json::from_value(config)?)
+    }
+
+    /// Reads a chain specification from a file, whose format is guessed from its extension.
+    pub fn from_spec_file(path: &Path) -> anyhow::Result<Self> {
+        let spec = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::from_spec(&spec, ChainSpecFormat::from_path(path))
+            .with_context(|| format!("invalid chain specification {}", path.display()))
+    }
+
+    /// Serializes the configuration as a chain specification.
+    pub fn to_spec(&self, format: ChainSpecFormat) -> anyhow::Result<String> {
+        Ok(match format {
+            ChainSpecFormat::Toml => toml::to_string_pretty(self)?,
+            ChainSpecFormat::Json => serde_json::to_string_pretty(self)?,
+        })
+    }
+}
+
+/// Recursively merges `patch` into `base`: objects are merged key by key, and any other value
+/// replaces the one it overrides.
+fn merge(base: &mut Value, patch: Value) {
+    match (base, patch) {
+        (Value::Object(base), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match base.get_mut(&key) {
+                    Some(base) => merge(base, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::Height;
+
+    #[test]
+    fn builtin_networks_round_trip() {
+        for config in [
+            ChainConfig::mainnet(),
+            ChainConfig::calibnet(),
+            ChainConfig::butterflynet(),
+            ChainConfig::devnet(),
+        ] {
+            for format in [ChainSpecFormat::Toml, ChainSpecFormat::Json] {
+                let spec = config.to_spec(format).unwrap();
+                assert_eq!(ChainConfig::from_spec(&spec, format).unwrap(), config);
+            }
+        }
+    }
+
+    #[test]
+    fn partial_spec() {
+        let config = ChainConfig::from_spec(
+            r#"
+            network = { type = "devnet", name = "private" }
+            block_delay_secs = 2
+            eth_chain_id = 1234
+
+            [height_infos.Teep]
+            epoch = 100
+            "#,
+            ChainSpecFormat::Toml,
+        )
+        .unwrap();
+        assert_eq!(config.network, NetworkChain::Devnet("private".into()));
+        assert_eq!(config.block_delay_secs, 2);
+        assert_eq!(config.eth_chain_id, 1234);
+        assert_eq!(config.epoch(Height::Teep), 100);
+        // Omitted parameters are those of the devnet, including those of the overridden tables
+        let devnet = ChainConfig::devnet();
+        assert_eq!(config.height_infos.len(), devnet.height_infos.len());
+        assert_eq!(
+            config.height_infos[&Height::Teep].bundle,
+            devnet.height_infos[&Height::Teep].bundle
+        );
+        assert_eq!(config.epoch(Height::Tock), devnet.epoch(Height::Tock));
+        assert_eq!(config.drand_schedule, devnet.drand_schedule);
+        assert_eq!(config.policy, devnet.policy);
+
+        let config = ChainConfig::from_spec(
+            r#"{"network": {"type": "calibnet"}}"#,
+            ChainSpecFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(config, ChainConfig::calibnet());
+
+        assert!(ChainConfig::from_spec("block_delay_secs = 2", ChainSpecFormat::Toml).is_err());
+    }
+
+    #[test]
+    fn partial_policy() {
+        let config = ChainConfig::from_spec(
+            r#"
+            network = { type = "calibnet" }
+
+            [policy]
+            chain_finality = 20
+            "#,
+            ChainSpecFormat::Toml,
+        )
+        .unwrap();
+        let calibnet = ChainConfig::calibnet();
+        assert_eq!(config.policy.chain_finality, 20);
+        assert_eq!(
+            config.policy.valid_pre_commit_proof_type,
+            calibnet.policy.valid_pre_commit_proof_type
+        );
+        assert_eq!(
+            config.policy.minimum_consensus_power,
+            calibnet.policy.minimum_consensus_power
+        );
+        assert_eq!(config.height_infos, calibnet.height_infos);
+    }
+}
@@ -11,6 +11,7 @@ use itertools::Itertools;
 use libp2p::Multiaddr;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
 use strum_macros::Display;
 use tracing::warn;
 
@@ -31,6 +32,9 @@ pub use actors_bundle::{
     ACTOR_BUNDLES_METADATA,
 };
 
+mod chain_spec;
+pub use chain_spec::ChainSpecFormat;
+
 mod drand;
 
 pub mod butterflynet;
@@ -200,10 +204,12 @@ impl From<Height> for NetworkVersion {
     }
 }
 
+#[serde_as]
 #[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
 pub struct HeightInfo {
     pub epoch: ChainEpoch,
+    #[serde_as(as = "Option<DisplayFromStr>")]
     pub bundle: Option<Cid>,
 }
 
@@ -213,7 +219,37 @@ struct DrandPoint<'a> {
     pub config: &'a Lazy<DrandConfig<'a>>,
 }
 
+/// The `drand` network in use from a given epoch.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct DrandSchedulePoint {
+    pub height: ChainEpoch,
+    pub config: DrandConfig<'static>,
+}
+
+fn drand_schedule(points: &[DrandPoint<'static>]) -> Vec<DrandSchedulePoint> {
+    points
+        .iter()
+        .map(|point| DrandSchedulePoint {
+            height: point.height,
+            config: DrandConfig::clone(point.config),
+        })
+        .collect()
+}
+
+/// Serializes the upgrades in chronological order.
+fn serialize_height_infos<S: serde::Serializer>(
+    height_infos: &HashMap<Height, HeightInfo>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(
+        height_infos
+            .iter()
+            .sorted_by_key(|(height, info)| (info.epoch, **height as u8)),
+    )
+}
+
 /// Defines all network configuration parameters.
+#[serde_as]
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[cfg_attr(test, derive(derive_quickcheck_arbitrary::Arbitrary))]
 #[serde(default)]
@@ -230,7 +266,10 @@ pub struct ChainConfig {
     pub bootstrap_peers: Vec<Multiaddr>,
     pub block_delay_secs: u32,
     pub propagation_delay_secs: u32,
+    #[cfg_attr(test, arbitrary(gen(|_g| vec![])))]
+    pub drand_schedule: Vec<DrandSchedulePoint>,
     pub genesis_network: NetworkVersion,
+    #[serde(serialize_with = "serialize_height_infos")]
     pub height_infos: HashMap<Height, HeightInfo>,
     #[cfg_attr(test, arbitrary(gen(|_g| Policy::default())))]
     pub policy: Policy,
@@ -239,11 +278,13 @@ pub struct ChainConfig {
     // FIP0081 gradually comes into effect over this many epochs.
     pub fip0081_ramp_duration_epochs: u64,
     // See FIP-0100 and https://github.com/filecoin-project/lotus/pull/12938 for why this exists
+    #[serde(with = "crate::lotus_json")]
     pub upgrade_teep_initial_fil_reserved: Option<TokenAmount>,
     pub f3_enabled: bool,
     // F3Consensus set whether F3 should checkpoint tipsets finalized by F3. This flag has no effect if F3 is not enabled.
     pub f3_consensus: bool,
     pub f3_bootstrap_epoch: i64,
+    #[serde_as(as = "Option<DisplayFromStr>")]
     pub f3_initial_power_table: Option<Cid>,
     #[cfg_attr(test, arbitrary(gen(|_| Some(EthAddress::from_str("0x476AC9256b9921C9C6a0fC237B7fE05fe9874F50").unwrap()))))]
     f3_contract_address: Option<EthAddress>,
@@ -262,6 +303,7 @@ impl ChainConfig {
                 EPOCH_DURATION_SECONDS as u32,
             ),
             propagation_delay_secs: env_or_default(ENV_FOREST_PROPAGATION_DELAY_SECS, 10),
+            drand_schedule: drand_schedule(&*DRAND_SCHEDULE),
             genesis_network: GENESIS_NETWORK_VERSION,
             height_infos: HEIGHT_INFOS.clone(),
             policy: make_mainnet_policy!(v13),
@@ -293,6 +335,7 @@ impl ChainConfig {
                 EPOCH_DURATION_SECONDS as u32,
             ),
             propagation_delay_secs: env_or_default(ENV_FOREST_PROPAGATION_DELAY_SECS, 10),
+            drand_schedule: drand_schedule(&*DRAND_SCHEDULE),
             genesis_network: GENESIS_NETWORK_VERSION,
             height_infos: HEIGHT_INFOS.clone(),
             policy: make_calibnet_policy!(v13),
@@ -326,6 +369,7 @@ impl ChainConfig {
             bootstrap_peers: Vec::new(),
             block_delay_secs: env_or_default(ENV_FOREST_BLOCK_DELAY_SECS, 4),
             propagation_delay_secs: env_or_default(ENV_FOREST_PROPAGATION_DELAY_SECS, 1),
+            drand_schedule: drand_schedule(&*DRAND_SCHEDULE),
             genesis_network: *GENESIS_NETWORK_VERSION,
             height_infos: HEIGHT_INFOS.clone(),
             policy: make_devnet_policy!(v13),
@@ -355,6 +399,7 @@ impl ChainConfig {
                 EPOCH_DURATION_SECONDS as u32,
             ),
             propagation_delay_secs: env_or_default(ENV_FOREST_PROPAGATION_DELAY_SECS, 6),
+            drand_schedule: drand_schedule(&*DRAND_SCHEDULE),
             genesis_network: GENESIS_NETWORK_VERSION,
             height_infos: HEIGHT_INFOS.clone(),
             policy: make_butterfly_policy!(v13),
@@ -404,22 +449,29 @@ impl ChainConfig {
             .max(self.genesis_network)
     }
 
-    pub fn get_beacon_schedule(&self, genesis_ts: u64) -> BeaconSchedule {
-        let ds_iter = match self.network {
-            NetworkChain::Mainnet => mainnet::DRAND_SCHEDULE.iter(),
-            NetworkChain::Calibnet => calibnet::DRAND_SCHEDULE.iter(),
-            NetworkChain::Butterflynet => butterflynet::DRAND_SCHEDULE.iter(),
-            NetworkChain::Devnet(_) => devnet::DRAND_SCHEDULE.iter(),
-        };
+    /// Actor bundle run at the given network version, i.e., that of the latest upgrade up to it,
+    /// including its fixes.
+    pub fn actors_bundle(&self, network_version: NetworkVersion) -> Option<Cid> {
+        self.height_infos
+            .iter()
+            .filter_map(|(height, info)| {
+                Some((NetworkVersion::from(*height), info.epoch, info.bundle?))
+            })
+            .filter(|(version, _, _)| *version <= network_version)
+            .max_by_key(|(version, epoch, _)| (*version, *epoch))
+            .map(|(_, _, bundle)| bundle)
+    }
 
+    pub fn get_beacon_schedule(&self, genesis_ts: u64) -> BeaconSchedule {
         BeaconSchedule(
-            ds_iter
+            self.drand_schedule
+                .iter()
                 .map(|dc| BeaconPoint {
                     height: dc.height,
                     beacon: Box::new(DrandBeacon::new(
                         genesis_ts,
                         self.block_delay_secs as u64,
-                        dc.config,
+                        &dc.config,
                     )),
                 })
                 .collect(),
@@ -723,4 +775,17 @@ mod tests {
         ChainConfig::devnet();
         ChainConfig::butterflynet();
     }
+
+    #[test]
+    fn actors_bundle_of_network_version() {
+        let config = ChainConfig::mainnet();
+        let teep = config.height_infos[&Height::Teep].bundle;
+        assert_eq!(config.actors_bundle(NetworkVersion::V25), teep);
+        assert_eq!(config.actors_bundle(NetworkVersion::V26), teep);
+        assert_eq!(
+            config.actors_bundle(NetworkVersion::V24),
+            config.height_infos[&Height::TukTuk].bundle
+        );
+        assert_eq!(config.actors_bundle(NetworkVersion::V0), None);
+    }
 }
@@ -0,0 +1,292 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! # Payment Channel Manager
+//!
+//! Keeps track of the payment channels this node is a party of, together with the vouchers it
+//! created (outbound channels) or received (inbound channels). The actor state only records the
+//! vouchers that have been redeemed on chain, so the local records are required to allocate
+//! lanes and nonces and to check that the channel can cover all the vouchers handed out.
+//!
+//! All the channels are persisted as a single object in the [`SettingsStore`].
+
+use crate::db::{setting_keys::PAYCH_CHANNELS_KEY, SettingsStore, SettingsStoreExt as _};
+use crate::shim::actors::paych::SignedVoucher;
+use crate::shim::{address::Address, clock::ChainEpoch, econ::TokenAmount};
+use cid::Cid;
+use serde::{Deserialize, Serialize};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use tokio::sync::{Mutex, MutexGuard};
+
+/// Direction of a payment channel, from the point of view of this node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    /// The node receives vouchers through the channel.
+    Inbound,
+    /// The node funds the channel and creates vouchers.
+    Outbound,
+}
+
+/// A payment channel tracked by this node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TrackedChannel {
+    /// Address of the channel actor, unknown until the creation message has been executed.
+    #[serde(with = "crate::lotus_json")]
+    pub channel: Option<Address>,
+    /// Address of the local wallet that is a party of the channel, i.e., the payer of an outbound
+    /// channel or the payee of an inbound channel.
+    #[serde(with = "crate::lotus_json")]
+    pub control: Address,
+    /// Address of the other party of the channel.
+    #[serde(with = "crate::lotus_json")]
+    pub target: Address,
+    pub direction: Direction,
+    /// Message creating the channel, if it was created by this node.
+    #[serde(with = "crate::lotus_json")]
+    pub create_msg: Option<Cid>,
+    /// Messages adding funds to the channel that have not been executed yet.
+    pub pending_funds: Vec<PendingFunds>,
+    /// Next lane to allocate.
+    pub next_lane: u64,
+    pub vouchers: Vec<VoucherInfo>,
+    /// Whether a settle message has been sent for the channel.
+    pub settling: bool,
+}
+
+impl TrackedChannel {
+    /// An outbound channel whose creation message, funding it, has just been sent.
+    pub fn new_outbound(from: Address, to: Address, create: PendingFunds) -> Self {
+        Self {
+            channel: None,
+            control: from,
+            target: to,
+            direction: Direction::Outbound,
+            create_msg: Some(create.message),
+            pending_funds: vec![create],
+            next_lane: 0,
+            vouchers: vec![],
+            settling: false,
+        }
+    }
+
+    /// An inbound channel, discovered when receiving its first voucher.
+    pub fn new_inbound(channel: Address, from: Address, to: Address) -> Self {
+        Self {
+            channel: Some(channel),
+            control: to,
+            target: from,
+            direction: Direction::Inbound,
+            create_msg: None,
+            pending_funds: vec![],
+            next_lane: 0,
+            vouchers: vec![],
+            settling: false,
+        }
+    }
+
+    /// The payer of the channel.
+    pub fn from(&self) -> Address {
+        match self.direction {
+            Direction::Outbound => self.control,
+            Direction::Inbound => self.target,
+        }
+    }
+
+    /// The payee of the channel.
+    pub fn to(&self) -> Address {
+        match self.direction {
+            Direction::Outbound => self.target,
+            Direction::Inbound => self.control,
+        }
+    }
+
+    /// Returns `true` if the voucher has already been stored for this channel.
+    pub fn has_voucher(&self, voucher: &SignedVoucher) -> bool {
+        self.vouchers.iter().any(|info| &info.voucher == voucher)
+    }
+
+    /// Total amount of the messages funding the channel that have not been executed yet.
+    pub fn pending_amount(&self) -> TokenAmount {
+        self.pending_funds
+            .iter()
+            .fold(TokenAmount::default(), |acc, pending| acc + &pending.amount)
+    }
+}
+
+/// A message adding funds to a payment channel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PendingFunds {
+    #[serde(with = "crate::lotus_json")]
+    pub message: Cid,
+    #[serde(with = "crate::lotus_json")]
+    pub amount: TokenAmount,
+    /// Epoch of the head when the message was sent, from which it is searched for on chain.
+    #[serde(default)]
+    pub epoch: ChainEpoch,
+}
+
+/// A voucher created or received by this node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct VoucherInfo {
+    #[serde(with = "crate::lotus_json")]
+    pub voucher: SignedVoucher,
+    #[serde(with = "crate::lotus_json")]
+    pub proof: Vec<u8>,
+    /// Whether the voucher has been submitted to the channel actor.
+    pub submitted: bool,
+}
+
+/// Persists the [`TrackedChannel`]s in the [`SettingsStore`].
+pub struct PaychStore {
+    settings: Arc<dyn SettingsStore + Sync + Send>,
+    lock: Mutex<()>,
+}
+
+impl PaychStore {
+    pub fn new(settings: Arc<dyn SettingsStore + Sync + Send>) -> Self {
+        Self {
+            settings,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns a snapshot of the tracked channels.
+    pub fn channels(&self) -> anyhow::Result<Vec<TrackedChannel>> {
+        Ok(self
+            .settings
+            .read_obj(PAYCH_CHANNELS_KEY)?
+            .unwrap_or_default())
+    }
+
+    /// Returns the tracked channel with the given actor address.
+    pub fn channel(&self, channel: &Address) -> anyhow::Result<Option<TrackedChannel>> {
+        Ok(self
+            .channels()?
+            .into_iter()
+            .find(|tracked| tracked.channel.as_ref() == Some(channel)))
+    }
+
+    /// Locks the tracked channels for modification. Concurrent modifications wait until the
+    /// returned guard is dropped, so the channels must be written back with
+    /// [`LockedChannels::save`] before that.
+    pub async fn lock(&self) -> anyhow::Result<LockedChannels<'_>> {
+        let guard = self.lock.lock().await;
+        Ok(LockedChannels {
+            channels: self.channels()?,
+            store: self,
+            _guard: guard,
+        })
+    }
+}
+
+/// The tracked channels, locked for modification by [`PaychStore::lock`].
+pub struct LockedChannels<'a> {
+    channels: Vec<TrackedChannel>,
+    store: &'a PaychStore,
+    _guard: MutexGuard<'a, ()>,
+}
+
+impl LockedChannels<'_> {
+    /// Returns the index of the tracked channel with the given actor address.
+    pub fn position(&self, channel: &Address) -> Option<usize> {
+        self.channels
+            .iter()
+            .position(|tracked| tracked.channel.as_ref() == Some(channel))
+    }
+
+    /// Writes the channels back to the store.
+    pub fn save(&self) -> anyhow::Result<()> {
+        self.store
+            .settings
+            .write_obj(PAYCH_CHANNELS_KEY, &self.channels)
+    }
+}
+
+impl Deref for LockedChannels<'_> {
+    type Target = Vec<TrackedChannel>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.channels
+    }
+}
+
+impl DerefMut for LockedChannels<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.channels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDB;
+    use crate::shim::crypto::Signature;
+
+    #[tokio::test]
+    async fn test_paych_store() {
+        let store = PaychStore::new(Arc::new(MemoryDB::default()));
+        assert!(store.channels().unwrap().is_empty());
+
+        let from = Address::new_id(100);
+        let to = Address::new_id(200);
+        let create_msg = Cid::default();
+        {
+            let mut channels = store.lock().await.unwrap();
+            channels.push(TrackedChannel::new_outbound(
+                from,
+                to,
+                PendingFunds {
+                    message: create_msg,
+                    amount: TokenAmount::from_atto(10),
+                    epoch: 0,
+                },
+            ));
+            channels.save().unwrap();
+        }
+        let mut tracked = store.channels().unwrap().pop().unwrap();
+        assert_eq!(tracked.channel, None);
+        assert_eq!((tracked.from(), tracked.to()), (from, to));
+        assert_eq!(tracked.pending_amount(), TokenAmount::from_atto(10));
+
+        let channel = Address::new_id(1000);
+        let voucher = SignedVoucher {
+            channel_addr: channel,
+            time_lock_min: 0,
+            time_lock_max: 0,
+            secret_hash: vec![],
+            extra: None,
+            lane: 0,
+            nonce: 1,
+            amount: TokenAmount::from_atto(5),
+            min_settle_height: 0,
+            merges: vec![],
+            signature: Some(Signature::new_secp256k1(vec![1; 65])),
+        };
+        {
+            let mut channels = store.lock().await.unwrap();
+            let tracked = channels.first_mut().unwrap();
+            tracked.channel = Some(channel);
+            tracked.pending_funds.clear();
+            tracked.vouchers.push(VoucherInfo {
+                voucher: voucher.clone(),
+                proof: vec![],
+                submitted: false,
+            });
+            channels.save().unwrap();
+        }
+        tracked = store.channel(&channel).unwrap().unwrap();
+        assert!(tracked.has_voucher(&voucher));
+        assert_eq!(tracked.pending_amount(), TokenAmount::default());
+
+        // Modifications are discarded unless saved
+        {
+            let mut channels = store.lock().await.unwrap();
+            channels.clear();
+        }
+        assert_eq!(store.channels().unwrap().len(), 1);
+    }
+}
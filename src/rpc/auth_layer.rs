@@ -1,9 +1,10 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use crate::auth::{verify_token, JWT_IDENTIFIER};
+use crate::auth::{verify_keystore_token, Claims};
+use crate::db::SettingsStore;
 use crate::key_management::KeyStore;
-use crate::rpc::{chain, Permission, RpcMethod as _, CANCEL_METHOD_NAME};
+use crate::rpc::{chain, eth::pubsub, Permission, RpcMethod as _, CANCEL_METHOD_NAME};
 use ahash::{HashMap, HashMapExt as _};
 use futures::future::BoxFuture;
 use futures::FutureExt;
@@ -36,10 +37,39 @@ static METHOD_NAME2REQUIRED_PERMISSION: Lazy<HashMap<&str, Permission>> = Lazy::
 
     access.insert(chain::CHAIN_NOTIFY, Permission::Read);
     access.insert(CANCEL_METHOD_NAME, Permission::Read);
+    access.insert(pubsub::ETH_SUBSCRIBE, Permission::Read);
+    access.insert(pubsub::ETH_SUBSCRIBE_ALIAS, Permission::Read);
+    access.insert(pubsub::ETH_UNSUBSCRIBE, Permission::Read);
+    access.insert(pubsub::ETH_UNSUBSCRIBE_ALIAS, Permission::Read);
 
     access
 });
 
+static METHOD_ALIAS2NAME: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
+    let mut aliases = HashMap::new();
+
+    macro_rules! insert {
+        ($ty:ty) => {
+            if let Some(alias) = <$ty>::NAME_ALIAS {
+                aliases.insert(alias, <$ty>::NAME);
+            }
+        };
+    }
+    super::for_each_rpc_method!(insert);
+
+    aliases.insert(pubsub::ETH_SUBSCRIBE_ALIAS, pubsub::ETH_SUBSCRIBE);
+    aliases.insert(pubsub::ETH_UNSUBSCRIBE_ALIAS, pubsub::ETH_UNSUBSCRIBE);
+
+    aliases
+});
+
+static METHOD_NAME2ALIAS: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
+    METHOD_ALIAS2NAME
+        .iter()
+        .map(|(alias, name)| (*name, *alias))
+        .collect()
+});
+
 fn is_allowed(required_by_method: Permission, claimed_by_user: &[String]) -> bool {
     let needle = match required_by_method {
         Permission::Admin => "admin",
@@ -50,10 +80,15 @@ fn is_allowed(required_by_method: Permission, claimed_by_user: &[String]) -> boo
     claimed_by_user.iter().any(|haystack| haystack == needle)
 }
 
+/// The `sub` claim of the JWT a call was authorized with, stored in the request extensions.
+#[derive(Clone, Debug)]
+pub struct TokenSubject(pub String);
+
 #[derive(Clone)]
 pub struct AuthLayer {
     pub headers: HeaderMap,
     pub keystore: Arc<RwLock<KeyStore>>,
+    pub settings_store: Arc<dyn SettingsStore + Sync + Send>,
 }
 
 impl<S> Layer<S> for AuthLayer {
@@ -63,6 +98,7 @@ impl<S> Layer<S> for AuthLayer {
         Auth {
             headers: self.headers.clone(),
             keystore: self.keystore.clone(),
+            settings_store: self.settings_store.clone(),
             service,
         }
     }
@@ -72,6 +108,7 @@ impl<S> Layer<S> for AuthLayer {
 pub struct Auth<S> {
     headers: HeaderMap,
     keystore: Arc<RwLock<KeyStore>>,
+    settings_store: Arc<dyn SettingsStore + Sync + Send>,
     service: S,
 }
 
@@ -81,18 +118,25 @@ where
 {
     type Future = BoxFuture<'a, MethodResponse>;
 
-    fn call(&self, req: jsonrpsee::types::Request<'a>) -> Self::Future {
+    fn call(&self, mut req: jsonrpsee::types::Request<'a>) -> Self::Future {
         let headers = self.headers.clone();
         let keystore = self.keystore.clone();
+        let settings_store = self.settings_store.clone();
         let service = self.service.clone();
 
         async move {
             let auth_header = headers.get(AUTHORIZATION).cloned();
-            let res = check_permissions(keystore, auth_header, req.method_name()).await;
+            let res =
+                check_permissions(keystore, &*settings_store, auth_header, req.method_name()).await;
 
             match res {
-                Ok(true) => service.call(req).await,
-                Ok(false) => MethodResponse::error(
+                Ok(Some(claims)) => {
+                    if let Some(subject) = claims.sub {
+                        req.extensions_mut().insert(TokenSubject(subject));
+                    }
+                    service.call(req).await
+                }
+                Ok(None) => MethodResponse::error(
                     req.id(),
                     ErrorObject::borrowed(
                         http::StatusCode::UNAUTHORIZED.as_u16() as _,
@@ -107,19 +151,33 @@ where
     }
 }
 
-/// Verify JWT Token and return the token's permissions.
-async fn auth_verify(token: &str, keystore: Arc<RwLock<KeyStore>>) -> anyhow::Result<Vec<String>> {
-    let ks = keystore.read().await;
-    let ki = ks.get(JWT_IDENTIFIER)?;
-    let perms = verify_token(token, ki.private_key())?;
-    Ok(perms)
+/// Verify JWT Token and return the token's claims.
+async fn auth_verify(
+    token: &str,
+    keystore: Arc<RwLock<KeyStore>>,
+    settings_store: &(dyn SettingsStore + Sync + Send),
+) -> anyhow::Result<Claims> {
+    verify_keystore_token(&*keystore.read().await, settings_store, token)
 }
 
+/// Returns the claims of the token in the authorization header if they allow calling `method`.
 async fn check_permissions(
     keystore: Arc<RwLock<KeyStore>>,
+    settings_store: &(dyn SettingsStore + Sync + Send),
     auth_header: Option<HeaderValue>,
     method: &str,
-) -> anyhow::Result<bool, ErrorCode> {
+) -> anyhow::Result<Option<Claims>, ErrorCode> {
+    let claims = verify_claims(keystore, settings_store, auth_header).await?;
+    let allowed = is_method_allowed(method, &claims.allow)? && is_method_in_scope(method, &claims);
+    Ok(allowed.then_some(claims))
+}
+
+/// Returns the claims of the token in the authorization header.
+async fn verify_claims(
+    keystore: Arc<RwLock<KeyStore>>,
+    settings_store: &(dyn SettingsStore + Sync + Send),
+    auth_header: Option<HeaderValue>,
+) -> anyhow::Result<Claims, ErrorCode> {
     let claims = match auth_header {
         Some(token) => {
             let token = token
@@ -129,25 +187,57 @@ async fn check_permissions(
 
             debug!("JWT from HTTP Header: {}", token);
 
-            auth_verify(token, keystore)
+            auth_verify(token, keystore, settings_store)
                 .await
                 .map_err(|_| ErrorCode::InvalidRequest)?
         }
         // If no token is passed, assume read behavior
-        None => vec!["read".to_owned()],
+        None => Claims {
+            allow: vec!["read".to_owned()],
+            exp: 0,
+            sub: None,
+            jti: None,
+            allow_methods: vec![],
+            deny_methods: vec![],
+        },
     };
-    debug!("Decoded JWT Claims: {}", claims.join(","));
+    debug!("Decoded JWT Claims: {}", claims.allow.join(","));
+    Ok(claims)
+}
 
+/// Whether `method` is the name or the alias of an RPC method.
+pub(super) fn is_known_method(method: &str) -> bool {
+    METHOD_NAME2REQUIRED_PERMISSION.contains_key(method)
+}
+
+/// Name of the RPC method `method` is an alias of, or `method` itself.
+pub(super) fn canonical_method_name(method: &str) -> &str {
+    METHOD_ALIAS2NAME.get(method).copied().unwrap_or(method)
+}
+
+fn is_method_allowed(method: &str, claimed_by_user: &[String]) -> anyhow::Result<bool, ErrorCode> {
     match METHOD_NAME2REQUIRED_PERMISSION.get(&method) {
-        Some(required_by_method) => Ok(is_allowed(*required_by_method, &claims)),
+        Some(required_by_method) => Ok(is_allowed(*required_by_method, claimed_by_user)),
         None => Err(ErrorCode::MethodNotFound),
     }
 }
 
+/// Checks the method against the allow and deny lists of the token. Aliases are resolved in both
+/// directions so that, e.g., denying either `Filecoin.EthCall` or `eth_call` denies both.
+fn is_method_in_scope(method: &str, claims: &Claims) -> bool {
+    let alias = METHOD_ALIAS2NAME
+        .get(method)
+        .or_else(|| METHOD_NAME2ALIAS.get(method));
+    // Cancelling a subscription is always allowed
+    method == CANCEL_METHOD_NAME
+        || claims.allows_method(std::iter::once(method).chain(alias.copied()))
+}
+
 #[cfg(test)]
 mod tests {
     use self::chain::ChainHead;
     use super::*;
+    use crate::db::{MemoryDB, SettingsExt as _};
     use crate::rpc::wallet;
     use chrono::Duration;
 
@@ -156,14 +246,31 @@ mod tests {
         let keystore = Arc::new(RwLock::new(
             KeyStore::new(crate::KeyStoreConfig::Memory).unwrap(),
         ));
+        let settings_store = MemoryDB::default();
 
-        let res = check_permissions(keystore.clone(), None, ChainHead::NAME).await;
+        let res = check_permissions(keystore.clone(), &settings_store, None, ChainHead::NAME)
+            .await
+            .map(|claims| claims.is_some());
         assert_eq!(res, Ok(true));
 
-        let res = check_permissions(keystore.clone(), None, "Cthulhu.InvokeElderGods").await;
+        let res = check_permissions(
+            keystore.clone(),
+            &settings_store,
+            None,
+            "Cthulhu.InvokeElderGods",
+        )
+        .await
+        .map(|claims| claims.is_some());
         assert_eq!(res.unwrap_err(), ErrorCode::MethodNotFound);
 
-        let res = check_permissions(keystore.clone(), None, wallet::WalletNew::NAME).await;
+        let res = check_permissions(
+            keystore.clone(),
+            &settings_store,
+            None,
+            wallet::WalletNew::NAME,
+        )
+        .await
+        .map(|claims| claims.is_some());
         assert_eq!(res, Ok(false));
     }
 
@@ -172,13 +279,28 @@ mod tests {
         let keystore = Arc::new(RwLock::new(
             KeyStore::new(crate::KeyStoreConfig::Memory).unwrap(),
         ));
+        let settings_store = MemoryDB::default();
 
         let auth_header = HeaderValue::from_static("Bearer Azathoth");
-        let res = check_permissions(keystore.clone(), Some(auth_header), ChainHead::NAME).await;
+        let res = check_permissions(
+            keystore.clone(),
+            &settings_store,
+            Some(auth_header),
+            ChainHead::NAME,
+        )
+        .await
+        .map(|claims| claims.is_some());
         assert_eq!(res.unwrap_err(), ErrorCode::InvalidRequest);
 
         let auth_header = HeaderValue::from_static("Cthulhu");
-        let res = check_permissions(keystore.clone(), Some(auth_header), ChainHead::NAME).await;
+        let res = check_permissions(
+            keystore.clone(),
+            &settings_store,
+            Some(auth_header),
+            ChainHead::NAME,
+        )
+        .await
+        .map(|claims| claims.is_some());
         assert_eq!(res.unwrap_err(), ErrorCode::InvalidRequest);
     }
 
@@ -188,6 +310,7 @@ mod tests {
         let keystore = Arc::new(RwLock::new(
             KeyStore::new(crate::KeyStoreConfig::Memory).unwrap(),
         ));
+        let settings_store = MemoryDB::default();
 
         // generate a key and store it in the keystore
         let key_info = generate_priv_key();
@@ -206,22 +329,135 @@ mod tests {
 
         // Should work with the `Bearer` prefix
         let auth_header = HeaderValue::from_str(&format!("Bearer {token}")).unwrap();
-        let res =
-            check_permissions(keystore.clone(), Some(auth_header.clone()), ChainHead::NAME).await;
+        let res = check_permissions(
+            keystore.clone(),
+            &settings_store,
+            Some(auth_header.clone()),
+            ChainHead::NAME,
+        )
+        .await
+        .map(|claims| claims.is_some());
         assert_eq!(res, Ok(true));
 
         let res = check_permissions(
             keystore.clone(),
+            &settings_store,
             Some(auth_header.clone()),
             wallet::WalletNew::NAME,
         )
-        .await;
+        .await
+        .map(|claims| claims.is_some());
         assert_eq!(res, Ok(true));
 
         // Should work without the `Bearer` prefix
         let auth_header = HeaderValue::from_str(&token).unwrap();
-        let res =
-            check_permissions(keystore.clone(), Some(auth_header), wallet::WalletNew::NAME).await;
+        let res = check_permissions(
+            keystore.clone(),
+            &settings_store,
+            Some(auth_header),
+            wallet::WalletNew::NAME,
+        )
+        .await
+        .map(|claims| claims.is_some());
         assert_eq!(res, Ok(true));
     }
+
+    #[tokio::test]
+    async fn check_permissions_scoped_token() {
+        use crate::auth::*;
+        use crate::rpc::eth::EthCall;
+        let keystore = Arc::new(RwLock::new(
+            KeyStore::new(crate::KeyStoreConfig::Memory).unwrap(),
+        ));
+        let settings_store = MemoryDB::default();
+
+        let key_info = generate_priv_key();
+        keystore
+            .write()
+            .await
+            .put(JWT_IDENTIFIER, key_info.clone())
+            .unwrap();
+        let token = create_scoped_token(
+            ADMIN.iter().map(ToString::to_string).collect(),
+            TokenScope {
+                id: Some("partner-1".into()),
+                allow_methods: vec!["Filecoin.Eth*".into(), ChainHead::NAME.into()],
+                deny_methods: vec![EthCall::NAME.into()],
+                ..Default::default()
+            },
+            key_info.private_key(),
+            Duration::hours(1),
+        )
+        .unwrap();
+        let auth_header = HeaderValue::from_str(&format!("Bearer {token}")).unwrap();
+
+        for (method, expected) in [
+            (ChainHead::NAME, true),
+            ("Filecoin.EthChainId", true),
+            ("eth_chainId", true),
+            (EthCall::NAME, false),
+            ("eth_call", false),
+            (wallet::WalletNew::NAME, false),
+        ] {
+            let res = check_permissions(
+                keystore.clone(),
+                &settings_store,
+                Some(auth_header.clone()),
+                method,
+            )
+            .await
+            .map(|claims| claims.is_some());
+            assert_eq!(res, Ok(expected), "{method}");
+        }
+
+        settings_store.revoke_token("partner-1").unwrap();
+        let res = check_permissions(
+            keystore.clone(),
+            &settings_store,
+            Some(auth_header),
+            ChainHead::NAME,
+        )
+        .await
+        .map(|claims| claims.is_some());
+        assert_eq!(res.unwrap_err(), ErrorCode::InvalidRequest);
+    }
+
+    #[tokio::test]
+    async fn check_permissions_token_denying_aliases() {
+        use crate::auth::*;
+        use crate::rpc::eth::{EthChainId, EthSendRawTransaction};
+        let keystore = Arc::new(RwLock::new(
+            KeyStore::new(crate::KeyStoreConfig::Memory).unwrap(),
+        ));
+        let settings_store = MemoryDB::default();
+
+        let key_info = generate_priv_key();
+        keystore
+            .write()
+            .await
+            .put(JWT_IDENTIFIER, key_info.clone())
+            .unwrap();
+
+        for (deny, method) in [
+            ("eth_sendRawTransaction", EthSendRawTransaction::NAME),
+            ("eth_*", EthChainId::NAME),
+        ] {
+            let token = create_scoped_token(
+                ADMIN.iter().map(ToString::to_string).collect(),
+                TokenScope {
+                    deny_methods: vec![deny.into()],
+                    ..Default::default()
+                },
+                key_info.private_key(),
+                Duration::hours(1),
+            )
+            .unwrap();
+            let auth_header = HeaderValue::from_str(&format!("Bearer {token}")).unwrap();
+            let res =
+                check_permissions(keystore.clone(), &settings_store, Some(auth_header), method)
+                    .await
+                    .map(|claims| claims.is_some());
+            assert_eq!(res, Ok(false), "{deny} {method}");
+        }
+    }
 }
@@ -0,0 +1,372 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! HTTP middleware layer executing JSON-RPC batch requests.
+//!
+//! `jsonrpsee` executes the calls of a batch one after the other, without any time limit. This
+//! layer instead splits the batches received over HTTP into single calls, going through the RPC
+//! middleware layers as if they had been sent alone. At most
+//! [`RpcBatchConfig::max_concurrent_calls`] of them are executed at once, and the calls still in
+//! progress after [`RpcBatchConfig::timeout_secs`] fail.
+
+use std::borrow::Cow;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::{FutureExt as _, StreamExt as _};
+use jsonrpsee::core::http_helpers::{read_body, HttpError};
+use jsonrpsee::server::{
+    batch_response_error, http::response, HttpBody, HttpRequest, HttpResponse,
+};
+use jsonrpsee::types::error::{reject_too_big_batch_request, reject_too_big_request};
+use jsonrpsee::types::{ErrorObject, Id};
+use jsonrpsee::MethodResponse;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use tower::{BoxError, Layer, Service, ServiceExt as _};
+
+use super::{MAX_REQUEST_BODY_SIZE, MAX_RESPONSE_BODY_SIZE};
+use crate::cli_shared::cli::RpcBatchConfig;
+use crate::metrics;
+
+#[derive(Clone)]
+pub(super) struct BatchLayer {
+    config: RpcBatchConfig,
+}
+
+impl BatchLayer {
+    pub fn new(config: RpcBatchConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl<S> Layer<S> for BatchLayer {
+    type Service = Batching<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        Batching {
+            service,
+            config: self.config.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct Batching<S> {
+    service: S,
+    config: RpcBatchConfig,
+}
+
+impl<S, B> Service<HttpRequest<B>> for Batching<S>
+where
+    S: Service<HttpRequest, Response = HttpResponse, Error = BoxError> + Clone + Send + 'static,
+    S::Future: Send,
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<HttpResponse, BoxError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: HttpRequest<B>) -> Self::Future {
+        // Batches can only be sent in the body of HTTP POST requests
+        if req.method() != http::Method::POST || jsonrpsee::server::ws::is_upgrade_request(&req) {
+            return self.service.call(req.map(HttpBody::new)).boxed();
+        }
+        let service = self.service.clone();
+        let config = self.config.clone();
+
+        async move {
+            let (mut parts, body) = req.into_parts();
+            let body = match read_body(&parts.headers, body, MAX_REQUEST_BODY_SIZE).await {
+                Ok((body, _is_single)) => body,
+                Err(HttpError::TooLarge) => return Ok(response::too_large(MAX_REQUEST_BODY_SIZE)),
+                Err(HttpError::Malformed) => return Ok(response::malformed()),
+                Err(e) => return Err(e.into()),
+            };
+            let batch = match serde_json::from_slice::<Vec<Box<RawValue>>>(&body) {
+                Ok(batch) if !batch.is_empty() => batch,
+                // Single calls, and invalid batches that `jsonrpsee` reports errors for
+                _ => {
+                    return service
+                        .oneshot(HttpRequest::from_parts(parts, HttpBody::from(body)))
+                        .await
+                }
+            };
+            if batch.len() > config.max_batch_size as usize {
+                let response = MethodResponse::error(
+                    Id::Null,
+                    reject_too_big_batch_request(config.max_batch_size as usize),
+                );
+                return Ok(response::ok_response(response.into_result()));
+            }
+
+            metrics::RPC_BATCH_SIZE.observe(batch.len() as f64);
+            for call in &batch {
+                if let Ok(Call {
+                    method: Some(method),
+                    ..
+                }) = serde_json::from_str(call.get())
+                {
+                    metrics::RPC_BATCH_METHOD
+                        .get_or_create(&metrics::RpcMethodLabel {
+                            method: method_label(method),
+                        })
+                        .inc();
+                }
+            }
+
+            // The calls have bodies of their own
+            parts.headers.remove(http::header::CONTENT_LENGTH);
+            let deadline = (config.timeout_secs > 0).then(|| {
+                tokio::time::Instant::now() + Duration::from_secs(config.timeout_secs.into())
+            });
+            let outcomes = futures::stream::iter(batch)
+                .map(move |call| {
+                    let mut request = HttpRequest::new(HttpBody::from(call.get().to_owned()));
+                    *request.method_mut() = parts.method.clone();
+                    *request.uri_mut() = parts.uri.clone();
+                    *request.version_mut() = parts.version;
+                    *request.headers_mut() = parts.headers.clone();
+                    let response = service.clone().oneshot(request);
+                    async move {
+                        let response = match deadline {
+                            Some(deadline) => {
+                                match tokio::time::timeout_at(deadline, response).await {
+                                    Ok(response) => response,
+                                    Err(_) => return Outcome::TimedOut(call),
+                                }
+                            }
+                            None => response.await,
+                        };
+                        match response {
+                            Ok(response) if response.status().is_success() => {
+                                let (parts, body) = response.into_parts();
+                                match read_body(&parts.headers, body, u32::MAX).await {
+                                    Ok((body, _)) => Outcome::Done(body),
+                                    // Including the empty responses to notifications
+                                    Err(_) => Outcome::Failed(call),
+                                }
+                            }
+                            _ => Outcome::Failed(call),
+                        }
+                    }
+                })
+                .buffered(config.max_concurrent_calls.max(1) as usize)
+                .collect::<Vec<_>>()
+                .await;
+
+            if outcomes
+                .iter()
+                .any(|outcome| matches!(outcome, Outcome::TimedOut(_)))
+            {
+                metrics::RPC_BATCH_TIMEOUT.inc();
+            }
+            let responses = outcomes
+                .into_iter()
+                .filter_map(Outcome::into_response)
+                .collect::<Vec<_>>();
+            // A batch of notifications has no response
+            if responses.is_empty() {
+                return Ok(response::ok_response(String::new()));
+            }
+            let response = format!("[{}]", responses.join(","));
+            if response.len() > MAX_RESPONSE_BODY_SIZE as usize {
+                return Ok(response::ok_response(batch_response_error(
+                    Id::Null,
+                    reject_too_big_request(MAX_RESPONSE_BODY_SIZE),
+                )));
+            }
+            Ok(response::ok_response(response))
+        }
+        .boxed()
+    }
+}
+
+/// The fields of a call needed by this layer.
+#[derive(Deserialize)]
+struct Call<'a> {
+    #[serde(borrow, default)]
+    id: Option<Id<'a>>,
+    #[serde(borrow, default)]
+    method: Option<Cow<'a, str>>,
+}
+
+/// The outcome of a call of a batch.
+enum Outcome {
+    /// The body of the response
+    Done(Vec<u8>),
+    TimedOut(Box<RawValue>),
+    Failed(Box<RawValue>),
+}
+
+impl Outcome {
+    /// Returns the response to include in the batch response, if any.
+    fn into_response(self) -> Option<String> {
+        let (call, error) = match self {
+            Self::Done(body) => return String::from_utf8(body).ok(),
+            Self::TimedOut(call) => (
+                call,
+                ErrorObject::borrowed(
+                    http::StatusCode::REQUEST_TIMEOUT.as_u16() as _,
+                    "Request Timeout: batch timeout exceeded",
+                    None,
+                ),
+            ),
+            Self::Failed(call) => (
+                call,
+                ErrorObject::from(jsonrpsee::types::ErrorCode::InternalError),
+            ),
+        };
+        // Notifications are never answered
+        let id = serde_json::from_str::<Call>(call.get()).ok()?.id?;
+        Some(MethodResponse::error(id, error).into_result())
+    }
+}
+
+/// Label of a method in the metrics. Unknown methods share a single label, so that clients can't
+/// create an unbounded number of series.
+fn method_label(method: Cow<'_, str>) -> String {
+    if super::auth_layer::is_known_method(&method) {
+        method.into_owned()
+    } else {
+        "unknown".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use tower::util::BoxCloneService;
+
+    /// Answers calls with their method name, after sleeping for the number of milliseconds given
+    /// as their first parameter.
+    fn service() -> BoxCloneService<HttpRequest, HttpResponse, BoxError> {
+        let service = tower::service_fn(|request: HttpRequest| async move {
+            let (body, _) = read_body(&Default::default(), request.into_body(), u32::MAX)
+                .await
+                .unwrap();
+            let call: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            tokio::time::sleep(Duration::from_millis(call["params"][0].as_u64().unwrap())).await;
+            let response = match call.get("id") {
+                Some(id) => {
+                    serde_json::json!({"jsonrpc": "2.0", "id": id, "result": call["method"]})
+                        .to_string()
+                }
+                None => String::new(),
+            };
+            Ok::<_, Infallible>(response::ok_response(response))
+        });
+        BoxCloneService::new(service.map_err(BoxError::from))
+    }
+
+    async fn call(config: RpcBatchConfig, body: &str) -> serde_json::Value {
+        let request = http::Request::post("/rpc/v1")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(HttpBody::from(body.to_owned()))
+            .unwrap();
+        let response = BatchLayer::new(config)
+            .layer(service())
+            .oneshot(request)
+            .await
+            .unwrap();
+        let (parts, body) = response.into_parts();
+        match read_body(&parts.headers, body, u32::MAX).await {
+            Ok((body, _)) => serde_json::from_slice(&body).unwrap(),
+            // Empty bodies are reported as malformed
+            Err(HttpError::Malformed) => serde_json::Value::Null,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_responses_keep_call_order() {
+        let response = call(
+            RpcBatchConfig::default(),
+            r#"[
+                {"jsonrpc": "2.0", "id": 1, "method": "a", "params": [20]},
+                {"jsonrpc": "2.0", "method": "notification", "params": [0]},
+                {"jsonrpc": "2.0", "id": 2, "method": "b", "params": [0]}
+            ]"#,
+        )
+        .await;
+        assert_eq!(
+            response,
+            serde_json::json!([
+                {"jsonrpc": "2.0", "id": 1, "result": "a"},
+                {"jsonrpc": "2.0", "id": 2, "result": "b"}
+            ])
+        );
+
+        let response = call(
+            RpcBatchConfig::default(),
+            r#"[{"jsonrpc": "2.0", "method": "notification", "params": [0]}]"#,
+        )
+        .await;
+        assert_eq!(response, serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn batch_size_limit() {
+        let config = RpcBatchConfig {
+            max_batch_size: 1,
+            ..Default::default()
+        };
+        let response = call(
+            config,
+            r#"[
+                {"jsonrpc": "2.0", "id": 1, "method": "a", "params": [0]},
+                {"jsonrpc": "2.0", "id": 2, "method": "b", "params": [0]}
+            ]"#,
+        )
+        .await;
+        assert_eq!(response["id"], serde_json::Value::Null);
+        assert_eq!(
+            response["error"]["code"],
+            jsonrpsee::types::error::TOO_BIG_BATCH_REQUEST_CODE
+        );
+    }
+
+    #[tokio::test]
+    async fn batch_timeout() {
+        let config = RpcBatchConfig {
+            max_batch_size: 10,
+            max_concurrent_calls: 1,
+            timeout_secs: 1,
+        };
+        // The calls are executed one at a time, so the third one doesn't start in time
+        let response = call(
+            config,
+            r#"[
+                {"jsonrpc": "2.0", "id": 1, "method": "a", "params": [400]},
+                {"jsonrpc": "2.0", "id": 2, "method": "b", "params": [400]},
+                {"jsonrpc": "2.0", "id": 3, "method": "c", "params": [400]}
+            ]"#,
+        )
+        .await;
+        assert_eq!(response[0]["result"], "a");
+        assert_eq!(response[1]["result"], "b");
+        assert_eq!(response[2]["id"], 3);
+        assert_eq!(
+            response[2]["error"]["code"],
+            http::StatusCode::REQUEST_TIMEOUT.as_u16()
+        );
+    }
+
+    #[test]
+    fn unknown_methods_share_a_label() {
+        assert_eq!(
+            method_label("Filecoin.ChainHead".into()),
+            "Filecoin.ChainHead"
+        );
+        assert_eq!(method_label("eth_chainId".into()), "eth_chainId");
+        assert_eq!(method_label("Filecoin.NoSuchMethod".into()), "unknown");
+    }
+}
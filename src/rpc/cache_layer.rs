@@ -0,0 +1,349 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Middleware layer caching the results of RPC calls.
+//!
+//! Methods opt in with [`RpcMethod::CACHEABILITY`](super::RpcMethod::CACHEABILITY). Calls are keyed
+//! by their method name and canonicalized parameters, and only successful results that can no
+//! longer change, e.g. because they refer to a final epoch, are stored. The least recently used
+//! results are evicted once [`RpcCacheConfig::max_entries`] or [`RpcCacheConfig::max_size_bytes`]
+//! is exceeded.
+
+use std::sync::Arc;
+
+use ahash::{HashMap, HashMapExt as _};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use jsonrpsee::server::middleware::rpc::RpcServiceT;
+use jsonrpsee::{MethodResponse, ResponsePayload};
+use lru::LruCache;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use serde_json::Value;
+use tower::Layer;
+
+use super::{Cacheability, RpcMethod as _};
+use crate::cli_shared::cli::RpcCacheConfig;
+use crate::metrics;
+use crate::shim::clock::ChainEpoch;
+
+/// Canonical name and cacheability of the methods, by name and alias.
+static METHOD_CACHEABILITY: Lazy<HashMap<&str, (&str, Cacheability)>> = Lazy::new(|| {
+    let mut methods = HashMap::new();
+
+    macro_rules! insert {
+        ($ty:ty) => {
+            if <$ty>::CACHEABILITY != Cacheability::Never {
+                methods.insert(<$ty>::NAME, (<$ty>::NAME, <$ty>::CACHEABILITY));
+                if let Some(alias) = <$ty>::NAME_ALIAS {
+                    methods.insert(alias, (<$ty>::NAME, <$ty>::CACHEABILITY));
+                }
+            }
+        };
+    }
+    super::for_each_rpc_method!(insert);
+
+    methods
+});
+
+/// Returns the latest final epoch.
+pub(super) type FinalizedEpoch = Arc<dyn Fn() -> ChainEpoch + Send + Sync>;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct CacheKey {
+    method: &'static str,
+    params: String,
+}
+
+impl CacheKey {
+    /// Returns the key of a call, or [`None`] if its result can't be cached.
+    fn new(
+        method: &str,
+        params: Option<&str>,
+        finalized_epoch: impl FnOnce() -> ChainEpoch,
+    ) -> Option<Self> {
+        let (method, cacheability) = *METHOD_CACHEABILITY.get(method)?;
+        let mut params = match params {
+            Some(params) => match serde_json::from_str(params).ok()? {
+                Value::Array(params) => params,
+                // Parameters by name
+                _ => return None,
+            },
+            None => vec![],
+        };
+        // Omitted optional parameters are equivalent to `null` ones
+        while matches!(params.last(), Some(Value::Null)) {
+            params.pop();
+        }
+        let cacheable = match cacheability {
+            Cacheability::Never => false,
+            Cacheability::Immutable => true,
+            Cacheability::AtTipset(i) => params
+                .get(i)
+                .and_then(Value::as_array)
+                .is_some_and(|tsk| !tsk.is_empty()),
+            Cacheability::AtEpoch(i) => params
+                .get(i)
+                .and_then(epoch_param)
+                .is_some_and(|epoch| epoch <= finalized_epoch()),
+        };
+        cacheable.then(|| Self {
+            method,
+            params: canonicalize(Value::Array(params)).to_string(),
+        })
+    }
+
+    fn size(&self) -> usize {
+        self.method.len() + self.params.len()
+    }
+}
+
+/// Parses a chain epoch, or an Ethereum block number in hexadecimal, possibly as an
+/// [EIP-1898](https://eips.ethereum.org/EIPS/eip-1898) object.
+fn epoch_param(param: &Value) -> Option<ChainEpoch> {
+    match param {
+        Value::Number(epoch) => epoch.as_i64(),
+        Value::String(number) => ChainEpoch::from_str_radix(number.strip_prefix("0x")?, 16).ok(),
+        Value::Object(object) => epoch_param(object.get("blockNumber")?),
+        _ => None,
+    }
+}
+
+/// Sorts the keys of the objects, so that equivalent parameters have the same representation.
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Array(values) => Value::Array(values.into_iter().map(canonicalize).collect()),
+        Value::Object(object) => {
+            let mut entries = object.into_iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, canonicalize(value)))
+                    .collect(),
+            )
+        }
+        value => value,
+    }
+}
+
+/// Results of RPC calls, shared by all connections.
+pub struct ResponseCache {
+    config: RpcCacheConfig,
+    entries: Mutex<Entries>,
+}
+
+struct Entries {
+    lru: LruCache<CacheKey, Arc<RawValue>>,
+    size: usize,
+}
+
+impl ResponseCache {
+    pub fn new(config: RpcCacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(Entries {
+                lru: LruCache::unbounded(),
+                size: 0,
+            }),
+        }
+    }
+
+    fn get(&self, key: &CacheKey) -> Option<Arc<RawValue>> {
+        self.entries.lock().lru.get(key).cloned()
+    }
+
+    fn insert(&self, key: CacheKey, result: Arc<RawValue>) {
+        let size = key.size() + result.get().len();
+        if size > self.config.max_size_bytes {
+            return;
+        }
+        let mut entries = self.entries.lock();
+        if let Some(old) = entries.lru.put(key.clone(), result) {
+            entries.size -= key.size() + old.get().len();
+        }
+        entries.size += size;
+        while entries.lru.len() > self.config.max_entries
+            || entries.size > self.config.max_size_bytes
+        {
+            match entries.lru.pop_lru() {
+                Some((key, result)) => entries.size -= key.size() + result.get().len(),
+                None => break,
+            }
+        }
+        metrics::RPC_CACHE_SIZE.set(entries.size as _);
+    }
+}
+
+/// JSON-RPC middleware layer answering cacheable calls from a [`ResponseCache`].
+#[derive(Clone)]
+pub(super) struct CacheLayer {
+    cache: Option<Arc<ResponseCache>>,
+    finalized_epoch: FinalizedEpoch,
+}
+
+impl CacheLayer {
+    pub fn new(cache: Option<Arc<ResponseCache>>, finalized_epoch: FinalizedEpoch) -> Self {
+        Self {
+            cache,
+            finalized_epoch,
+        }
+    }
+}
+
+impl<S> Layer<S> for CacheLayer {
+    type Service = Caching<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        Caching {
+            service,
+            cache: self.cache.clone(),
+            finalized_epoch: self.finalized_epoch.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct Caching<S> {
+    service: S,
+    cache: Option<Arc<ResponseCache>>,
+    finalized_epoch: FinalizedEpoch,
+}
+
+/// The result of a successful response.
+#[derive(Deserialize)]
+struct Success {
+    result: Box<RawValue>,
+}
+
+impl<'a, S> RpcServiceT<'a> for Caching<S>
+where
+    S: RpcServiceT<'a> + Send + Sync + Clone + 'static,
+{
+    type Future = BoxFuture<'a, MethodResponse>;
+
+    fn call(&self, req: jsonrpsee::types::Request<'a>) -> Self::Future {
+        let service = self.service.clone();
+        let key = self.cache.as_ref().and_then(|_| {
+            CacheKey::new(req.method_name(), req.params().as_str(), || {
+                (self.finalized_epoch)()
+            })
+        });
+        let (Some(cache), Some(key)) = (self.cache.clone(), key) else {
+            return async move { service.call(req).await }.boxed();
+        };
+        let method = metrics::RpcMethodLabel {
+            method: req.method_name().to_owned(),
+        };
+
+        if let Some(result) = cache.get(&key) {
+            metrics::RPC_CACHE_HIT.get_or_create(&method).inc();
+            let response =
+                MethodResponse::response(req.id(), ResponsePayload::success(&*result), usize::MAX);
+            return async move { response }.boxed();
+        }
+        metrics::RPC_CACHE_MISS.get_or_create(&method).inc();
+
+        async move {
+            let response = service.call(req).await;
+            if response.is_success() {
+                if let Ok(Success { result }) = serde_json::from_str(response.as_result()) {
+                    cache.insert(key, result.into());
+                }
+            }
+            response
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::{chain, eth, state};
+
+    fn key(method: &str, params: &str) -> Option<CacheKey> {
+        CacheKey::new(method, Some(params), || 100)
+    }
+
+    #[test]
+    fn cacheable_calls() {
+        let cid = r#"{"/":"bafy2bzacedbaliyx3k64d4rxy5q2og3wjzxp4b3x4bqzevr3uvcdthuhdktgg"}"#;
+        assert!(key(chain::ChainHead::NAME, "[]").is_none());
+        assert!(key(chain::ChainGetBlock::NAME, &format!("[{cid}]")).is_some());
+
+        // Only at a given tipset
+        let actor = state::StateGetActor::NAME;
+        assert!(key(actor, &format!(r#"["f01234", [{cid}]]"#)).is_some());
+        assert!(key(actor, r#"["f01234", []]"#).is_none());
+        assert!(key(actor, r#"["f01234", null]"#).is_none());
+        assert!(key(actor, r#"["f01234"]"#).is_none());
+
+        // Only at a final epoch
+        let block = eth::EthGetBlockByNumber::NAME;
+        assert!(key(block, r#"["0x64", false]"#).is_some());
+        assert!(key(block, r#"["0x65", false]"#).is_none());
+        assert!(key(block, r#"[{"blockNumber": "0x10"}, false]"#).is_some());
+        assert!(key(block, r#"["latest", false]"#).is_none());
+        assert!(key(chain::ChainGetTipSetByHeight::NAME, "[100, null]").is_some());
+        assert!(key(chain::ChainGetTipSetByHeight::NAME, "[101, null]").is_none());
+
+        // Parameters by name aren't supported
+        assert!(key(block, r#"{"blockParam": "0x10", "fullTxInfo": false}"#).is_none());
+    }
+
+    #[test]
+    fn canonical_keys() {
+        let block = eth::EthGetBlockByNumber::NAME;
+        assert_eq!(
+            key(block, r#"["0x10", false]"#),
+            key("eth_getBlockByNumber", r#"[ "0x10" , false ]"#)
+        );
+        let actor = state::StateGetActor::NAME;
+        assert_eq!(
+            key(actor, r#"["f01234", [{"/":"a"}], null]"#),
+            key(actor, r#"["f01234", [{"/":"a"}]]"#)
+        );
+        assert_eq!(
+            canonicalize(serde_json::json!({"b": 1, "a": {"d": 2, "c": 3}})).to_string(),
+            r#"{"a":{"c":3,"d":2},"b":1}"#
+        );
+    }
+
+    #[test]
+    fn size_bounds() {
+        let cache = ResponseCache::new(RpcCacheConfig {
+            enabled: true,
+            max_entries: 2,
+            max_size_bytes: 100,
+        });
+        let key =
+            |epoch: i64| key(chain::ChainGetTipSetByHeight::NAME, &format!("[{epoch}]")).unwrap();
+        let result = |len: usize| {
+            Arc::<RawValue>::from(
+                RawValue::from_string(format!("\"{}\"", "x".repeat(len))).unwrap(),
+            )
+        };
+
+        cache.insert(key(1), result(10));
+        cache.insert(key(2), result(10));
+        cache.insert(key(3), result(10));
+        // Evicted by the number of entries
+        assert!(cache.get(&key(1)).is_none());
+        assert!(cache.get(&key(2)).is_some());
+        assert!(cache.get(&key(3)).is_some());
+
+        // Evicted by the size of the entries
+        cache.insert(key(4), result(60));
+        assert!(cache.get(&key(2)).is_none());
+        assert!(cache.get(&key(3)).is_none());
+        assert_eq!(cache.get(&key(4)).unwrap().get().len(), 62);
+
+        // Too large to be cached
+        cache.insert(key(5), result(100));
+        assert!(cache.get(&key(5)).is_none());
+        assert!(cache.get(&key(4)).is_some());
+    }
+}
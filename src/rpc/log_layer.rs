@@ -10,25 +10,40 @@ use std::{
 
 use futures::future::BoxFuture;
 use futures::FutureExt;
+use http::HeaderMap;
 use jsonrpsee::MethodResponse;
 use jsonrpsee::{server::middleware::rpc::RpcServiceT, types::Id};
 use tower::Layer;
+use tracing::Instrument as _;
 
-// State-less jsonrpcsee layer for logging information about RPC calls
-#[derive(Clone, Default)]
-pub(super) struct LogLayer {}
+// jsonrpcsee layer for logging information about RPC calls and tracing them. The trace context
+// propagated in the request headers, if any, is used as the parent of the RPC spans.
+#[derive(Clone)]
+pub(super) struct LogLayer {
+    headers: HeaderMap,
+}
+
+impl LogLayer {
+    pub fn new(headers: HeaderMap) -> Self {
+        Self { headers }
+    }
+}
 
 impl<S> Layer<S> for LogLayer {
     type Service = Logging<S>;
 
     fn layer(&self, service: S) -> Self::Service {
-        Logging { service }
+        Logging {
+            service,
+            headers: self.headers.clone(),
+        }
     }
 }
 
 #[derive(Clone)]
 pub(super) struct Logging<S> {
     service: S,
+    headers: HeaderMap,
 }
 
 impl<'a, S> RpcServiceT<'a> for Logging<S>
@@ -39,6 +54,13 @@ where
 
     fn call(&self, req: jsonrpsee::types::Request<'a>) -> Self::Future {
         let service = self.service.clone();
+        let span = tracing::info_span!(
+            "rpc",
+            method = %req.method_name(),
+            otel.name = %req.method_name(),
+            otel.kind = "server",
+        );
+        crate::cli_shared::logger::set_remote_parent(&span, &self.headers);
 
         async move {
             // Avoid performance overhead if DEBUG level is not enabled.
@@ -66,6 +88,7 @@ where
 
             resp
         }
+        .instrument(span)
         .boxed()
     }
 }
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::auth::*;
+use crate::db::SettingsExt as _;
 use crate::lotus_json::lotus_json_with_self;
 use crate::rpc::{ApiPaths, Ctx, Permission, RpcMethod, ServerError};
 use anyhow::Result;
@@ -52,9 +53,57 @@ impl RpcMethod<1> for AuthVerify {
     ) -> Result<Self::Ok, ServerError> {
         let ks = ctx.keystore.read().await;
         let token = header_raw.trim_start_matches("Bearer ");
+        let claims = verify_keystore_token(&ks, &*ctx.settings_store, token)?;
+        Ok(claims.allow)
+    }
+}
+
+/// RPC call to create a new JWT Token restricted to some methods
+pub enum AuthNewScoped {}
+impl RpcMethod<3> for AuthNewScoped {
+    const NAME: &'static str = "Forest.AuthNewScoped";
+    const N_REQUIRED_PARAMS: usize = 2;
+    const PARAM_NAMES: [&'static str; 3] = ["permissions", "scope", "expiration_secs"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Admin;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Creates a JWT token with an ID, and optionally a subject and allow and deny lists of method names or prefixes ending with `*`.",
+    );
+    type Params = (Vec<String>, TokenScope, Option<i64>);
+    type Ok = Vec<u8>;
+    async fn handle(
+        ctx: Ctx<impl Blockstore>,
+        (permissions, scope, expiration_secs): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let ks = ctx.keystore.read().await;
         let ki = ks.get(JWT_IDENTIFIER)?;
-        let perms = verify_token(token, ki.private_key())?;
-        Ok(perms)
+        let token = create_scoped_token(
+            permissions,
+            scope,
+            ki.private_key(),
+            // default to 24h
+            chrono::Duration::seconds(expiration_secs.unwrap_or(60 * 60 * 24)),
+        )?;
+        Ok(token.as_bytes().to_vec())
+    }
+}
+
+/// RPC call to revoke the JWT Tokens with a given ID
+pub enum AuthRevoke {}
+impl RpcMethod<1> for AuthRevoke {
+    const NAME: &'static str = "Forest.AuthRevoke";
+    const PARAM_NAMES: [&'static str; 1] = ["token_id"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Admin;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Revokes the JWT tokens with the given ID. Returns false if they already were.");
+    type Params = (String,);
+    type Ok = bool;
+    async fn handle(
+        ctx: Ctx<impl Blockstore>,
+        (token_id,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        Ok(ctx.settings_store.revoke_token(&token_id)?)
     }
 }
 
@@ -67,6 +116,7 @@ pub struct AuthNewParams {
     pub token_exp: Duration,
 }
 lotus_json_with_self!(AuthNewParams);
+lotus_json_with_self!(TokenScope);
 
 impl AuthNewParams {
     pub fn process_perms(perm: String) -> Result<Vec<String>, ServerError> {
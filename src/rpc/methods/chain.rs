@@ -8,15 +8,16 @@ use types::*;
 use crate::blocks::RawBlockHeader;
 use crate::blocks::{CachingBlockHeader, Tipset, TipsetKey};
 use crate::chain::index::ResolveNullTipset;
-use crate::chain::{ChainStore, HeadChange};
+use crate::chain::{ChainStore, ExportFormat, HeadChange};
 use crate::cid_collections::CidHashSet;
+use crate::db::{PruneOptions, PruneReport, SettingsExt as _};
 use crate::ipld::DfsIter;
 #[cfg(test)]
 use crate::lotus_json::{assert_all_snapshots, assert_unchanged_via_json};
 use crate::lotus_json::{lotus_json_with_self, HasLotusJson, LotusJson};
 use crate::message::{ChainMessage, SignedMessage};
-use crate::rpc::types::ApiTipsetKey;
-use crate::rpc::{ApiPaths, Ctx, Permission, RpcMethod, ServerError};
+use crate::rpc::types::{ApiTipsetKey, Event};
+use crate::rpc::{ApiPaths, Cacheability, Ctx, Permission, RpcMethod, ServerError};
 use crate::shim::clock::ChainEpoch;
 use crate::shim::error::ExitCode;
 use crate::shim::executor::Receipt;
@@ -35,7 +36,7 @@ use num::BigInt;
 use once_cell::sync::Lazy;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sha2::Sha256;
+use sha2::{Digest as _, Sha256};
 use std::{any::Any, collections::VecDeque, path::PathBuf, sync::Arc};
 use tokio::sync::{
     broadcast::{self, Receiver as Subscriber},
@@ -48,6 +49,7 @@ impl RpcMethod<1> for ChainGetMessage {
     const PARAM_NAMES: [&'static str; 1] = ["messageCid"];
     const API_PATHS: ApiPaths = ApiPaths::V1;
     const PERMISSION: Permission = Permission::Read;
+    const CACHEABILITY: Cacheability = Cacheability::Immutable;
     const DESCRIPTION: Option<&'static str> = Some("Returns the message with the specified CID.");
 
     type Params = (Cid,);
@@ -78,7 +80,7 @@ impl RpcMethod<1> for ChainGetEvents {
         Some("Returns the events under the given event AMT root CID.");
 
     type Params = (Cid,);
-    type Ok = Vec<types::Event>;
+    type Ok = Vec<Event>;
     async fn handle(_: Ctx<impl Any>, (_,): Self::Params) -> Result<Self::Ok, ServerError> {
         Err(ServerError::stubbed_for_openrpc())
     }
@@ -202,6 +204,7 @@ impl RpcMethod<1> for ChainExport {
             tipset_keys: ApiTipsetKey(tsk),
             skip_checksum,
             dry_run,
+            format,
         } = params;
 
         static LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
@@ -224,6 +227,18 @@ impl RpcMethod<1> for ChainExport {
             ctx.chain_index()
                 .tipset_by_height(epoch, head, ResolveNullTipset::TakeOlder)?;
 
+        if format == ExportFormat::CarV2 {
+            return export_car_v2(
+                ctx,
+                &start_ts,
+                recent_roots,
+                output_path,
+                skip_checksum,
+                dry_run,
+            )
+            .await;
+        }
+
         match if dry_run {
             crate::chain::export::<Sha256>(
                 ctx.store_owned(),
@@ -252,12 +267,54 @@ impl RpcMethod<1> for ChainExport {
     }
 }
 
+async fn export_car_v2(
+    ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+    start_ts: &Tipset,
+    recent_roots: i64,
+    output_path: PathBuf,
+    skip_checksum: bool,
+    dry_run: bool,
+) -> Result<Option<String>, ServerError> {
+    if dry_run {
+        crate::chain::export_car_v2(
+            ctx.store_owned(),
+            start_ts,
+            recent_roots,
+            VoidAsyncWriter,
+            CidHashSet::default(),
+        )
+        .await?;
+        return Ok(None);
+    }
+    let file = tokio::fs::File::create(&output_path).await?;
+    crate::chain::export_car_v2(
+        ctx.store_owned(),
+        start_ts,
+        recent_roots,
+        file,
+        CidHashSet::default(),
+    )
+    .await?;
+    if skip_checksum {
+        return Ok(None);
+    }
+    // The CARv2 header is written last, hence the checksum is computed from the complete file.
+    let checksum = tokio::task::spawn_blocking(move || {
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut std::fs::File::open(output_path)?, &mut hasher)?;
+        anyhow::Ok(hasher.finalize())
+    })
+    .await??;
+    Ok(Some(checksum.encode_hex()))
+}
+
 pub enum ChainReadObj {}
 impl RpcMethod<1> for ChainReadObj {
     const NAME: &'static str = "Filecoin.ChainReadObj";
     const PARAM_NAMES: [&'static str; 1] = ["cid"];
     const API_PATHS: ApiPaths = ApiPaths::V1;
     const PERMISSION: Permission = Permission::Read;
+    const CACHEABILITY: Cacheability = Cacheability::Immutable;
     const DESCRIPTION: Option<&'static str> = Some("Reads IPLD nodes referenced by the specified CID from the chain blockstore and returns raw bytes.");
 
     type Params = (Cid,);
@@ -355,6 +412,7 @@ impl RpcMethod<1> for ChainGetBlockMessages {
     const PARAM_NAMES: [&'static str; 1] = ["blockCid"];
     const API_PATHS: ApiPaths = ApiPaths::V1;
     const PERMISSION: Permission = Permission::Read;
+    const CACHEABILITY: Cacheability = Cacheability::Immutable;
     const DESCRIPTION: Option<&'static str> =
         Some("Returns all messages from the specified block.");
 
@@ -466,6 +524,7 @@ impl RpcMethod<2> for ChainGetTipSetByHeight {
     const PARAM_NAMES: [&'static str; 2] = ["height", "tipsetKey"];
     const API_PATHS: ApiPaths = ApiPaths::V1;
     const PERMISSION: Permission = Permission::Read;
+    const CACHEABILITY: Cacheability = Cacheability::AtEpoch(0);
     const DESCRIPTION: Option<&'static str> = Some("Returns the tipset at the specified height.");
 
     type Params = (ChainEpoch, ApiTipsetKey);
@@ -553,6 +612,7 @@ impl RpcMethod<1> for ChainGetBlock {
     const PARAM_NAMES: [&'static str; 1] = ["blockCid"];
     const API_PATHS: ApiPaths = ApiPaths::V1;
     const PERMISSION: Permission = Permission::Read;
+    const CACHEABILITY: Cacheability = Cacheability::Immutable;
     const DESCRIPTION: Option<&'static str> = Some("Returns the block with the specified CID.");
 
     type Params = (Cid,);
@@ -573,6 +633,7 @@ impl RpcMethod<1> for ChainGetTipSet {
     const PARAM_NAMES: [&'static str; 1] = ["tipsetKey"];
     const API_PATHS: ApiPaths = ApiPaths::V1;
     const PERMISSION: Permission = Permission::Read;
+    const CACHEABILITY: Cacheability = Cacheability::AtTipset(0);
     const DESCRIPTION: Option<&'static str> = Some("Returns the tipset with the specified CID.");
 
     type Params = (ApiTipsetKey,);
@@ -650,6 +711,130 @@ impl RpcMethod<1> for ChainGetMinBaseFee {
     }
 }
 
+pub enum ChainPrune {}
+impl RpcMethod<1> for ChainPrune {
+    const NAME: &'static str = "Forest.ChainPrune";
+    const PARAM_NAMES: [&'static str; 1] = ["options"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Admin;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Removes the data that is neither reachable within the state retention nor from the pinned tipsets. Waits for chain finality before removing anything, unless it is a dry run.",
+    );
+
+    type Params = (PruneOptions,);
+    type Ok = PruneReport;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore>,
+        (options,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let pruner = ctx
+            .chain_pruner
+            .as_ref()
+            .context("pruning is not supported by this node")?;
+        Ok(pruner.prune(options).await?)
+    }
+}
+
+pub enum ChainProduceTipset {}
+impl RpcMethod<1> for ChainProduceTipset {
+    const NAME: &'static str = "Forest.ChainProduceTipset";
+    const PARAM_NAMES: [&'static str; 1] = ["epoch"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Admin;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Produces a local tipset on top of the heaviest tipset, with the messages of the message pool, at the given epoch or the next one.",
+    );
+
+    type Params = (Option<ChainEpoch>,);
+    type Ok = Tipset;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (epoch,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let producer = ctx
+            .block_producer
+            .as_ref()
+            .context("block production is not enabled on this node")?;
+        let epoch = epoch.unwrap_or_else(|| ctx.chain_store().heaviest_tipset().epoch() + 1);
+        Ok((*producer.produce_tipset(epoch).await?).clone())
+    }
+}
+
+pub enum ChainPinTipset {}
+impl RpcMethod<1> for ChainPinTipset {
+    const NAME: &'static str = "Forest.ChainPinTipset";
+    const PARAM_NAMES: [&'static str; 1] = ["tsk"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Admin;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Pins the specified tipset so that its block headers and state are never garbage collected. The ancestors of the tipset are not retained.");
+
+    type Params = (TipsetKey,);
+    type Ok = ();
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore>,
+        (tsk,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        ctx.chain_index().load_required_tipset(&tsk)?;
+        let mut pinned = ctx.settings_store.gc_pinned_tipsets()?;
+        if !pinned.contains(&tsk) {
+            pinned.push(tsk);
+            ctx.settings_store.set_gc_pinned_tipsets(&pinned)?;
+        }
+        Ok(())
+    }
+}
+
+pub enum ChainUnpinTipset {}
+impl RpcMethod<1> for ChainUnpinTipset {
+    const NAME: &'static str = "Forest.ChainUnpinTipset";
+    const PARAM_NAMES: [&'static str; 1] = ["tsk"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Admin;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Unpins the specified tipset, allowing its state to be garbage collected.");
+
+    type Params = (TipsetKey,);
+    type Ok = ();
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore>,
+        (tsk,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let mut pinned = ctx.settings_store.gc_pinned_tipsets()?;
+        let len = pinned.len();
+        pinned.retain(|it| it != &tsk);
+        if pinned.len() == len {
+            return Err(ServerError::invalid_params(
+                format!("tipset {tsk} is not pinned"),
+                None,
+            ));
+        }
+        ctx.settings_store.set_gc_pinned_tipsets(&pinned)?;
+        Ok(())
+    }
+}
+
+pub enum ChainPinnedTipsets {}
+impl RpcMethod<0> for ChainPinnedTipsets {
+    const NAME: &'static str = "Forest.ChainPinnedTipsets";
+    const PARAM_NAMES: [&'static str; 0] = [];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Returns the tipsets whose state is never garbage collected.");
+
+    type Params = ();
+    type Ok = Vec<TipsetKey>;
+
+    async fn handle(ctx: Ctx<impl Blockstore>, (): Self::Params) -> Result<Self::Ok, ServerError> {
+        Ok(ctx.settings_store.gc_pinned_tipsets()?)
+    }
+}
+
 pub enum ChainTipSetWeight {}
 impl RpcMethod<1> for ChainTipSetWeight {
     const NAME: &'static str = "Filecoin.ChainTipSetWeight";
@@ -805,6 +990,8 @@ pub struct ChainExportParams {
     pub tipset_keys: ApiTipsetKey,
     pub skip_checksum: bool,
     pub dry_run: bool,
+    #[serde(default)]
+    pub format: ExportFormat,
 }
 lotus_json_with_self!(ChainExportParams);
 
@@ -2,7 +2,6 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::*;
-use crate::rpc::types::EventEntry;
 
 #[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Eq, PartialEq, Default)]
 #[serde(rename_all = "PascalCase")]
@@ -11,12 +10,3 @@ pub struct ObjStat {
     pub links: usize,
 }
 lotus_json_with_self!(ObjStat);
-
-#[derive(Serialize, Deserialize, JsonSchema, Clone)]
-#[serde(rename_all = "PascalCase")]
-pub struct Event {
-    /// Actor ID
-    pub emitter: u64,
-    pub entries: Vec<EventEntry>,
-}
-lotus_json_with_self!(Event);
@@ -81,6 +81,45 @@ impl RpcMethod<0> for StartTime {
     }
 }
 
+pub enum LogList {}
+impl RpcMethod<0> for LogList {
+    const NAME: &'static str = "Filecoin.LogList";
+    const PARAM_NAMES: [&'static str; 0] = [];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Returns the directives of the node log filter, e.g. `info` or `libp2p_kad=error`.");
+
+    type Params = ();
+    type Ok = Vec<String>;
+
+    async fn handle(_: Ctx<impl Any>, (): Self::Params) -> Result<Self::Ok, ServerError> {
+        Ok(crate::cli_shared::logger::log_directives()?)
+    }
+}
+
+pub enum LogSetLevel {}
+impl RpcMethod<2> for LogSetLevel {
+    const NAME: &'static str = "Filecoin.LogSetLevel";
+    const PARAM_NAMES: [&'static str; 2] = ["subsystem", "level"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Admin;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Sets the log level of a target, e.g. `forest::chain_sync`, or the default level with `*`.",
+    );
+
+    type Params = (String, String);
+    type Ok = ();
+
+    async fn handle(
+        _: Ctx<impl Any>,
+        (subsystem, level): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        crate::cli_shared::logger::set_log_level(&subsystem, &level)
+            .map_err(|e| ServerError::invalid_params(e.to_string(), None))
+    }
+}
+
 /// Represents the current version of the API.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "PascalCase")]
@@ -3,6 +3,7 @@
 
 mod eth_tx;
 pub mod filter;
+pub mod pubsub;
 mod trace;
 pub mod types;
 mod utils;
@@ -28,9 +29,10 @@ use crate::rpc::eth::filter::{
     event::EventFilter, mempool::MempoolFilter, tipset::TipSetFilter, SkipEvent,
 };
 use crate::rpc::eth::types::{EthBlockTrace, EthTrace};
+use crate::rpc::state::ApiInvocResult;
 use crate::rpc::types::{ApiTipsetKey, EventEntry, MessageLookup};
 use crate::rpc::EthEventHandler;
-use crate::rpc::{ApiPaths, Ctx, Permission, RpcMethod};
+use crate::rpc::{ApiPaths, Cacheability, Ctx, Permission, RpcMethod};
 use crate::shim::actors::eam;
 use crate::shim::actors::evm;
 use crate::shim::actors::is_evm_actor;
@@ -47,10 +49,14 @@ use crate::shim::gas::GasOutputs;
 use crate::shim::message::Message;
 use crate::shim::trace::{CallReturn, ExecutionEvent};
 use crate::shim::{clock::ChainEpoch, state_tree::StateTree};
+use crate::state_manager::state_override::{
+    ActorOverride, StateOverride, StorageOverride, StorageSlot,
+};
 use crate::utils::db::BlockstoreExt as _;
 use crate::utils::encoding::from_slice_with_fallback;
 use crate::utils::misc::env::env_or_default;
 use crate::utils::multihash::prelude::*;
+use ahash::HashMap;
 use anyhow::{anyhow, bail, ensure, Context, Error, Result};
 use cid::Cid;
 use filter::{ParsedFilter, ParsedFilterTipsets};
@@ -1404,6 +1410,7 @@ impl RpcMethod<2> for EthGetBlockByNumber {
     const PARAM_NAMES: [&'static str; 2] = ["blockParam", "fullTxInfo"];
     const API_PATHS: ApiPaths = ApiPaths::V1;
     const PERMISSION: Permission = Permission::Read;
+    const CACHEABILITY: Cacheability = Cacheability::AtEpoch(0);
 
     type Params = (ExtBlockNumberOrHash, bool);
     type Ok = Block;
@@ -1722,7 +1729,7 @@ impl EthEstimateGas {
             )
         }) {
             let ret = Self::gas_search(data, &msg, &prior_messages, ts).await?;
-            Ok(((ret as f64) * data.mpool.config.gas_limit_overestimation) as u64)
+            Ok(((ret as f64) * data.mpool.get_config().gas_limit_overestimation) as u64)
         } else {
             anyhow::bail!(
                 "message execution failed: exit {}, reason: {}",
@@ -2361,38 +2368,143 @@ impl RpcMethod<1> for EthGetTransactionHashByCid {
 }
 
 pub enum EthCall {}
-impl RpcMethod<2> for EthCall {
+impl RpcMethod<3> for EthCall {
     const NAME: &'static str = "Filecoin.EthCall";
     const NAME_ALIAS: Option<&'static str> = Some("eth_call");
     const N_REQUIRED_PARAMS: usize = 2;
-    const PARAM_NAMES: [&'static str; 2] = ["tx", "blockParam"];
+    const PARAM_NAMES: [&'static str; 3] = ["tx", "blockParam", "stateOverride"];
     const API_PATHS: ApiPaths = ApiPaths::V1;
     const PERMISSION: Permission = Permission::Read;
-    type Params = (EthCallMessage, BlockNumberOrHash);
+    type Params = (EthCallMessage, BlockNumberOrHash, Option<EthStateOverride>);
     type Ok = EthBytes;
     async fn handle(
         ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
-        (tx, block_param): Self::Params,
+        (tx, block_param, state_override): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
         let msg = tx.try_into()?;
+        let state_override = eth_state_override(state_override.unwrap_or_default())?;
         let ts = tipset_by_block_number_or_hash(ctx.chain_store(), block_param)?;
-        let invoke_result = ctx.state_manager.call(&msg, Some(ts))?;
+        let invoke_result =
+            ctx.state_manager
+                .call_with_state_override(&msg, Some(ts), &state_override)?;
+        Ok(eth_call_output(&msg, invoke_result)?)
+    }
+}
 
-        if msg.to() == FilecoinAddress::ETHEREUM_ACCOUNT_MANAGER_ACTOR {
-            Ok(EthBytes::default())
+fn eth_call_output(msg: &Message, invoke_result: ApiInvocResult) -> Result<EthBytes> {
+    if msg.to() == FilecoinAddress::ETHEREUM_ACCOUNT_MANAGER_ACTOR {
+        Ok(EthBytes::default())
+    } else {
+        let msg_rct = invoke_result.msg_rct.context("no message receipt")?;
+        let return_data = msg_rct.return_data();
+        if return_data.is_empty() {
+            Ok(Default::default())
         } else {
-            let msg_rct = invoke_result.msg_rct.context("no message receipt")?;
-            let return_data = msg_rct.return_data();
-            if return_data.is_empty() {
-                Ok(Default::default())
-            } else {
-                let bytes = decode_payload(&return_data, CBOR)?;
-                Ok(bytes)
-            }
+            let bytes = decode_payload(&return_data, CBOR)?;
+            Ok(bytes)
         }
     }
 }
 
+/// Converts the Ethereum state overrides to the changes applied by the [`StateManager`](crate::state_manager::StateManager).
+fn eth_state_override(state_override: EthStateOverride) -> Result<StateOverride> {
+    let slots = |slots: HashMap<EthHash, EthHash>| -> Vec<StorageSlot> {
+        slots
+            .into_iter()
+            .map(|(key, value)| (key.0 .0, value.0 .0))
+            .collect()
+    };
+    state_override
+        .into_iter()
+        .map(|(address, account)| {
+            let storage = match (account.state, account.state_diff) {
+                (Some(_), Some(_)) => {
+                    bail!("account {:#x} has both state and stateDiff", address.0)
+                }
+                (Some(state), None) => Some(StorageOverride::Replace(slots(state))),
+                (None, Some(state_diff)) => Some(StorageOverride::Patch(slots(state_diff))),
+                (None, None) => None,
+            };
+            Ok((
+                address.to_filecoin_address()?,
+                ActorOverride {
+                    balance: account.balance.map(|it| TokenAmount::from_atto(it.0)),
+                    nonce: account.nonce.map(|it| it.0),
+                    code: account.code.map(|it| it.0),
+                    storage,
+                },
+            ))
+        })
+        .collect()
+}
+
+pub enum EthCallMany {}
+impl RpcMethod<3> for EthCallMany {
+    const NAME: &'static str = "Forest.EthCallMany";
+    const NAME_ALIAS: Option<&'static str> = Some("eth_callMany");
+    const N_REQUIRED_PARAMS: usize = 2;
+    const PARAM_NAMES: [&'static str; 3] = ["bundles", "simulationContext", "stateOverride"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Simulates bundles of calls in order, each call seeing the changes made by the previous ones, without persisting any change.",
+    );
+
+    type Params = (
+        Vec<EthCallBundle>,
+        EthSimulationContext,
+        Option<EthStateOverride>,
+    );
+    type Ok = Vec<Vec<EthCallManyResult>>;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (bundles, simulation_context, state_override): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let state_override = eth_state_override(state_override.unwrap_or_default())?;
+        let ts =
+            tipset_by_block_number_or_hash(ctx.chain_store(), simulation_context.block_number)?;
+        let bundle_sizes: Vec<_> = bundles.iter().map(|it| it.transactions.len()).collect();
+        let messages = bundles
+            .into_iter()
+            .flat_map(|bundle| bundle.transactions)
+            .map(Message::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        let mut results = ctx
+            .state_manager
+            .call_many(&messages, Some(ts), &state_override)?
+            .into_iter()
+            .zip(messages.iter())
+            .map(|(invoke_result, msg)| {
+                let exit_code = invoke_result.msg_rct.as_ref().map(|rct| rct.exit_code());
+                if !exit_code.is_some_and(|it| it.is_success()) || !invoke_result.error.is_empty() {
+                    return EthCallManyResult {
+                        error: Some(format!(
+                            "message execution failed (exit=[{}], vm error=[{}])",
+                            exit_code.map(|it| it.to_string()).unwrap_or_default(),
+                            invoke_result.error
+                        )),
+                        ..Default::default()
+                    };
+                }
+                match eth_call_output(msg, invoke_result) {
+                    Ok(value) => EthCallManyResult {
+                        value: Some(value),
+                        ..Default::default()
+                    },
+                    Err(e) => EthCallManyResult {
+                        error: Some(e.to_string()),
+                        ..Default::default()
+                    },
+                }
+            });
+        Ok(bundle_sizes
+            .into_iter()
+            .map(|size| results.by_ref().take(size).collect())
+            .collect())
+    }
+}
+
 pub enum EthNewFilter {}
 impl RpcMethod<1> for EthNewFilter {
     const NAME: &'static str = "Filecoin.EthNewFilter";
@@ -3174,6 +3286,242 @@ impl RpcMethod<2> for EthTraceReplayBlockTransactions {
     }
 }
 
+pub enum EthDebugTraceTransaction {}
+impl RpcMethod<2> for EthDebugTraceTransaction {
+    const NAME: &'static str = "Forest.EthDebugTraceTransaction";
+    const NAME_ALIAS: Option<&'static str> = Some("debug_traceTransaction");
+    const N_REQUIRED_PARAMS: usize = 1;
+    const PARAM_NAMES: [&'static str; 2] = ["txHash", "options"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns the Geth-style trace of a transaction, as built by the callTracer or the prestateTracer.",
+    );
+
+    type Params = (EthHash, Option<GethDebugTracingOptions>);
+    type Ok = GethTrace;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (tx_hash, options): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let options = options.unwrap_or_default();
+        let tracer = geth_tracer(&options)?;
+        let eth_txn = get_eth_transaction_by_hash(&ctx, &tx_hash, None)
+            .await?
+            .ok_or(ServerError::internal_error("transaction not found", None))?;
+        let ts = tipset_by_ext_block_number_or_hash(
+            ctx.chain_store(),
+            ExtBlockNumberOrHash::from_block_number(eth_txn.block_number.0 as i64),
+        )?;
+        let (_, trace) = ctx.state_manager.execution_trace(&ts)?;
+        for ir in trace.into_iter() {
+            if ir.msg.from == system::ADDRESS.into() {
+                continue;
+            }
+            if EthGetTransactionHashByCid::handle(ctx.clone(), (ir.msg_cid,)).await?
+                == Some(tx_hash.clone())
+            {
+                let state_root = ctx
+                    .state_manager
+                    .state_before_message(ts.clone(), ir.msg_cid)
+                    .await?;
+                return geth_trace(&ctx, &state_root, ir, tracer, &options.tracer_config);
+            }
+        }
+        Err(anyhow::anyhow!("transaction {tx_hash} not found in the execution trace").into())
+    }
+}
+
+pub enum EthDebugTraceCall {}
+impl RpcMethod<3> for EthDebugTraceCall {
+    const NAME: &'static str = "Forest.EthDebugTraceCall";
+    const NAME_ALIAS: Option<&'static str> = Some("debug_traceCall");
+    const N_REQUIRED_PARAMS: usize = 2;
+    const PARAM_NAMES: [&'static str; 3] = ["tx", "blockParam", "options"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Executes a call without persisting any change and returns its Geth-style trace, as built by the callTracer or the prestateTracer.",
+    );
+
+    type Params = (
+        EthCallMessage,
+        BlockNumberOrHash,
+        Option<GethDebugTracingOptions>,
+    );
+    type Ok = GethTrace;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (tx, block_param, options): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let options = options.unwrap_or_default();
+        let tracer = geth_tracer(&options)?;
+        let state_override =
+            eth_state_override(options.state_overrides.clone().unwrap_or_default())?;
+        let msg = tx.try_into()?;
+        let ts = tipset_by_block_number_or_hash(ctx.chain_store(), block_param)?;
+        let ir =
+            ctx.state_manager
+                .call_with_state_override(&msg, Some(ts.clone()), &state_override)?;
+        geth_trace(&ctx, ts.parent_state(), ir, tracer, &options.tracer_config)
+    }
+}
+
+fn geth_tracer(
+    options: &GethDebugTracingOptions,
+) -> Result<GethDebugBuiltInTracerType, ServerError> {
+    options.tracer.ok_or_else(|| {
+        ServerError::invalid_params(
+            "the opcode logger is not supported, use the callTracer or the prestateTracer",
+            None,
+        )
+    })
+}
+
+/// Builds the Geth-style trace of an applied message from its FVM execution trace.
+///
+/// The `prestateTracer` reads the touched accounts from `state_root`, the state the message is
+/// applied on, with the sender nonce of the message.
+fn geth_trace<DB: Blockstore + Send + Sync>(
+    ctx: &Ctx<DB>,
+    state_root: &Cid,
+    ir: ApiInvocResult,
+    tracer: GethDebugBuiltInTracerType,
+    config: &GethTracerConfig,
+) -> Result<GethTrace, ServerError> {
+    let state = StateTree::new_from_root(ctx.store_owned(), state_root)?;
+    let sender = lookup_eth_address(&ir.msg.from, &state)?
+        .with_context(|| format!("message sender {} could not be found", ir.msg.from))?;
+    let mut env = trace::base_environment(&state, &ir.msg.from)
+        .map_err(|e| format!("when processing message {}: {}", ir.msg_cid, e))?;
+    if let Some(execution_trace) = ir.execution_trace {
+        trace::build_traces(&mut env, &[], execution_trace)?;
+    }
+    let mut frame = match trace::build_call_frame(env.traces) {
+        Some(frame) => frame,
+        // The message failed before the receiver could be invoked.
+        None => GethCallFrame {
+            r#type: "CALL".into(),
+            from: sender.clone(),
+            to: lookup_eth_address(&ir.msg.to, &state)?,
+            value: Some(ir.msg.value.clone().into()),
+            gas: ir.msg.gas_limit.into(),
+            gas_used: ir
+                .msg_rct
+                .as_ref()
+                .map(|rct| rct.gas_used())
+                .unwrap_or_default()
+                .into(),
+            error: Some(ir.error.clone()),
+            ..Default::default()
+        },
+    };
+
+    match tracer {
+        GethDebugBuiltInTracerType::CallTracer => {
+            if config.only_top_call {
+                frame.calls.clear();
+            }
+            Ok(GethTrace::Call(frame))
+        }
+        GethDebugBuiltInTracerType::PrestateTracer => {
+            let mut addresses = vec![];
+            let mut frames = vec![&frame];
+            while let Some(frame) = frames.pop() {
+                addresses.push(frame.from.clone());
+                addresses.extend(frame.to.clone());
+                frames.extend(frame.calls.iter());
+            }
+            let mut accounts = HashMap::default();
+            for address in addresses {
+                if accounts.contains_key(&address) {
+                    continue;
+                }
+                let Some(actor) = state.get_actor(&address.to_filecoin_address()?)? else {
+                    // Created by the message.
+                    continue;
+                };
+                let mut account = GethPrestateAccount {
+                    balance: EthBigInt(actor.balance.atto().clone()),
+                    nonce: actor.sequence,
+                    code: None,
+                };
+                if is_evm_actor(&actor.code) {
+                    let evm_state = evm::State::load(ctx.store(), actor.code, actor.state)?;
+                    account.nonce = 0;
+                    if evm_state.is_alive() {
+                        account.nonce = evm_state.nonce();
+                        account.code =
+                            Some(EthBytes(ctx.store().get_required(&evm_state.bytecode())?));
+                    }
+                }
+                if address == sender {
+                    account.nonce = ir.msg.sequence;
+                }
+                accounts.insert(address, account);
+            }
+            Ok(GethTrace::Prestate(accounts))
+        }
+    }
+}
+
+pub enum EthTraceCall {}
+impl RpcMethod<4> for EthTraceCall {
+    const NAME: &'static str = "Forest.EthTraceCall";
+    const NAME_ALIAS: Option<&'static str> = Some("trace_call");
+    const N_REQUIRED_PARAMS: usize = 3;
+    const PARAM_NAMES: [&'static str; 4] = ["tx", "traceTypes", "blockParam", "stateOverride"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Executes a call without persisting any change and returns its traces.");
+
+    type Params = (
+        EthCallMessage,
+        Vec<String>,
+        BlockNumberOrHash,
+        Option<EthStateOverride>,
+    );
+    type Ok = EthTraceResults;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (tx, trace_types, block_param, state_override): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        if trace_types.as_slice() != ["trace"] {
+            return Err(anyhow::anyhow!("only trace is supported").into());
+        }
+        let msg: Message = tx.try_into()?;
+        let state_override = eth_state_override(state_override.unwrap_or_default())?;
+        let ts = tipset_by_block_number_or_hash(ctx.chain_store(), block_param)?;
+        let ir =
+            ctx.state_manager
+                .call_with_state_override(&msg, Some(ts.clone()), &state_override)?;
+
+        let state = StateTree::new_from_root(ctx.store_owned(), ts.parent_state())?;
+        let mut env = trace::base_environment(&state, &msg.from)
+            .map_err(|e| format!("when processing message {}: {}", ir.msg_cid, e))?;
+        if let Some(execution_trace) = ir.execution_trace {
+            trace::build_traces(&mut env, &[], execution_trace)?;
+        }
+        let output =
+            env.traces
+                .first()
+                .map_or_else(EthBytes::default, |trace| match &trace.result {
+                    TraceResult::Call(r) => r.output.clone(),
+                    TraceResult::Create(r) => r.code.clone(),
+                });
+        Ok(EthTraceResults {
+            output,
+            state_diff: None,
+            trace: env.traces,
+            vm_trace: None,
+        })
+    }
+}
+
 fn get_eth_block_number_from_string<DB: Blockstore>(
     chain_store: &ChainStore<DB>,
     block: Option<&str>,
@@ -3705,4 +4053,33 @@ mod test {
         ];
         assert!(eth_log_from_event(&entries).is_none());
     }
+
+    #[test]
+    fn test_eth_state_override() {
+        let address = EthAddress::from_str("0x0c1d86d34e469770339b53613f3a2343accd62cb").unwrap();
+        let slot = |b: u8| EthHash(ethereum_types::H256([b; 32]));
+        let state_override: EthStateOverride = HashMap::from_iter([(
+            address.clone(),
+            EthAccountOverride {
+                balance: Some(EthBigInt(42.into())),
+                nonce: Some(EthUint64(7)),
+                state_diff: Some(HashMap::from_iter([(slot(1), slot(2))])),
+                ..Default::default()
+            },
+        )]);
+        let converted = eth_state_override(state_override.clone()).unwrap();
+        assert_eq!(
+            converted.get(&address.to_filecoin_address().unwrap()),
+            Some(&ActorOverride {
+                balance: Some(TokenAmount::from_atto(42)),
+                nonce: Some(7),
+                code: None,
+                storage: Some(StorageOverride::Patch(vec![([1; 32], [2; 32])])),
+            })
+        );
+
+        let mut both = state_override;
+        both.get_mut(&address).unwrap().state = Some(HashMap::default());
+        assert!(eth_state_override(both).is_err());
+    }
 }
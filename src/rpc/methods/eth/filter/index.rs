@@ -0,0 +1,717 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! # Persistent Actor Event Index
+//!
+//! Answering `eth_getLogs`-style queries by re-executing every tipset in the requested range is
+//! prohibitively slow for large ranges. The [`EventIndex`] stores the events emitted by each
+//! executed tipset together with posting keys for emitter addresses and entry values so that
+//! queries only need to load the epochs that can possibly match.
+//!
+//! ## Key layout
+//!
+//! All epochs are encoded as big-endian integers so that the byte-wise ordering of the keys
+//! matches the numeric ordering of the epochs.
+//!
+//! - `e | epoch` - the [`IndexedTipset`] executed at `epoch`.
+//! - `a | len(address) | address | epoch` - the emitter `address` emitted events at `epoch`.
+//! - `f | blake2b(key, codec, value) | epoch` - an event entry with the given `key`, `codec` and
+//!   `value` was emitted at `epoch`.
+//! - `r` - the contiguous [`IndexedRange`] of epochs covered by the index.
+//!
+//! The index is updated from head changes. When the new head is not a descendant of the indexed
+//! chain, the stale epochs are rolled back before the new branch is indexed.
+
+use super::{EventCounter, ParsedFilter, SkipEvent};
+use crate::blocks::{Tipset, TipsetKey};
+use crate::db::EventIndexStore;
+use crate::rpc::eth::CollectedEvent;
+use crate::rpc::types::EventEntry;
+use crate::shim::address::Address;
+use crate::shim::clock::ChainEpoch;
+use crate::shim::executor::Entry;
+use crate::state_manager::{StateEvents, StateManager};
+use crate::utils::encoding::blake2b_256;
+use ahash::HashSet;
+use anyhow::{bail, ensure, Context as _};
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use fvm_ipld_encoding::{strict_bytes, to_vec};
+use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+const TIPSET_PREFIX: u8 = b'e';
+const EMITTER_PREFIX: u8 = b'a';
+const FIELD_PREFIX: u8 = b'f';
+const RANGE_KEY: &[u8] = b"r";
+
+/// The events emitted while executing the messages of a single tipset.
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct IndexedTipset {
+    pub tipset_key: TipsetKey,
+    pub events: Vec<IndexedEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct IndexedEvent {
+    /// The robust address of the emitter, or its ID address if it could not be resolved.
+    pub emitter: Address,
+    /// Whether `emitter` has been resolved to a robust address.
+    pub resolved: bool,
+    pub msg_idx: u64,
+    pub msg_cid: Cid,
+    pub entries: Vec<IndexedEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct IndexedEntry {
+    pub flags: u64,
+    pub key: String,
+    pub codec: u64,
+    #[serde(with = "strict_bytes")]
+    pub value: Vec<u8>,
+}
+
+impl From<Entry> for IndexedEntry {
+    fn from(entry: Entry) -> Self {
+        let (flags, key, codec, value) = entry.into_parts();
+        Self {
+            flags,
+            key,
+            codec,
+            value,
+        }
+    }
+}
+
+/// Inclusive range of epochs for which every executed tipset has been indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct IndexedRange {
+    pub min: ChainEpoch,
+    pub max: ChainEpoch,
+}
+
+impl IndexedRange {
+    pub fn contains(&self, range: &RangeInclusive<ChainEpoch>) -> bool {
+        self.min <= *range.start() && *range.end() <= self.max
+    }
+}
+
+/// Persistent index of the actor events emitted on the canonical chain.
+pub struct EventIndex {
+    store: Arc<dyn EventIndexStore + Sync + Send>,
+}
+
+impl EventIndex {
+    pub fn new(store: Arc<dyn EventIndexStore + Sync + Send>) -> Self {
+        Self { store }
+    }
+
+    /// Returns the range of epochs covered by the index, if any.
+    pub fn indexed_range(&self) -> anyhow::Result<Option<IndexedRange>> {
+        self.store
+            .read_bin(RANGE_KEY)?
+            .map(|bytes| fvm_ipld_encoding::from_slice(&bytes))
+            .transpose()
+            .context("corrupted event index range")
+    }
+
+    /// Returns the indexed events of the tipset executed at `epoch`, if any.
+    pub fn indexed_tipset(&self, epoch: ChainEpoch) -> anyhow::Result<Option<IndexedTipset>> {
+        self.store
+            .read_bin(&tipset_key(epoch))?
+            .map(|bytes| fvm_ipld_encoding::from_slice(&bytes))
+            .transpose()
+            .with_context(|| format!("corrupted event index entry at epoch {epoch}"))
+    }
+
+    /// Brings the index up to date with the new `head`.
+    ///
+    /// The messages of `head` have not been executed yet, so the index covers the epochs up to
+    /// (but excluding) `head`. Tipsets are walked back from the parent of `head` until an epoch that
+    /// is already indexed with the same tipset is found, or until `lookback` epochs have been
+    /// walked. Any indexed epoch that is not on the chain of `head` is rolled back.
+    pub async fn apply<DB: Blockstore + Send + Sync + 'static>(
+        &self,
+        state_manager: &Arc<StateManager<DB>>,
+        head: &Tipset,
+        lookback: ChainEpoch,
+    ) -> anyhow::Result<()> {
+        if head.epoch() == 0 {
+            return Ok(());
+        }
+        let store = state_manager.blockstore();
+        let parent = Tipset::load_required(store, head.parents())?;
+        let min_epoch = (parent.epoch() - lookback).max(0);
+
+        let mut pending = vec![];
+        let mut anchored = false;
+        for ts in parent.clone().chain(store) {
+            if let Some(indexed) = self.indexed_tipset(ts.epoch())? {
+                if &indexed.tipset_key == ts.key() {
+                    anchored = true;
+                    break;
+                }
+            }
+            if ts.epoch() < min_epoch {
+                break;
+            }
+            pending.push(Arc::new(ts));
+        }
+
+        let truncate_from = pending
+            .last()
+            .map(|ts| ts.epoch())
+            .unwrap_or(parent.epoch() + 1);
+        self.truncate(truncate_from)?;
+
+        let min = match self.indexed_range()? {
+            Some(range) if anchored && range.min < truncate_from => range.min,
+            _ => truncate_from,
+        };
+        // Write the range before indexing so that an interrupted update is never reported as
+        // covered. The range is widened after each indexed tipset.
+        self.write_range(min, truncate_from - 1)?;
+        for ts in pending.into_iter().rev() {
+            tracing::debug!("Indexing events of tipset {}", ts.key());
+            let indexed = Self::collect_tipset(state_manager, &ts).await?;
+            self.put_tipset(ts.epoch(), &indexed)?;
+            self.write_range(min, ts.epoch())?;
+        }
+        self.write_range(min, head.epoch() - 1)
+    }
+
+    /// Removes all the epochs that precede `epoch` from the index.
+    pub fn prune_before(&self, epoch: ChainEpoch) -> anyhow::Result<()> {
+        let Some(range) = self.indexed_range()? else {
+            return Ok(());
+        };
+        if range.min >= epoch {
+            return Ok(());
+        }
+        self.delete_epochs(&tipset_key(0), &tipset_key(epoch - 1))?;
+        self.write_range(epoch.min(range.max + 1), range.max)
+    }
+
+    /// Collects the events matching `filter` in the given epoch `range`, in descending epoch
+    /// order. Returns [`None`] if the index does not cover the whole range.
+    pub fn collect_events(
+        &self,
+        range: RangeInclusive<ChainEpoch>,
+        filter: &ParsedFilter,
+        skip_event: SkipEvent,
+        max_results: usize,
+    ) -> anyhow::Result<Option<Vec<CollectedEvent>>> {
+        match self.indexed_range()? {
+            Some(indexed) if indexed.contains(&range) => {}
+            _ => return Ok(None),
+        }
+
+        let mut collected_events = vec![];
+        for epoch in self.candidate_epochs(&range, filter)?.into_iter().rev() {
+            let indexed = self
+                .indexed_tipset(epoch)?
+                .with_context(|| format!("missing event index entry at epoch {epoch}"))?;
+            Self::match_events(
+                epoch,
+                indexed,
+                filter,
+                &skip_event,
+                max_results,
+                &mut collected_events,
+            )?;
+        }
+        Ok(Some(collected_events))
+    }
+
+    /// Collects the events matching `filter` that were emitted by `tipset`. Returns [`None`] if
+    /// the tipset is not indexed.
+    pub fn collect_tipset_events(
+        &self,
+        tipset: &Tipset,
+        filter: &ParsedFilter,
+        skip_event: SkipEvent,
+        max_results: usize,
+    ) -> anyhow::Result<Option<Vec<CollectedEvent>>> {
+        match self.indexed_tipset(tipset.epoch())? {
+            Some(indexed) if &indexed.tipset_key == tipset.key() => {
+                let mut collected_events = vec![];
+                Self::match_events(
+                    tipset.epoch(),
+                    indexed,
+                    filter,
+                    &skip_event,
+                    max_results,
+                    &mut collected_events,
+                )?;
+                Ok(Some(collected_events))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn match_events(
+        height: ChainEpoch,
+        indexed: IndexedTipset,
+        filter: &ParsedFilter,
+        skip_event: &SkipEvent,
+        max_results: usize,
+        collected_events: &mut Vec<CollectedEvent>,
+    ) -> anyhow::Result<()> {
+        let IndexedTipset { tipset_key, events } = indexed;
+        // Number the events like `EthEventHandler::collect_events` does
+        let mut event_counter = EventCounter::default();
+        for event in events {
+            if !event.resolved {
+                event_counter.unresolved();
+                if matches!(skip_event, SkipEvent::OnUnresolvedAddress) {
+                    continue;
+                }
+            }
+            let fields: Vec<_> = event
+                .entries
+                .iter()
+                .map(|entry| (entry.key.as_str(), entry.codec, entry.value.as_slice()))
+                .collect();
+            if !filter.matches_fields(&event.emitter, &fields) {
+                continue;
+            }
+            if collected_events.len() >= max_results {
+                bail!("filter matches too many events, try a more restricted filter");
+            }
+            collected_events.push(CollectedEvent {
+                entries: event
+                    .entries
+                    .into_iter()
+                    .map(|entry| EventEntry {
+                        flags: entry.flags,
+                        key: entry.key,
+                        codec: entry.codec,
+                        value: entry.value.into(),
+                    })
+                    .collect(),
+                emitter_addr: event.emitter,
+                event_idx: event_counter.matched(),
+                reverted: false,
+                height,
+                tipset_key: tipset_key.clone(),
+                msg_idx: event.msg_idx,
+                msg_cid: event.msg_cid,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the indexed epochs in `range` that may contain events matching `filter`.
+    fn candidate_epochs(
+        &self,
+        range: &RangeInclusive<ChainEpoch>,
+        filter: &ParsedFilter,
+    ) -> anyhow::Result<BTreeSet<ChainEpoch>> {
+        let (start, end) = (*range.start(), *range.end());
+        let mut candidates: Option<BTreeSet<ChainEpoch>> = None;
+
+        if !filter.addresses.is_empty() {
+            let mut epochs = BTreeSet::new();
+            for address in &filter.addresses {
+                let prefix = emitter_prefix(address);
+                epochs.extend(self.epochs_in_range(&prefix, start, end)?);
+            }
+            candidates = Some(epochs);
+        }
+
+        for (key, blocks) in &filter.keys {
+            let mut epochs = BTreeSet::new();
+            for block in blocks {
+                let prefix = field_prefix(key, block.codec, &block.value);
+                epochs.extend(self.epochs_in_range(&prefix, start, end)?);
+            }
+            candidates = Some(match candidates {
+                Some(current) => current.intersection(&epochs).copied().collect(),
+                None => epochs,
+            });
+        }
+
+        match candidates {
+            Some(candidates) => Ok(candidates),
+            None => self.epochs_in_range(&[TIPSET_PREFIX], start, end),
+        }
+    }
+
+    fn epochs_in_range(
+        &self,
+        prefix: &[u8],
+        start: ChainEpoch,
+        end: ChainEpoch,
+    ) -> anyhow::Result<BTreeSet<ChainEpoch>> {
+        self.store
+            .keys_in_range(&with_epoch(prefix, start), &with_epoch(prefix, end))?
+            .iter()
+            .map(|key| epoch_suffix(key))
+            .collect()
+    }
+
+    async fn collect_tipset<DB: Blockstore + Send + Sync + 'static>(
+        state_manager: &Arc<StateManager<DB>>,
+        tipset: &Arc<Tipset>,
+    ) -> anyhow::Result<IndexedTipset> {
+        let messages = state_manager.chain_store().messages_for_tipset(tipset)?;
+        let StateEvents { events } = state_manager.tipset_state_events(tipset).await?;
+        ensure!(
+            messages.len() == events.len(),
+            "Length of messages and events do not match"
+        );
+
+        let mut indexed_events = vec![];
+        for (msg_idx, (message, events)) in messages.iter().zip(events).enumerate() {
+            for event in events {
+                let id_addr = Address::new_id(event.emitter());
+                let (emitter, resolved) = match state_manager
+                    .resolve_to_deterministic_address(id_addr, tipset.clone())
+                    .await
+                {
+                    Ok(resolved) => (resolved, true),
+                    Err(_) => (id_addr, false),
+                };
+                indexed_events.push(IndexedEvent {
+                    emitter,
+                    resolved,
+                    msg_idx: msg_idx as u64,
+                    msg_cid: message.cid(),
+                    entries: event
+                        .event()
+                        .entries()
+                        .into_iter()
+                        .map(IndexedEntry::from)
+                        .collect(),
+                });
+            }
+        }
+
+        Ok(IndexedTipset {
+            tipset_key: tipset.key().clone(),
+            events: indexed_events,
+        })
+    }
+
+    pub(crate) fn put_tipset(
+        &self,
+        epoch: ChainEpoch,
+        indexed: &IndexedTipset,
+    ) -> anyhow::Result<()> {
+        let mut ops = vec![(tipset_key(epoch), Some(to_vec(indexed)?))];
+        ops.extend(
+            posting_prefixes(indexed)
+                .into_iter()
+                .map(|prefix| (with_epoch(&prefix, epoch), Some(vec![]))),
+        );
+        self.store.write_batch(ops)
+    }
+
+    pub(crate) fn write_range(&self, min: ChainEpoch, max: ChainEpoch) -> anyhow::Result<()> {
+        let op = if min <= max {
+            Some(to_vec(&IndexedRange { min, max })?)
+        } else {
+            None
+        };
+        self.store.write_batch(vec![(RANGE_KEY.to_vec(), op)])
+    }
+
+    /// Removes the epochs starting from `epoch` from the index.
+    fn truncate(&self, epoch: ChainEpoch) -> anyhow::Result<()> {
+        self.delete_epochs(&tipset_key(epoch), &tipset_key(ChainEpoch::MAX))
+    }
+
+    fn delete_epochs(&self, from: &[u8], to: &[u8]) -> anyhow::Result<()> {
+        let mut ops = vec![];
+        for key in self.store.keys_in_range(from, to)? {
+            let epoch = epoch_suffix(&key)?;
+            if let Some(indexed) = self.indexed_tipset(epoch)? {
+                ops.extend(
+                    posting_prefixes(&indexed)
+                        .into_iter()
+                        .map(|prefix| (with_epoch(&prefix, epoch), None)),
+                );
+            }
+            ops.push((key, None));
+        }
+        if ops.is_empty() {
+            return Ok(());
+        }
+        self.store.write_batch(ops)
+    }
+}
+
+fn encode_epoch(epoch: ChainEpoch) -> [u8; 8] {
+    (epoch.max(0) as u64).to_be_bytes()
+}
+
+fn epoch_suffix(key: &[u8]) -> anyhow::Result<ChainEpoch> {
+    let suffix = key
+        .len()
+        .checked_sub(8)
+        .and_then(|start| key.get(start..))
+        .context("invalid event index key")?;
+    Ok(u64::from_be_bytes(suffix.try_into()?) as ChainEpoch)
+}
+
+fn with_epoch(prefix: &[u8], epoch: ChainEpoch) -> Vec<u8> {
+    let mut key = Vec::with_capacity(prefix.len() + 8);
+    key.extend_from_slice(prefix);
+    key.extend_from_slice(&encode_epoch(epoch));
+    key
+}
+
+fn tipset_key(epoch: ChainEpoch) -> Vec<u8> {
+    with_epoch(&[TIPSET_PREFIX], epoch)
+}
+
+fn emitter_prefix(address: &Address) -> Vec<u8> {
+    let bytes = address.to_bytes();
+    let mut prefix = Vec::with_capacity(bytes.len() + 2);
+    prefix.push(EMITTER_PREFIX);
+    prefix.push(bytes.len() as u8);
+    prefix.extend_from_slice(&bytes);
+    prefix
+}
+
+fn field_prefix(key: &str, codec: u64, value: &[u8]) -> Vec<u8> {
+    let mut preimage = Vec::with_capacity(key.len() + value.len() + 9);
+    preimage.extend_from_slice(key.as_bytes());
+    // The key is a string, so a zero byte unambiguously separates it from the rest.
+    preimage.push(0);
+    preimage.extend_from_slice(&codec.to_be_bytes());
+    preimage.extend_from_slice(value);
+    let mut prefix = Vec::with_capacity(33);
+    prefix.push(FIELD_PREFIX);
+    prefix.extend_from_slice(&blake2b_256(&preimage));
+    prefix
+}
+
+/// Returns the distinct emitter and field prefixes of the events in `indexed`.
+fn posting_prefixes(indexed: &IndexedTipset) -> HashSet<Vec<u8>> {
+    let mut prefixes = HashSet::default();
+    for event in &indexed.events {
+        prefixes.insert(emitter_prefix(&event.emitter));
+        for entry in &event.entries {
+            prefixes.insert(field_prefix(&entry.key, entry.codec, &entry.value));
+        }
+    }
+    prefixes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDB;
+    use crate::rpc::eth::filter::{ActorEventBlock, ParsedFilterTipsets};
+    use crate::utils::multihash::prelude::*;
+    use ahash::AHashMap as HashMap;
+    use fvm_ipld_encoding::{DAG_CBOR, IPLD_RAW};
+
+    fn dummy_tipset_key(seed: u8) -> TipsetKey {
+        let cid = Cid::new_v1(DAG_CBOR, MultihashCode::Blake2b256.digest(&[seed]));
+        TipsetKey::from(nunny::vec![cid])
+    }
+
+    fn indexed_tipset(seed: u8, emitter: Address, topic: &[u8]) -> IndexedTipset {
+        IndexedTipset {
+            tipset_key: dummy_tipset_key(seed),
+            events: vec![IndexedEvent {
+                emitter,
+                resolved: true,
+                msg_idx: 0,
+                msg_cid: Cid::new_v1(DAG_CBOR, MultihashCode::Blake2b256.digest(&[seed, 1])),
+                entries: vec![IndexedEntry {
+                    flags: 3,
+                    key: "t1".into(),
+                    codec: IPLD_RAW,
+                    value: topic.to_vec(),
+                }],
+            }],
+        }
+    }
+
+    fn filter(addresses: Vec<Address>, topic: Option<&[u8]>) -> ParsedFilter {
+        let mut keys = HashMap::new();
+        if let Some(topic) = topic {
+            keys.insert(
+                "t1".to_string(),
+                vec![ActorEventBlock {
+                    codec: IPLD_RAW,
+                    value: topic.to_vec(),
+                }],
+            );
+        }
+        ParsedFilter {
+            tipsets: ParsedFilterTipsets::Range(0..=0),
+            addresses,
+            keys,
+        }
+    }
+
+    fn populated_index() -> EventIndex {
+        let index = EventIndex::new(Arc::new(MemoryDB::default()));
+        index
+            .put_tipset(10, &indexed_tipset(10, Address::new_id(1000), b"foo"))
+            .unwrap();
+        index
+            .put_tipset(11, &indexed_tipset(11, Address::new_id(1001), b"bar"))
+            .unwrap();
+        index
+            .put_tipset(13, &indexed_tipset(13, Address::new_id(1000), b"bar"))
+            .unwrap();
+        index.write_range(10, 13).unwrap();
+        index
+    }
+
+    fn heights(events: &[CollectedEvent]) -> Vec<ChainEpoch> {
+        events.iter().map(|e| e.height).collect()
+    }
+
+    #[test]
+    fn test_collect_events_by_address_and_topic() {
+        let index = populated_index();
+
+        let all = index
+            .collect_events(10..=13, &filter(vec![], None), SkipEvent::Never, 100)
+            .unwrap()
+            .unwrap();
+        assert_eq!(heights(&all), vec![13, 11, 10]);
+
+        let by_address = index
+            .collect_events(
+                10..=13,
+                &filter(vec![Address::new_id(1000)], None),
+                SkipEvent::Never,
+                100,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(heights(&by_address), vec![13, 10]);
+
+        let by_topic = index
+            .collect_events(
+                10..=12,
+                &filter(vec![], Some(b"bar")),
+                SkipEvent::Never,
+                100,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(heights(&by_topic), vec![11]);
+
+        let by_both = index
+            .collect_events(
+                10..=13,
+                &filter(vec![Address::new_id(1000)], Some(b"bar")),
+                SkipEvent::Never,
+                100,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(heights(&by_both), vec![13]);
+    }
+
+    #[test]
+    fn test_collect_events_outside_indexed_range() {
+        let index = populated_index();
+        assert!(index
+            .collect_events(9..=13, &filter(vec![], None), SkipEvent::Never, 100)
+            .unwrap()
+            .is_none());
+        assert!(index
+            .collect_events(10..=14, &filter(vec![], None), SkipEvent::Never, 100)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_collect_events_max_results() {
+        let index = populated_index();
+        assert!(index
+            .collect_events(10..=13, &filter(vec![], None), SkipEvent::Never, 2)
+            .is_err());
+    }
+
+    #[test]
+    fn test_collect_events_numbering() {
+        let event = |emitter: u64, resolved: bool, topic: &[u8]| IndexedEvent {
+            resolved,
+            ..indexed_tipset(10, Address::new_id(emitter), topic)
+                .events
+                .pop()
+                .unwrap()
+        };
+        let indexed = IndexedTipset {
+            tipset_key: dummy_tipset_key(10),
+            events: vec![
+                event(1000, true, b"foo"),
+                event(1001, false, b"foo"),
+                event(1002, true, b"bar"),
+                event(1003, true, b"foo"),
+            ],
+        };
+        let event_indices = |skip_event| {
+            let mut collected = vec![];
+            EventIndex::match_events(
+                10,
+                indexed.clone(),
+                &filter(vec![], Some(b"foo")),
+                &skip_event,
+                100,
+                &mut collected,
+            )
+            .unwrap();
+            collected
+                .iter()
+                .map(|e| (e.emitter_addr, e.event_idx))
+                .collect::<Vec<_>>()
+        };
+
+        // Same numbering as `EthEventHandler::collect_events`: unresolved and matched events are
+        // counted, unmatched resolved events are not.
+        assert_eq!(
+            event_indices(SkipEvent::OnUnresolvedAddress),
+            vec![(Address::new_id(1000), 0), (Address::new_id(1003), 2)]
+        );
+        assert_eq!(
+            event_indices(SkipEvent::Never),
+            vec![
+                (Address::new_id(1000), 0),
+                (Address::new_id(1001), 2),
+                (Address::new_id(1003), 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_truncate_and_prune() {
+        let index = populated_index();
+
+        index.truncate(11).unwrap();
+        index.write_range(10, 10).unwrap();
+        assert!(index.indexed_tipset(11).unwrap().is_none());
+        assert!(index.indexed_tipset(13).unwrap().is_none());
+        let by_topic = index
+            .collect_events(
+                10..=10,
+                &filter(vec![], Some(b"bar")),
+                SkipEvent::Never,
+                100,
+            )
+            .unwrap()
+            .unwrap();
+        assert!(by_topic.is_empty());
+        assert!(index
+            .epochs_in_range(&field_prefix("t1", IPLD_RAW, b"bar"), 0, 100)
+            .unwrap()
+            .is_empty());
+
+        index.prune_before(11).unwrap();
+        assert!(index.indexed_tipset(10).unwrap().is_none());
+        assert!(index.indexed_range().unwrap().is_none());
+    }
+}
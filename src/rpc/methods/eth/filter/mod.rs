@@ -15,6 +15,7 @@
 //! - **TipSet Filter**: Tracks changes in the blockchain's tipset (the latest set of blocks).
 //! - **Mempool Filter**: Monitors the Ethereum mempool for new pending transactions that meet certain criteria.
 pub mod event;
+pub mod index;
 pub mod mempool;
 mod store;
 pub mod tipset;
@@ -28,6 +29,7 @@ use crate::blocks::TipsetKey;
 use crate::chain::index::ResolveNullTipset;
 use crate::cli_shared::cli::EventsConfig;
 use crate::rpc::eth::filter::event::*;
+use crate::rpc::eth::filter::index::EventIndex;
 use crate::rpc::eth::filter::mempool::*;
 use crate::rpc::eth::filter::tipset::*;
 use crate::rpc::eth::types::*;
@@ -91,6 +93,7 @@ pub struct EthEventHandler {
     event_filter_manager: Option<Arc<EventFilterManager>>,
     tipset_filter_manager: Option<Arc<TipSetFilterManager>>,
     mempool_filter_manager: Option<Arc<MempoolFilterManager>>,
+    event_index: Option<Arc<EventIndex>>,
 }
 
 #[derive(Clone)]
@@ -99,6 +102,25 @@ pub enum SkipEvent {
     Never,
 }
 
+/// Numbers the events of a tipset. Only the events with an unresolved emitter and the matched
+/// events are counted, so the index of a collected event depends on the filter.
+#[derive(Default)]
+pub struct EventCounter(u64);
+
+impl EventCounter {
+    /// Counts an event whose emitter could not be resolved.
+    pub fn unresolved(&mut self) {
+        self.0 += 1;
+    }
+
+    /// Counts a matched event and returns its index.
+    pub fn matched(&mut self) -> u64 {
+        let index = self.0;
+        self.0 += 1;
+        index
+    }
+}
+
 impl EthEventHandler {
     pub fn new() -> Self {
         let config = EventsConfig::default();
@@ -140,9 +162,17 @@ impl EthEventHandler {
             event_filter_manager,
             tipset_filter_manager,
             mempool_filter_manager,
+            event_index: None,
         }
     }
 
+    /// Uses the persistent [`EventIndex`] to answer event queries whenever it covers the
+    /// requested tipsets, instead of re-executing them.
+    pub fn with_event_index(mut self, event_index: Arc<EventIndex>) -> Self {
+        self.event_index = Some(event_index);
+        self
+    }
+
     // Installs an eth filter based on given filter spec.
     pub fn eth_new_filter(
         &self,
@@ -267,6 +297,9 @@ impl EthEventHandler {
         )
     }
 
+    /// Collects the events of `tipset` matching `spec` by executing the tipset. The events are
+    /// numbered with an [`EventCounter`], like the events collected from the
+    /// [`index::EventIndex`].
     pub async fn collect_events<DB: Blockstore + Send + Sync + 'static>(
         ctx: &Ctx<DB>,
         tipset: &Arc<Tipset>,
@@ -286,7 +319,7 @@ impl EthEventHandler {
             "Length of messages and events do not match"
         );
 
-        let mut event_count = 0;
+        let mut event_counter = EventCounter::default();
         for (i, (message, events)) in messages.iter().zip(events.into_iter()).enumerate() {
             for event in events.iter() {
                 let id_addr = Address::new_id(event.emitter());
@@ -304,7 +337,7 @@ impl EthEventHandler {
                 let resolved = if let Ok(resolved) = result {
                     resolved
                 } else {
-                    event_count += 1;
+                    event_counter.unresolved();
                     if let SkipEvent::OnUnresolvedAddress = skip_event {
                         // Skip event
                         continue;
@@ -319,7 +352,7 @@ impl EthEventHandler {
                     let matched = spec.matches(&resolved, &entries)?;
                     tracing::debug!(
                         "Event {} {}match filter topics",
-                        event_count,
+                        event_counter.0,
                         if matched { "" } else { "do not " }
                     );
                     matched
@@ -340,21 +373,20 @@ impl EthEventHandler {
                         })
                         .collect();
 
+                    if collected_events.len() >= ctx.eth_event_handler.max_filter_results {
+                        bail!("filter matches too many events, try a more restricted filter");
+                    }
                     let ce = CollectedEvent {
                         entries,
                         emitter_addr: resolved,
-                        event_idx: event_count,
+                        event_idx: event_counter.matched(),
                         reverted: false,
                         height,
                         tipset_key: tipset_key.clone(),
                         msg_idx: i as u64,
                         msg_cid: message.cid(),
                     };
-                    if collected_events.len() >= ctx.eth_event_handler.max_filter_results {
-                        bail!("filter matches too many events, try a more restricted filter");
-                    }
                     collected_events.push(ce);
-                    event_count += 1;
                 }
             }
         }
@@ -373,11 +405,17 @@ impl EthEventHandler {
             ParsedFilterTipsets::Hash(block_hash) => {
                 let tipset = get_tipset_from_hash(ctx.chain_store(), block_hash)?;
                 let tipset = Arc::new(tipset);
+                if let Some(events) = self.indexed_tipset_events(&tipset, pf, &skip_event)? {
+                    return Ok(events);
+                }
                 Self::collect_events(ctx, &tipset, Some(pf), skip_event, &mut collected_events)
                     .await?;
             }
             ParsedFilterTipsets::Key(tsk) => {
                 let tipset = Arc::new(Tipset::load_required(ctx.store(), tsk)?);
+                if let Some(events) = self.indexed_tipset_events(&tipset, pf, &skip_event)? {
+                    return Ok(events);
+                }
                 Self::collect_events(ctx, &tipset, Some(pf), skip_event, &mut collected_events)
                     .await?;
             }
@@ -396,6 +434,17 @@ impl EthEventHandler {
                     *range.end()
                 };
 
+                if let Some(event_index) = &self.event_index {
+                    if let Some(events) = event_index.collect_events(
+                        *range.start()..=max_height,
+                        pf,
+                        skip_event.clone(),
+                        self.max_filter_results,
+                    )? {
+                        return Ok(events);
+                    }
+                }
+
                 let max_tipset = ctx.chain_store().chain_index.tipset_by_height(
                     max_height,
                     ctx.chain_store().heaviest_tipset(),
@@ -422,6 +471,23 @@ impl EthEventHandler {
 
         Ok(collected_events)
     }
+
+    fn indexed_tipset_events(
+        &self,
+        tipset: &Tipset,
+        pf: &ParsedFilter,
+        skip_event: &SkipEvent,
+    ) -> anyhow::Result<Option<Vec<CollectedEvent>>> {
+        match &self.event_index {
+            Some(event_index) => event_index.collect_tipset_events(
+                tipset,
+                pf,
+                skip_event.clone(),
+                self.max_filter_results,
+            ),
+            None => Ok(None),
+        }
+    }
 }
 
 impl EthFilterSpec {
@@ -447,26 +513,7 @@ impl EthFilterSpec {
             ParsedFilterTipsets::Range(RangeInclusive::new(min, max))
         };
 
-        let addresses: Vec<_> = self
-            .address
-            .iter()
-            .map(|ea| {
-                ea.to_filecoin_address()
-                    .map_err(|e| anyhow!("invalid address {}", e))
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let keys = if let Some(topics) = &self.topics {
-            keys_to_keys_with_codec(parse_eth_topics(topics)?)
-        } else {
-            HashMap::new()
-        };
-
-        Ok(ParsedFilter {
-            tipsets,
-            addresses,
-            keys,
-        })
+        ParsedFilter::from_eth_filter_spec(tipsets, self)
     }
 }
 
@@ -650,6 +697,34 @@ impl ParsedFilter {
             keys: HashMap::new(),
         }
     }
+    /// Creates a filter for the given tipsets out of the address and topic criteria of
+    /// `filter_spec`. The block range of `filter_spec` is ignored.
+    pub fn from_eth_filter_spec(
+        tipsets: ParsedFilterTipsets,
+        filter_spec: &EthFilterSpec,
+    ) -> anyhow::Result<Self> {
+        let addresses: Vec<_> = filter_spec
+            .address
+            .iter()
+            .map(|ea| {
+                ea.to_filecoin_address()
+                    .map_err(|e| anyhow!("invalid address {}", e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let keys = if let Some(topics) = &filter_spec.topics {
+            keys_to_keys_with_codec(parse_eth_topics(topics)?)
+        } else {
+            HashMap::new()
+        };
+
+        Ok(ParsedFilter {
+            tipsets,
+            addresses,
+            keys,
+        })
+    }
+
     pub fn from_actor_event_filter(
         chain_height: ChainEpoch,
         _max_filter_height_range: ChainEpoch,
@@ -688,12 +763,13 @@ impl ParsedFilter {
     }
 }
 
-impl Matcher for ParsedFilter {
-    fn matches(
+impl ParsedFilter {
+    /// Same as [`Matcher::matches`], with the event entries given as `(key, codec, value)`.
+    pub fn matches_fields(
         &self,
         emitter_addr: &crate::shim::address::Address,
-        entries: &[Entry],
-    ) -> anyhow::Result<bool> {
+        entries: &[(&str, u64, &[u8])],
+    ) -> bool {
         let match_addr = if self.addresses.is_empty() {
             true
         } else {
@@ -704,16 +780,36 @@ impl Matcher for ParsedFilter {
             true
         } else {
             let matched = self.keys.iter().all(|(k, v)| {
-                entries.iter().any(|entry| {
-                    k == entry.key()
+                entries.iter().any(|(key, codec, value)| {
+                    k == key
                         && v.iter()
-                            .any(|aeb| aeb.codec == entry.codec() && &aeb.value == entry.value())
+                            .any(|aeb| aeb.codec == *codec && aeb.value.as_slice() == *value)
                 })
             });
             matched
         };
 
-        Ok(match_addr && match_fields)
+        match_addr && match_fields
+    }
+}
+
+impl Matcher for ParsedFilter {
+    fn matches(
+        &self,
+        emitter_addr: &crate::shim::address::Address,
+        entries: &[Entry],
+    ) -> anyhow::Result<bool> {
+        let fields: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.key().as_str(),
+                    entry.codec(),
+                    entry.value().as_slice(),
+                )
+            })
+            .collect();
+        Ok(self.matches_fields(emitter_addr, &fields))
     }
 }
 
@@ -0,0 +1,261 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! # Ethereum Subscriptions
+//!
+//! Implements `eth_subscribe` and `eth_unsubscribe` over WebSocket, following the
+//! [Ethereum pub-sub specification](https://geth.ethereum.org/docs/interacting-with-geth/rpc/pubsub).
+//!
+//! Unlike `Filecoin.ChainNotify`, notifications are sent with the Ethereum layout:
+//! ```text
+//! { "jsonrpc": "2.0", "method": "eth_subscription", "params": { "subscription": <id>, "result": <item> } }
+//! ```
+//!
+//! ## Subscription Types:
+//!
+//! - **newHeads**: Sends a block (with transaction hashes) for each tipset applied to the chain.
+//! - **logs**: Sends the logs matching the optional `address` and `topics` criteria. Logs of a tipset
+//!   are sent once its child is applied, as events only exist after the execution of the tipset messages.
+//! - **newPendingTransactions**: Sends the hash of each message added to the message pool.
+
+use super::filter::{ParsedFilter, ParsedFilterTipsets, SkipEvent};
+use super::types::{EthAddressList, EthFilterSpec, EthTopicSpec};
+use super::{
+    block_from_filecoin_tipset, eth_filter_logs_from_events, eth_tx_hash_from_signed_message,
+};
+use crate::blocks::Tipset;
+use crate::chain::HeadChange;
+use crate::rpc::error::ServerError;
+use crate::rpc::{Ctx, RPCState};
+use fvm_ipld_blockstore::Blockstore;
+use jsonrpsee::core::{traits::IdProvider, SubscriptionResult};
+use jsonrpsee::server::{RegisterMethodError, RpcModule};
+use jsonrpsee::types::{Params, SubscriptionId};
+use jsonrpsee::{PendingSubscriptionSink, SubscriptionMessage, SubscriptionSink};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+pub const ETH_SUBSCRIBE: &str = "Filecoin.EthSubscribe";
+pub const ETH_SUBSCRIBE_ALIAS: &str = "eth_subscribe";
+pub const ETH_UNSUBSCRIBE: &str = "Filecoin.EthUnsubscribe";
+pub const ETH_UNSUBSCRIBE_ALIAS: &str = "eth_unsubscribe";
+pub const ETH_SUBSCRIPTION: &str = "eth_subscription";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EthSubscriptionKind {
+    NewHeads,
+    Logs,
+    NewPendingTransactions,
+}
+
+/// Optional criteria of a `logs` subscription.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EthSubscriptionParams {
+    #[serde(default)]
+    pub address: EthAddressList,
+    #[serde(default)]
+    pub topics: Option<EthTopicSpec>,
+}
+
+impl From<EthSubscriptionParams> for EthFilterSpec {
+    fn from(params: EthSubscriptionParams) -> Self {
+        EthFilterSpec {
+            address: params.address,
+            topics: params.topics,
+            ..Default::default()
+        }
+    }
+}
+
+/// Generates Ethereum-style subscription identifiers, i.e., random 32-byte hex strings.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EthSubscriptionIdProvider;
+
+impl IdProvider for EthSubscriptionIdProvider {
+    fn next_id(&self) -> SubscriptionId<'static> {
+        let bytes = rand::random::<[u8; 32]>();
+        SubscriptionId::Str(format!("0x{}", hex::encode(bytes)).into())
+    }
+}
+
+/// Registers `eth_subscribe` and `eth_unsubscribe`, along with their `Filecoin.` prefixed names.
+pub(crate) fn register_eth_subscriptions<DB>(
+    module: &mut RpcModule<RPCState<DB>>,
+) -> Result<(), RegisterMethodError>
+where
+    DB: Blockstore + Send + Sync + 'static,
+{
+    module.register_subscription(
+        ETH_SUBSCRIBE,
+        ETH_SUBSCRIPTION,
+        ETH_UNSUBSCRIBE,
+        |params, pending, ctx, _| eth_subscribe(params, pending, ctx),
+    )?;
+    module.register_alias(ETH_SUBSCRIBE_ALIAS, ETH_SUBSCRIBE)?;
+    module.register_alias(ETH_UNSUBSCRIBE_ALIAS, ETH_UNSUBSCRIBE)?;
+    Ok(())
+}
+
+fn parse_params(params: &Params) -> Result<(EthSubscriptionKind, EthFilterSpec), ServerError> {
+    let mut seq = params.sequence();
+    let kind: EthSubscriptionKind = seq.next()?;
+    let criteria: Option<EthSubscriptionParams> = seq.optional_next()?;
+    if kind != EthSubscriptionKind::Logs && criteria.is_some() {
+        return Err(ServerError::invalid_params(
+            format!("{kind:?} subscriptions do not accept parameters"),
+            None,
+        ));
+    }
+    Ok((kind, criteria.unwrap_or_default().into()))
+}
+
+async fn eth_subscribe<DB>(
+    params: Params<'static>,
+    pending: PendingSubscriptionSink,
+    ctx: Ctx<DB>,
+) -> SubscriptionResult
+where
+    DB: Blockstore + Send + Sync + 'static,
+{
+    let (kind, filter_spec) = match parse_params(&params) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            pending.reject(e).await;
+            return Ok(());
+        }
+    };
+    // Validate the criteria before accepting the subscription
+    let filter =
+        match ParsedFilter::from_eth_filter_spec(ParsedFilterTipsets::Range(0..=0), &filter_spec) {
+            Ok(filter) => filter,
+            Err(e) => {
+                pending.reject(ServerError::invalid_params(e, None)).await;
+                return Ok(());
+            }
+        };
+
+    let sink = pending.accept().await?;
+    tracing::debug!(
+        "Accepted {kind:?} subscription (id={:?})",
+        sink.subscription_id()
+    );
+    match kind {
+        EthSubscriptionKind::NewHeads => {
+            let mut head_changes = ctx.chain_store().publisher().subscribe();
+            while let Some(HeadChange::Apply(tipset)) = next_item(&mut head_changes, &sink).await {
+                match block_from_filecoin_tipset(ctx.clone(), tipset, false).await {
+                    Ok(block) => send(&sink, &block).await?,
+                    Err(e) => tracing::warn!("Failed to notify new head: {e:#}"),
+                }
+            }
+        }
+        EthSubscriptionKind::Logs => {
+            let mut filter = filter;
+            let mut head_changes = ctx.chain_store().publisher().subscribe();
+            while let Some(HeadChange::Apply(tipset)) = next_item(&mut head_changes, &sink).await {
+                match collect_logs(&ctx, &mut filter, &tipset).await {
+                    Ok(logs) => {
+                        for log in logs {
+                            send(&sink, &log).await?;
+                        }
+                    }
+                    Err(e) => tracing::warn!("Failed to notify logs: {e:#}"),
+                }
+            }
+        }
+        EthSubscriptionKind::NewPendingTransactions => {
+            let eth_chain_id = ctx.chain_config().eth_chain_id;
+            let mut pending_messages = ctx.mpool.subscribe_pending();
+            while let Some(message) = next_item(&mut pending_messages, &sink).await {
+                match eth_tx_hash_from_signed_message(&message, eth_chain_id) {
+                    Ok(hash) => send(&sink, &hash).await?,
+                    Err(e) => tracing::warn!("Failed to notify pending transaction: {e:#}"),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the logs emitted by the execution of the parent of `tipset`.
+async fn collect_logs<DB>(
+    ctx: &Ctx<DB>,
+    filter: &mut ParsedFilter,
+    tipset: &Tipset,
+) -> anyhow::Result<Vec<super::EthLog>>
+where
+    DB: Blockstore + Send + Sync + 'static,
+{
+    filter.tipsets = ParsedFilterTipsets::Key(tipset.parents().clone());
+    let events = ctx
+        .eth_event_handler
+        .get_events_for_parsed_filter(ctx, filter, SkipEvent::OnUnresolvedAddress)
+        .await?;
+    eth_filter_logs_from_events(ctx, &events)
+}
+
+/// Waits for the next item of `receiver`, returning [`None`] once either `receiver` or the
+/// subscription is closed.
+async fn next_item<T: Clone>(
+    receiver: &mut broadcast::Receiver<T>,
+    sink: &SubscriptionSink,
+) -> Option<T> {
+    loop {
+        tokio::select! {
+            item = receiver.recv() => match item {
+                Ok(item) => return Some(item),
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Subscription lagged: skipping {skipped} items");
+                }
+                Err(RecvError::Closed) => return None,
+            },
+            _ = sink.closed() => return None,
+        }
+    }
+}
+
+async fn send(sink: &SubscriptionSink, item: &impl Serialize) -> SubscriptionResult {
+    sink.send(SubscriptionMessage::from_json(item)?).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_params() {
+        let params = Params::new(Some(r#"["newHeads"]"#));
+        let (kind, _) = parse_params(&params).unwrap();
+        assert_eq!(kind, EthSubscriptionKind::NewHeads);
+
+        let params = Params::new(Some(
+            r#"["logs", {"address": "0xff38c072f286e3b20b3954ca9f99c05fbecc64aa", "topics": [["0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925"]]}]"#,
+        ));
+        let (kind, filter_spec) = parse_params(&params).unwrap();
+        assert_eq!(kind, EthSubscriptionKind::Logs);
+        assert_eq!(filter_spec.address.iter().count(), 1);
+        assert!(filter_spec.topics.is_some());
+
+        let params = Params::new(Some(r#"["logs"]"#));
+        let (_, filter_spec) = parse_params(&params).unwrap();
+        assert_eq!(filter_spec.address.iter().count(), 0);
+        assert!(filter_spec.topics.is_none());
+
+        let params = Params::new(Some(r#"["newPendingTransactions", {}]"#));
+        assert!(parse_params(&params).is_err());
+
+        let params = Params::new(Some(r#"["syncing"]"#));
+        assert!(parse_params(&params).is_err());
+    }
+
+    #[test]
+    fn test_subscription_id() {
+        let SubscriptionId::Str(id) = EthSubscriptionIdProvider.next_id() else {
+            panic!("expected a string subscription id");
+        };
+        assert!(id.starts_with("0x"));
+        assert_eq!(id.len(), 2 + 64);
+    }
+}
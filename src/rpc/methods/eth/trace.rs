@@ -1,7 +1,9 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::types::{EthAddress, EthBytes, EthCallTraceAction, EthTrace, TraceAction, TraceResult};
+use super::types::{
+    EthAddress, EthBytes, EthCallTraceAction, EthTrace, GethCallFrame, TraceAction, TraceResult,
+};
 use super::utils::{decode_params, decode_return};
 use super::{
     decode_payload, encode_filecoin_params_as_abi, encode_filecoin_returns_as_abi,
@@ -619,3 +621,119 @@ fn trace_evm_private(
         }
     }
 }
+
+impl From<EthTrace> for GethCallFrame {
+    fn from(trace: EthTrace) -> Self {
+        let (r#type, from, to, value, gas, input) = match trace.action {
+            TraceAction::Call(action) => (
+                action.call_type.to_uppercase(),
+                action.from,
+                action.to,
+                action.value,
+                action.gas,
+                action.input,
+            ),
+            TraceAction::Create(action) => (
+                "CREATE".into(),
+                action.from,
+                None,
+                action.value,
+                action.gas,
+                action.init,
+            ),
+        };
+        let (to, gas_used, output) = match trace.result {
+            TraceResult::Call(result) => (to, result.gas_used, result.output),
+            TraceResult::Create(result) => (result.address, result.gas_used, result.code),
+        };
+        GethCallFrame {
+            r#type,
+            from,
+            to,
+            value: Some(value),
+            gas,
+            gas_used,
+            input,
+            output: (!output.0.is_empty()).then_some(output),
+            error: trace.error,
+            calls: vec![],
+        }
+    }
+}
+
+/// Nests the flat, depth-first traces built by [`build_traces`] into a tree of call frames, as
+/// reported by the Geth `callTracer`. Returns `None` if there are no traces.
+pub fn build_call_frame(traces: Vec<EthTrace>) -> Option<GethCallFrame> {
+    // `stack[i]` is the frame being built at depth `i`.
+    let mut stack: Vec<GethCallFrame> = vec![];
+    let mut root = None;
+    let mut unwind = |stack: &mut Vec<GethCallFrame>, depth: usize| {
+        while stack.len() > depth {
+            let frame = stack.pop().expect("Infallible");
+            match stack.last_mut() {
+                Some(parent) => parent.calls.push(frame),
+                None => {
+                    root.get_or_insert(frame);
+                }
+            }
+        }
+    };
+    for trace in traces {
+        let depth = trace.trace_address.len();
+        unwind(&mut stack, depth);
+        stack.push(trace.into());
+    }
+    unwind(&mut stack, 0);
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::eth::EthCallTraceResult;
+
+    fn call(trace_address: Vec<i64>, to: u64) -> EthTrace {
+        EthTrace {
+            r#type: "call".into(),
+            subtraces: 0,
+            trace_address,
+            action: TraceAction::Call(EthCallTraceAction {
+                call_type: "call".into(),
+                to: Some(EthAddress::from_actor_id(to)),
+                ..Default::default()
+            }),
+            result: TraceResult::Call(EthCallTraceResult::default()),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn build_call_frame_nests_traces() {
+        let frame = build_call_frame(vec![
+            call(vec![], 1),
+            call(vec![0], 2),
+            call(vec![0, 0], 3),
+            call(vec![1], 4),
+        ])
+        .unwrap();
+        let to = |frame: &GethCallFrame| frame.to.clone().unwrap();
+        assert_eq!(frame.r#type, "CALL");
+        assert_eq!(to(&frame), EthAddress::from_actor_id(1));
+        let [first, second] = frame.calls.as_slice() else {
+            panic!("expected two subcalls");
+        };
+        assert_eq!(to(first), EthAddress::from_actor_id(2));
+        assert_eq!(first.calls.len(), 1);
+        assert_eq!(
+            to(first.calls.first().unwrap()),
+            EthAddress::from_actor_id(3)
+        );
+        assert_eq!(to(second), EthAddress::from_actor_id(4));
+        assert!(second.calls.is_empty());
+    }
+
+    #[test]
+    fn build_call_frame_empty() {
+        assert_eq!(build_call_frame(vec![]), None);
+    }
+}
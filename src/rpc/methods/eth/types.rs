@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::*;
+use ahash::HashMap;
 use anyhow::ensure;
 use ipld_core::serde::SerdeError;
 use libsecp256k1::util::FULL_PUBLIC_KEY_SIZE;
@@ -82,6 +83,8 @@ impl GetStorageAtParams {
 
 #[derive(
     PartialEq,
+    Eq,
+    Hash,
     Debug,
     Deserialize,
     Serialize,
@@ -600,6 +603,142 @@ pub struct EthReplayBlockTransactionTrace {
 }
 lotus_json_with_self!(EthReplayBlockTransactionTrace);
 
+#[derive(PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EthTraceResults {
+    pub output: EthBytes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<String>,
+    pub trace: Vec<EthTrace>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vm_trace: Option<String>,
+}
+lotus_json_with_self!(EthTraceResults);
+
+/// Changes applied to an account before simulating a call, see
+/// <https://geth.ethereum.org/docs/interacting-with-geth/rpc/ns-eth#eth-call>.
+#[derive(PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EthAccountOverride {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub balance: Option<EthBigInt>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub nonce: Option<EthUint64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub code: Option<EthBytes>,
+    /// Replaces the whole contract storage.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub state: Option<HashMap<EthHash, EthHash>>,
+    /// Sets the given storage slots, leaving the others untouched.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub state_diff: Option<HashMap<EthHash, EthHash>>,
+}
+lotus_json_with_self!(EthAccountOverride);
+
+pub type EthStateOverride = HashMap<EthAddress, EthAccountOverride>;
+
+/// A bundle of calls simulated by `eth_callMany`.
+#[derive(PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EthCallBundle {
+    pub transactions: Vec<EthCallMessage>,
+}
+lotus_json_with_self!(EthCallBundle);
+
+/// The state `eth_callMany` bundles are simulated on.
+#[derive(PartialEq, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EthSimulationContext {
+    pub block_number: BlockNumberOrHash,
+}
+lotus_json_with_self!(EthSimulationContext);
+
+/// Outcome of a call simulated by `eth_callMany`, either its return value or its error.
+#[derive(PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EthCallManyResult {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub value: Option<EthBytes>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub error: Option<String>,
+}
+lotus_json_with_self!(EthCallManyResult);
+
+/// Built-in tracers supported by `debug_traceTransaction` and `debug_traceCall`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub enum GethDebugBuiltInTracerType {
+    /// Nested call frames, see <https://geth.ethereum.org/docs/developers/evm-tracing/built-in-tracers#call-tracer>.
+    #[serde(rename = "callTracer")]
+    CallTracer,
+    /// Accounts touched by the transaction, see <https://geth.ethereum.org/docs/developers/evm-tracing/built-in-tracers#prestate-tracer>.
+    #[serde(rename = "prestateTracer")]
+    PrestateTracer,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GethTracerConfig {
+    /// Only report the top-level call frame, `callTracer` only.
+    #[serde(default)]
+    pub only_top_call: bool,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GethDebugTracingOptions {
+    /// The opcode logger, used when no tracer is set, is not supported as the FVM does not trace
+    /// EVM opcodes.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub tracer: Option<GethDebugBuiltInTracerType>,
+    #[serde(default)]
+    pub tracer_config: GethTracerConfig,
+    /// Changes applied to the state before the call, `debug_traceCall` only. They are not
+    /// reflected in the `prestateTracer` output.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub state_overrides: Option<EthStateOverride>,
+}
+lotus_json_with_self!(GethDebugTracingOptions);
+
+/// A call frame as reported by the `callTracer`.
+#[derive(PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GethCallFrame {
+    /// One of `CALL`, `STATICCALL`, `DELEGATECALL` or `CREATE`.
+    pub r#type: String,
+    pub from: EthAddress,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub to: Option<EthAddress>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub value: Option<EthBigInt>,
+    pub gas: EthUint64,
+    pub gas_used: EthUint64,
+    pub input: EthBytes,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub output: Option<EthBytes>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub calls: Vec<GethCallFrame>,
+}
+
+/// An account as reported by the `prestateTracer`.
+#[derive(PartialEq, Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GethPrestateAccount {
+    pub balance: EthBigInt,
+    pub nonce: u64,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub code: Option<EthBytes>,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum GethTrace {
+    Call(GethCallFrame),
+    Prestate(HashMap<EthAddress, GethPrestateAccount>),
+}
+lotus_json_with_self!(GethTrace);
+
 // EthTraceFilterCriteria defines the criteria for filtering traces.
 #[derive(Default, Serialize, Deserialize, Debug, Clone, JsonSchema)]
 #[serde(rename_all = "camelCase")]
@@ -176,6 +176,25 @@ pub async fn estimate_gas_premium<DB: Blockstore>(
     Ok(premium)
 }
 
+/// Pretends that a message sent from the key address `from` is signed. This has an influence on
+/// the gas cost. We obviously can't generate a valid signature. Instead, we just fill the
+/// signature with zeros. The validity is not checked.
+pub fn pretend_signed(msg: Message, from: &Address) -> ChainMessage {
+    match from.protocol() {
+        Protocol::Secp256k1 => ChainMessage::Signed(SignedMessage::new_unchecked(
+            msg,
+            Signature::new_secp256k1(vec![0; SECP_SIG_LEN]),
+        )),
+        Protocol::Delegated => ChainMessage::Signed(SignedMessage::new_unchecked(
+            msg,
+            // In Lotus, delegated signatures have the same length as SECP256k1.
+            // This may or may not change in the future.
+            Signature::new(SignatureType::Delegated, vec![0; SECP_SIG_LEN]),
+        )),
+        _ => ChainMessage::Unsigned(msg),
+    }
+}
+
 pub enum GasEstimateGasLimit {}
 impl RpcMethod<2> for GasEstimateGasLimit {
     const NAME: &'static str = "Filecoin.GasEstimateGasLimit";
@@ -222,22 +241,7 @@ impl GasEstimateGasLimit {
             .unwrap_or_default();
 
         let ts = data.mpool.cur_tipset.lock().clone();
-        // Pretend that the message is signed. This has an influence on the gas
-        // cost. We obviously can't generate a valid signature. Instead, we just
-        // fill the signature with zeros. The validity is not checked.
-        let mut chain_msg = match from_a.protocol() {
-            Protocol::Secp256k1 => ChainMessage::Signed(SignedMessage::new_unchecked(
-                msg,
-                Signature::new_secp256k1(vec![0; SECP_SIG_LEN]),
-            )),
-            Protocol::Delegated => ChainMessage::Signed(SignedMessage::new_unchecked(
-                msg,
-                // In Lotus, delegated signatures have the same length as SECP256k1.
-                // This may or may not change in the future.
-                Signature::new(SignatureType::Delegated, vec![0; SECP_SIG_LEN]),
-            )),
-            _ => ChainMessage::Unsigned(msg),
-        };
+        let mut chain_msg = pretend_signed(msg, &from_a);
 
         let (invoc_res, apply_ret) = data
             .state_manager
@@ -304,7 +308,7 @@ where
 {
     if msg.gas_limit == 0 {
         let gl = GasEstimateGasLimit::estimate_gas_limit(data, msg.clone(), &tsk).await?;
-        let gl = gl as f64 * data.mpool.config.gas_limit_overestimation;
+        let gl = gl as f64 * data.mpool.get_config().gas_limit_overestimation;
         msg.set_gas_limit((gl as u64).min(BLOCK_GAS_LIMIT));
     }
     if msg.gas_premium.is_zero() {
@@ -23,6 +23,7 @@ use crate::shim::clock::ChainEpoch;
 use crate::shim::crypto::{Signature, SignatureType};
 
 use crate::shim::sector::PoStProof;
+use crate::state_manager::StateManager;
 use crate::utils::db::CborStoreExt;
 
 use anyhow::{Context as _, Result};
@@ -90,6 +91,12 @@ pub struct BlockMessage {
 
 lotus_json_with_self!(BlockMessage);
 
+impl BlockMessage {
+    pub fn header(&self) -> &CachingBlockHeader {
+        &self.header
+    }
+}
+
 #[derive(Serialize_tuple)]
 struct MessageMeta {
     bls_messages: Cid,
@@ -111,7 +118,6 @@ impl RpcMethod<1> for MinerCreateBlock {
         ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
         (block_template,): Self::Params,
     ) -> Result<Self::Ok, ServerError> {
-        let store = ctx.store();
         let parent_tipset = ctx
             .chain_index()
             .load_required_tipset(&block_template.parents)?;
@@ -119,7 +125,7 @@ impl RpcMethod<1> for MinerCreateBlock {
         let lookback_state = ChainStore::get_lookback_tipset_for_round(
             ctx.chain_index().clone(),
             ctx.chain_config().clone(),
-            parent_tipset.clone(),
+            parent_tipset,
             block_template.epoch,
         )
         .map(|(_, s)| Arc::new(s))?;
@@ -128,98 +134,108 @@ impl RpcMethod<1> for MinerCreateBlock {
             .state_manager
             .get_miner_work_addr(*lookback_state, &block_template.miner)?;
 
-        let parent_weight = weight(store, &parent_tipset)?;
-        //let parent_weight = parent_tipset.weight().to_owned();
-        let parent_base_fee = compute_base_fee(
-            store,
-            &parent_tipset,
-            ctx.chain_config()
-                .height_infos
-                .get(&Height::Smoke)
-                .context("Missing Smoke height")?
-                .epoch,
-        )?;
-        let (state, receipts) = ctx.state_manager.tipset_state(&parent_tipset).await?;
-
-        let network_version = ctx.state_manager.get_network_version(block_template.epoch);
-
-        let mut bls_messages = Vec::new();
-        let mut secpk_messages = Vec::new();
-        let mut bls_msg_cids = Vec::new();
-        let mut secpk_msg_cids = Vec::new();
-        let mut bls_sigs = Vec::new();
-
-        for msg in block_template.messages {
-            match msg.signature().signature_type() {
-                SignatureType::Bls => {
-                    let cid = ctx.store().put_cbor_default(&msg.message)?;
-                    bls_msg_cids.push(cid);
-                    bls_sigs.push(msg.signature);
-                    bls_messages.push(msg.message);
-                }
-                SignatureType::Secp256k1 | SignatureType::Delegated => {
-                    if msg.signature.is_valid_secpk_sig_type(network_version) {
-                        let cid = ctx.store().put_cbor_default(&msg)?;
-                        secpk_msg_cids.push(cid);
-                        secpk_messages.push(msg);
-                    } else {
-                        Err(anyhow::anyhow!(
-                            "unknown sig type: {}",
-                            msg.signature.signature_type()
-                        ))?;
-                    }
-                }
-            }
-        }
-
-        let store = ctx.store();
-        let mut message_array = Amt::<Cid, _>::new(store);
-        for (i, cid) in bls_msg_cids.iter().enumerate() {
-            message_array.set(i as u64, *cid)?;
-        }
-        let bls_msgs_root = message_array.flush()?;
-        let mut message_array = Amt::<Cid, _>::new(store);
-        for (i, cid) in secpk_msg_cids.iter().enumerate() {
-            message_array.set(i as u64, *cid)?;
-        }
-        let secpk_msgs_root = message_array.flush()?;
-
-        let message_meta_cid = store.put_cbor_default(&MessageMeta {
-            bls_messages: bls_msgs_root,
-            secpk_messages: secpk_msgs_root,
-        })?;
-
-        let bls_aggregate = aggregate_from_bls_signatures(bls_sigs)?;
-
-        let mut block_header = RawBlockHeader {
-            miner_address: block_template.miner,
-            ticket: block_template.ticket.into(),
-            election_proof: block_template.eproof.into(),
-            beacon_entries: block_template.beacon_values,
-            winning_post_proof: block_template.winning_post_proof,
-            parents: block_template.parents,
-            weight: parent_weight,
-            epoch: block_template.epoch,
-            state_root: state,
-            message_receipts: receipts,
-            messages: message_meta_cid,
-            bls_aggregate: bls_aggregate.into(),
-            timestamp: block_template.timestamp,
-            signature: None,
-            fork_signal: Default::default(),
-            parent_base_fee,
-        };
-
+        let mut block = create_unsigned_block(&ctx.state_manager, block_template).await?;
+        let mut block_header = block.header.into_raw();
         block_header.signature = sign_block_header(&block_header, &worker, ctx.keystore.clone())
             .await?
             .into();
+        block.header = CachingBlockHeader::from(block_header);
 
-        Ok(BlockMessage {
-            header: CachingBlockHeader::from(block_header),
-            bls_messages: bls_msg_cids,
-            secpk_messages: secpk_msg_cids,
-        })
+        Ok(block)
+    }
+}
+
+/// Fills a block template without signing it, storing the messages of the block in the
+/// blockstore.
+pub async fn create_unsigned_block<DB: Blockstore + Send + Sync + 'static>(
+    state_manager: &Arc<StateManager<DB>>,
+    block_template: BlockTemplate,
+) -> anyhow::Result<BlockMessage> {
+    let store = state_manager.blockstore();
+    let parent_tipset = state_manager
+        .chain_store()
+        .chain_index
+        .load_required_tipset(&block_template.parents)?;
+
+    let parent_weight = weight(store, &parent_tipset)?;
+    let parent_base_fee = compute_base_fee(
+        store,
+        &parent_tipset,
+        state_manager
+            .chain_config()
+            .height_infos
+            .get(&Height::Smoke)
+            .context("Missing Smoke height")?
+            .epoch,
+    )?;
+    let (state, receipts) = state_manager.tipset_state(&parent_tipset).await?;
+
+    let network_version = state_manager.get_network_version(block_template.epoch);
+
+    let mut bls_msg_cids = Vec::new();
+    let mut secpk_msg_cids = Vec::new();
+    let mut bls_sigs = Vec::new();
+
+    for msg in block_template.messages {
+        match msg.signature().signature_type() {
+            SignatureType::Bls => {
+                let cid = store.put_cbor_default(&msg.message)?;
+                bls_msg_cids.push(cid);
+                bls_sigs.push(msg.signature);
+            }
+            SignatureType::Secp256k1 | SignatureType::Delegated => {
+                if msg.signature.is_valid_secpk_sig_type(network_version) {
+                    let cid = store.put_cbor_default(&msg)?;
+                    secpk_msg_cids.push(cid);
+                } else {
+                    anyhow::bail!("unknown sig type: {}", msg.signature.signature_type());
+                }
+            }
+        }
+    }
+
+    let mut message_array = Amt::<Cid, _>::new(store);
+    for (i, cid) in bls_msg_cids.iter().enumerate() {
+        message_array.set(i as u64, *cid)?;
     }
+    let bls_msgs_root = message_array.flush()?;
+    let mut message_array = Amt::<Cid, _>::new(store);
+    for (i, cid) in secpk_msg_cids.iter().enumerate() {
+        message_array.set(i as u64, *cid)?;
+    }
+    let secpk_msgs_root = message_array.flush()?;
+
+    let message_meta_cid = store.put_cbor_default(&MessageMeta {
+        bls_messages: bls_msgs_root,
+        secpk_messages: secpk_msgs_root,
+    })?;
+
+    let bls_aggregate = aggregate_from_bls_signatures(bls_sigs)?;
+
+    let block_header = RawBlockHeader {
+        miner_address: block_template.miner,
+        ticket: block_template.ticket.into(),
+        election_proof: block_template.eproof.into(),
+        beacon_entries: block_template.beacon_values,
+        winning_post_proof: block_template.winning_post_proof,
+        parents: block_template.parents,
+        weight: parent_weight,
+        epoch: block_template.epoch,
+        state_root: state,
+        message_receipts: receipts,
+        messages: message_meta_cid,
+        bls_aggregate: bls_aggregate.into(),
+        timestamp: block_template.timestamp,
+        signature: None,
+        fork_signal: Default::default(),
+        parent_base_fee,
+    };
+
+    Ok(BlockMessage {
+        header: CachingBlockHeader::from(block_header),
+        bls_messages: bls_msg_cids,
+        secpk_messages: secpk_msg_cids,
+    })
 }
 
 async fn sign_block_header(
@@ -293,3 +309,50 @@ impl RpcMethod<3> for MinerGetBaseInfo {
             .await?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::VRFProof;
+    use crate::chain_sync::block_producer::tests::SoloDevnet;
+    use crate::genesis::test_utils::SOLO_MINER;
+
+    #[tokio::test]
+    async fn create_block_signs_the_unsigned_block() {
+        let devnet = SoloDevnet::new();
+        let genesis = devnet.ctx.chain_store().genesis_block_header().clone();
+        let template = BlockTemplate {
+            miner: SOLO_MINER,
+            parents: devnet.ctx.chain_store().genesis_tipset().key().clone(),
+            ticket: Ticket::new(VRFProof::new(vec![1; 32])),
+            eproof: ElectionProof {
+                win_count: 1,
+                vrfproof: VRFProof::new(vec![2; 32]),
+            },
+            beacon_values: genesis.beacon_entries.clone(),
+            messages: vec![devnet.transfer(0)],
+            epoch: 1,
+            timestamp: genesis.timestamp + devnet.ctx.chain_config().block_delay_secs as u64,
+            winning_post_proof: vec![],
+        };
+
+        let unsigned = create_unsigned_block(&devnet.ctx.state_manager, template.clone())
+            .await
+            .unwrap();
+        assert!(unsigned.header.signature.is_none());
+        assert_eq!(unsigned.secpk_messages.len(), 1);
+
+        let signed = MinerCreateBlock::handle(devnet.ctx.clone(), (template,))
+            .await
+            .unwrap();
+        signed
+            .header
+            .verify_signature_against(&devnet.worker.address)
+            .unwrap();
+        assert_eq!(signed.bls_messages, unsigned.bls_messages);
+        assert_eq!(signed.secpk_messages, unsigned.secpk_messages);
+        let mut header = signed.header.into_raw();
+        header.signature = None;
+        assert_eq!(header, unsigned.header.into_raw());
+    }
+}
@@ -2,18 +2,27 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use super::gas::estimate_message_gas;
-use crate::lotus_json::NotNullVec;
-use crate::message::SignedMessage;
+use crate::key_management::KeyStore;
+use crate::lotus_json::{lotus_json_with_self, LotusJson, NotNullVec};
+use crate::message::{Message as _, SignedMessage};
+use crate::message_pool::{min_rbf_premium, MpoolConfig};
 use crate::rpc::error::ServerError;
 use crate::rpc::types::{ApiTipsetKey, MessageSendSpec};
 use crate::rpc::{ApiPaths, Ctx, Permission, RpcMethod};
 use crate::shim::{
     address::{Address, Protocol},
+    clock::ChainEpoch,
+    crypto::SignatureType,
+    econ::TokenAmount,
     message::Message,
 };
 use ahash::{HashSet, HashSetExt as _};
 use cid::Cid;
 use fvm_ipld_blockstore::Blockstore;
+use itertools::Itertools as _;
+use num::Zero as _;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 /// Gets next nonce for the specified sender.
 pub enum MpoolGetNonce {}
@@ -36,6 +45,51 @@ impl RpcMethod<1> for MpoolGetNonce {
     }
 }
 
+/// Returns the message pool configuration
+pub enum MpoolGetConfig {}
+impl RpcMethod<0> for MpoolGetConfig {
+    const NAME: &'static str = "Filecoin.MpoolGetConfig";
+    const PARAM_NAMES: [&'static str; 0] = [];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Admin;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Returns the current configuration of the message pool.");
+
+    type Params = ();
+    type Ok = MpoolConfig;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        Ok(ctx.mpool.get_config())
+    }
+}
+
+/// Validates, persists and applies a new message pool configuration
+pub enum MpoolSetConfig {}
+impl RpcMethod<1> for MpoolSetConfig {
+    const NAME: &'static str = "Filecoin.MpoolSetConfig";
+    const PARAM_NAMES: [&'static str; 1] = ["config"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Admin;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Sets the configuration of the message pool. The configuration is persisted and takes effect immediately.",
+    );
+
+    type Params = (MpoolConfig,);
+    type Ok = ();
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (config,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        ctx.mpool
+            .set_config(ctx.settings_store.as_ref(), config)
+            .map_err(|e| ServerError::invalid_params(e.to_string(), None))
+    }
+}
+
 /// Return `Vec` of pending messages in `mpool`
 pub enum MpoolPending {}
 impl RpcMethod<1> for MpoolPending {
@@ -271,20 +325,260 @@ impl RpcMethod<2> for MpoolPushMessage {
         }
         let nonce = ctx.mpool.get_sequence(&from)?;
         message.sequence = nonce;
-        let key = crate::key_management::Key::try_from(crate::key_management::try_find(
-            &key_addr,
-            &mut keystore,
-        )?)?;
-        let sig = crate::key_management::sign(
-            *key.key_info.key_type(),
-            key.key_info.private_key(),
-            message.cid().to_bytes().as_slice(),
-        )?;
-
-        let smsg = SignedMessage::new_from_parts(message, sig)?;
+        let smsg = sign_message(&mut keystore, &key_addr, message)?;
+
+        ctx.mpool.as_ref().push(smsg.clone()).await?;
+
+        Ok(smsg)
+    }
+}
+
+/// Re-prices a pending message, replacing it in `mpool` with a copy paying higher gas fees
+pub enum MpoolReplace {}
+impl RpcMethod<3> for MpoolReplace {
+    const NAME: &'static str = "Forest.MpoolReplace";
+    const PARAM_NAMES: [&'static str; 3] = ["from", "nonce", "replaceSpec"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Replaces the pending message with the given sender and nonce by a copy paying higher gas fees, signs it, and pushes it to the mempool.",
+    );
+
+    type Params = (Address, u64, Option<MpoolReplaceSpec>);
+    type Ok = SignedMessage;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (from, nonce, spec): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let MpoolReplaceSpec {
+            gas_premium,
+            gas_fee_cap,
+            gas_limit,
+        } = spec.unwrap_or_default();
+        let pending = ctx
+            .mpool
+            .pending_for(&from)
+            .unwrap_or_default()
+            .into_iter()
+            .find(|smsg| smsg.sequence() == nonce)
+            .ok_or_else(|| {
+                ServerError::invalid_params(
+                    format!("no pending message from {from} with nonce {nonce}"),
+                    None,
+                )
+            })?;
+        if pending.signature().signature_type() == SignatureType::Delegated {
+            return Err(ServerError::invalid_params(
+                "replacing Ethereum transactions is not supported, resend the transaction with a higher priority fee instead",
+                None,
+            ));
+        }
+
+        let min_premium = min_rbf_premium(
+            &pending.message().gas_premium,
+            ctx.mpool.get_config().replace_by_fee_ratio,
+        );
+        let mut message = pending.message().clone();
+        if let Some(gas_limit) = gas_limit {
+            message.gas_limit = gas_limit;
+        }
+        if gas_premium.is_none() || gas_fee_cap.is_none() {
+            let estimated = estimate_message_gas(
+                &ctx,
+                Message {
+                    gas_premium: TokenAmount::zero(),
+                    gas_fee_cap: TokenAmount::zero(),
+                    ..message.clone()
+                },
+                None,
+                Default::default(),
+            )
+            .await?;
+            message.gas_premium = estimated.gas_premium.max(min_premium.clone());
+            message.gas_fee_cap = estimated.gas_fee_cap.max(message.gas_premium.clone());
+        }
+        if let Some(gas_premium) = gas_premium {
+            message.gas_premium = gas_premium;
+        }
+        if let Some(gas_fee_cap) = gas_fee_cap {
+            message.gas_fee_cap = gas_fee_cap;
+        }
+        if message.gas_premium < min_premium {
+            return Err(ServerError::invalid_params(
+                format!(
+                    "gas premium must be at least {min_premium} to replace message {}",
+                    pending.cid()
+                ),
+                None,
+            ));
+        }
+        if message.gas_premium > message.gas_fee_cap {
+            return Err(ServerError::invalid_params(
+                "gas premium is greater than gas fee cap",
+                None,
+            ));
+        }
+
+        let key_addr = ctx
+            .state_manager
+            .resolve_to_key_addr(&message.from, &ctx.chain_store().heaviest_tipset())
+            .await?;
+        let smsg = {
+            let mut keystore = ctx.keystore.as_ref().write().await;
+            sign_message(&mut keystore, &key_addr, message)?
+        };
 
         ctx.mpool.as_ref().push(smsg.clone()).await?;
 
         Ok(smsg)
     }
 }
+
+/// Reports nonce gaps and long-pending messages of the senders of local messages
+pub enum MpoolFindStuck {}
+impl RpcMethod<1> for MpoolFindStuck {
+    const NAME: &'static str = "Forest.MpoolFindStuck";
+    const PARAM_NAMES: [&'static str; 1] = ["minAge"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns, for each address that pushed messages through this node, the nonce gaps in its pending messages and the messages pending for at least the given number of epochs.",
+    );
+
+    type Params = (ChainEpoch,);
+    type Ok = Vec<StuckMessages>;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (min_age,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let head = ctx.chain_store().heaviest_tipset().epoch();
+        let mut report = vec![];
+        for address in ctx.mpool.local_addrs() {
+            let Some(pending) = ctx.mpool.pending_for(&address) else {
+                continue;
+            };
+            let state_nonce = ctx.mpool.get_state_sequence_at_head(&address)?;
+            let nonce_gaps =
+                find_nonce_gaps(state_nonce, pending.iter().map(|smsg| smsg.sequence()));
+            let long_pending = pending
+                .iter()
+                .filter_map(|smsg| {
+                    let pending_since = ctx.mpool.pending_since(smsg)?;
+                    (head - pending_since >= min_age).then(|| PendingMessageInfo {
+                        cid: smsg.cid(),
+                        nonce: smsg.sequence(),
+                        pending_since,
+                        gas_premium: smsg.message().gas_premium.clone(),
+                        gas_fee_cap: smsg.message().gas_fee_cap.clone(),
+                    })
+                })
+                .collect_vec();
+            if !nonce_gaps.is_empty() || !long_pending.is_empty() {
+                report.push(StuckMessages {
+                    address,
+                    state_nonce,
+                    nonce_gaps,
+                    long_pending,
+                });
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct MpoolReplaceSpec {
+    /// Gas premium of the replacement. Defaults to the estimated premium, raised to the minimum
+    /// required to replace the pending message.
+    #[schemars(with = "LotusJson<Option<TokenAmount>>")]
+    #[serde(with = "crate::lotus_json", default)]
+    pub gas_premium: Option<TokenAmount>,
+    /// Gas fee cap of the replacement. Defaults to the estimated fee cap, raised to the premium.
+    #[schemars(with = "LotusJson<Option<TokenAmount>>")]
+    #[serde(with = "crate::lotus_json", default)]
+    pub gas_fee_cap: Option<TokenAmount>,
+    /// Gas limit of the replacement. Defaults to the gas limit of the pending message.
+    #[serde(default)]
+    pub gas_limit: Option<u64>,
+}
+
+lotus_json_with_self!(MpoolReplaceSpec);
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct StuckMessages {
+    #[schemars(with = "LotusJson<Address>")]
+    #[serde(with = "crate::lotus_json")]
+    pub address: Address,
+    /// Nonce of the next message to be executed.
+    pub state_nonce: u64,
+    /// Nonces missing from the pending messages. The messages after a gap cannot be included in
+    /// a block until it is filled.
+    pub nonce_gaps: Vec<u64>,
+    /// Messages pending for at least the requested number of epochs.
+    pub long_pending: Vec<PendingMessageInfo>,
+}
+
+lotus_json_with_self!(StuckMessages);
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct PendingMessageInfo {
+    #[schemars(with = "LotusJson<Cid>")]
+    #[serde(rename = "CID", with = "crate::lotus_json")]
+    pub cid: Cid,
+    pub nonce: u64,
+    /// Epoch at which the message was added to the mempool.
+    pub pending_since: ChainEpoch,
+    #[schemars(with = "LotusJson<TokenAmount>")]
+    #[serde(with = "crate::lotus_json")]
+    pub gas_premium: TokenAmount,
+    #[schemars(with = "LotusJson<TokenAmount>")]
+    #[serde(with = "crate::lotus_json")]
+    pub gas_fee_cap: TokenAmount,
+}
+
+lotus_json_with_self!(PendingMessageInfo);
+
+fn sign_message(
+    keystore: &mut KeyStore,
+    key_addr: &Address,
+    message: Message,
+) -> anyhow::Result<SignedMessage> {
+    let key =
+        crate::key_management::Key::try_from(crate::key_management::try_find(key_addr, keystore)?)?;
+    let sig = crate::key_management::sign(
+        *key.key_info.key_type(),
+        key.key_info.private_key(),
+        message.cid().to_bytes().as_slice(),
+    )?;
+    SignedMessage::new_from_parts(message, sig)
+}
+
+/// Returns the nonces missing between the state nonce and the highest pending nonce.
+fn find_nonce_gaps(state_nonce: u64, pending: impl IntoIterator<Item = u64>) -> Vec<u64> {
+    let pending = pending.into_iter().collect::<HashSet<_>>();
+    let Some(highest) = pending.iter().copied().max() else {
+        return vec![];
+    };
+    (state_nonce..highest)
+        .filter(|nonce| !pending.contains(nonce))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_nonce_gaps() {
+        assert!(find_nonce_gaps(3, []).is_empty());
+        assert!(find_nonce_gaps(3, [3, 4, 5]).is_empty());
+        assert_eq!(find_nonce_gaps(3, [5, 7]), vec![3, 4, 6]);
+        // Messages whose nonce is already executed do not create gaps
+        assert_eq!(find_nonce_gaps(3, [1, 4]), vec![3]);
+    }
+}
@@ -5,11 +5,20 @@ use crate::rpc::error::ServerError;
 use crate::rpc::types::ApiTipsetKey;
 use crate::rpc::types::*;
 use crate::rpc::{ApiPaths, Ctx, Permission, RpcMethod};
-use crate::shim::actors::multisig;
+use crate::shim::actors::init::{self, params::ExecParams};
 use crate::shim::actors::multisig::ext::MultisigExt;
+use crate::shim::actors::multisig::{
+    self,
+    params::{ConstructorParams, ProposalHashData, ProposeParams, SwapSignerParams, TxnIDParams},
+};
 use crate::shim::actors::MultisigActorStateLoad as _;
-use crate::shim::{address::Address, econ::TokenAmount};
+use crate::shim::machine::BuiltinActor;
+use crate::shim::message::{Message, MethodNum};
+use crate::shim::{address::Address, clock::ChainEpoch, econ::TokenAmount};
+use crate::utils::encoding::blake2b_256;
 use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::RawBytes;
+use num::Zero as _;
 use num_bigint::BigInt;
 
 pub enum MsigGetAvailableBalance {}
@@ -133,3 +142,442 @@ impl RpcMethod<2> for MsigGetVestingSchedule {
         Ok(ms.get_vesting_schedule()?)
     }
 }
+
+pub enum MsigCreate {}
+impl RpcMethod<6> for MsigCreate {
+    const NAME: &'static str = "Filecoin.MsigCreate";
+    const PARAM_NAMES: [&'static str; 6] = [
+        "required",
+        "signers",
+        "unlock_duration",
+        "value",
+        "from",
+        "gas_price",
+    ];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns a message creating a multisig wallet with the given signers, number of required approvals and vesting duration, funded with the given value.",
+    );
+
+    type Params = (
+        u64,
+        Vec<Address>,
+        ChainEpoch,
+        TokenAmount,
+        Address,
+        TokenAmount,
+    );
+    type Ok = MessagePrototype;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (required, signers, unlock_duration, value, from, gas_price): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        if signers.is_empty() {
+            return Err(ServerError::invalid_params(
+                "must provide at least one signer",
+                None,
+            ));
+        }
+        if required > signers.len() as u64 {
+            return Err(ServerError::invalid_params(
+                "cannot require more approvals than there are signers",
+                None,
+            ));
+        }
+        // Lotus requires all the signers to approve when no threshold is given
+        let num_approvals_threshold = if required == 0 {
+            signers.len() as u64
+        } else {
+            required
+        };
+        let constructor_params = RawBytes::serialize(ConstructorParams {
+            signers,
+            num_approvals_threshold,
+            unlock_duration,
+            start_epoch: 0,
+        })?;
+        let code_cid = ctx
+            .state_manager
+            .get_builtin_actor_code(&ctx.chain_store().heaviest_tipset(), BuiltinActor::Multisig)?;
+        let params = RawBytes::serialize(ExecParams {
+            code_cid,
+            constructor_params,
+        })?;
+        Ok(MessagePrototype {
+            message: Message {
+                from,
+                to: Address::INIT_ACTOR,
+                method_num: init::Method::Exec as MethodNum,
+                params,
+                value,
+                gas_premium: gas_price,
+                ..Default::default()
+            },
+            valid_nonce: false,
+        })
+    }
+}
+
+pub enum MsigPropose {}
+impl RpcMethod<6> for MsigPropose {
+    const NAME: &'static str = "Filecoin.MsigPropose";
+    const PARAM_NAMES: [&'static str; 6] = ["msig", "to", "value", "from", "method", "params"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns a message proposing a multisig transaction sending the given value and invoking the given method on the recipient.",
+    );
+
+    type Params = (Address, Address, TokenAmount, Address, MethodNum, Vec<u8>);
+    type Ok = MessagePrototype;
+
+    async fn handle(
+        _: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (msig, to, value, from, method, params): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        if value.is_negative() {
+            return Err(ServerError::invalid_params(
+                "must provide a non-negative value",
+                None,
+            ));
+        }
+        Ok(MessagePrototype {
+            message: propose_message(msig, from, to, value, method, RawBytes::new(params))?,
+            valid_nonce: false,
+        })
+    }
+}
+
+pub enum MsigApprove {}
+impl RpcMethod<3> for MsigApprove {
+    const NAME: &'static str = "Filecoin.MsigApprove";
+    const PARAM_NAMES: [&'static str; 3] = ["msig", "txn_id", "from"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Returns a message approving a pending multisig transaction.");
+
+    type Params = (Address, u64, Address);
+    type Ok = MessagePrototype;
+
+    async fn handle(
+        _: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (msig, txn_id, from): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        Ok(MessagePrototype {
+            message: txn_message(TxnOperation::Approve, msig, from, txn_id, vec![])?,
+            valid_nonce: false,
+        })
+    }
+}
+
+pub enum MsigCancel {}
+impl RpcMethod<3> for MsigCancel {
+    const NAME: &'static str = "Filecoin.MsigCancel";
+    const PARAM_NAMES: [&'static str; 3] = ["msig", "txn_id", "from"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns a message cancelling a pending multisig transaction. Only its proposer can cancel it.",
+    );
+
+    type Params = (Address, u64, Address);
+    type Ok = MessagePrototype;
+
+    async fn handle(
+        _: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (msig, txn_id, from): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        Ok(MessagePrototype {
+            message: txn_message(TxnOperation::Cancel, msig, from, txn_id, vec![])?,
+            valid_nonce: false,
+        })
+    }
+}
+
+pub enum MsigSwapPropose {}
+impl RpcMethod<4> for MsigSwapPropose {
+    const NAME: &'static str = "Filecoin.MsigSwapPropose";
+    const PARAM_NAMES: [&'static str; 4] = ["msig", "from", "old_signer", "new_signer"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Returns a message proposing to replace a signer of a multisig wallet.");
+
+    type Params = (Address, Address, Address, Address);
+    type Ok = MessagePrototype;
+
+    async fn handle(
+        _: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (msig, from, old_signer, new_signer): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let params = swap_signer_params(old_signer, new_signer)?;
+        Ok(MessagePrototype {
+            message: propose_message(
+                msig,
+                from,
+                msig,
+                TokenAmount::zero(),
+                multisig::Method::SwapSigner as MethodNum,
+                params,
+            )?,
+            valid_nonce: false,
+        })
+    }
+}
+
+pub enum MsigSwapApprove {}
+impl RpcMethod<6> for MsigSwapApprove {
+    const NAME: &'static str = "Filecoin.MsigSwapApprove";
+    const PARAM_NAMES: [&'static str; 6] = [
+        "msig",
+        "from",
+        "txn_id",
+        "proposer",
+        "old_signer",
+        "new_signer",
+    ];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns a message approving a pending multisig transaction replacing a signer, failing on chain if the transaction does not match.",
+    );
+
+    type Params = (Address, Address, u64, Address, Address, Address);
+    type Ok = MessagePrototype;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (msig, from, txn_id, proposer, old_signer, new_signer): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let proposer = lookup_proposer_id(&ctx, &proposer)?;
+        let hash = swap_proposal_hash(msig, proposer, old_signer, new_signer)?;
+        Ok(MessagePrototype {
+            message: txn_message(TxnOperation::Approve, msig, from, txn_id, hash)?,
+            valid_nonce: false,
+        })
+    }
+}
+
+pub enum MsigSwapCancel {}
+impl RpcMethod<5> for MsigSwapCancel {
+    const NAME: &'static str = "Filecoin.MsigSwapCancel";
+    const PARAM_NAMES: [&'static str; 5] = ["msig", "from", "txn_id", "old_signer", "new_signer"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns a message cancelling a pending multisig transaction replacing a signer, failing on chain if the transaction does not match.",
+    );
+
+    type Params = (Address, Address, u64, Address, Address);
+    type Ok = MessagePrototype;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (msig, from, txn_id, old_signer, new_signer): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        // Only the proposer of a transaction can cancel it
+        let proposer = lookup_proposer_id(&ctx, &from)?;
+        let hash = swap_proposal_hash(msig, proposer, old_signer, new_signer)?;
+        Ok(MessagePrototype {
+            message: txn_message(TxnOperation::Cancel, msig, from, txn_id, hash)?,
+            valid_nonce: false,
+        })
+    }
+}
+
+/// Resolves the ID address of the proposer of a transaction at the heaviest tipset. The multisig
+/// actor hashes proposals with the ID address of their proposer, see [`proposal_hash`].
+fn lookup_proposer_id(
+    ctx: &Ctx<impl Blockstore + Send + Sync + 'static>,
+    proposer: &Address,
+) -> anyhow::Result<Address> {
+    Ok(ctx
+        .state_manager
+        .lookup_required_id(proposer, &ctx.chain_store().heaviest_tipset())?)
+}
+
+/// Operation on a pending multisig transaction.
+#[derive(Debug, Clone, Copy)]
+enum TxnOperation {
+    Approve,
+    Cancel,
+}
+
+fn propose_message(
+    msig: Address,
+    from: Address,
+    to: Address,
+    value: TokenAmount,
+    method: MethodNum,
+    params: RawBytes,
+) -> anyhow::Result<Message> {
+    let params = RawBytes::serialize(ProposeParams {
+        to,
+        value,
+        method,
+        params,
+    })?;
+    Ok(Message {
+        from,
+        to: msig,
+        method_num: multisig::Method::Propose as MethodNum,
+        params,
+        ..Default::default()
+    })
+}
+
+/// Builds a message approving or cancelling the pending transaction `txn_id`. A non-empty
+/// `proposal_hash` makes the operation fail unless the transaction matches it.
+fn txn_message(
+    operation: TxnOperation,
+    msig: Address,
+    from: Address,
+    txn_id: u64,
+    proposal_hash: Vec<u8>,
+) -> anyhow::Result<Message> {
+    let method = match operation {
+        TxnOperation::Approve => multisig::Method::Approve,
+        TxnOperation::Cancel => multisig::Method::Cancel,
+    };
+    let params = RawBytes::serialize(TxnIDParams {
+        id: i64::try_from(txn_id)?,
+        proposal_hash,
+    })?;
+    Ok(Message {
+        from,
+        to: msig,
+        method_num: method as MethodNum,
+        params,
+        ..Default::default()
+    })
+}
+
+fn swap_signer_params(old_signer: Address, new_signer: Address) -> anyhow::Result<RawBytes> {
+    Ok(RawBytes::serialize(SwapSignerParams {
+        from: old_signer,
+        to: new_signer,
+    })?)
+}
+
+/// Hash of a transaction proposed by `proposer` replacing `old_signer` with `new_signer`.
+fn swap_proposal_hash(
+    msig: Address,
+    proposer: Address,
+    old_signer: Address,
+    new_signer: Address,
+) -> anyhow::Result<Vec<u8>> {
+    proposal_hash(
+        proposer,
+        msig,
+        TokenAmount::zero(),
+        multisig::Method::SwapSigner as MethodNum,
+        swap_signer_params(old_signer, new_signer)?,
+    )
+}
+
+/// Computes the hash identifying a proposed transaction, as done by the multisig actor. The
+/// `proposer` must be an ID address.
+fn proposal_hash(
+    proposer: Address,
+    to: Address,
+    value: TokenAmount,
+    method: MethodNum,
+    params: RawBytes,
+) -> anyhow::Result<Vec<u8>> {
+    let data = fvm_ipld_encoding::to_vec(&ProposalHashData {
+        requester: Some(&proposer),
+        to: &to,
+        value: &value,
+        method: &method,
+        params: &params,
+    })?;
+    Ok(blake2b_256(&data).to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_sync::block_producer::tests::SoloDevnet;
+
+    #[test]
+    fn test_proposal_hash() {
+        let msig = Address::new_id(1000);
+        let proposer = Address::new_id(100);
+        let hash =
+            swap_proposal_hash(msig, proposer, Address::new_id(101), Address::new_id(102)).unwrap();
+        assert_eq!(hash.len(), 32);
+        // The hash commits to the requester and the proposed transaction
+        assert_ne!(
+            hash,
+            swap_proposal_hash(msig, msig, Address::new_id(101), Address::new_id(102)).unwrap()
+        );
+        assert_ne!(
+            hash,
+            swap_proposal_hash(msig, proposer, Address::new_id(101), Address::new_id(103)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_txn_message() {
+        let msig = Address::new_id(1000);
+        let from = Address::new_id(100);
+        let message = txn_message(TxnOperation::Cancel, msig, from, 7, vec![]).unwrap();
+        assert_eq!((message.from, message.to), (from, msig));
+        assert_eq!(message.method_num, multisig::Method::Cancel as MethodNum);
+        let params: TxnIDParams = message.params.deserialize().unwrap();
+        assert_eq!(params.id, 7);
+        assert!(params.proposal_hash.is_empty());
+        assert!(txn_message(TxnOperation::Approve, msig, from, u64::MAX, vec![]).is_err());
+    }
+
+    #[test]
+    fn proposal_hash_data_layout_matches_actors() {
+        let (requester, to) = (Address::new_id(100), Address::new_id(1000));
+        let (value, method, params) = (TokenAmount::from_atto(7), 2, RawBytes::new(vec![1, 2, 3]));
+        let (actors_requester, actors_to, actors_value) =
+            (requester.into(), to.into(), value.clone().into());
+        let actors_data = fil_actor_multisig_state::v16::ProposalHashData {
+            requester: Some(&actors_requester),
+            to: &actors_to,
+            value: &actors_value,
+            method: &method,
+            params: &params,
+        };
+        assert_eq!(
+            proposal_hash(requester, to, value, method, params.clone()).unwrap(),
+            blake2b_256(&fvm_ipld_encoding::to_vec(&actors_data).unwrap()).to_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn swap_cancel_hashes_the_proposer_id() {
+        let devnet = SoloDevnet::new();
+        let (msig, old_signer, new_signer) = (
+            Address::new_id(2000),
+            Address::new_id(101),
+            Address::new_id(102),
+        );
+        let from = devnet.account.address;
+        let id = devnet
+            .ctx
+            .state_manager
+            .lookup_required_id(&from, &devnet.ctx.chain_store().heaviest_tipset())
+            .unwrap();
+        assert_ne!(id, from);
+
+        let message =
+            MsigSwapCancel::handle(devnet.ctx.clone(), (msig, from, 3, old_signer, new_signer))
+                .await
+                .unwrap()
+                .message;
+        assert_eq!(message.from, from);
+        let params: TxnIDParams = message.params.deserialize().unwrap();
+        assert_eq!(
+            params.proposal_hash,
+            swap_proposal_hash(msig, id, old_signer, new_signer).unwrap()
+        );
+    }
+}
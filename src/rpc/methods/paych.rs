@@ -0,0 +1,927 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Payment channel API.
+//!
+//! Outbound channels are created and funded by this node, which hands out vouchers to the payee.
+//! Inbound channels are funded by another party, and the vouchers received from it are submitted
+//! by this node to redeem the funds. See [`crate::paychmgr`] for the local bookkeeping.
+
+use crate::lotus_json::{lotus_json_with_self, LotusJson};
+use crate::message::Message as _;
+use crate::paychmgr::{Direction, LockedChannels, PendingFunds, TrackedChannel, VoucherInfo};
+use crate::rpc::error::ServerError;
+use crate::rpc::mpool::MpoolPushMessage;
+use crate::rpc::wallet::WalletSign;
+use crate::rpc::{ApiPaths, Ctx, Permission, RpcMethod};
+use crate::shim::actors::init::{
+    self,
+    params::{ExecParams, ExecReturn},
+};
+use crate::shim::actors::paych::{self, LaneState, SignedVoucher, UpdateChannelStateParams};
+use crate::shim::address::Address;
+use crate::shim::econ::TokenAmount;
+use crate::shim::machine::BuiltinActor;
+use crate::shim::message::{Message, MethodNum, METHOD_SEND};
+use crate::shim::state_tree::ActorState;
+use anyhow::{bail, ensure, Context as _};
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::RawBytes;
+use num::Zero as _;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Number of epochs a payment channel message must be buried under before it is considered final.
+const MESSAGE_CONFIDENCE: i64 = 5;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct PaychGetOpts {
+    /// Only return an existing channel, without creating or funding it.
+    #[serde(default)]
+    pub off_chain: bool,
+}
+
+lotus_json_with_self!(PaychGetOpts);
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChannelInfo {
+    /// Address of the channel, unknown until the creation message has been executed.
+    #[schemars(with = "LotusJson<Option<Address>>")]
+    #[serde(with = "crate::lotus_json")]
+    pub channel: Option<Address>,
+    /// Message to wait for with `PaychGetWaitReady` before using the channel or its new funds.
+    #[schemars(with = "LotusJson<Option<Cid>>")]
+    #[serde(with = "crate::lotus_json")]
+    pub wait_sentinel: Option<Cid>,
+}
+
+lotus_json_with_self!(ChannelInfo);
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChannelAvailableFunds {
+    #[schemars(with = "LotusJson<Option<Address>>")]
+    #[serde(with = "crate::lotus_json")]
+    pub channel: Option<Address>,
+    #[schemars(with = "LotusJson<Address>")]
+    #[serde(with = "crate::lotus_json")]
+    pub from: Address,
+    #[schemars(with = "LotusJson<Address>")]
+    #[serde(with = "crate::lotus_json")]
+    pub to: Address,
+    /// Balance of the channel actor.
+    #[schemars(with = "LotusJson<TokenAmount>")]
+    #[serde(with = "crate::lotus_json")]
+    pub confirmed_amt: TokenAmount,
+    /// Amount being added to the channel by messages that have not been executed yet.
+    #[schemars(with = "LotusJson<TokenAmount>")]
+    #[serde(with = "crate::lotus_json")]
+    pub pending_amt: TokenAmount,
+    /// Message to wait for before the pending amount is confirmed.
+    #[schemars(with = "LotusJson<Option<Cid>>")]
+    #[serde(with = "crate::lotus_json")]
+    pub pending_wait_sentinel: Option<Cid>,
+    /// Confirmed amount that is not yet covered by vouchers.
+    #[schemars(with = "LotusJson<TokenAmount>")]
+    #[serde(with = "crate::lotus_json")]
+    pub non_reserved_amt: TokenAmount,
+    /// Amount covered by the vouchers, whether redeemed on chain or only stored locally.
+    #[schemars(with = "LotusJson<TokenAmount>")]
+    // The typo is part of the `Lotus` API.
+    #[serde(rename = "VoucherReedeemedAmt", with = "crate::lotus_json")]
+    pub voucher_redeemed_amt: TokenAmount,
+}
+
+lotus_json_with_self!(ChannelAvailableFunds);
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct VoucherCreateResult {
+    /// The created voucher, if the channel has enough funds to cover it.
+    #[schemars(with = "LotusJson<Option<SignedVoucher>>")]
+    #[serde(with = "crate::lotus_json")]
+    pub voucher: Option<SignedVoucher>,
+    /// Additional funds required in the channel to create the voucher.
+    #[schemars(with = "LotusJson<TokenAmount>")]
+    #[serde(with = "crate::lotus_json")]
+    pub shortfall: TokenAmount,
+}
+
+lotus_json_with_self!(VoucherCreateResult);
+
+pub enum PaychGet {}
+impl RpcMethod<4> for PaychGet {
+    const NAME: &'static str = "Filecoin.PaychGet";
+    const PARAM_NAMES: [&'static str; 4] = ["from", "to", "amount", "opts"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns a payment channel from the given payer to the given payee, creating it if needed, and adds the given amount to it.",
+    );
+
+    type Params = (Address, Address, TokenAmount, Option<PaychGetOpts>);
+    type Ok = ChannelInfo;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (from, to, amount, opts): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let off_chain = opts.unwrap_or_default().off_chain;
+        let mut channels = ctx.paych_store.lock().await?;
+        update_pending_funds(&ctx, &mut channels).await?;
+        let existing = channels.iter().position(|tracked| {
+            tracked.direction == Direction::Outbound
+                && tracked.control == from
+                && tracked.target == to
+                && !tracked.settling
+        });
+        let Some(idx) = existing else {
+            if off_chain {
+                return Err(anyhow::anyhow!("no payment channel from {from} to {to}").into());
+            }
+            let ts = ctx.chain_store().heaviest_tipset();
+            let code_cid = ctx
+                .state_manager
+                .get_builtin_actor_code(&ts, BuiltinActor::PaymentChannel)?;
+            let constructor_params = RawBytes::serialize(paych::ConstructorParams { from, to })?;
+            let params = RawBytes::serialize(ExecParams {
+                code_cid,
+                constructor_params,
+            })?;
+            let create_msg = push_message(
+                &ctx,
+                from,
+                Address::INIT_ACTOR,
+                init::Method::Exec as MethodNum,
+                params,
+                amount.clone(),
+            )
+            .await?;
+            channels.push(TrackedChannel::new_outbound(
+                from,
+                to,
+                PendingFunds {
+                    message: create_msg,
+                    amount,
+                    epoch: ts.epoch(),
+                },
+            ));
+            channels.save()?;
+            return Ok(ChannelInfo {
+                channel: None,
+                wait_sentinel: Some(create_msg),
+            });
+        };
+
+        let tracked = channels
+            .get_mut(idx)
+            .context("channel index out of bounds")?;
+        let Some(channel) = tracked.channel else {
+            if !off_chain && !amount.is_zero() {
+                return Err(anyhow::anyhow!(
+                    "payment channel from {from} to {to} is still being created, wait for message {} before adding funds",
+                    tracked.create_msg.unwrap_or_default()
+                )
+                .into());
+            }
+            return Ok(ChannelInfo {
+                channel: None,
+                wait_sentinel: tracked.create_msg,
+            });
+        };
+        if off_chain || amount.is_zero() {
+            return Ok(ChannelInfo {
+                channel: Some(channel),
+                wait_sentinel: None,
+            });
+        }
+        let epoch = ctx.chain_store().heaviest_tipset().epoch();
+        let message = push_message(
+            &ctx,
+            from,
+            channel,
+            METHOD_SEND,
+            RawBytes::default(),
+            amount.clone(),
+        )
+        .await?;
+        tracked.pending_funds.push(PendingFunds {
+            message,
+            amount,
+            epoch,
+        });
+        channels.save()?;
+        Ok(ChannelInfo {
+            channel: Some(channel),
+            wait_sentinel: Some(message),
+        })
+    }
+}
+
+pub enum PaychGetWaitReady {}
+impl RpcMethod<1> for PaychGetWaitReady {
+    const NAME: &'static str = "Filecoin.PaychGetWaitReady";
+    const PARAM_NAMES: [&'static str; 1] = ["sentinel"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Waits until the message returned by PaychGet has been executed, and returns the address of the payment channel.",
+    );
+
+    type Params = (Cid,);
+    type Ok = Address;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (sentinel,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let (_, receipt) = ctx
+            .state_manager
+            .wait_for_message(sentinel, MESSAGE_CONFIDENCE, None, Some(true))
+            .await?;
+        let receipt = receipt.context("wait for msg returned empty receipt")?;
+        if !receipt.exit_code().is_success() {
+            return Err(anyhow::anyhow!(
+                "payment channel message {sentinel} failed with exit code {}",
+                receipt.exit_code()
+            )
+            .into());
+        }
+        let mut channels = ctx.paych_store.lock().await?;
+        update_pending_funds(&ctx, &mut channels).await?;
+        match channels
+            .iter()
+            .find(|tracked| tracked.create_msg == Some(sentinel))
+        {
+            Some(tracked) => Ok(tracked
+                .channel
+                .context("payment channel address is unknown")?),
+            // The sentinel is a message adding funds to an existing channel
+            None => Ok(crate::chain::get_chain_message(ctx.store(), &sentinel)?.to()),
+        }
+    }
+}
+
+pub enum PaychAvailableFunds {}
+impl RpcMethod<1> for PaychAvailableFunds {
+    const NAME: &'static str = "Filecoin.PaychAvailableFunds";
+    const PARAM_NAMES: [&'static str; 1] = ["channel"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Returns the funds available in and pending for the given payment channel.");
+
+    type Params = (Address,);
+    type Ok = ChannelAvailableFunds;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (channel,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let tracked = {
+            let mut channels = ctx.paych_store.lock().await?;
+            update_pending_funds(&ctx, &mut channels).await?;
+            channels
+                .get(required_position(&channels, &channel)?)
+                .cloned()
+                .context("channel index out of bounds")?
+        };
+        let (actor, state) = load_channel_state(&ctx, &channel)?;
+        let lanes = lane_amounts(&state.load_lane_states(ctx.store())?, &tracked.vouchers);
+        let confirmed_amt = TokenAmount::from(&actor.balance);
+        let voucher_redeemed_amt = total_amount(&lanes);
+        let non_reserved_amt = if confirmed_amt > voucher_redeemed_amt {
+            confirmed_amt.clone() - &voucher_redeemed_amt
+        } else {
+            TokenAmount::zero()
+        };
+        Ok(ChannelAvailableFunds {
+            channel: Some(channel),
+            from: tracked.from(),
+            to: tracked.to(),
+            pending_amt: tracked.pending_amount(),
+            pending_wait_sentinel: tracked.pending_funds.last().map(|pending| pending.message),
+            confirmed_amt,
+            non_reserved_amt,
+            voucher_redeemed_amt,
+        })
+    }
+}
+
+pub enum PaychList {}
+impl RpcMethod<0> for PaychList {
+    const NAME: &'static str = "Filecoin.PaychList";
+    const PARAM_NAMES: [&'static str; 0] = [];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Returns the addresses of the payment channels tracked by the node.");
+
+    type Params = ();
+    type Ok = Vec<Address>;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        Ok(ctx
+            .paych_store
+            .channels()?
+            .into_iter()
+            .filter_map(|tracked| tracked.channel)
+            .collect())
+    }
+}
+
+pub enum PaychAllocateLane {}
+impl RpcMethod<1> for PaychAllocateLane {
+    const NAME: &'static str = "Filecoin.PaychAllocateLane";
+    const PARAM_NAMES: [&'static str; 1] = ["channel"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Allocates a new lane in the given outbound payment channel.");
+
+    type Params = (Address,);
+    type Ok = u64;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (channel,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let mut channels = ctx.paych_store.lock().await?;
+        let idx = required_outbound_position(&channels, &channel)?;
+        let tracked = channels
+            .get_mut(idx)
+            .context("channel index out of bounds")?;
+        let lane = tracked.next_lane;
+        tracked.next_lane += 1;
+        channels.save()?;
+        Ok(lane)
+    }
+}
+
+pub enum PaychVoucherCreate {}
+impl RpcMethod<3> for PaychVoucherCreate {
+    const NAME: &'static str = "Filecoin.PaychVoucherCreate";
+    const PARAM_NAMES: [&'static str; 3] = ["channel", "amount", "lane"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Creates a voucher redeeming the given total amount in the given lane of an outbound payment channel. If the channel does not have enough funds, no voucher is created and the shortfall is returned.",
+    );
+
+    type Params = (Address, TokenAmount, u64);
+    type Ok = VoucherCreateResult;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (channel, amount, lane): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let mut channels = ctx.paych_store.lock().await?;
+        let idx = required_outbound_position(&channels, &channel)?;
+        let tracked = channels
+            .get_mut(idx)
+            .context("channel index out of bounds")?;
+        let (actor, state) = load_channel_state(&ctx, &channel)?;
+        let mut lanes = lane_amounts(&state.load_lane_states(ctx.store())?, &tracked.vouchers);
+        let current = lanes.remove(&lane).unwrap_or_default();
+        if amount <= current.redeemed {
+            return Err(ServerError::invalid_params(
+                format!(
+                    "voucher amount {amount} must be greater than the amount {} already in lane {lane}",
+                    current.redeemed
+                ),
+                None,
+            ));
+        }
+        let total = total_amount(&lanes) + &amount;
+        let balance = TokenAmount::from(&actor.balance);
+        if total > balance {
+            return Ok(VoucherCreateResult {
+                voucher: None,
+                shortfall: total - &balance,
+            });
+        }
+
+        let mut voucher = SignedVoucher {
+            channel_addr: channel,
+            time_lock_min: 0,
+            time_lock_max: 0,
+            secret_hash: vec![],
+            extra: None,
+            lane,
+            nonce: current.nonce + 1,
+            amount,
+            min_settle_height: 0,
+            merges: vec![],
+            signature: None,
+        };
+        let signature =
+            WalletSign::handle(ctx.clone(), (tracked.control, voucher.signing_bytes()?)).await?;
+        voucher.signature = Some(signature);
+        tracked.vouchers.push(VoucherInfo {
+            voucher: voucher.clone(),
+            proof: vec![],
+            submitted: false,
+        });
+        tracked.next_lane = tracked.next_lane.max(lane + 1);
+        channels.save()?;
+        Ok(VoucherCreateResult {
+            voucher: Some(voucher),
+            shortfall: TokenAmount::zero(),
+        })
+    }
+}
+
+pub enum PaychVoucherCheckValid {}
+impl RpcMethod<2> for PaychVoucherCheckValid {
+    const NAME: &'static str = "Filecoin.PaychVoucherCheckValid";
+    const PARAM_NAMES: [&'static str; 2] = ["channel", "voucher"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Checks that the given voucher is valid for the given payment channel.");
+
+    type Params = (Address, SignedVoucher);
+    type Ok = ();
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (channel, voucher): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let vouchers = ctx
+            .paych_store
+            .channel(&channel)?
+            .map(|tracked| tracked.vouchers)
+            .unwrap_or_default();
+        check_voucher(&ctx, &channel, &voucher, &vouchers).await?;
+        Ok(())
+    }
+}
+
+pub enum PaychVoucherAdd {}
+impl RpcMethod<4> for PaychVoucherAdd {
+    const NAME: &'static str = "Filecoin.PaychVoucherAdd";
+    const PARAM_NAMES: [&'static str; 4] = ["channel", "voucher", "proof", "minDelta"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Write;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Stores a voucher received for the given payment channel and returns the amount it adds to its lane, which must be at least the given minimum.",
+    );
+
+    type Params = (Address, SignedVoucher, Vec<u8>, TokenAmount);
+    type Ok = TokenAmount;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (channel, voucher, proof, min_delta): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let mut channels = ctx.paych_store.lock().await?;
+        let idx = track_channel(&ctx, &mut channels, &channel)?;
+        let tracked = channels
+            .get_mut(idx)
+            .context("channel index out of bounds")?;
+        if tracked.has_voucher(&voucher) {
+            return Ok(TokenAmount::zero());
+        }
+        let delta = check_voucher(&ctx, &channel, &voucher, &tracked.vouchers).await?;
+        if delta < min_delta {
+            return Err(ServerError::invalid_params(
+                format!("voucher delta {delta} is less than the minimum {min_delta}"),
+                None,
+            ));
+        }
+        tracked.vouchers.push(VoucherInfo {
+            voucher,
+            proof,
+            submitted: false,
+        });
+        channels.save()?;
+        Ok(delta)
+    }
+}
+
+pub enum PaychVoucherList {}
+impl RpcMethod<1> for PaychVoucherList {
+    const NAME: &'static str = "Filecoin.PaychVoucherList";
+    const PARAM_NAMES: [&'static str; 1] = ["channel"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Write;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Returns the vouchers stored for the given payment channel.");
+
+    type Params = (Address,);
+    type Ok = Vec<SignedVoucher>;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (channel,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let tracked = ctx
+            .paych_store
+            .channel(&channel)?
+            .with_context(|| format!("payment channel {channel} is not tracked"))?;
+        Ok(tracked
+            .vouchers
+            .into_iter()
+            .map(|info| info.voucher)
+            .collect())
+    }
+}
+
+pub enum PaychVoucherSubmit {}
+impl RpcMethod<4> for PaychVoucherSubmit {
+    const NAME: &'static str = "Filecoin.PaychVoucherSubmit";
+    const PARAM_NAMES: [&'static str; 4] = ["channel", "voucher", "secret", "proof"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Submits a voucher to the given payment channel to redeem its amount, and returns the CID of the message.",
+    );
+
+    type Params = (Address, SignedVoucher, Vec<u8>, Vec<u8>);
+    type Ok = Cid;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (channel, voucher, secret, proof): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        if !proof.is_empty() {
+            return Err(ServerError::invalid_params(
+                "proofs are not supported by payment channels",
+                None,
+            ));
+        }
+        let mut channels = ctx.paych_store.lock().await?;
+        let idx = track_channel(&ctx, &mut channels, &channel)?;
+        let tracked = channels
+            .get_mut(idx)
+            .context("channel index out of bounds")?;
+        if !tracked.has_voucher(&voucher) {
+            check_voucher(&ctx, &channel, &voucher, &tracked.vouchers).await?;
+            tracked.vouchers.push(VoucherInfo {
+                voucher: voucher.clone(),
+                proof: vec![],
+                submitted: false,
+            });
+        }
+        let params = RawBytes::serialize(UpdateChannelStateParams {
+            sv: voucher.clone(),
+            secret,
+        })?;
+        let message = push_message(
+            &ctx,
+            tracked.control,
+            channel,
+            paych::Method::UpdateChannelState as MethodNum,
+            params,
+            TokenAmount::zero(),
+        )
+        .await?;
+        for info in tracked
+            .vouchers
+            .iter_mut()
+            .filter(|info| info.voucher == voucher)
+        {
+            info.submitted = true;
+        }
+        channels.save()?;
+        Ok(message)
+    }
+}
+
+pub enum PaychSettle {}
+impl RpcMethod<1> for PaychSettle {
+    const NAME: &'static str = "Filecoin.PaychSettle";
+    const PARAM_NAMES: [&'static str; 1] = ["channel"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Starts settling the given payment channel, after which it can be collected, and returns the CID of the message.",
+    );
+
+    type Params = (Address,);
+    type Ok = Cid;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (channel,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let mut channels = ctx.paych_store.lock().await?;
+        let idx = required_position(&channels, &channel)?;
+        let tracked = channels
+            .get_mut(idx)
+            .context("channel index out of bounds")?;
+        let message = push_message(
+            &ctx,
+            tracked.control,
+            channel,
+            paych::Method::Settle as MethodNum,
+            RawBytes::default(),
+            TokenAmount::zero(),
+        )
+        .await?;
+        tracked.settling = true;
+        channels.save()?;
+        Ok(message)
+    }
+}
+
+pub enum PaychCollect {}
+impl RpcMethod<1> for PaychCollect {
+    const NAME: &'static str = "Filecoin.PaychCollect";
+    const PARAM_NAMES: [&'static str; 1] = ["channel"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Sign;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Collects the funds of the given settled payment channel, paying out the redeemed amount to the payee and the rest to the payer, and returns the CID of the message.",
+    );
+
+    type Params = (Address,);
+    type Ok = Cid;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (channel,): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let control = ctx
+            .paych_store
+            .channel(&channel)?
+            .with_context(|| format!("payment channel {channel} is not tracked"))?
+            .control;
+        let (_, state) = load_channel_state(&ctx, &channel)?;
+        let epoch = ctx.chain_store().heaviest_tipset().epoch();
+        if state.settling_at == 0 || epoch < state.settling_at {
+            return Err(anyhow::anyhow!(
+                "payment channel {channel} cannot be collected before it is settled"
+            )
+            .into());
+        }
+        push_message(
+            &ctx,
+            control,
+            channel,
+            paych::Method::Collect as MethodNum,
+            RawBytes::default(),
+            TokenAmount::zero(),
+        )
+        .await
+    }
+}
+
+/// Signs and pushes a message to the message pool, returning its CID.
+async fn push_message(
+    ctx: &Ctx<impl Blockstore + Send + Sync + 'static>,
+    from: Address,
+    to: Address,
+    method_num: MethodNum,
+    params: RawBytes,
+    value: TokenAmount,
+) -> Result<Cid, ServerError> {
+    let message = Message {
+        from,
+        to,
+        method_num,
+        params,
+        value,
+        ..Default::default()
+    };
+    let smsg = MpoolPushMessage::handle(ctx.clone(), (message, None)).await?;
+    Ok(smsg.cid())
+}
+
+/// Drops the executed messages from the pending funds of the tracked channels, learning the
+/// address of the channels whose creation message has been executed, and saves the channels.
+/// Channels whose creation failed are forgotten.
+///
+/// Messages are only searched for in the tipsets after the one they were sent on, as the
+/// channels are locked meanwhile.
+async fn update_pending_funds(
+    ctx: &Ctx<impl Blockstore + Send + Sync + 'static>,
+    channels: &mut LockedChannels<'_>,
+) -> anyhow::Result<()> {
+    for tracked in channels.iter_mut() {
+        let mut still_pending = vec![];
+        for pending in std::mem::take(&mut tracked.pending_funds) {
+            let Some((_, receipt)) = ctx
+                .state_manager
+                .search_for_message(None, pending.message, Some(pending.epoch), Some(true))
+                .await?
+            else {
+                still_pending.push(pending);
+                continue;
+            };
+            if !receipt.exit_code().is_success() {
+                tracing::warn!(
+                    "Payment channel message {} failed with exit code {}",
+                    pending.message,
+                    receipt.exit_code()
+                );
+            } else if tracked.create_msg == Some(pending.message) {
+                let ret: ExecReturn = receipt.return_data().deserialize()?;
+                tracked.channel = Some(ret.robust_address);
+            }
+        }
+        tracked.pending_funds = still_pending;
+    }
+    channels.retain(|tracked| tracked.channel.is_some() || !tracked.pending_funds.is_empty());
+    channels.save()
+}
+
+fn required_position(channels: &[TrackedChannel], channel: &Address) -> anyhow::Result<usize> {
+    channels
+        .iter()
+        .position(|tracked| tracked.channel.as_ref() == Some(channel))
+        .with_context(|| format!("payment channel {channel} is not tracked"))
+}
+
+fn required_outbound_position(
+    channels: &[TrackedChannel],
+    channel: &Address,
+) -> anyhow::Result<usize> {
+    let idx = required_position(channels, channel)?;
+    ensure!(
+        channels
+            .get(idx)
+            .is_some_and(|tracked| tracked.direction == Direction::Outbound),
+        "payment channel {channel} is not an outbound channel"
+    );
+    Ok(idx)
+}
+
+/// Returns the index of the tracked channel with the given address, tracking it as an inbound
+/// channel if it is unknown.
+fn track_channel(
+    ctx: &Ctx<impl Blockstore + Send + Sync + 'static>,
+    channels: &mut crate::paychmgr::LockedChannels<'_>,
+    channel: &Address,
+) -> anyhow::Result<usize> {
+    if let Some(idx) = channels.position(channel) {
+        return Ok(idx);
+    }
+    let (_, state) = load_channel_state(ctx, channel)?;
+    channels.push(TrackedChannel::new_inbound(*channel, state.from, state.to));
+    Ok(channels.len() - 1)
+}
+
+fn load_channel_state(
+    ctx: &Ctx<impl Blockstore + Send + Sync + 'static>,
+    channel: &Address,
+) -> anyhow::Result<(ActorState, paych::State)> {
+    let ts = ctx.chain_store().heaviest_tipset();
+    let actor = ctx
+        .state_manager
+        .get_required_actor(channel, *ts.parent_state())?;
+    let state = paych::State::load(ctx.store(), actor.code, actor.state)?;
+    Ok((actor, state))
+}
+
+/// Merges the lane states redeemed on chain with the vouchers stored locally, keeping the highest
+/// nonce of each lane.
+fn lane_amounts(
+    on_chain: &BTreeMap<u64, LaneState>,
+    vouchers: &[VoucherInfo],
+) -> BTreeMap<u64, LaneState> {
+    let mut lanes = on_chain.clone();
+    for VoucherInfo { voucher, .. } in vouchers {
+        let lane = lanes.entry(voucher.lane).or_default();
+        if voucher.nonce > lane.nonce {
+            *lane = LaneState {
+                redeemed: voucher.amount.clone(),
+                nonce: voucher.nonce,
+            };
+        }
+    }
+    lanes
+}
+
+fn total_amount(lanes: &BTreeMap<u64, LaneState>) -> TokenAmount {
+    lanes
+        .values()
+        .fold(TokenAmount::zero(), |acc, lane| acc + &lane.redeemed)
+}
+
+/// Checks that the voucher is signed by the payer of the channel, supersedes the known vouchers of
+/// its lane, and is covered by the channel balance. Returns the amount the voucher adds to its
+/// lane.
+async fn check_voucher(
+    ctx: &Ctx<impl Blockstore + Send + Sync + 'static>,
+    channel: &Address,
+    voucher: &SignedVoucher,
+    vouchers: &[VoucherInfo],
+) -> anyhow::Result<TokenAmount> {
+    ensure!(
+        &voucher.channel_addr == channel,
+        "voucher is for channel {}, not {channel}",
+        voucher.channel_addr
+    );
+    if !voucher.merges.is_empty() {
+        bail!("vouchers with merges are not supported");
+    }
+    let (actor, state) = load_channel_state(ctx, channel)?;
+    ensure!(
+        state.settling_at == 0,
+        "payment channel {channel} is settling"
+    );
+
+    let signature = voucher
+        .signature
+        .as_ref()
+        .context("voucher is not signed")?;
+    let ts = ctx.chain_store().heaviest_tipset();
+    let from = ctx
+        .state_manager
+        .resolve_to_key_addr(&state.from, &ts)
+        .await?;
+    signature
+        .verify(&voucher.signing_bytes()?, &from)
+        .context("voucher signature is invalid")?;
+
+    let mut lanes = lane_amounts(&state.load_lane_states(ctx.store())?, vouchers);
+    let current = lanes.remove(&voucher.lane).unwrap_or_default();
+    ensure!(
+        voucher.nonce > current.nonce,
+        "voucher nonce {} is not greater than the nonce {} of lane {}",
+        voucher.nonce,
+        current.nonce,
+        voucher.lane
+    );
+    ensure!(
+        voucher.amount >= current.redeemed,
+        "voucher amount {} is lower than the amount {} of lane {}",
+        voucher.amount,
+        current.redeemed,
+        voucher.lane
+    );
+    let balance = TokenAmount::from(&actor.balance);
+    let total = total_amount(&lanes) + &voucher.amount;
+    ensure!(
+        total <= balance,
+        "not enough funds in payment channel {channel} to cover the voucher: {total} > {balance}"
+    );
+    Ok(&voucher.amount - current.redeemed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voucher_info(lane: u64, nonce: u64, amount: u64) -> VoucherInfo {
+        VoucherInfo {
+            voucher: SignedVoucher {
+                channel_addr: Address::new_id(1000),
+                time_lock_min: 0,
+                time_lock_max: 0,
+                secret_hash: vec![],
+                extra: None,
+                lane,
+                nonce,
+                amount: TokenAmount::from_atto(amount),
+                min_settle_height: 0,
+                merges: vec![],
+                signature: None,
+            },
+            proof: vec![],
+            submitted: false,
+        }
+    }
+
+    #[test]
+    fn test_lane_amounts() {
+        let on_chain = BTreeMap::from([(
+            0,
+            LaneState {
+                redeemed: TokenAmount::from_atto(10),
+                nonce: 2,
+            },
+        )]);
+        let vouchers = [
+            // Superseded by the on-chain state
+            voucher_info(0, 1, 5),
+            voucher_info(0, 3, 20),
+            voucher_info(1, 2, 7),
+            voucher_info(1, 1, 3),
+        ];
+        let lanes = lane_amounts(&on_chain, &vouchers);
+        assert_eq!(
+            lanes.get(&0),
+            Some(&LaneState {
+                redeemed: TokenAmount::from_atto(20),
+                nonce: 3
+            })
+        );
+        assert_eq!(
+            lanes.get(&1),
+            Some(&LaneState {
+                redeemed: TokenAmount::from_atto(7),
+                nonce: 2
+            })
+        );
+        assert_eq!(total_amount(&lanes), TokenAmount::from_atto(27));
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+mod actor_methods;
 mod types;
 use crate::shim::actors::init;
 use crate::shim::actors::miner::ext::DeadlineExt;
@@ -9,13 +10,14 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 pub use types::*;
 
-use crate::blocks::{Tipset, TipsetKey};
+use crate::blocks::{Tipset, TipsetKey, BLOCK_MESSAGE_LIMIT};
 use crate::chain::index::ResolveNullTipset;
 use crate::cid_collections::CidHashSet;
 use crate::eth::EthChainId;
 use crate::interpreter::VMEvent;
 use crate::libp2p::NetworkMessage;
 use crate::lotus_json::lotus_json_with_self;
+use crate::message::{ChainMessage, Message as _};
 use crate::networks::ChainConfig;
 use crate::shim::actors::market::ext::MarketStateExt as _;
 use crate::shim::actors::market::DealState;
@@ -33,23 +35,32 @@ use crate::shim::actors::{
     power::ext::PowerStateExt as _,
 };
 use crate::shim::address::Payload;
-use crate::shim::message::Message;
+use crate::shim::message::{Message, MethodNum};
 use crate::shim::piece::PaddedPieceSize;
 use crate::shim::sector::{SectorNumber, SectorSize};
 use crate::shim::state_tree::{ActorID, StateTree};
 use crate::shim::{
-    address::Address, clock::ChainEpoch, deal::DealID, econ::TokenAmount, executor::Receipt,
-    state_tree::ActorState, version::NetworkVersion,
+    address::Address,
+    clock::ChainEpoch,
+    deal::DealID,
+    econ::{TokenAmount, BLOCK_GAS_LIMIT},
+    executor::Receipt,
+    state_tree::ActorState,
+    version::NetworkVersion,
 };
 use crate::state_manager::circulating_supply::GenesisInfo;
 use crate::state_manager::{MarketBalance, StateManager, StateOutput};
+use crate::statediff::{self, ActorDiff};
 use crate::utils::db::{
     car_stream::{CarBlock, CarWriter},
     BlockstoreExt as _,
 };
 use crate::{
     beacon::BeaconEntry,
-    rpc::{types::*, ApiPaths, Ctx, Permission, RpcMethod, ServerError},
+    rpc::{
+        gas::pretend_signed, types::*, ApiPaths, Cacheability, Ctx, Permission, RpcMethod,
+        ServerError,
+    },
 };
 use ahash::{HashMap, HashMapExt, HashSet};
 use anyhow::Context as _;
@@ -111,6 +122,90 @@ impl RpcMethod<2> for StateCall {
     }
 }
 
+pub enum StateSimulate {}
+impl RpcMethod<2> for StateSimulate {
+    const NAME: &'static str = "Forest.StateSimulate";
+    const PARAM_NAMES: [&'static str; 2] = ["messages", "tipsetKey"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some("Applies the given unsigned messages in order, each one seeing the changes made by the previous ones, and returns their results without persisting or broadcasting anything. The messages are applied on top of the state computed for the given tipset or, if no tipset is given, on top of the pending messages of their senders in the message pool. Message nonces are set from the state. The messages must fit in a block, those without a gas limit share the gas left by the others.");
+
+    type Params = (Vec<Message>, ApiTipsetKey);
+    type Ok = Vec<ApiSimulationResult>;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (messages, ApiTipsetKey(tsk)): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let (tipset, use_pending) = match tsk {
+            Some(tsk) => (ctx.chain_index().load_required_tipset(&tsk)?, false),
+            None => (ctx.mpool.cur_tipset.lock().clone(), true),
+        };
+
+        // The messages must fit in a block
+        if messages.len() > BLOCK_MESSAGE_LIMIT {
+            return Err(ServerError::invalid_params(
+                format!("cannot simulate more than {BLOCK_MESSAGE_LIMIT} messages"),
+                None,
+            ));
+        }
+        let explicit_gas = messages
+            .iter()
+            .fold(0u64, |gas, message| gas.saturating_add(message.gas_limit));
+        if explicit_gas > BLOCK_GAS_LIMIT {
+            return Err(ServerError::invalid_params(
+                format!(
+                    "total gas limit of the messages exceeds the block gas limit {BLOCK_GAS_LIMIT}"
+                ),
+                None,
+            ));
+        }
+        // Messages without a gas limit share the gas left evenly
+        let default_gas_limit = match messages.iter().filter(|m| m.gas_limit == 0).count() {
+            0 => 0,
+            unset => (BLOCK_GAS_LIMIT - explicit_gas) / unset as u64,
+        };
+
+        let mut senders = Vec::new();
+        let mut chain_messages = Vec::with_capacity(messages.len());
+        for mut message in messages {
+            if message.gas_limit == 0 {
+                message.set_gas_limit(default_gas_limit);
+            }
+            let from = ctx
+                .state_manager
+                .resolve_to_key_addr(&message.from, &tipset)
+                .await?;
+            if !senders.contains(&from) {
+                senders.push(from);
+            }
+            chain_messages.push(pretend_signed(message, &from));
+        }
+        let prior_messages: Vec<_> = if use_pending {
+            senders
+                .iter()
+                .filter_map(|from| ctx.mpool.pending_for(from))
+                .flatten()
+                .map(ChainMessage::Signed)
+                .collect()
+        } else {
+            vec![]
+        };
+
+        let results = ctx
+            .state_manager
+            .simulate(chain_messages, prior_messages, Some(tipset))
+            .await?;
+        Ok(results
+            .into_iter()
+            .map(|(invoc_result, ret)| ApiSimulationResult {
+                invoc_result,
+                events: ret.events().iter().map(Event::from).collect(),
+            })
+            .collect())
+    }
+}
+
 pub enum StateReplay {}
 impl RpcMethod<2> for StateReplay {
     const NAME: &'static str = "Filecoin.StateReplay";
@@ -285,6 +380,7 @@ impl RpcMethod<2> for StateGetActor {
     const PARAM_NAMES: [&'static str; 2] = ["address", "tipsetKey"];
     const API_PATHS: ApiPaths = ApiPaths::V1;
     const PERMISSION: Permission = Permission::Read;
+    const CACHEABILITY: Cacheability = Cacheability::AtTipset(1);
     const DESCRIPTION: Option<&'static str> =
         Some("Returns the nonce and balance for the specified actor.");
 
@@ -1586,6 +1682,128 @@ impl RpcMethod<2> for StateReadState {
     }
 }
 
+pub enum StateDecodeParams {}
+
+impl RpcMethod<4> for StateDecodeParams {
+    const NAME: &'static str = "Filecoin.StateDecodeParams";
+    const PARAM_NAMES: [&'static str; 4] = ["address", "method", "params", "tipsetKey"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Decodes the CBOR parameters of a method of the given actor to JSON, based on the actor code at the given tipset.",
+    );
+
+    type Params = (Address, MethodNum, Vec<u8>, ApiTipsetKey);
+    type Ok = serde_json::Value;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (address, method, params, ApiTipsetKey(tsk)): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let ts = ctx.chain_store().load_required_tipset_or_heaviest(&tsk)?;
+        let actor = ctx
+            .state_manager
+            .get_required_actor(&address, *ts.parent_state())?;
+        Ok(actor_methods::decode_params(&actor.code, method, &params)?)
+    }
+}
+
+pub enum StateEncodeParams {}
+
+impl RpcMethod<3> for StateEncodeParams {
+    const NAME: &'static str = "Filecoin.StateEncodeParams";
+    const PARAM_NAMES: [&'static str; 3] = ["actorCode", "method", "params"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Encodes the JSON parameters of a method of the actor with the given code to CBOR.");
+
+    type Params = (Cid, MethodNum, serde_json::Value);
+    type Ok = Vec<u8>;
+
+    async fn handle(
+        _: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (actor_code, method, params): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        actor_methods::encode_params(&actor_code, method, params)
+            .map_err(|e| ServerError::invalid_params(format!("{e:#}"), None))
+    }
+}
+
+pub enum StateDecodeReturn {}
+
+impl RpcMethod<3> for StateDecodeReturn {
+    const NAME: &'static str = "Forest.StateDecodeReturn";
+    const PARAM_NAMES: [&'static str; 3] = ["actorCode", "method", "return"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> =
+        Some("Decodes the CBOR return value of a method of the actor with the given code to JSON.");
+
+    type Params = (Cid, MethodNum, Vec<u8>);
+    type Ok = serde_json::Value;
+
+    async fn handle(
+        _: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (actor_code, method, ret): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        Ok(actor_methods::decode_return(&actor_code, method, &ret)?)
+    }
+}
+
+pub enum StateChangedActors {}
+
+impl RpcMethod<2> for StateChangedActors {
+    const NAME: &'static str = "Filecoin.StateChangedActors";
+    const PARAM_NAMES: [&'static str; 2] = ["oldStateRoot", "newStateRoot"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns the actors that were added or changed between the two state roots, with their new state.",
+    );
+
+    type Params = (Cid, Cid);
+    type Ok = HashMap<String, ActorState>;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (old_root, new_root): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        let actors = statediff::changed_actors(&ctx.store_owned(), &old_root, &new_root)?;
+        Ok(actors
+            .into_iter()
+            .map(|(address, actor)| (address.to_string(), actor))
+            .collect())
+    }
+}
+
+pub enum StateActorDiff {}
+
+impl RpcMethod<3> for StateActorDiff {
+    const NAME: &'static str = "Forest.StateActorDiff";
+    const PARAM_NAMES: [&'static str; 3] = ["address", "oldStateRoot", "newStateRoot"];
+    const API_PATHS: ApiPaths = ApiPaths::V1;
+    const PERMISSION: Permission = Permission::Read;
+    const DESCRIPTION: Option<&'static str> = Some(
+        "Returns the difference of the given actor between the two state roots, down to the fields of its state.",
+    );
+
+    type Params = (Address, Cid, Cid);
+    type Ok = ActorDiff;
+
+    async fn handle(
+        ctx: Ctx<impl Blockstore + Send + Sync + 'static>,
+        (address, old_root, new_root): Self::Params,
+    ) -> Result<Self::Ok, ServerError> {
+        Ok(statediff::actor_diff(
+            &ctx.store_owned(),
+            &address,
+            &old_root,
+            &new_root,
+        )?)
+    }
+}
+
 pub enum StateCirculatingSupply {}
 
 impl RpcMethod<1> for StateCirculatingSupply {
@@ -2962,3 +3180,99 @@ fn get_pledge_ramp_params(
         Ok((0, 0))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_sync::block_producer::tests::{sign_transfer, SoloDevnet};
+    use crate::genesis::test_utils::VERIFIER_ALLOWANCE;
+    use crate::key_management::generate_key;
+    use crate::shim::actors::verifreg::params::VerifierParams;
+    use crate::shim::crypto::SignatureType;
+    use fvm_ipld_encoding::RawBytes;
+
+    #[tokio::test]
+    async fn simulate_dependent_messages() {
+        // Run the test only in CI so that regular test on dev machines don't download the actor
+        // bundle on poor internet connections.
+        if std::env::var("CI").is_err() {
+            return;
+        }
+
+        let devnet = SoloDevnet::with_actor_bundle().await;
+
+        // Simulated messages are applied after the pending ones of their sender
+        devnet
+            .ctx
+            .mpool
+            .push(sign_transfer(&devnet.account, devnet.worker.address, 0))
+            .await
+            .unwrap();
+        // The second grant exceeds the allowance left by the first one
+        let grant = || Message {
+            from: devnet.account.address,
+            to: Address::VERIFIED_REGISTRY_ACTOR,
+            method_num: fil_actor_verifreg_state::v16::Method::AddVerifiedClient as MethodNum,
+            params: RawBytes::serialize(VerifierParams {
+                address: generate_key(SignatureType::Secp256k1).unwrap().address,
+                allowance: (VERIFIER_ALLOWANCE * 2 / 3).into(),
+            })
+            .unwrap(),
+            gas_fee_cap: TokenAmount::from_nano(1),
+            ..Default::default()
+        };
+
+        let results = StateSimulate::handle(
+            devnet.ctx.clone(),
+            (vec![grant(), grant()], ApiTipsetKey(None)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result.invoc_result.msg.sequence)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        let receipts = results
+            .iter()
+            .map(|result| result.invoc_result.msg_rct.clone().unwrap())
+            .collect::<Vec<_>>();
+        assert!(receipts[0].exit_code().is_success());
+        assert!(!receipts[1].exit_code().is_success());
+        // The first grant emits the new balance of the verifier
+        assert!(!results[0].events.is_empty());
+        assert!(results[0]
+            .events
+            .iter()
+            .any(|event| event.emitter == Address::VERIFIED_REGISTRY_ACTOR.id().unwrap()));
+        assert!(results[1].events.is_empty());
+    }
+
+    #[tokio::test]
+    async fn simulate_rejects_messages_exceeding_a_block() {
+        let devnet = SoloDevnet::new();
+        let message = devnet.transfer(0).message;
+
+        let simulate = |messages| async {
+            match StateSimulate::handle(devnet.ctx.clone(), (messages, ApiTipsetKey(None))).await {
+                Ok(_) => panic!("simulation should fail"),
+                Err(e) => e.message().to_owned(),
+            }
+        };
+
+        let too_many = vec![message.clone(); BLOCK_MESSAGE_LIMIT + 1];
+        assert!(simulate(too_many)
+            .await
+            .contains("cannot simulate more than"));
+
+        let mut too_much_gas = vec![message.clone(), message];
+        for message in &mut too_much_gas {
+            message.gas_limit = BLOCK_GAS_LIMIT / 2 + 1;
+        }
+        assert!(simulate(too_much_gas)
+            .await
+            .contains("exceeds the block gas limit"));
+    }
+}
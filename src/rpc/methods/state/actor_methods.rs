@@ -0,0 +1,556 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Conversion of the CBOR parameters and return values of builtin actor methods to Lotus JSON and
+//! back, see [`super::StateDecodeParams`] and [`super::StateEncodeParams`].
+//!
+//! Methods are looked up by the code CID of the actor, which determines the major actors version,
+//! and the method number. The parameters and return values are the version-independent `params`
+//! types of the actor shims. Those of methods without a known type, and of actors that are not
+//! builtin, are converted structurally, see [`Ipld`].
+
+use crate::lotus_json::HasLotusJson;
+use crate::shim::actors::{
+    account, builtin_actor_version, datacap, eam, evm, init, market, miner, multisig, paych, power,
+    verifreg,
+};
+use crate::shim::address::Address;
+use crate::shim::econ::TokenAmount;
+use crate::shim::machine::BuiltinActor;
+use crate::shim::message::MethodNum;
+use anyhow::Context as _;
+use cid::Cid;
+use fil_actor_account_state::{v16 as account_v16, v9 as account_v9};
+use fil_actor_cron_state::v16 as cron_v16;
+use fil_actor_datacap_state::{v16 as datacap_v16, v9 as datacap_v9};
+use fil_actor_eam_state::v16 as eam_v16;
+use fil_actor_evm_state::v16 as evm_v16;
+use fil_actor_init_state::{v10 as init_v10, v16 as init_v16};
+use fil_actor_market_state::{v11 as market_v11, v16 as market_v16};
+use fil_actor_miner_state::{
+    v12 as miner_v12, v13 as miner_v13, v14 as miner_v14, v16 as miner_v16, v9 as miner_v9,
+};
+use fil_actor_multisig_state::v16 as multisig_v16;
+use fil_actor_power_state::{v13 as power_v13, v16 as power_v16};
+use fil_actor_reward_state::v16 as reward_v16;
+use fil_actor_system_state::v16 as system_v16;
+use fil_actor_verifreg_state::{v16 as verifreg_v16, v8 as verifreg_v8};
+use ipld_core::ipld::Ipld;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Converts the CBOR of a parameter or return type to its Lotus JSON, and back.
+#[derive(Clone, Copy)]
+struct Codec {
+    decode: fn(&[u8]) -> anyhow::Result<serde_json::Value>,
+    encode: fn(serde_json::Value) -> anyhow::Result<Vec<u8>>,
+}
+
+impl Codec {
+    fn of<T: HasLotusJson + Serialize + DeserializeOwned>() -> Self {
+        Self {
+            decode: |bytes| Ok(fvm_ipld_encoding::from_slice::<T>(bytes)?.into_lotus_json_value()?),
+            encode: |json| {
+                let value = T::from_lotus_json(serde_json::from_value(json)?);
+                Ok(fvm_ipld_encoding::to_vec(&value)?)
+            },
+        }
+    }
+}
+
+struct ActorMethod {
+    name: &'static str,
+    params: Option<Codec>,
+    ret: Option<Codec>,
+}
+
+/// Declares the methods of the builtin actors as
+/// `Actor[versions] => module { Method[versions](ParamsType) -> ReturnType, .. }`, where the
+/// method numbers are those of the actors `module`, and the version patterns and types are
+/// optional. The first declaration of a method that matches the major version of the actor wins,
+/// so methods whose numbers or layouts changed are declared once per range of versions.
+macro_rules! builtin_methods {
+    (@codec) => {
+        None
+    };
+    (@codec $ty:ty) => {
+        Some(Codec::of::<$ty>())
+    };
+    (@versions) => {
+        _
+    };
+    (@versions $versions:pat) => {
+        $versions
+    };
+    ($($actor:ident $([$actor_versions:pat])? => $module:ident {
+        $($method:ident $([$versions:pat])? $(($params:ty))? $(-> $ret:ty)?),* $(,)?
+    })*) => {
+        fn builtin_method(
+            actor: BuiltinActor,
+            version: u64,
+            method: MethodNum,
+        ) -> Option<ActorMethod> {
+            $(
+                if actor == BuiltinActor::$actor
+                    && matches!(version, builtin_methods!(@versions $($actor_versions)?))
+                {
+                    $(
+                        if method == $module::Method::$method as MethodNum
+                            && matches!(version, builtin_methods!(@versions $($versions)?))
+                        {
+                            return Some(ActorMethod {
+                                name: stringify!($method),
+                                params: builtin_methods!(@codec $($params)?),
+                                ret: builtin_methods!(@codec $($ret)?),
+                            });
+                        }
+                    )*
+                }
+            )*
+            None
+        }
+    };
+}
+
+builtin_methods! {
+    System => system_v16 {
+        Constructor,
+    }
+    Init => init_v16 {
+        Constructor(init::params::ConstructorParams),
+        Exec(init::params::ExecParams) -> init::params::ExecReturn,
+        Exec4[11..](init::params::Exec4Params) -> init::params::ExecReturn,
+    }
+    Init[10] => init_v10 {
+        ExecExported(init::params::ExecParams) -> init::params::ExecReturn,
+    }
+    Cron => cron_v16 {
+        Constructor,
+        EpochTick,
+    }
+    Account => account_v16 {
+        Constructor(account::params::ConstructorParams),
+        PubkeyAddress -> account::params::PubkeyAddressReturn,
+        AuthenticateMessageExported[10](account::params::AuthenticateMessageParams),
+        AuthenticateMessageExported[11..](account::params::AuthenticateMessageParams)
+            -> account::params::AuthenticateMessageReturn,
+    }
+    Account[9] => account_v9 {
+        AuthenticateMessage(account::params::AuthenticateMessageParams),
+    }
+    Power => power_v16 {
+        Constructor,
+        CreateMiner(power::params::CreateMinerParams) -> power::params::CreateMinerReturn,
+        UpdateClaimedPower,
+        EnrollCronEvent,
+        OnEpochTickEnd,
+        UpdatePledgeTotal,
+        CurrentTotalPower,
+        CreateMinerExported[10..](power::params::CreateMinerParams)
+            -> power::params::CreateMinerReturn,
+        NetworkRawPowerExported[10..],
+        MinerRawPowerExported[10..],
+        MinerCountExported[10..],
+        MinerConsensusCountExported[10..],
+        MinerPowerExported[16..],
+    }
+    Power[0..=13] => power_v13 {
+        SubmitPoRepForBulkVerify,
+    }
+    Miner => miner_v16 {
+        Constructor,
+        ControlAddresses -> miner::params::GetControlAddressesReturn,
+        ChangeWorkerAddress(miner::params::ChangeWorkerAddressParams),
+        ChangePeerID(miner::params::ChangePeerIDParams),
+        SubmitWindowedPoSt(miner::params::SubmitWindowedPoStParams),
+        ExtendSectorExpiration,
+        TerminateSectors(miner::params::TerminateSectorsParams)
+            -> miner::params::TerminateSectorsReturn,
+        DeclareFaults(miner::params::DeclareFaultsParams),
+        DeclareFaultsRecovered(miner::params::DeclareFaultsRecoveredParams),
+        OnDeferredCronEvent,
+        CheckSectorProven,
+        ApplyRewards,
+        ReportConsensusFault,
+        WithdrawBalance(miner::params::WithdrawBalanceParams)
+            -> miner::params::WithdrawBalanceReturn,
+        InternalSectorSetupForPreseal[15..],
+        ChangeMultiaddrs(miner::params::ChangeMultiaddrsParams),
+        CompactPartitions,
+        CompactSectorNumbers,
+        ConfirmChangeWorkerAddress[10..],
+        RepayDebt,
+        ChangeOwnerAddress(miner::params::ChangeOwnerAddressParams),
+        DisputeWindowedPoSt,
+        ProveCommitAggregate(miner::params::ProveCommitAggregateParams),
+        ProveReplicaUpdates,
+        PreCommitSectorBatch2[9..](miner::params::PreCommitSectorBatchParams2),
+        ChangeBeneficiary[9..](miner::params::ChangeBeneficiaryParams),
+        GetBeneficiary[9..],
+        ExtendSectorExpiration2[9..](miner::params::ExtendSectorExpiration2Params),
+        ProveCommitSectors3[13..](miner::params::ProveCommitSectors3Params)
+            -> miner::params::ProveCommitSectors3Return,
+        ProveReplicaUpdates3[13..],
+        ProveCommitSectorsNI[14..],
+        ChangeWorkerAddressExported[10..](miner::params::ChangeWorkerAddressParams),
+        ChangePeerIDExported[10..](miner::params::ChangePeerIDParams),
+        WithdrawBalanceExported[10..](miner::params::WithdrawBalanceParams)
+            -> miner::params::WithdrawBalanceReturn,
+        ChangeMultiaddrsExported[10..](miner::params::ChangeMultiaddrsParams),
+        ConfirmChangeWorkerAddressExported[10..],
+        RepayDebtExported[10..],
+        ChangeOwnerAddressExported[10..](miner::params::ChangeOwnerAddressParams),
+        ChangeBeneficiaryExported[10..](miner::params::ChangeBeneficiaryParams),
+        GetBeneficiaryExported[10..],
+        GetOwnerExported[10..],
+        IsControllingAddressExported[10..],
+        GetSectorSizeExported[10..],
+        GetAvailableBalanceExported[10..],
+        GetVestingFundsExported[10..],
+        GetPeerIDExported[10..],
+        GetMultiaddrsExported[10..],
+        MaxTerminationFeeExported[16..],
+        InitialPledgeExported[16..],
+    }
+    Miner[0..=14] => miner_v14 {
+        ConfirmSectorProofsValid,
+    }
+    Miner[0..=13] => miner_v13 {
+        ProveCommitSector,
+    }
+    Miner[0..=12] => miner_v12 {
+        PreCommitSector,
+        PreCommitSectorBatch,
+        ProveReplicaUpdates2[9..],
+    }
+    Miner[0..=9] => miner_v9 {
+        ConfirmUpdateWorkerKey,
+    }
+    Market => market_v16 {
+        Constructor,
+        AddBalance(market::params::AddBalanceParams),
+        WithdrawBalance(market::params::WithdrawBalanceParams)
+            -> market::params::WithdrawBalanceReturn,
+        PublishStorageDeals(market::params::PublishStorageDealsParams)
+            -> market::params::PublishStorageDealsReturn,
+        VerifyDealsForActivation,
+        BatchActivateDeals[12..],
+        OnMinerSectorsTerminate,
+        CronTick,
+        AddBalanceExported[10..](market::params::AddBalanceParams),
+        WithdrawBalanceExported[10..](market::params::WithdrawBalanceParams)
+            -> market::params::WithdrawBalanceReturn,
+        PublishStorageDealsExported[10..](market::params::PublishStorageDealsParams)
+            -> market::params::PublishStorageDealsReturn,
+        GetBalanceExported[10..],
+        GetDealDataCommitmentExported[10..],
+        GetDealClientExported[10..],
+        GetDealProviderExported[10..],
+        GetDealLabelExported[10..],
+        GetDealTermExported[10..],
+        GetDealTotalPriceExported[10..],
+        GetDealClientCollateralExported[10..],
+        GetDealProviderCollateralExported[10..],
+        GetDealVerifiedExported[10..],
+        GetDealActivationExported[10..],
+        GetDealSectorExported[13..],
+        SettleDealPaymentsExported[13..],
+        SectorContentChangedExported[13..],
+    }
+    Market[0..=11] => market_v11 {
+        ActivateDeals,
+        ComputeDataCommitment,
+    }
+    PaymentChannel => paych {
+        Constructor(paych::ConstructorParams),
+        UpdateChannelState(paych::UpdateChannelStateParams),
+        Settle,
+        Collect,
+    }
+    Multisig => multisig_v16 {
+        Constructor(multisig::params::ConstructorParams),
+        Propose(multisig::params::ProposeParams) -> multisig::params::ProposeReturn,
+        Approve(multisig::params::TxnIDParams) -> multisig::params::ApproveReturn,
+        Cancel(multisig::params::TxnIDParams),
+        AddSigner(multisig::params::AddSignerParams),
+        RemoveSigner(multisig::params::RemoveSignerParams),
+        SwapSigner(multisig::params::SwapSignerParams),
+        ChangeNumApprovalsThreshold(multisig::params::ChangeNumApprovalsThresholdParams),
+        LockBalance(multisig::params::LockBalanceParams),
+        UniversalReceiverHook[9..],
+    }
+    Reward => reward_v16 {
+        Constructor,
+        AwardBlockReward,
+        ThisEpochReward,
+        UpdateNetworkKPI,
+    }
+    VerifiedRegistry => verifreg_v16 {
+        Constructor,
+        AddVerifier(verifreg::params::VerifierParams),
+        RemoveVerifier(Address),
+        AddVerifiedClient(verifreg::params::VerifierParams),
+        RemoveVerifiedClientDataCap,
+        RemoveExpiredAllocations[9..],
+        ClaimAllocations[9..=11](verifreg::params::ClaimAllocationsParamsV9)
+            -> verifreg::params::ClaimAllocationsReturnV9,
+        ClaimAllocations[12..](verifreg::params::ClaimAllocationsParams)
+            -> verifreg::params::ClaimAllocationsReturn,
+        GetClaims[9..],
+        ExtendClaimTerms[9..],
+        RemoveExpiredClaims[9..],
+        AddVerifiedClientExported[10..](verifreg::params::VerifierParams),
+        RemoveExpiredAllocationsExported[10..],
+        GetClaimsExported[10..],
+        ExtendClaimTermsExported[10..],
+        RemoveExpiredClaimsExported[10..],
+        UniversalReceiverHook[9..],
+    }
+    VerifiedRegistry[8] => verifreg_v8 {
+        UseBytes,
+        RestoreBytes,
+    }
+    DataCap[9] => datacap_v9 {
+        Constructor(Address),
+        Mint(datacap::params::MintParams) -> datacap::params::MintReturn,
+        Destroy(datacap::params::DestroyParams) -> datacap::params::BurnReturn,
+        Name -> String,
+        Symbol -> String,
+        TotalSupply -> TokenAmount,
+        BalanceOf(Address) -> TokenAmount,
+        Transfer(datacap::params::TransferParams) -> datacap::params::TransferReturn,
+        TransferFrom(datacap::params::TransferFromParams) -> datacap::params::TransferFromReturn,
+        IncreaseAllowance(datacap::params::IncreaseAllowanceParams) -> TokenAmount,
+        DecreaseAllowance(datacap::params::DecreaseAllowanceParams) -> TokenAmount,
+        RevokeAllowance(datacap::params::RevokeAllowanceParams) -> TokenAmount,
+        Burn(datacap::params::BurnParams) -> datacap::params::BurnReturn,
+        BurnFrom(datacap::params::BurnFromParams) -> datacap::params::BurnFromReturn,
+        Allowance(datacap::params::GetAllowanceParams) -> TokenAmount,
+    }
+    DataCap[10..] => datacap_v16 {
+        Constructor(Address),
+        MintExported(datacap::params::MintParams) -> datacap::params::MintReturn,
+        DestroyExported(datacap::params::DestroyParams) -> datacap::params::BurnReturn,
+        NameExported -> String,
+        SymbolExported -> String,
+        GranularityExported -> u64,
+        TotalSupplyExported -> TokenAmount,
+        BalanceExported(Address) -> TokenAmount,
+        TransferExported(datacap::params::TransferParams) -> datacap::params::TransferReturn,
+        TransferFromExported(datacap::params::TransferFromParams)
+            -> datacap::params::TransferFromReturn,
+        IncreaseAllowanceExported(datacap::params::IncreaseAllowanceParams) -> TokenAmount,
+        DecreaseAllowanceExported(datacap::params::DecreaseAllowanceParams) -> TokenAmount,
+        RevokeAllowanceExported(datacap::params::RevokeAllowanceParams) -> TokenAmount,
+        BurnExported(datacap::params::BurnParams) -> datacap::params::BurnReturn,
+        BurnFromExported(datacap::params::BurnFromParams) -> datacap::params::BurnFromReturn,
+        AllowanceExported(datacap::params::GetAllowanceParams) -> TokenAmount,
+    }
+    EVM => evm_v16 {
+        Constructor,
+        Resurrect,
+        GetBytecode -> evm::params::BytecodeReturn,
+        GetBytecodeHash,
+        GetStorageAt(evm::params::GetStorageAtParams) -> evm::params::GetStorageAtReturn,
+        InvokeContractDelegate,
+        InvokeContract(evm::params::InvokeContractParams) -> evm::params::InvokeContractReturn,
+    }
+    EAM => eam_v16 {
+        Constructor,
+        Create(eam::params::CreateParams) -> eam::params::CreateReturn,
+        Create2(eam::params::Create2Params) -> eam::params::CreateReturn,
+        CreateExternal(eam::params::CreateExternalParams) -> eam::params::CreateReturn,
+    }
+}
+
+/// Decodes the CBOR parameters of a method to Lotus JSON, empty parameters are `null`.
+pub fn decode_params(
+    code: &Cid,
+    method: MethodNum,
+    params: &[u8],
+) -> anyhow::Result<serde_json::Value> {
+    decode(code, method, params, |it| it.params, "parameters")
+}
+
+/// Decodes the CBOR return value of a method to Lotus JSON, an empty return value is `null`.
+pub fn decode_return(
+    code: &Cid,
+    method: MethodNum,
+    ret: &[u8],
+) -> anyhow::Result<serde_json::Value> {
+    decode(code, method, ret, |it| it.ret, "return value")
+}
+
+/// Encodes the Lotus JSON parameters of a method to CBOR, `null` parameters are empty.
+pub fn encode_params(
+    code: &Cid,
+    method: MethodNum,
+    params: serde_json::Value,
+) -> anyhow::Result<Vec<u8>> {
+    if params.is_null() {
+        return Ok(vec![]);
+    }
+    let (name, codec) = codec(code, method, |it| it.params);
+    (codec.encode)(params).with_context(|| format!("failed to encode the parameters of {name}"))
+}
+
+fn decode(
+    code: &Cid,
+    method: MethodNum,
+    bytes: &[u8],
+    select: fn(&ActorMethod) -> Option<Codec>,
+    what: &str,
+) -> anyhow::Result<serde_json::Value> {
+    if bytes.is_empty() {
+        return Ok(serde_json::Value::Null);
+    }
+    let (name, codec) = codec(code, method, select);
+    (codec.decode)(bytes).with_context(|| format!("failed to decode the {what} of {name}"))
+}
+
+/// Returns the name of the method and the codec of its parameters or return value for the major
+/// version of the actor, falling back to the structural [`Ipld`] codec.
+fn codec(
+    code: &Cid,
+    method: MethodNum,
+    select: fn(&ActorMethod) -> Option<Codec>,
+) -> (String, Codec) {
+    match builtin_actor_version(code)
+        .and_then(|(actor, version)| builtin_method(actor, version, method))
+    {
+        Some(it) => (
+            it.name.to_string(),
+            select(&it).unwrap_or_else(Codec::of::<Ipld>),
+        ),
+        None => (format!("method {method}"), Codec::of::<Ipld>()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::ACTOR_BUNDLES_METADATA;
+    use serde_json::json;
+
+    fn code(actor: BuiltinActor, version: u64) -> Cid {
+        ACTOR_BUNDLES_METADATA
+            .values()
+            .find(|bundle| bundle.actor_major_version().ok() == Some(version))
+            .unwrap()
+            .manifest
+            .get(actor)
+            .unwrap()
+    }
+
+    #[test]
+    fn typed_params_round_trip() {
+        let code = code(BuiltinActor::Miner, 16);
+        let method = miner_v16::Method::ChangeWorkerAddress as MethodNum;
+        let params = miner::params::ChangeWorkerAddressParams {
+            new_worker: Address::new_id(1000),
+            new_control_addresses: vec![Address::new_id(1001)],
+        };
+        let bytes = fvm_ipld_encoding::to_vec(&params).unwrap();
+
+        let decoded = decode_params(&code, method, &bytes).unwrap();
+        assert_eq!(
+            decoded,
+            json!({ "NewWorker": "f01000", "NewControlAddresses": ["f01001"] })
+        );
+        assert_eq!(encode_params(&code, method, decoded).unwrap(), bytes);
+    }
+
+    #[test]
+    fn methods_follow_the_actors_version() {
+        // `Mint` is method 2 of the v9 datacap actor only, later versions use `MintExported`.
+        let params = fvm_ipld_encoding::to_vec(&datacap_v9::MintParams {
+            to: fvm_shared2::address::Address::new_id(1000),
+            amount: fvm_shared2::econ::TokenAmount::from_atto(5),
+            operators: vec![fvm_shared2::address::Address::new_id(1001)],
+        })
+        .unwrap();
+        assert_eq!(
+            decode_params(&code(BuiltinActor::DataCap, 9), 2, &params).unwrap(),
+            json!({ "To": "f01000", "Amount": "5", "Operators": ["f01001"] })
+        );
+        assert!(decode_params(&code(BuiltinActor::DataCap, 16), 2, &params)
+            .unwrap()
+            .is_array());
+
+        // `ProveCommitSectors3` does not exist before v13.
+        let method = miner_v16::Method::ProveCommitSectors3 as MethodNum;
+        let (name, _) = codec(&code(BuiltinActor::Miner, 12), method, |it| it.params);
+        assert_eq!(name, format!("method {method}"));
+        let (name, _) = codec(&code(BuiltinActor::Miner, 13), method, |it| it.params);
+        assert_eq!(name, "ProveCommitSectors3");
+    }
+
+    #[test]
+    fn layouts_follow_the_actors_version() {
+        let method = verifreg_v16::Method::ClaimAllocations as MethodNum;
+        let data = Cid::default();
+        let v11 =
+            fvm_ipld_encoding::to_vec(&fil_actor_verifreg_state::v11::ClaimAllocationsParams {
+                sectors: vec![fil_actor_verifreg_state::v11::SectorAllocationClaim {
+                    client: 1000,
+                    allocation_id: 1,
+                    data,
+                    size: fvm_shared3::piece::PaddedPieceSize(2048),
+                    sector: 7,
+                    sector_expiry: 100,
+                }],
+                all_or_nothing: true,
+            })
+            .unwrap();
+        let decoded =
+            decode_params(&code(BuiltinActor::VerifiedRegistry, 11), method, &v11).unwrap();
+        assert_eq!(decoded["Sectors"][0]["SectorExpiry"], json!(100));
+        assert_eq!(
+            encode_params(&code(BuiltinActor::VerifiedRegistry, 11), method, decoded).unwrap(),
+            v11
+        );
+        assert!(decode_params(&code(BuiltinActor::VerifiedRegistry, 12), method, &v11).is_err());
+
+        let v16 = fvm_ipld_encoding::to_vec(&verifreg_v16::ClaimAllocationsParams {
+            sectors: vec![verifreg_v16::SectorAllocationClaims {
+                sector: 7,
+                expiry: 100,
+                claims: vec![verifreg_v16::AllocationClaim {
+                    client: 1000,
+                    allocation_id: 1,
+                    data,
+                    size: fvm_shared4::piece::PaddedPieceSize(2048),
+                }],
+            }],
+            all_or_nothing: true,
+        })
+        .unwrap();
+        let decoded =
+            decode_params(&code(BuiltinActor::VerifiedRegistry, 16), method, &v16).unwrap();
+        assert_eq!(decoded["Sectors"][0]["Claims"][0]["Size"], json!(2048));
+        assert_eq!(
+            encode_params(&code(BuiltinActor::VerifiedRegistry, 16), method, decoded).unwrap(),
+            v16
+        );
+    }
+
+    #[test]
+    fn untyped_params_round_trip() {
+        let code = Cid::default();
+        let bytes = fvm_ipld_encoding::to_vec(&(1u64, "forest")).unwrap();
+
+        let decoded = decode_params(&code, 2, &bytes).unwrap();
+        assert_eq!(encode_params(&code, 2, decoded).unwrap(), bytes);
+    }
+
+    #[test]
+    fn empty_params() {
+        let code = code(BuiltinActor::Miner, 16);
+        assert_eq!(
+            decode_params(&code, 1, &[]).unwrap(),
+            serde_json::Value::Null
+        );
+        assert!(encode_params(&code, 1, serde_json::Value::Null)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -3,6 +3,7 @@
 
 use crate::lotus_json::{lotus_json_with_self, LotusJson};
 use crate::message::Message as _;
+use crate::rpc::types::Event;
 use crate::shim::executor::ApplyRet;
 use crate::shim::{
     address::Address,
@@ -50,6 +51,17 @@ impl PartialEq for ApiInvocResult {
     }
 }
 
+/// Result of a message applied by `Forest.StateSimulate`
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiSimulationResult {
+    pub invoc_result: ApiInvocResult,
+    /// Events emitted by the message
+    pub events: Vec<Event>,
+}
+
+lotus_json_with_self!(ApiSimulationResult);
+
 #[derive(Default, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "PascalCase")]
 pub struct MessageGasCost {
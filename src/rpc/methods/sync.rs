@@ -168,6 +168,7 @@ mod tests {
     use crate::libp2p::{NetworkMessage, PeerManager};
     use crate::message_pool::{MessagePool, MpoolRpcProvider};
     use crate::networks::ChainConfig;
+    use crate::paychmgr::PaychStore;
     use crate::rpc::eth::filter::EthEventHandler;
     use crate::rpc::RPCState;
     use crate::shim::address::Address;
@@ -246,6 +247,10 @@ mod tests {
             msgs_in_tipset: Default::default(),
             sync_states: Arc::new(parking_lot::RwLock::new(nunny::vec![Default::default()])),
             eth_event_handler: Arc::new(EthEventHandler::new()),
+            paych_store: Arc::new(PaychStore::new(Arc::new(MemoryDB::default()))),
+            settings_store: Arc::new(MemoryDB::default()),
+            chain_pruner: None,
+            block_producer: None,
             sync_network_context,
             network_name: TEST_NET_NAME.to_owned(),
             start_time,
@@ -2,14 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 mod auth_layer;
+mod batch_layer;
+mod cache_layer;
 mod channel;
 mod client;
 mod filter_layer;
 mod filter_list;
 mod log_layer;
 mod metrics_layer;
+mod rate_limit_layer;
 mod request;
 
+use batch_layer::BatchLayer;
+use cache_layer::CacheLayer;
+pub use cache_layer::ResponseCache;
 pub use client::Client;
 pub use error::ServerError;
 use eth::filter::EthEventHandler;
@@ -17,8 +23,10 @@ use filter_layer::FilterLayer;
 pub use filter_list::FilterList;
 use futures::FutureExt as _;
 use log_layer::LogLayer;
+use rate_limit_layer::RateLimitLayer;
+pub use rate_limit_layer::RateLimiter;
 use reflect::Ctx;
-pub use reflect::{ApiPath, ApiPaths, Permission, RpcMethod, RpcMethodExt};
+pub use reflect::{ApiPath, ApiPaths, Cacheability, Permission, RpcMethod, RpcMethodExt};
 pub use request::Request;
 mod error;
 mod reflect;
@@ -40,6 +48,8 @@ macro_rules! for_each_rpc_method {
     ($callback:path) => {
         // auth vertical
         $callback!($crate::rpc::auth::AuthNew);
+        $callback!($crate::rpc::auth::AuthNewScoped);
+        $callback!($crate::rpc::auth::AuthRevoke);
         $callback!($crate::rpc::auth::AuthVerify);
 
         // beacon vertical
@@ -62,12 +72,19 @@ macro_rules! for_each_rpc_method {
         $callback!($crate::rpc::chain::ChainGetTipSetByHeight);
         $callback!($crate::rpc::chain::ChainHasObj);
         $callback!($crate::rpc::chain::ChainHead);
+        $callback!($crate::rpc::chain::ChainPinTipset);
+        $callback!($crate::rpc::chain::ChainPinnedTipsets);
+        $callback!($crate::rpc::chain::ChainProduceTipset);
+        $callback!($crate::rpc::chain::ChainPrune);
         $callback!($crate::rpc::chain::ChainReadObj);
         $callback!($crate::rpc::chain::ChainSetHead);
         $callback!($crate::rpc::chain::ChainStatObj);
         $callback!($crate::rpc::chain::ChainTipSetWeight);
+        $callback!($crate::rpc::chain::ChainUnpinTipset);
 
         // common vertical
+        $callback!($crate::rpc::common::LogList);
+        $callback!($crate::rpc::common::LogSetLevel);
         $callback!($crate::rpc::common::Session);
         $callback!($crate::rpc::common::Shutdown);
         $callback!($crate::rpc::common::StartTime);
@@ -78,7 +95,10 @@ macro_rules! for_each_rpc_method {
         $callback!($crate::rpc::eth::EthAddressToFilecoinAddress);
         $callback!($crate::rpc::eth::EthBlockNumber);
         $callback!($crate::rpc::eth::EthCall);
+        $callback!($crate::rpc::eth::EthCallMany);
         $callback!($crate::rpc::eth::EthChainId);
+        $callback!($crate::rpc::eth::EthDebugTraceCall);
+        $callback!($crate::rpc::eth::EthDebugTraceTransaction);
         $callback!($crate::rpc::eth::EthEstimateGas);
         $callback!($crate::rpc::eth::EthFeeHistory);
         $callback!($crate::rpc::eth::EthGasPrice);
@@ -111,6 +131,7 @@ macro_rules! for_each_rpc_method {
         $callback!($crate::rpc::eth::EthUninstallFilter);
         $callback!($crate::rpc::eth::EthSyncing);
         $callback!($crate::rpc::eth::EthTraceBlock);
+        $callback!($crate::rpc::eth::EthTraceCall);
         $callback!($crate::rpc::eth::EthTraceFilter);
         $callback!($crate::rpc::eth::EthTraceTransaction);
         $callback!($crate::rpc::eth::EthTraceReplayBlockTransactions);
@@ -133,18 +154,43 @@ macro_rules! for_each_rpc_method {
         // mpool vertical
         $callback!($crate::rpc::mpool::MpoolBatchPush);
         $callback!($crate::rpc::mpool::MpoolBatchPushUntrusted);
+        $callback!($crate::rpc::mpool::MpoolFindStuck);
+        $callback!($crate::rpc::mpool::MpoolGetConfig);
         $callback!($crate::rpc::mpool::MpoolGetNonce);
         $callback!($crate::rpc::mpool::MpoolPending);
         $callback!($crate::rpc::mpool::MpoolPush);
         $callback!($crate::rpc::mpool::MpoolPushMessage);
         $callback!($crate::rpc::mpool::MpoolPushUntrusted);
+        $callback!($crate::rpc::mpool::MpoolReplace);
         $callback!($crate::rpc::mpool::MpoolSelect);
+        $callback!($crate::rpc::mpool::MpoolSetConfig);
 
         // msig vertical
+        $callback!($crate::rpc::msig::MsigApprove);
+        $callback!($crate::rpc::msig::MsigCancel);
+        $callback!($crate::rpc::msig::MsigCreate);
         $callback!($crate::rpc::msig::MsigGetAvailableBalance);
         $callback!($crate::rpc::msig::MsigGetPending);
         $callback!($crate::rpc::msig::MsigGetVested);
         $callback!($crate::rpc::msig::MsigGetVestingSchedule);
+        $callback!($crate::rpc::msig::MsigPropose);
+        $callback!($crate::rpc::msig::MsigSwapApprove);
+        $callback!($crate::rpc::msig::MsigSwapCancel);
+        $callback!($crate::rpc::msig::MsigSwapPropose);
+
+        // paych vertical
+        $callback!($crate::rpc::paych::PaychAllocateLane);
+        $callback!($crate::rpc::paych::PaychAvailableFunds);
+        $callback!($crate::rpc::paych::PaychCollect);
+        $callback!($crate::rpc::paych::PaychGet);
+        $callback!($crate::rpc::paych::PaychGetWaitReady);
+        $callback!($crate::rpc::paych::PaychList);
+        $callback!($crate::rpc::paych::PaychSettle);
+        $callback!($crate::rpc::paych::PaychVoucherAdd);
+        $callback!($crate::rpc::paych::PaychVoucherCheckValid);
+        $callback!($crate::rpc::paych::PaychVoucherCreate);
+        $callback!($crate::rpc::paych::PaychVoucherList);
+        $callback!($crate::rpc::paych::PaychVoucherSubmit);
 
         // net vertical
         $callback!($crate::rpc::net::NetAddrsListen);
@@ -166,10 +212,15 @@ macro_rules! for_each_rpc_method {
 
         // state vertical
         $callback!($crate::rpc::state::StateAccountKey);
+        $callback!($crate::rpc::state::StateActorDiff);
         $callback!($crate::rpc::state::StateCall);
+        $callback!($crate::rpc::state::StateChangedActors);
         $callback!($crate::rpc::state::StateCirculatingSupply);
         $callback!($crate::rpc::state::StateCompute);
         $callback!($crate::rpc::state::StateDealProviderCollateralBounds);
+        $callback!($crate::rpc::state::StateDecodeParams);
+        $callback!($crate::rpc::state::StateDecodeReturn);
+        $callback!($crate::rpc::state::StateEncodeParams);
         $callback!($crate::rpc::state::StateFetchRoot);
         $callback!($crate::rpc::state::StateGetActor);
         $callback!($crate::rpc::state::StateGetAllAllocations);
@@ -222,6 +273,7 @@ macro_rules! for_each_rpc_method {
         $callback!($crate::rpc::state::StateSectorPartition);
         $callback!($crate::rpc::state::StateSectorPreCommitInfo);
         $callback!($crate::rpc::state::StateSectorPreCommitInfoV0);
+        $callback!($crate::rpc::state::StateSimulate);
         $callback!($crate::rpc::state::StateVerifiedClientStatus);
         $callback!($crate::rpc::state::StateVerifiedRegistryRootKey);
         $callback!($crate::rpc::state::StateVerifierStatus);
@@ -360,11 +412,13 @@ mod methods {
     pub mod msig;
     pub mod net;
     pub mod node;
+    pub mod paych;
     pub mod state;
     pub mod sync;
     pub mod wallet;
 }
 
+use crate::cli_shared::cli::RpcBatchConfig;
 use crate::rpc::auth_layer::AuthLayer;
 use crate::rpc::channel::RpcModule as FilRpcModule;
 pub use crate::rpc::channel::CANCEL_METHOD_NAME;
@@ -374,7 +428,10 @@ use crate::{chain_sync::network_context::SyncNetworkContext, key_management::Key
 use crate::blocks::FullTipset;
 use fvm_ipld_blockstore::Blockstore;
 use jsonrpsee::{
-    server::{stop_channel, RpcModule, RpcServiceBuilder, Server, StopHandle, TowerServiceBuilder},
+    server::{
+        stop_channel, BatchRequestConfig, RpcModule, RpcServiceBuilder, Server, StopHandle,
+        TowerServiceBuilder,
+    },
     Methods,
 };
 use once_cell::sync::Lazy;
@@ -411,6 +468,10 @@ pub struct RPCState<DB> {
     pub msgs_in_tipset: Arc<crate::chain::store::MsgsInTipsetCache>,
     pub sync_states: Arc<parking_lot::RwLock<nunny::Vec<crate::chain_sync::SyncState>>>,
     pub eth_event_handler: Arc<EthEventHandler>,
+    pub paych_store: Arc<crate::paychmgr::PaychStore>,
+    pub settings_store: Arc<dyn crate::db::SettingsStore + Sync + Send>,
+    pub chain_pruner: Option<Arc<dyn crate::db::Prune>>,
+    pub block_producer: Option<Arc<crate::chain_sync::BlockProducer<DB>>>,
     pub sync_network_context: SyncNetworkContext<DB>,
     pub network_name: String,
     pub tipset_send: flume::Sender<Arc<FullTipset>>,
@@ -459,12 +520,19 @@ struct PerConnection<RpcMiddleware, HttpMiddleware> {
     stop_handle: StopHandle,
     svc_builder: TowerServiceBuilder<RpcMiddleware, HttpMiddleware>,
     keystore: Arc<RwLock<KeyStore>>,
+    settings_store: Arc<dyn crate::db::SettingsStore + Sync + Send>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    response_cache: Option<Arc<ResponseCache>>,
+    finalized_epoch: cache_layer::FinalizedEpoch,
 }
 
 pub async fn start_rpc<DB>(
     state: RPCState<DB>,
     rpc_endpoint: SocketAddr,
     filter_list: Option<FilterList>,
+    rate_limiter: Option<RateLimiter>,
+    response_cache: Option<ResponseCache>,
+    batch_config: RpcBatchConfig,
 ) -> anyhow::Result<()>
 where
     DB: Blockstore + Send + Sync + 'static,
@@ -473,7 +541,9 @@ where
     // `Arc` is needed because we will share the state between two modules
     let state = Arc::new(state);
     let keystore = state.keystore.clone();
+    let settings_store = state.settings_store.clone();
     let mut module = create_module(state.clone());
+    eth::pubsub::register_eth_subscriptions(&mut module)?;
 
     let mut pubsub_module = FilRpcModule::default();
 
@@ -492,17 +562,29 @@ where
             // Default size (10 MiB) is not enough for methods like `Filecoin.StateMinerActiveSectors`
             .max_request_body_size(MAX_REQUEST_BODY_SIZE)
             .max_response_body_size(MAX_RESPONSE_BODY_SIZE)
+            .set_id_provider(eth::pubsub::EthSubscriptionIdProvider)
+            .set_batch_request_config(BatchRequestConfig::Limit(batch_config.max_batch_size))
             .to_service_builder(),
         keystore,
+        settings_store,
+        rate_limiter: rate_limiter.map(Arc::new),
+        response_cache: response_cache.map(Arc::new),
+        finalized_epoch: {
+            let state = state.clone();
+            Arc::new(move || {
+                state.chain_store().heaviest_tipset().epoch()
+                    - state.chain_config().policy.chain_finality
+            })
+        },
     };
 
     let listener = tokio::net::TcpListener::bind(rpc_endpoint).await.unwrap();
     tracing::info!("Ready for RPC connections");
     loop {
-        let sock = tokio::select! {
+        let (sock, remote_addr) = tokio::select! {
         res = listener.accept() => {
             match res {
-              Ok((stream, _remote_addr)) => stream,
+              Ok(accepted) => accepted,
               Err(e) => {
                 tracing::error!("failed to accept v4 connection: {:?}", e);
                 continue;
@@ -515,6 +597,7 @@ where
         let svc = tower::service_fn({
             let per_conn = per_conn.clone();
             let filter_list = filter_list.clone();
+            let batch_config = batch_config.clone();
             move |req| {
                 let is_websocket = jsonrpsee::server::ws::is_upgrade_request(&req);
                 let PerConnection {
@@ -522,24 +605,32 @@ where
                     stop_handle,
                     svc_builder,
                     keystore,
+                    settings_store,
+                    rate_limiter,
+                    response_cache,
+                    finalized_epoch,
                 } = per_conn.clone();
                 let http_middleware = tower::ServiceBuilder::new()
                     .layer(CompressionLayer::new())
                     // Mark the `Authorization` request header as sensitive so it doesn't show in logs
                     .layer(SetSensitiveRequestHeadersLayer::new(std::iter::once(
                         http::header::AUTHORIZATION,
-                    )));
+                    )))
+                    .layer(BatchLayer::new(batch_config.clone()));
                 // NOTE, the rpc middleware must be initialized here to be able to created once per connection
                 // with data from the connection such as the headers in this example
                 let headers = req.headers().clone();
                 let rpc_middleware = RpcServiceBuilder::new()
                     .layer(FilterLayer::new(filter_list.clone()))
                     .layer(AuthLayer {
-                        headers,
+                        headers: headers.clone(),
                         keystore: keystore.clone(),
+                        settings_store: settings_store.clone(),
                     })
-                    .layer(LogLayer::default())
-                    .layer(MetricsLayer::default());
+                    .layer(RateLimitLayer::new(rate_limiter, remote_addr.ip()))
+                    .layer(LogLayer::new(headers))
+                    .layer(MetricsLayer::default())
+                    .layer(CacheLayer::new(response_cache, finalized_epoch));
                 let mut jsonrpsee_svc = svc_builder
                     .set_http_middleware(http_middleware)
                     .set_rpc_middleware(rpc_middleware)
@@ -0,0 +1,364 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Middleware layer for throttling RPC calls.
+//!
+//! Each client, identified by the `sub` claim of its JWT or by its IP address (its `/64` prefix for
+//! IPv6), gets a token bucket
+//! refilled at [`RpcRateLimitConfig::requests_per_second`] and holding up to
+//! [`RpcRateLimitConfig::burst`] tokens. A call spends as many tokens as the weight of its method,
+//! and is rejected if the bucket doesn't hold enough of them or if the client already has too many
+//! calls in progress.
+
+use std::net::{IpAddr, Ipv6Addr};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Instant;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use jsonrpsee::server::middleware::rpc::RpcServiceT;
+use jsonrpsee::types::ErrorObject;
+use jsonrpsee::MethodResponse;
+use lru::LruCache;
+use nonzero_ext::nonzero;
+use parking_lot::Mutex;
+use tower::Layer;
+
+use super::auth_layer::{canonical_method_name, TokenSubject};
+use crate::cli_shared::cli::RpcRateLimitConfig;
+use crate::metrics;
+
+/// Number of clients above which the least recently seen ones are forgotten.
+const MAX_TRACKED_CLIENTS: NonZeroUsize = nonzero!(10_000_usize);
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum ClientKey {
+    Subject(String),
+    Ip(IpAddr),
+}
+
+impl ClientKey {
+    /// Key of the clients connecting from `ip`. IPv6 clients are keyed by their `/64` prefix, as
+    /// a single host usually gets a whole prefix and could otherwise evade the limits.
+    fn from_ip(ip: IpAddr) -> Self {
+        match ip {
+            IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+                Some(ip) => Self::Ip(IpAddr::V4(ip)),
+                None => Self::Ip(IpAddr::V6(Ipv6Addr::from(
+                    u128::from(ip) & !u128::from(u64::MAX),
+                ))),
+            },
+            ip => Self::Ip(ip),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ClientState {
+    tokens: f64,
+    updated_at: Instant,
+    in_flight: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Throttled {
+    Rate,
+    Concurrency,
+}
+
+impl Throttled {
+    fn reason(self) -> &'static str {
+        match self {
+            Self::Rate => "rate",
+            Self::Concurrency => "concurrency",
+        }
+    }
+
+    fn message(self) -> &'static str {
+        match self {
+            Self::Rate => "Too Many Requests: rate limit exceeded",
+            Self::Concurrency => "Too Many Requests: too many concurrent requests",
+        }
+    }
+}
+
+/// Rate and concurrency limits of the RPC clients, shared by all connections.
+pub struct RateLimiter {
+    config: RpcRateLimitConfig,
+    clients: Mutex<LruCache<ClientKey, ClientState>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RpcRateLimitConfig) -> Self {
+        Self {
+            config,
+            clients: Mutex::new(LruCache::new(MAX_TRACKED_CLIENTS)),
+        }
+    }
+
+    /// Returns the number of tokens a call to `method` spends. Weights are capped to the burst so
+    /// that expensive methods can still be called by idle clients. Aliases weigh as much as the
+    /// methods they stand for.
+    fn weight(&self, method: &str) -> f64 {
+        let weight = self
+            .config
+            .method_weights
+            .get(canonical_method_name(method))
+            .copied()
+            .unwrap_or(1);
+        f64::from(weight.min(self.config.burst))
+    }
+
+    fn acquire(
+        self: &Arc<Self>,
+        key: ClientKey,
+        method: &str,
+        now: Instant,
+    ) -> Result<Permit, Throttled> {
+        let RpcRateLimitConfig {
+            requests_per_second,
+            burst,
+            max_concurrent_requests,
+            ..
+        } = self.config;
+        let weight = self.weight(method);
+        let mut clients = self.clients.lock();
+        let state = clients.get_or_insert_mut(key.clone(), || ClientState {
+            tokens: f64::from(burst),
+            updated_at: now,
+            in_flight: 0,
+        });
+        state.refill(now, requests_per_second, burst);
+        if max_concurrent_requests > 0 && state.in_flight >= max_concurrent_requests {
+            return Err(Throttled::Concurrency);
+        }
+        if requests_per_second > 0 {
+            if state.tokens < weight {
+                return Err(Throttled::Rate);
+            }
+            state.tokens -= weight;
+        }
+        state.in_flight += 1;
+        Ok(Permit {
+            limiter: self.clone(),
+            key,
+        })
+    }
+}
+
+impl ClientState {
+    fn refill(&mut self, now: Instant, requests_per_second: u32, burst: u32) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * f64::from(requests_per_second)).min(f64::from(burst));
+        self.updated_at = self.updated_at.max(now);
+    }
+}
+
+/// A call in progress, counted towards the concurrency limit of its client until dropped.
+struct Permit {
+    limiter: Arc<RateLimiter>,
+    key: ClientKey,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if let Some(state) = self.limiter.clients.lock().peek_mut(&self.key) {
+            state.in_flight = state.in_flight.saturating_sub(1);
+        }
+    }
+}
+
+/// JSON-RPC middleware layer throttling calls. Must be applied after the [`super::AuthLayer`],
+/// which provides the JWT subject of the calls.
+#[derive(Clone)]
+pub(super) struct RateLimitLayer {
+    limiter: Option<Arc<RateLimiter>>,
+    remote_ip: IpAddr,
+}
+
+impl RateLimitLayer {
+    pub fn new(limiter: Option<Arc<RateLimiter>>, remote_ip: IpAddr) -> Self {
+        Self { limiter, remote_ip }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimiting<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        RateLimiting {
+            service,
+            limiter: self.limiter.clone(),
+            remote_ip: self.remote_ip,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct RateLimiting<S> {
+    service: S,
+    limiter: Option<Arc<RateLimiter>>,
+    remote_ip: IpAddr,
+}
+
+impl<'a, S> RpcServiceT<'a> for RateLimiting<S>
+where
+    S: RpcServiceT<'a> + Send + Sync + Clone + 'static,
+{
+    type Future = BoxFuture<'a, MethodResponse>;
+
+    fn call(&self, req: jsonrpsee::types::Request<'a>) -> Self::Future {
+        let service = self.service.clone();
+        let Some(limiter) = &self.limiter else {
+            return async move { service.call(req).await }.boxed();
+        };
+        let key = match req.extensions().get::<TokenSubject>() {
+            Some(TokenSubject(subject)) => ClientKey::Subject(subject.clone()),
+            None => ClientKey::from_ip(self.remote_ip),
+        };
+        let permit = limiter.acquire(key, req.method_name(), Instant::now());
+        async move {
+            match permit {
+                Ok(_permit) => service.call(req).await,
+                Err(throttled) => {
+                    metrics::RPC_METHOD_THROTTLED
+                        .get_or_create(&metrics::RpcThrottledLabel {
+                            method: req.method_name().to_owned(),
+                            reason: throttled.reason(),
+                        })
+                        .inc();
+                    MethodResponse::error(
+                        req.id(),
+                        ErrorObject::borrowed(
+                            http::StatusCode::TOO_MANY_REQUESTS.as_u16() as _,
+                            throttled.message(),
+                            None,
+                        ),
+                    )
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    fn limiter(
+        requests_per_second: u32,
+        burst: u32,
+        max_concurrent_requests: u32,
+    ) -> Arc<RateLimiter> {
+        Arc::new(RateLimiter::new(RpcRateLimitConfig {
+            enabled: true,
+            requests_per_second,
+            burst,
+            max_concurrent_requests,
+            method_weights: [("Filecoin.StateMarketDeals".to_owned(), 5)].into(),
+        }))
+    }
+
+    #[test]
+    fn rate_limit_refills() {
+        let limiter = limiter(2, 4, 0);
+        let key = ClientKey::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let now = Instant::now();
+        for _ in 0..4 {
+            limiter
+                .acquire(key.clone(), "Filecoin.ChainHead", now)
+                .unwrap();
+        }
+        assert_eq!(
+            limiter
+                .acquire(key.clone(), "Filecoin.ChainHead", now)
+                .err(),
+            Some(Throttled::Rate)
+        );
+        // Other clients have their own bucket
+        limiter
+            .acquire(
+                ClientKey::Subject("alice".into()),
+                "Filecoin.ChainHead",
+                now,
+            )
+            .unwrap();
+        // Two tokens per second
+        let later = now + Duration::from_secs(1);
+        limiter
+            .acquire(key.clone(), "Filecoin.ChainHead", later)
+            .unwrap();
+        limiter
+            .acquire(key.clone(), "Filecoin.ChainHead", later)
+            .unwrap();
+        assert!(limiter.acquire(key, "Filecoin.ChainHead", later).is_err());
+    }
+
+    #[test]
+    fn rate_limit_weights() {
+        let limiter = limiter(1, 4, 0);
+        let key = ClientKey::Subject("alice".into());
+        let now = Instant::now();
+        // The weight is capped to the burst
+        limiter
+            .acquire(key.clone(), "Filecoin.StateMarketDeals", now)
+            .unwrap();
+        assert_eq!(
+            limiter.acquire(key, "Filecoin.ChainHead", now).err(),
+            Some(Throttled::Rate)
+        );
+    }
+
+    #[test]
+    fn rate_limit_weights_of_aliases() {
+        let limiter = Arc::new(RateLimiter::new(RpcRateLimitConfig {
+            enabled: true,
+            requests_per_second: 1,
+            burst: 4,
+            max_concurrent_requests: 0,
+            method_weights: [("Filecoin.EthCall".to_owned(), 4)].into(),
+        }));
+        let key = ClientKey::Subject("alice".into());
+        let now = Instant::now();
+        limiter.acquire(key.clone(), "eth_call", now).unwrap();
+        assert_eq!(
+            limiter.acquire(key, "Filecoin.ChainHead", now).err(),
+            Some(Throttled::Rate)
+        );
+    }
+
+    #[test]
+    fn ipv6_clients_share_their_prefix() {
+        let key = |ip: &str| ClientKey::from_ip(ip.parse().unwrap());
+        assert_eq!(key("2001:db8:1:2:3:4:5:6"), key("2001:db8:1:2::7"));
+        assert_ne!(key("2001:db8:1:2::1"), key("2001:db8:1:3::1"));
+        assert_eq!(key("::ffff:10.0.0.1"), key("10.0.0.1"));
+        assert_ne!(key("10.0.0.1"), key("10.0.0.2"));
+    }
+
+    #[test]
+    fn concurrency_limit() {
+        let limiter = limiter(0, 0, 2);
+        let key = ClientKey::Subject("alice".into());
+        let now = Instant::now();
+        let first = limiter
+            .acquire(key.clone(), "Filecoin.ChainHead", now)
+            .unwrap();
+        let _second = limiter
+            .acquire(key.clone(), "Filecoin.ChainHead", now)
+            .unwrap();
+        assert_eq!(
+            limiter
+                .acquire(key.clone(), "Filecoin.ChainHead", now)
+                .err(),
+            Some(Throttled::Concurrency)
+        );
+        drop(first);
+        limiter.acquire(key, "Filecoin.ChainHead", now).unwrap();
+    }
+}
@@ -69,6 +69,8 @@ pub trait RpcMethod<const ARITY: usize> {
     const SUMMARY: Option<&'static str> = None;
     /// Becomes [`openrpc_types::Method::description`].
     const DESCRIPTION: Option<&'static str> = None;
+    /// See [`Cacheability`].
+    const CACHEABILITY: Cacheability = Cacheability::Never;
     /// Types of each argument. [`Option`]-al arguments MUST follow mandatory ones.
     type Params: Params<ARITY>;
     /// Return value of this method.
@@ -93,6 +95,22 @@ pub enum Permission {
     Read,
 }
 
+/// When the result of an RPC method can be stored by the response cache.
+///
+/// Parameters are referred to by position, and calls with parameters by name are never cached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cacheability {
+    /// The result may change over time.
+    Never,
+    /// The result only depends on the parameters, e.g. content-addressed data.
+    Immutable,
+    /// The result only depends on the parameters if the given one is a non-empty tipset key.
+    AtTipset(usize),
+    /// The result only depends on the parameters if the given one is a final epoch, or an
+    /// Ethereum block number of a final epoch.
+    AtEpoch(usize),
+}
+
 /// Which paths should this method be exposed on?
 ///
 /// This information is important when using [`crate::rpc::client`].
@@ -400,8 +418,8 @@ do_impls!(1, T0);
 do_impls!(2, T0, T1);
 do_impls!(3, T0, T1, T2);
 do_impls!(4, T0, T1, T2, T3);
-// do_impls!(5, T0, T1, T2, T3, T4);
-// do_impls!(6, T0, T1, T2, T3, T4, T5);
+do_impls!(5, T0, T1, T2, T3, T4);
+do_impls!(6, T0, T1, T2, T3, T4, T5);
 // do_impls!(7, T0, T1, T2, T3, T4, T5, T6);
 // do_impls!(8, T0, T1, T2, T3, T4, T5, T6, T7);
 // do_impls!(9, T0, T1, T2, T3, T4, T5, T6, T7, T8);
@@ -25,7 +25,7 @@ use crate::shim::{
     clock::ChainEpoch,
     deal::DealID,
     econ::TokenAmount,
-    executor::Receipt,
+    executor::{Receipt, StampedEvent},
     fvm_shared_latest::MethodNum,
     message::Message,
     sector::{ExtendedSectorInfo, RegisteredSealProof, SectorNumber, StoragePower},
@@ -495,6 +495,19 @@ pub struct Transaction {
 
 lotus_json_with_self!(Transaction);
 
+/// An unsigned message built by the node, to be signed and pushed by the caller.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct MessagePrototype {
+    #[schemars(with = "LotusJson<Message>")]
+    #[serde(with = "crate::lotus_json")]
+    pub message: Message,
+    /// Whether the nonce of the message has been set.
+    pub valid_nonce: bool,
+}
+
+lotus_json_with_self!(MessagePrototype);
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "PascalCase")]
 pub struct DealCollateralBounds {
@@ -544,3 +557,34 @@ pub struct EventEntry {
     pub codec: u64,
     pub value: LotusJson<Vec<u8>>,
 }
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Event {
+    /// Actor ID
+    pub emitter: u64,
+    pub entries: Vec<EventEntry>,
+}
+lotus_json_with_self!(Event);
+
+impl From<&StampedEvent> for Event {
+    fn from(event: &StampedEvent) -> Self {
+        Self {
+            emitter: event.emitter(),
+            entries: event
+                .event()
+                .entries()
+                .into_iter()
+                .map(|entry| {
+                    let (flags, key, codec, value) = entry.into_parts();
+                    EventEntry {
+                        flags,
+                        key,
+                        codec,
+                        value: value.into(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
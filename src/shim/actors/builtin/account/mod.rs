@@ -1,6 +1,8 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+pub mod params;
+
 use super::super::convert::{from_address_v3_to_v2, from_address_v4_to_v2};
 use fvm_shared2::address::Address;
 use serde::Serialize;
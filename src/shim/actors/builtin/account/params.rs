@@ -0,0 +1,39 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Parameters and return values of the account actor methods. The layouts have not changed since
+//! the actors version that introduced each method.
+
+use crate::shim::address::Address;
+use fvm_ipld_encoding::strict_bytes;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+
+/// Parameters of [`super::Method::Constructor`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ConstructorParams {
+    pub address: Address,
+}
+
+/// Return value of [`super::Method::PubkeyAddress`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct PubkeyAddressReturn {
+    pub address: Address,
+}
+
+/// Parameters of `AuthenticateMessage`, since actors `v9`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct AuthenticateMessageParams {
+    #[serde(with = "strict_bytes")]
+    pub signature: Vec<u8>,
+    #[serde(with = "strict_bytes")]
+    pub message: Vec<u8>,
+}
+
+/// Return value of `AuthenticateMessage`, since actors `v11`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct AuthenticateMessageReturn {
+    pub authenticated: bool,
+}
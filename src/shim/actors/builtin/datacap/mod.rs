@@ -1,6 +1,8 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+pub mod params;
+
 use anyhow::anyhow;
 use fil_actor_datacap_state::v12::DATACAP_GRANULARITY;
 use fil_actors_shared::ext::TokenStateExt;
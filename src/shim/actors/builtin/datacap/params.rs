@@ -0,0 +1,134 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Parameters and return values of the datacap actor methods, which implement an `FRC-0046`
+//! token. The layouts have not changed since actors `v9`, only the method numbers did in `v10`.
+
+use crate::shim::address::Address;
+use crate::shim::econ::TokenAmount;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use fvm_ipld_encoding::RawBytes;
+
+/// Parameters of `Mint`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct MintParams {
+    /// Recipient of the newly minted tokens.
+    pub to: Address,
+    /// Amount of tokens to mint.
+    pub amount: TokenAmount,
+    /// Addresses to be granted effectively-infinite operator allowance for the recipient.
+    pub operators: Vec<Address>,
+}
+
+/// Return value of `Mint`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct MintReturn {
+    /// The new balance of the recipient.
+    pub balance: TokenAmount,
+    /// The new total supply.
+    pub supply: TokenAmount,
+    /// Data returned by the receiver hook of the recipient.
+    pub recipient_data: RawBytes,
+}
+
+/// Parameters of `Destroy`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct DestroyParams {
+    pub owner: Address,
+    pub amount: TokenAmount,
+}
+
+/// Parameters of `Transfer`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct TransferParams {
+    pub to: Address,
+    pub amount: TokenAmount,
+    /// Data passed to the receiver hook of the recipient.
+    pub operator_data: RawBytes,
+}
+
+/// Return value of `Transfer`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct TransferReturn {
+    pub from_balance: TokenAmount,
+    pub to_balance: TokenAmount,
+    /// Data returned by the receiver hook of the recipient.
+    pub recipient_data: RawBytes,
+}
+
+/// Parameters of `TransferFrom`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct TransferFromParams {
+    pub from: Address,
+    pub to: Address,
+    pub amount: TokenAmount,
+    /// Data passed to the receiver hook of the recipient.
+    pub operator_data: RawBytes,
+}
+
+/// Return value of `TransferFrom`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct TransferFromReturn {
+    pub from_balance: TokenAmount,
+    pub to_balance: TokenAmount,
+    /// The remaining allowance of the operator.
+    pub allowance: TokenAmount,
+    /// Data returned by the receiver hook of the recipient.
+    pub recipient_data: RawBytes,
+}
+
+/// Parameters of `IncreaseAllowance`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct IncreaseAllowanceParams {
+    pub operator: Address,
+    pub increase: TokenAmount,
+}
+
+/// Parameters of `DecreaseAllowance`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct DecreaseAllowanceParams {
+    pub operator: Address,
+    pub decrease: TokenAmount,
+}
+
+/// Parameters of `RevokeAllowance`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct RevokeAllowanceParams {
+    pub operator: Address,
+}
+
+/// Parameters of `Allowance`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetAllowanceParams {
+    pub owner: Address,
+    pub operator: Address,
+}
+
+/// Parameters of `Burn`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct BurnParams {
+    pub amount: TokenAmount,
+}
+
+/// Return value of `Burn` and `Destroy`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct BurnReturn {
+    /// The new balance of the owner.
+    pub balance: TokenAmount,
+}
+
+/// Parameters of `BurnFrom`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct BurnFromParams {
+    pub owner: Address,
+    pub amount: TokenAmount,
+}
+
+/// Return value of `BurnFrom`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct BurnFromReturn {
+    /// The new balance of the owner.
+    pub balance: TokenAmount,
+    /// The remaining allowance of the operator.
+    pub allowance: TokenAmount,
+}
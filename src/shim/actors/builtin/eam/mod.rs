@@ -1,4 +1,9 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+pub mod params;
+
+/// EAM actor method.
+pub type Method = fil_actor_eam_state::v10::Method;
+
 pub type CreateExternalReturn = fil_actor_eam_state::v16::CreateExternalReturn;
@@ -0,0 +1,46 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Parameters and return values of the Ethereum address manager actor methods. The layouts have
+//! not changed since actors `v10`.
+
+use crate::shim::address::Address;
+use fvm_ipld_encoding::strict_bytes;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+
+/// Parameters of [`super::Method::Create`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct CreateParams {
+    #[serde(with = "strict_bytes")]
+    pub initcode: Vec<u8>,
+    pub nonce: u64,
+}
+
+/// Parameters of [`super::Method::Create2`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct Create2Params {
+    #[serde(with = "strict_bytes")]
+    pub initcode: Vec<u8>,
+    /// 32 bytes of salt.
+    #[serde(with = "strict_bytes")]
+    pub salt: Vec<u8>,
+}
+
+/// Parameters of [`super::Method::CreateExternal`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct CreateExternalParams {
+    #[serde(with = "strict_bytes")]
+    pub initcode: Vec<u8>,
+}
+
+/// Return value of [`super::Method::Create`], [`super::Method::Create2`] and
+/// [`super::Method::CreateExternal`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct CreateReturn {
+    pub actor_id: u64,
+    pub robust_address: Option<Address>,
+    /// The 20 byte Ethereum address of the new actor.
+    #[serde(with = "strict_bytes")]
+    pub eth_address: Vec<u8>,
+}
@@ -1,6 +1,9 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+pub mod params;
+
+use anyhow::Context as _;
 use serde::Serialize;
 
 /// EVM actor method.
@@ -20,6 +23,72 @@ pub enum State {
 }
 
 impl State {
+    /// Creates the state of a live contract with the given bytecode, for the EVM actor with the
+    /// given code.
+    pub fn new(
+        code: &cid::Cid,
+        bytecode: cid::Cid,
+        bytecode_hash: [u8; 32],
+        contract_state: cid::Cid,
+    ) -> anyhow::Result<Self> {
+        let version = (10..=16)
+            .find(|version| crate::shim::actors::is_evm_cid_version(code, *version))
+            .with_context(|| format!("unknown EVM actor code {code}"))?;
+        Ok(match version {
+            10 => State::V10(fil_actor_evm_state::v10::State {
+                bytecode,
+                bytecode_hash: bytecode_hash.into(),
+                contract_state,
+                nonce: 0,
+                tombstone: None,
+            }),
+            11 => State::V11(fil_actor_evm_state::v11::State {
+                bytecode,
+                bytecode_hash: bytecode_hash.into(),
+                contract_state,
+                nonce: 0,
+                tombstone: None,
+            }),
+            12 => State::V12(fil_actor_evm_state::v12::State {
+                bytecode,
+                bytecode_hash: bytecode_hash.into(),
+                contract_state,
+                nonce: 0,
+                tombstone: None,
+            }),
+            13 => State::V13(fil_actor_evm_state::v13::State {
+                bytecode,
+                bytecode_hash: bytecode_hash.into(),
+                contract_state,
+                nonce: 0,
+                tombstone: None,
+            }),
+            14 => State::V14(fil_actor_evm_state::v14::State {
+                bytecode,
+                bytecode_hash: bytecode_hash.into(),
+                contract_state,
+                nonce: 0,
+                tombstone: None,
+            }),
+            15 => State::V15(fil_actor_evm_state::v15::State {
+                bytecode,
+                bytecode_hash: bytecode_hash.into(),
+                contract_state,
+                nonce: 0,
+                tombstone: None,
+            }),
+            16 => State::V16(fil_actor_evm_state::v16::State {
+                bytecode,
+                bytecode_hash: bytecode_hash.into(),
+                contract_state,
+                transient_data: None,
+                nonce: 0,
+                tombstone: None,
+            }),
+            _ => unreachable!("EVM actor versions are checked above"),
+        })
+    }
+
     pub fn nonce(&self) -> u64 {
         match self {
             State::V10(st) => st.nonce,
@@ -32,6 +101,102 @@ impl State {
         }
     }
 
+    /// Returns the CID of the contract bytecode.
+    pub fn bytecode(&self) -> cid::Cid {
+        match self {
+            State::V10(st) => st.bytecode,
+            State::V11(st) => st.bytecode,
+            State::V12(st) => st.bytecode,
+            State::V13(st) => st.bytecode,
+            State::V14(st) => st.bytecode,
+            State::V15(st) => st.bytecode,
+            State::V16(st) => st.bytecode,
+        }
+    }
+
+    /// Returns the Keccak-256 hash of the contract bytecode.
+    pub fn bytecode_hash(&self) -> [u8; 32] {
+        match self {
+            State::V10(st) => st.bytecode_hash.into(),
+            State::V11(st) => st.bytecode_hash.into(),
+            State::V12(st) => st.bytecode_hash.into(),
+            State::V13(st) => st.bytecode_hash.into(),
+            State::V14(st) => st.bytecode_hash.into(),
+            State::V15(st) => st.bytecode_hash.into(),
+            State::V16(st) => st.bytecode_hash.into(),
+        }
+    }
+
+    /// Returns the CID of the contract storage KAMT.
+    pub fn contract_state(&self) -> cid::Cid {
+        match self {
+            State::V10(st) => st.contract_state,
+            State::V11(st) => st.contract_state,
+            State::V12(st) => st.contract_state,
+            State::V13(st) => st.contract_state,
+            State::V14(st) => st.contract_state,
+            State::V15(st) => st.contract_state,
+            State::V16(st) => st.contract_state,
+        }
+    }
+
+    pub fn set_nonce(&mut self, nonce: u64) {
+        match self {
+            State::V10(st) => st.nonce = nonce,
+            State::V11(st) => st.nonce = nonce,
+            State::V12(st) => st.nonce = nonce,
+            State::V13(st) => st.nonce = nonce,
+            State::V14(st) => st.nonce = nonce,
+            State::V15(st) => st.nonce = nonce,
+            State::V16(st) => st.nonce = nonce,
+        }
+    }
+
+    pub fn set_bytecode(&mut self, bytecode: cid::Cid, bytecode_hash: [u8; 32]) {
+        match self {
+            State::V10(st) => {
+                st.bytecode = bytecode;
+                st.bytecode_hash = bytecode_hash.into();
+            }
+            State::V11(st) => {
+                st.bytecode = bytecode;
+                st.bytecode_hash = bytecode_hash.into();
+            }
+            State::V12(st) => {
+                st.bytecode = bytecode;
+                st.bytecode_hash = bytecode_hash.into();
+            }
+            State::V13(st) => {
+                st.bytecode = bytecode;
+                st.bytecode_hash = bytecode_hash.into();
+            }
+            State::V14(st) => {
+                st.bytecode = bytecode;
+                st.bytecode_hash = bytecode_hash.into();
+            }
+            State::V15(st) => {
+                st.bytecode = bytecode;
+                st.bytecode_hash = bytecode_hash.into();
+            }
+            State::V16(st) => {
+                st.bytecode = bytecode;
+                st.bytecode_hash = bytecode_hash.into();
+            }
+        }
+    }
+
+    pub fn set_contract_state(&mut self, contract_state: cid::Cid) {
+        match self {
+            State::V10(st) => st.contract_state = contract_state,
+            State::V11(st) => st.contract_state = contract_state,
+            State::V12(st) => st.contract_state = contract_state,
+            State::V13(st) => st.contract_state = contract_state,
+            State::V14(st) => st.contract_state = contract_state,
+            State::V15(st) => st.contract_state = contract_state,
+            State::V16(st) => st.contract_state = contract_state,
+        }
+    }
+
     pub fn is_alive(&self) -> bool {
         match self {
             State::V10(st) => st.tombstone.is_none(),
@@ -0,0 +1,49 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Parameters and return values of the EVM actor methods. The layouts have not changed since
+//! actors `v10`.
+
+use cid::Cid;
+use fvm_ipld_encoding::strict_bytes;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+
+/// Parameters of [`super::Method::InvokeContract`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct InvokeContractParams {
+    #[serde(with = "strict_bytes")]
+    pub input_data: Vec<u8>,
+}
+
+/// Return value of [`super::Method::InvokeContract`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct InvokeContractReturn {
+    #[serde(with = "strict_bytes")]
+    pub output_data: Vec<u8>,
+}
+
+/// Return value of [`super::Method::GetBytecode`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct BytecodeReturn {
+    pub code: Option<Cid>,
+}
+
+/// Parameters of [`super::Method::GetStorageAt`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetStorageAtParams {
+    /// The storage slot, as a big-endian 256-bit integer without leading zeros.
+    #[serde(with = "strict_bytes")]
+    pub storage_key: Vec<u8>,
+}
+
+/// Return value of [`super::Method::GetStorageAt`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetStorageAtReturn {
+    /// The stored value, as a big-endian 256-bit integer without leading zeros.
+    #[serde(with = "strict_bytes")]
+    pub storage: Vec<u8>,
+}
@@ -1,6 +1,8 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+pub mod params;
+
 use fvm_shared2::address::Address;
 use serde::Serialize;
 
@@ -0,0 +1,75 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Parameters and return values of the init actor methods. The layouts have not changed since
+//! the actors version that introduced each method.
+
+use crate::shim::address::Address;
+use cid::Cid;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use fvm_ipld_encoding::RawBytes;
+
+/// Parameters of [`super::Method::Constructor`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ConstructorParams {
+    pub network_name: String,
+}
+
+/// Parameters of [`super::Method::Exec`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ExecParams {
+    pub code_cid: Cid,
+    pub constructor_params: RawBytes,
+}
+
+/// Return value of [`super::Method::Exec`] and `Exec4`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ExecReturn {
+    /// ID address of the new actor
+    pub id_address: Address,
+    /// Robust address of the new actor
+    pub robust_address: Address,
+}
+
+/// Parameters of `Exec4`, since actors `v10`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct Exec4Params {
+    pub code_cid: Cid,
+    pub constructor_params: RawBytes,
+    pub subaddress: RawBytes,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::multihash::prelude::*;
+
+    #[test]
+    fn exec_layout_matches_actors() {
+        let code_cid = Cid::new_v1(
+            fvm_ipld_encoding::IPLD_RAW,
+            MultihashCode::Identity.digest(b"paych"),
+        );
+        let params = ExecParams {
+            code_cid,
+            constructor_params: RawBytes::new(vec![1, 2, 3]),
+        };
+        let actors_params = fil_actor_init_state::v16::ExecParams {
+            code_cid,
+            constructor_params: RawBytes::new(vec![1, 2, 3]),
+        };
+        assert_eq!(
+            fvm_ipld_encoding::to_vec(&params).unwrap(),
+            fvm_ipld_encoding::to_vec(&actors_params).unwrap()
+        );
+
+        let ret = fil_actor_init_state::v8::ExecReturn {
+            id_address: fvm_shared2::address::Address::new_id(1000),
+            robust_address: fvm_shared2::address::Address::new_actor(b"paych"),
+        };
+        let decoded: ExecReturn =
+            fvm_ipld_encoding::from_slice(&fvm_ipld_encoding::to_vec(&ret).unwrap()).unwrap();
+        assert_eq!(decoded.id_address, Address::new_id(1000));
+        assert_eq!(decoded.robust_address, Address::new_actor(b"paych"));
+    }
+}
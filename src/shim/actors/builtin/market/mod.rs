@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 pub mod ext;
+pub mod params;
 
 use crate::shim::actors::convert::{from_address_v2_to_v3, from_address_v2_to_v4};
 use crate::shim::actors::convert::{
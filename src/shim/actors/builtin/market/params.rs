@@ -0,0 +1,107 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Parameters and return values of the market actor methods. The layouts have not changed since
+//! actors `v8`.
+
+use crate::shim::address::Address;
+use crate::shim::clock::ChainEpoch;
+use crate::shim::crypto::Signature;
+use crate::shim::econ::TokenAmount;
+use cid::Cid;
+use fil_actors_shared::fvm_ipld_bitfield::BitField;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use fvm_ipld_encoding::BytesSer;
+use ipld_core::ipld::Ipld;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Parameters of [`super::Method::AddBalance`], a plain address before actors `v11`, which has the
+/// same encoding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct AddBalanceParams {
+    pub provider_or_client: Address,
+}
+
+/// Parameters of [`super::Method::WithdrawBalance`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct WithdrawBalanceParams {
+    pub provider_or_client: Address,
+    pub amount: TokenAmount,
+}
+
+/// Return value of [`super::Method::WithdrawBalance`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct WithdrawBalanceReturn {
+    pub amount_withdrawn: TokenAmount,
+}
+
+/// Parameters of [`super::Method::PublishStorageDeals`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct PublishStorageDealsParams {
+    pub deals: Vec<ClientDealProposal>,
+}
+
+/// A [`DealProposal`] signed by the client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ClientDealProposal {
+    pub proposal: DealProposal,
+    pub client_signature: Signature,
+}
+
+/// A storage deal proposal, in the layout of the actors rather than that of
+/// [`super::DealProposal`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct DealProposal {
+    pub piece_cid: Cid,
+    /// Padded piece size.
+    pub piece_size: u64,
+    pub verified_deal: bool,
+    pub client: Address,
+    pub provider: Address,
+    /// Arbitrary client chosen label to apply to the deal.
+    pub label: Label,
+    pub start_epoch: ChainEpoch,
+    pub end_epoch: ChainEpoch,
+    pub storage_price_per_epoch: TokenAmount,
+    pub provider_collateral: TokenAmount,
+    pub client_collateral: TokenAmount,
+}
+
+/// Label of a [`DealProposal`], encoded as either a string or bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Label {
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+impl Serialize for Label {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Label::String(s) => s.serialize(serializer),
+            Label::Bytes(b) => BytesSer(b).serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Label {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Ipld::deserialize(deserializer)? {
+            Ipld::String(s) => Ok(Label::String(s)),
+            Ipld::Bytes(b) => Ok(Label::Bytes(b)),
+            other => Err(de::Error::custom(format!(
+                "expected a string or bytes label, got {other:?}"
+            ))),
+        }
+    }
+}
+
+/// Return value of [`super::Method::PublishStorageDeals`].
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct PublishStorageDealsReturn {
+    /// Identifiers of the published deals.
+    pub ids: Vec<u64>,
+    /// Indices of the valid proposals among the parameters.
+    pub valid_deals: BitField,
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 pub mod ext;
+pub mod params;
 
 use crate::shim::actors::convert::*;
 use crate::shim::actors::Policy;
@@ -0,0 +1,259 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Parameters and return values of the miner actor methods. The layouts have not changed since
+//! the actors version that introduced each method, apart from bit fields that are validated
+//! since actors `v9`, which does not change their encoding.
+
+use crate::shim::actors::BatchReturn;
+use crate::shim::address::Address;
+use crate::shim::clock::ChainEpoch;
+use crate::shim::econ::TokenAmount;
+use crate::shim::sector::{PoStProof, RegisteredSealProof};
+use cid::Cid;
+use fil_actors_shared::fvm_ipld_bitfield::BitField;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use fvm_ipld_encoding::{strict_bytes, BytesDe, RawBytes};
+use fvm_shared4::sector::RegisteredAggregateProof;
+
+/// Return value of [`super::Method::ControlAddresses`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetControlAddressesReturn {
+    pub owner: Address,
+    pub worker: Address,
+    pub control_addresses: Vec<Address>,
+}
+
+/// Parameters of [`super::Method::ChangeWorkerAddress`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ChangeWorkerAddressParams {
+    pub new_worker: Address,
+    pub new_control_addresses: Vec<Address>,
+}
+
+/// Parameters of [`super::Method::ChangePeerID`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ChangePeerIDParams {
+    #[serde(with = "strict_bytes")]
+    pub new_id: Vec<u8>,
+}
+
+/// Parameters of [`super::Method::ChangeMultiaddrs`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ChangeMultiaddrsParams {
+    pub new_multi_addrs: Vec<BytesDe>,
+}
+
+/// Parameters of [`super::Method::ChangeOwnerAddress`], a plain address before actors `v11`,
+/// which has the same encoding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ChangeOwnerAddressParams {
+    pub new_owner: Address,
+}
+
+/// Parameters of [`super::Method::SubmitWindowedPoSt`].
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct SubmitWindowedPoStParams {
+    /// The deadline index which the submission targets.
+    pub deadline: u64,
+    /// The partitions being proven.
+    pub partitions: Vec<PoStPartition>,
+    /// One proof per distinct registered proof type present in the sectors being proven.
+    pub proofs: Vec<PoStProof>,
+    /// The epoch at which these proofs are being committed to a particular chain.
+    pub chain_commit_epoch: ChainEpoch,
+    /// The ticket randomness at `chain_commit_epoch`.
+    #[serde(with = "strict_bytes")]
+    pub chain_commit_rand: Vec<u8>,
+}
+
+/// A partition proven by [`SubmitWindowedPoStParams`].
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct PoStPartition {
+    /// Partition index within the deadline.
+    pub index: u64,
+    /// Sectors skipped while proving that weren't already declared faulty.
+    pub skipped: BitField,
+}
+
+/// Parameters of [`super::Method::DeclareFaults`].
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct DeclareFaultsParams {
+    pub faults: Vec<FaultDeclaration>,
+}
+
+/// Faulty sectors of a partition, see [`DeclareFaultsParams`].
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct FaultDeclaration {
+    pub deadline: u64,
+    pub partition: u64,
+    pub sectors: BitField,
+}
+
+/// Parameters of [`super::Method::DeclareFaultsRecovered`].
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct DeclareFaultsRecoveredParams {
+    pub recoveries: Vec<RecoveryDeclaration>,
+}
+
+/// Recovered sectors of a partition, see [`DeclareFaultsRecoveredParams`].
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct RecoveryDeclaration {
+    pub deadline: u64,
+    pub partition: u64,
+    pub sectors: BitField,
+}
+
+/// Parameters of [`super::Method::TerminateSectors`].
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct TerminateSectorsParams {
+    pub terminations: Vec<TerminationDeclaration>,
+}
+
+/// Terminated sectors of a partition, see [`TerminateSectorsParams`].
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct TerminationDeclaration {
+    pub deadline: u64,
+    pub partition: u64,
+    pub sectors: BitField,
+}
+
+/// Return value of [`super::Method::TerminateSectors`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct TerminateSectorsReturn {
+    /// Whether all the terminations were processed.
+    pub done: bool,
+}
+
+/// Parameters of [`super::Method::WithdrawBalance`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct WithdrawBalanceParams {
+    pub amount_requested: TokenAmount,
+}
+
+/// Return value of [`super::Method::WithdrawBalance`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct WithdrawBalanceReturn {
+    pub amount_withdrawn: TokenAmount,
+}
+
+/// Parameters of [`super::Method::ProveCommitAggregate`].
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct ProveCommitAggregateParams {
+    pub sector_numbers: BitField,
+    pub aggregate_proof: RawBytes,
+}
+
+/// Parameters of `PreCommitSectorBatch2`, since actors `v9`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct PreCommitSectorBatchParams2 {
+    pub sectors: Vec<SectorPreCommitInfo>,
+}
+
+/// A sector pre-committed by [`PreCommitSectorBatchParams2`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SectorPreCommitInfo {
+    pub seal_proof: RegisteredSealProof,
+    pub sector_number: u64,
+    /// `CommR`
+    pub sealed_cid: Cid,
+    pub seal_rand_epoch: ChainEpoch,
+    pub deal_ids: Vec<u64>,
+    pub expiration: ChainEpoch,
+    /// `CommD`, absent for sectors without data.
+    pub unsealed_cid: Option<Cid>,
+}
+
+/// Parameters of `ProveCommitSectors3`, since actors `v13`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ProveCommitSectors3Params {
+    /// Activation manifest for each sector being proven.
+    pub sector_activations: Vec<SectorActivationManifest>,
+    /// Proofs for each sector, parallel to activation manifests.
+    pub sector_proofs: Vec<RawBytes>,
+    /// Aggregate proof for all sectors, instead of `sector_proofs`.
+    pub aggregate_proof: RawBytes,
+    /// The proof type of `aggregate_proof`.
+    pub aggregate_proof_type: Option<RegisteredAggregateProof>,
+    /// Whether to abort if any sector activation fails.
+    pub require_activation_success: bool,
+    /// Whether to abort if any notification returns a non-zero exit code.
+    pub require_notification_success: bool,
+}
+
+/// The pieces of a sector activated by [`ProveCommitSectors3Params`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SectorActivationManifest {
+    pub sector_number: u64,
+    pub pieces: Vec<PieceActivationManifest>,
+}
+
+/// A piece of a [`SectorActivationManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct PieceActivationManifest {
+    /// Piece data commitment.
+    pub cid: Cid,
+    /// Padded piece size.
+    pub size: u64,
+    /// Identifies a verified allocation to be claimed.
+    pub verified_allocation_key: Option<VerifiedAllocationKey>,
+    /// Synchronous notifications to be sent to other actors after activation.
+    pub notify: Vec<DataActivationNotification>,
+}
+
+/// A verified allocation claimed by a [`PieceActivationManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct VerifiedAllocationKey {
+    pub client: u64,
+    pub id: u64,
+}
+
+/// A notification sent after the activation of a [`PieceActivationManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct DataActivationNotification {
+    /// Actor to be notified.
+    pub address: Address,
+    /// Data to send in the notification.
+    pub payload: RawBytes,
+}
+
+/// Return value of `ProveCommitSectors3`, since actors `v13`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ProveCommitSectors3Return {
+    pub activation_results: BatchReturn,
+}
+
+/// Parameters of `ExtendSectorExpiration2`, since actors `v9`.
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct ExtendSectorExpiration2Params {
+    pub extensions: Vec<ExpirationExtension2>,
+}
+
+/// Sectors of a partition extended by [`ExtendSectorExpiration2Params`].
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct ExpirationExtension2 {
+    pub deadline: u64,
+    pub partition: u64,
+    /// Sectors without verified claims.
+    pub sectors: BitField,
+    pub sectors_with_claims: Vec<SectorClaim>,
+    pub new_expiration: ChainEpoch,
+}
+
+/// The verified claims of a sector extended by [`ExpirationExtension2`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SectorClaim {
+    pub sector_number: u64,
+    pub maintain_claims: Vec<u64>,
+    pub drop_claims: Vec<u64>,
+}
+
+/// Parameters of `ChangeBeneficiary`, since actors `v9`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ChangeBeneficiaryParams {
+    pub new_beneficiary: Address,
+    pub new_quota: TokenAmount,
+    pub new_expiration: ChainEpoch,
+}
@@ -0,0 +1,109 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Parameters and return values of the multisig actor methods. The layouts have not changed
+//! since actors `v8`.
+
+use crate::shim::address::Address;
+use crate::shim::clock::ChainEpoch;
+use crate::shim::econ::TokenAmount;
+use crate::shim::error::ExitCode;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use fvm_ipld_encoding::{strict_bytes, RawBytes};
+
+/// Identifier of a pending multisig transaction.
+pub type TxnID = i64;
+
+/// Parameters of [`super::Method::Constructor`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ConstructorParams {
+    pub signers: Vec<Address>,
+    pub num_approvals_threshold: u64,
+    pub unlock_duration: ChainEpoch,
+    pub start_epoch: ChainEpoch,
+}
+
+/// Parameters of [`super::Method::Propose`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ProposeParams {
+    pub to: Address,
+    pub value: TokenAmount,
+    pub method: u64,
+    pub params: RawBytes,
+}
+
+/// Return value of [`super::Method::Propose`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ProposeReturn {
+    /// Identifier of the proposed transaction.
+    pub txn_id: TxnID,
+    /// Whether the transaction was applied right away.
+    pub applied: bool,
+    /// Exit code of the transaction, if applied.
+    pub code: ExitCode,
+    /// Return value of the transaction, if applied.
+    pub ret: RawBytes,
+}
+
+/// Data hashed to identify a proposed transaction, see [`TxnIDParams::proposal_hash`].
+#[derive(Debug, Serialize_tuple)]
+pub struct ProposalHashData<'a> {
+    /// ID address of the proposer.
+    pub requester: Option<&'a Address>,
+    pub to: &'a Address,
+    pub value: &'a TokenAmount,
+    pub method: &'a u64,
+    pub params: &'a RawBytes,
+}
+
+/// Parameters of [`super::Method::Approve`] and [`super::Method::Cancel`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct TxnIDParams {
+    pub id: TxnID,
+    /// Optional hash of the proposal, to ensure the expected transaction is approved.
+    #[serde(with = "strict_bytes")]
+    pub proposal_hash: Vec<u8>,
+}
+
+/// Return value of [`super::Method::Approve`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ApproveReturn {
+    pub applied: bool,
+    pub code: ExitCode,
+    pub ret: RawBytes,
+}
+
+/// Parameters of [`super::Method::AddSigner`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct AddSignerParams {
+    pub signer: Address,
+    pub increase: bool,
+}
+
+/// Parameters of [`super::Method::RemoveSigner`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct RemoveSignerParams {
+    pub signer: Address,
+    pub decrease: bool,
+}
+
+/// Parameters of [`super::Method::SwapSigner`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SwapSignerParams {
+    pub from: Address,
+    pub to: Address,
+}
+
+/// Parameters of [`super::Method::ChangeNumApprovalsThreshold`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ChangeNumApprovalsThresholdParams {
+    pub new_threshold: u64,
+}
+
+/// Parameters of [`super::Method::LockBalance`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct LockBalanceParams {
+    pub start_epoch: ChainEpoch,
+    pub unlock_duration: ChainEpoch,
+    pub amount: TokenAmount,
+}
@@ -0,0 +1,210 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Payment channel actor types.
+//!
+//! The state and parameter layouts of the payment channel actor have not changed since actors
+//! `v8`, so the same types are used for all supported actor versions.
+
+use crate::shim::actors::is_paymentchannel_actor;
+use crate::shim::address::Address;
+use crate::shim::clock::ChainEpoch;
+use crate::shim::crypto::Signature;
+use crate::shim::econ::TokenAmount;
+use crate::utils::db::CborStoreExt as _;
+use anyhow::Context as _;
+use base64::{prelude::BASE64_URL_SAFE_NO_PAD, Engine as _};
+use cid::Cid;
+use fil_actors_shared::fvm_ipld_amt::Amt;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use fvm_ipld_encoding::{strict_bytes, RawBytes};
+use fvm_shared2::MethodNum;
+use std::collections::BTreeMap;
+
+/// Payment channel actor method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u64)]
+pub enum Method {
+    Constructor = 1,
+    UpdateChannelState = 2,
+    Settle = 3,
+    Collect = 4,
+}
+
+/// Payment channel actor state.
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct State {
+    /// Channel owner, who has funded the actor.
+    pub from: Address,
+    /// Recipient of payouts from the channel.
+    pub to: Address,
+    /// Amount successfully redeemed through the channel, paid out on `Collect`.
+    pub to_send: TokenAmount,
+    /// Height at which the channel can be collected, zero if the channel is not settling.
+    pub settling_at: ChainEpoch,
+    /// Height before which the channel cannot be collected.
+    pub min_settle_height: ChainEpoch,
+    /// Root of the `AMT` of [`LaneState`]s, keyed by lane.
+    pub lane_states: Cid,
+}
+
+impl State {
+    pub fn load<BS: Blockstore>(store: &BS, code: Cid, state: Cid) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            is_paymentchannel_actor(&code),
+            "Unknown actor code {}",
+            code
+        );
+        store.get_cbor_required(&state)
+    }
+
+    /// Returns the states of all the lanes of the channel that have been redeemed at least once.
+    pub fn load_lane_states<BS: Blockstore>(
+        &self,
+        store: &BS,
+    ) -> anyhow::Result<BTreeMap<u64, LaneState>> {
+        let lanes = Amt::<LaneState, _>::load(&self.lane_states, store)?;
+        let mut lane_states = BTreeMap::new();
+        lanes.for_each(|lane, state| {
+            lane_states.insert(lane, state.clone());
+            Ok(())
+        })?;
+        Ok(lane_states)
+    }
+}
+
+/// The redeemed amount and nonce of a payment channel lane.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize_tuple, Deserialize_tuple)]
+pub struct LaneState {
+    pub redeemed: TokenAmount,
+    pub nonce: u64,
+}
+
+/// Parameters of [`Method::Constructor`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ConstructorParams {
+    pub from: Address,
+    pub to: Address,
+}
+
+/// Parameters of [`Method::UpdateChannelState`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct UpdateChannelStateParams {
+    pub sv: SignedVoucher,
+    #[serde(with = "strict_bytes")]
+    pub secret: Vec<u8>,
+}
+
+/// A voucher signed by the channel owner, which can be redeemed by the recipient.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SignedVoucher {
+    /// Address of the payment channel this voucher is valid for.
+    pub channel_addr: Address,
+    /// Minimum epoch before which the voucher cannot be redeemed.
+    pub time_lock_min: ChainEpoch,
+    /// Maximum epoch after which the voucher cannot be redeemed, zero for no limit.
+    pub time_lock_max: ChainEpoch,
+    /// Hash of the secret that must be provided to redeem the voucher, if not empty.
+    #[serde(with = "strict_bytes")]
+    pub secret_hash: Vec<u8>,
+    /// Actor method that must return successfully to redeem the voucher.
+    pub extra: Option<ModVerifyParams>,
+    pub lane: u64,
+    /// Only the voucher with the highest nonce of a lane can be redeemed.
+    pub nonce: u64,
+    /// Total amount redeemable in the lane.
+    pub amount: TokenAmount,
+    /// Minimum epoch at which the channel can be settled after this voucher is redeemed.
+    pub min_settle_height: ChainEpoch,
+    /// Lanes merged into this one.
+    pub merges: Vec<Merge>,
+    /// Signature of the channel owner.
+    pub signature: Option<Signature>,
+}
+
+impl SignedVoucher {
+    /// Returns the bytes the channel owner signs, i.e., the voucher without its signature.
+    pub fn signing_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let unsigned = Self {
+            signature: None,
+            ..self.clone()
+        };
+        Ok(fvm_ipld_encoding::to_vec(&unsigned)?)
+    }
+
+    /// Encodes the voucher as a URL-safe base64 string of its `CBOR` representation, the format
+    /// used to hand vouchers over to the channel recipient.
+    pub fn encode(&self) -> anyhow::Result<String> {
+        Ok(BASE64_URL_SAFE_NO_PAD.encode(fvm_ipld_encoding::to_vec(self)?))
+    }
+
+    /// Decodes a voucher encoded with [`SignedVoucher::encode`].
+    pub fn decode(s: &str) -> anyhow::Result<Self> {
+        let bytes = BASE64_URL_SAFE_NO_PAD
+            .decode(s.trim())
+            .context("voucher is not valid base64")?;
+        fvm_ipld_encoding::from_slice(&bytes).context("voucher is not a valid CBOR object")
+    }
+}
+
+/// Actor method to invoke when redeeming a voucher.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ModVerifyParams {
+    pub actor: Address,
+    pub method: MethodNum,
+    pub data: RawBytes,
+}
+
+/// A lane to merge into the lane of a voucher, up to the given nonce.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct Merge {
+    pub lane: u64,
+    pub nonce: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voucher() -> SignedVoucher {
+        SignedVoucher {
+            channel_addr: Address::new_id(1000),
+            time_lock_min: 0,
+            time_lock_max: 0,
+            secret_hash: vec![],
+            extra: None,
+            lane: 1,
+            nonce: 2,
+            amount: TokenAmount::from_atto(100),
+            min_settle_height: 0,
+            merges: vec![],
+            signature: Some(Signature::new_secp256k1(vec![7; 65])),
+        }
+    }
+
+    #[test]
+    fn voucher_encode_decode_round_trip() {
+        let voucher = voucher();
+        let encoded = voucher.encode().unwrap();
+        assert_eq!(SignedVoucher::decode(&encoded).unwrap(), voucher);
+        assert!(SignedVoucher::decode("not a voucher").is_err());
+    }
+
+    #[test]
+    fn voucher_signing_bytes_exclude_signature() {
+        let signed = voucher();
+        let unsigned = SignedVoucher {
+            signature: None,
+            ..signed.clone()
+        };
+        assert_eq!(
+            signed.signing_bytes().unwrap(),
+            unsigned.signing_bytes().unwrap()
+        );
+        assert_eq!(
+            unsigned.signing_bytes().unwrap(),
+            fvm_ipld_encoding::to_vec(&unsigned).unwrap()
+        );
+    }
+}
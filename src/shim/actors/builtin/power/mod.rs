@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 pub mod ext;
+pub mod params;
 
 use crate::list_miners_for_state;
 use crate::shim::actors::convert::*;
@@ -0,0 +1,30 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Parameters and return values of the power actor methods. The layouts have not changed since
+//! actors `v8`.
+
+use crate::shim::address::Address;
+use crate::shim::sector::RegisteredPoStProof;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use fvm_ipld_encoding::{strict_bytes, BytesDe};
+
+/// Parameters of [`super::Method::CreateMiner`].
+#[derive(Debug, Clone, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct CreateMinerParams {
+    pub owner: Address,
+    pub worker: Address,
+    pub window_post_proof_type: RegisteredPoStProof,
+    #[serde(with = "strict_bytes")]
+    pub peer: Vec<u8>,
+    pub multiaddrs: Vec<BytesDe>,
+}
+
+/// Return value of [`super::Method::CreateMiner`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct CreateMinerReturn {
+    /// Canonical ID-based address for the actor.
+    pub id_address: Address,
+    /// Re-org safe address for created actor.
+    pub robust_address: Address,
+}
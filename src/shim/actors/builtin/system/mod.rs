@@ -1,6 +1,7 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use cid::Cid;
 use fvm_shared2::address::Address;
 use serde::Serialize;
 
@@ -24,3 +25,20 @@ pub enum State {
     V15(fil_actor_system_state::v15::State),
     V16(fil_actor_system_state::v16::State),
 }
+
+impl State {
+    /// Returns the CID of the list of built-in actors deployed on the network.
+    pub fn builtin_actors_cid(&self) -> &Cid {
+        match self {
+            State::V8(st) => &st.builtin_actors,
+            State::V9(st) => &st.builtin_actors,
+            State::V10(st) => &st.builtin_actors,
+            State::V11(st) => &st.builtin_actors,
+            State::V12(st) => &st.builtin_actors,
+            State::V13(st) => &st.builtin_actors,
+            State::V14(st) => &st.builtin_actors,
+            State::V15(st) => &st.builtin_actors,
+            State::V16(st) => &st.builtin_actors,
+        }
+    }
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 pub mod ext;
+pub mod params;
 use anyhow::anyhow;
 use cid::Cid;
 use fil_actor_verifreg_state::v13::ClaimID;
@@ -0,0 +1,91 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Parameters and return values of the verified registry actor methods. The layouts have not
+//! changed since the actors version that introduced each method, except for
+//! `ClaimAllocations`, which groups the claims by sector since actors `v12`.
+
+use crate::shim::actors::BatchReturn;
+use crate::shim::address::Address;
+use crate::shim::clock::ChainEpoch;
+use crate::shim::fvm_shared_latest::bigint::bigint_ser;
+use cid::Cid;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use num::BigInt;
+
+/// Parameters of `AddVerifier` and `AddVerifiedClient`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct VerifierParams {
+    pub address: Address,
+    #[serde(with = "bigint_ser")]
+    pub allowance: BigInt,
+}
+
+/// Parameters of `ClaimAllocations`, since actors `v12`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ClaimAllocationsParams {
+    pub sectors: Vec<SectorAllocationClaims>,
+    pub all_or_nothing: bool,
+}
+
+/// The allocations claimed for a sector, see [`ClaimAllocationsParams`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SectorAllocationClaims {
+    pub sector: u64,
+    pub expiry: ChainEpoch,
+    pub claims: Vec<AllocationClaim>,
+}
+
+/// An allocation claimed for a sector, see [`SectorAllocationClaims`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct AllocationClaim {
+    pub client: u64,
+    pub allocation_id: u64,
+    pub data: Cid,
+    /// Padded piece size.
+    pub size: u64,
+}
+
+/// Return value of `ClaimAllocations`, since actors `v12`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ClaimAllocationsReturn {
+    /// Status of each sector grouping of claims.
+    pub sector_results: BatchReturn,
+    /// The claimed space for each successful sector group.
+    pub sector_claims: Vec<SectorClaimSummary>,
+}
+
+/// The space claimed for a sector, see [`ClaimAllocationsReturn`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct SectorClaimSummary {
+    #[serde(with = "bigint_ser")]
+    pub claimed_space: BigInt,
+}
+
+/// Parameters of `ClaimAllocations` from actors `v9` to `v11`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ClaimAllocationsParamsV9 {
+    pub sectors: Vec<SectorAllocationClaimV9>,
+    pub all_or_nothing: bool,
+}
+
+/// An allocation claimed for a sector, see [`ClaimAllocationsParamsV9`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SectorAllocationClaimV9 {
+    pub client: u64,
+    pub allocation_id: u64,
+    pub data: Cid,
+    /// Padded piece size.
+    pub size: u64,
+    pub sector: u64,
+    pub sector_expiry: ChainEpoch,
+}
+
+/// Return value of `ClaimAllocations` from actors `v9` to `v11`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ClaimAllocationsReturnV9 {
+    pub batch_info: BatchReturn,
+    #[serde(with = "bigint_ser")]
+    pub claimed_space: BigInt,
+}
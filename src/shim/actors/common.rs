@@ -3,6 +3,7 @@
 
 use crate::shim::{address::Address, state_tree::ActorState};
 use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
 
 pub trait LoadActorStateFromBlockstore: Sized {
     const ACTOR: Option<Address> = None;
@@ -33,8 +34,26 @@ mod load_actor_state_trait_impl {
     impl_for!(market, Address::MARKET_ACTOR);
     impl_for!(miner);
     impl_for!(multisig);
+    impl_for!(paych);
     impl_for!(power, Address::POWER_ACTOR);
     impl_for!(reward, Address::REWARD_ACTOR);
     impl_for!(system, Address::SYSTEM_ACTOR);
     impl_for!(verifreg, Address::VERIFIED_REGISTRY_ACTOR);
 }
+
+/// Outcome of a batch of operations of an actor method, e.g., the activation of sectors. The
+/// layout has not changed since actors `v9`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize_tuple, Deserialize_tuple)]
+pub struct BatchReturn {
+    /// Number of successful operations.
+    pub success_count: u32,
+    /// Index and exit code of each failed operation.
+    pub fail_codes: Vec<FailCode>,
+}
+
+/// Index and exit code of a failed operation of a [`BatchReturn`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct FailCode {
+    pub idx: u32,
+    pub code: crate::shim::error::ExitCode,
+}
@@ -79,3 +79,17 @@ impl_actor_cids!(
 
 // A special snowflake which has a slightly different type and and package name.
 impl_actor_cids_type_actor!(VerifiedRegistry, Verifreg);
+
+/// Returns the builtin actor with the provided actor code CID and its major version, if the CID
+/// belongs to a known actors bundle.
+pub fn builtin_actor_version(actor_code_cid: &Cid) -> Option<(Type, u64)> {
+    crate::networks::ACTOR_BUNDLES_METADATA
+        .values()
+        .find_map(|bundle| {
+            let (actor, _) = bundle
+                .manifest
+                .builtin_actors()
+                .find(|(_, cid)| cid == actor_code_cid)?;
+            Some((actor, bundle.actor_major_version().ok()?))
+        })
+}
@@ -4,8 +4,10 @@
 pub mod chain_rand;
 pub mod circulating_supply;
 mod errors;
+pub mod state_override;
 pub mod utils;
 pub use self::errors::*;
+use self::state_override::{apply_state_override, StateOverride};
 use self::utils::structured;
 
 use crate::beacon::{BeaconEntry, BeaconSchedule};
@@ -14,8 +16,9 @@ use crate::chain::{
     index::{ChainIndex, ResolveNullTipset},
     ChainStore, HeadChange,
 };
+use crate::db::MemoryOverlay;
 use crate::interpreter::{
-    resolve_to_key_addr, ApplyResult, BlockMessages, CalledAt, ExecutionContext, VMEvent,
+    resolve_to_key_addr, BlockMessages, CalledAt, ExecutionContext, VMEvent,
     IMPLICIT_MESSAGE_GAS_LIMIT, VM,
 };
 use crate::interpreter::{MessageCallbackCtx, VMTrace};
@@ -34,6 +37,7 @@ use crate::shim::{
         LoadActorStateFromBlockstore,
     },
     executor::{ApplyRet, Receipt, StampedEvent},
+    machine::{BuiltinActor, BuiltinActorManifest},
 };
 use crate::shim::{
     address::{Address, Payload, Protocol},
@@ -301,6 +305,12 @@ where
         })
     }
 
+    /// Replaces the beacon schedule of the chain configuration, e.g., with a mock beacon.
+    pub fn with_beacon_schedule(mut self, beacon: Arc<BeaconSchedule>) -> Self {
+        self.beacon = beacon;
+        self
+    }
+
     pub fn beacon_schedule(&self) -> &Arc<BeaconSchedule> {
         &self.beacon
     }
@@ -344,6 +354,16 @@ where
         state_tree.get_actor_state_from_address(actor_address)
     }
 
+    /// Returns the code [`Cid`] of the given built-in actor as deployed in the state of the tipset.
+    pub fn get_builtin_actor_code(&self, ts: &Tipset, actor: BuiltinActor) -> anyhow::Result<Cid> {
+        let system_state: system::State = self.get_actor_state(ts)?;
+        BuiltinActorManifest::load_v1_actor_list(
+            self.blockstore(),
+            system_state.builtin_actors_cid(),
+        )?
+        .get(actor)
+    }
+
     /// Gets required actor from given [`Cid`].
     pub fn get_required_actor(&self, addr: &Address, state_cid: Cid) -> anyhow::Result<ActorState> {
         let state = self.get_state_tree(&state_cid)?;
@@ -524,84 +544,97 @@ where
             .await
     }
 
-    #[instrument(skip(self, rand))]
+    #[instrument(skip(self, rand, state_override))]
     fn call_raw(
         self: &Arc<Self>,
-        msg: &Message,
+        messages: &[Message],
         rand: ChainRand<DB>,
         tipset: &Arc<Tipset>,
-    ) -> Result<ApiInvocResult, Error> {
-        let mut msg = msg.clone();
-
+        state_override: &StateOverride,
+    ) -> Result<Vec<ApiInvocResult>, Error> {
         let state_cid = tipset.parent_state();
 
+        // Nothing computed here is persisted, write to an in-memory copy of the state.
+        let store = Arc::new(MemoryOverlay::new(self.blockstore_owned()));
+        let chain_index = Arc::new(ChainIndex::new(Arc::clone(&store)));
+
         let tipset_messages = self
             .chain_store()
             .messages_for_tipset(tipset)
             .map_err(|err| Error::Other(err.to_string()))?;
 
-        let prior_messsages = tipset_messages
-            .iter()
-            .filter(|ts_msg| ts_msg.message().from() == msg.from());
-
         // Handle state forks
         // TODO(elmattic): https://github.com/ChainSafe/forest/issues/3733
 
         let height = tipset.epoch();
         let genesis_info = GenesisInfo::from_chain_config(self.chain_config().clone());
-        let mut vm = VM::new(
-            ExecutionContext {
-                heaviest_tipset: Arc::clone(tipset),
-                state_tree_root: *state_cid,
-                epoch: height,
-                rand: Box::new(rand),
-                base_fee: tipset.block_headers().first().parent_base_fee.clone(),
-                circ_supply: genesis_info.get_vm_circulating_supply(
-                    height,
-                    &self.blockstore_owned(),
-                    state_cid,
-                )?,
-                chain_config: self.chain_config().clone(),
-                chain_index: Arc::clone(&self.chain_store().chain_index),
-                timestamp: tipset.min_timestamp(),
-            },
-            &self.engine,
-            VMTrace::Traced,
-        )?;
+        let circ_supply =
+            genesis_info.get_vm_circulating_supply(height, &self.blockstore_owned(), state_cid)?;
+        let new_vm = |state_tree_root| {
+            VM::new(
+                ExecutionContext {
+                    heaviest_tipset: Arc::clone(tipset),
+                    state_tree_root,
+                    epoch: height,
+                    rand: Box::new(rand.clone()),
+                    base_fee: tipset.block_headers().first().parent_base_fee.clone(),
+                    circ_supply: circ_supply.clone(),
+                    chain_config: self.chain_config().clone(),
+                    chain_index: Arc::clone(&chain_index),
+                    timestamp: tipset.min_timestamp(),
+                },
+                &self.engine,
+                VMTrace::Traced,
+            )
+        };
+        let mut vm = new_vm(*state_cid)?;
 
-        for m in prior_messsages {
+        let senders: Vec<_> = messages.iter().map(|msg| msg.from()).collect();
+        for m in tipset_messages
+            .iter()
+            .filter(|ts_msg| senders.contains(&ts_msg.message().from()))
+        {
             vm.apply_message(m)?;
         }
 
-        // We flush to get the VM's view of the state tree after applying the above messages
-        // This is needed to get the correct nonce from the actor state to match the VM
-        let state_cid = vm.flush()?;
-
-        let state = StateTree::new_from_root(self.blockstore_owned(), &state_cid)?;
-
-        let from_actor = state
-            .get_actor(&msg.from())?
-            .ok_or_else(|| anyhow::anyhow!("actor not found"))?;
-        msg.set_sequence(from_actor.sequence);
-
-        // If the fee cap is set to zero, make gas free
-        // TODO(elmattic): https://github.com/ChainSafe/forest/issues/3733
-
-        // Implicit messages need to set a special gas limit
-        let mut msg = msg.clone();
-        msg.gas_limit = IMPLICIT_MESSAGE_GAS_LIMIT as u64;
-
-        let (apply_ret, duration) = vm.apply_implicit_message(&msg)?;
+        if !state_override.is_empty() {
+            let state_cid = vm.flush()?;
+            vm = new_vm(apply_state_override(
+                &store,
+                state_cid,
+                state_override,
+                new_vm,
+            )?)?;
+        }
 
-        Ok(ApiInvocResult {
-            msg: msg.clone(),
-            msg_rct: Some(apply_ret.msg_receipt()),
-            msg_cid: msg.cid(),
-            error: apply_ret.failure_info().unwrap_or_default(),
-            duration: duration.as_nanos().clamp(0, u64::MAX as u128) as u64,
-            gas_cost: MessageGasCost::default(),
-            execution_trace: structured::parse_events(apply_ret.exec_trace()).unwrap_or_default(),
-        })
+        let mut results = Vec::with_capacity(messages.len());
+        for msg in messages {
+            let mut msg = msg.clone();
+            let from_actor = vm
+                .get_actor(&msg.from())?
+                .ok_or_else(|| anyhow::anyhow!("actor not found"))?;
+            msg.set_sequence(from_actor.sequence);
+
+            // If the fee cap is set to zero, make gas free
+            // TODO(elmattic): https://github.com/ChainSafe/forest/issues/3733
+
+            // Implicit messages need to set a special gas limit
+            msg.gas_limit = IMPLICIT_MESSAGE_GAS_LIMIT as u64;
+
+            let (apply_ret, duration) = vm.apply_implicit_message(&msg)?;
+
+            results.push(ApiInvocResult {
+                msg: msg.clone(),
+                msg_rct: Some(apply_ret.msg_receipt()),
+                msg_cid: msg.cid(),
+                error: apply_ret.failure_info().unwrap_or_default(),
+                duration: duration.as_nanos().clamp(0, u64::MAX as u128) as u64,
+                gas_cost: MessageGasCost::default(),
+                execution_trace: structured::parse_events(apply_ret.exec_trace())
+                    .unwrap_or_default(),
+            });
+        }
+        Ok(results)
     }
 
     /// runs the given message and returns its result without any persisted
@@ -611,69 +644,169 @@ where
         message: &Message,
         tipset: Option<Arc<Tipset>>,
     ) -> Result<ApiInvocResult, Error> {
+        self.call_with_state_override(message, tipset, &StateOverride::default())
+    }
+
+    /// Same as [`StateManager::call`], with the given changes applied to the state beforehand.
+    pub fn call_with_state_override(
+        self: &Arc<Self>,
+        message: &Message,
+        tipset: Option<Arc<Tipset>>,
+        state_override: &StateOverride,
+    ) -> Result<ApiInvocResult, Error> {
+        self.call_many(std::slice::from_ref(message), tipset, state_override)?
+            .pop()
+            .ok_or_else(|| Error::Other("no message was applied".into()))
+    }
+
+    /// Runs the given messages in order, each one seeing the changes made by the previous ones,
+    /// and returns their results without any persisted changes.
+    pub fn call_many(
+        self: &Arc<Self>,
+        messages: &[Message],
+        tipset: Option<Arc<Tipset>>,
+        state_override: &StateOverride,
+    ) -> Result<Vec<ApiInvocResult>, Error> {
         let ts = tipset.unwrap_or_else(|| self.cs.heaviest_tipset());
         let chain_rand = self.chain_rand(Arc::clone(&ts));
-        self.call_raw(message, chain_rand, &ts)
+        self.call_raw(messages, chain_rand, &ts, state_override)
     }
 
-    /// Computes message on the given [Tipset] state, after applying other
-    /// messages and returns the values computed in the VM.
-    pub async fn call_with_gas(
+    /// Runs `f` on a blocking thread with a VM on top of the state computed for the [Tipset], as if
+    /// the messages were included in the next tipset. The VM reads and writes through
+    /// `chain_index`.
+    async fn with_next_tipset_vm<S, T>(
         self: &Arc<Self>,
-        message: &mut ChainMessage,
-        prior_messages: &[ChainMessage],
-        tipset: Option<Arc<Tipset>>,
+        ts: &Arc<Tipset>,
+        chain_index: Arc<ChainIndex<Arc<S>>>,
         trace_config: VMTrace,
-    ) -> Result<(InvocResult, ApplyRet), Error> {
-        let ts = tipset.unwrap_or_else(|| self.cs.heaviest_tipset());
+        f: impl FnOnce(&mut VM<S>) -> anyhow::Result<T> + Send + 'static,
+    ) -> Result<T, Error>
+    where
+        S: Blockstore + Send + Sync + 'static,
+        T: Send + 'static,
+    {
         let (st, _) = self
-            .tipset_state(&ts)
+            .tipset_state(ts)
             .await
             .map_err(|_| Error::Other("Could not load tipset state".to_string()))?;
-        let chain_rand = self.chain_rand(Arc::clone(&ts));
+        let chain_rand = self.chain_rand(Arc::clone(ts));
 
         // Since we're simulating a future message, pretend we're applying it in the
         // "next" tipset
         let epoch = ts.epoch() + 1;
         let genesis_info = GenesisInfo::from_chain_config(self.chain_config().clone());
-        // FVM requires a stack size of 64MiB. The alternative is to use `ThreadedExecutor` from
-        // FVM, but that introduces some constraints, and possible deadlocks.
-        let (ret, _) = stacker::grow(64 << 20, || -> ApplyResult {
-            let mut vm = VM::new(
-                ExecutionContext {
-                    heaviest_tipset: Arc::clone(&ts),
-                    state_tree_root: st,
-                    epoch,
-                    rand: Box::new(chain_rand),
-                    base_fee: ts.block_headers().first().parent_base_fee.clone(),
-                    circ_supply: genesis_info.get_vm_circulating_supply(
+        let (this, ts) = (Arc::clone(self), Arc::clone(ts));
+        tokio::task::spawn_blocking(move || {
+            // FVM requires a stack size of 64MiB. The alternative is to use `ThreadedExecutor`
+            // from FVM, but that introduces some constraints, and possible deadlocks.
+            stacker::grow(64 << 20, || -> anyhow::Result<T> {
+                let mut vm = VM::new(
+                    ExecutionContext {
+                        heaviest_tipset: Arc::clone(&ts),
+                        state_tree_root: st,
                         epoch,
-                        &self.blockstore_owned(),
-                        &st,
-                    )?,
-                    chain_config: self.chain_config().clone(),
-                    chain_index: Arc::clone(&self.chain_store().chain_index),
-                    timestamp: ts.min_timestamp(),
-                },
-                &self.engine,
-                trace_config,
-            )?;
-
-            for msg in prior_messages {
-                vm.apply_message(msg)?;
-            }
-            let from_actor = vm
-                .get_actor(&message.from())
-                .map_err(|e| Error::Other(format!("Could not get actor from state: {e}")))?
-                .ok_or_else(|| Error::Other("cant find actor in state tree".to_string()))?;
-            message.set_sequence(from_actor.sequence);
+                        rand: Box::new(chain_rand),
+                        base_fee: ts.block_headers().first().parent_base_fee.clone(),
+                        circ_supply: genesis_info.get_vm_circulating_supply(
+                            epoch,
+                            &this.blockstore_owned(),
+                            &st,
+                        )?,
+                        chain_config: this.chain_config().clone(),
+                        chain_index,
+                        timestamp: ts.min_timestamp(),
+                    },
+                    &this.engine,
+                    trace_config,
+                )?;
+                f(&mut vm)
+            })
+        })
+        .await?
+        .map_err(Error::from)
+    }
 
-            vm.apply_message(message)
-        })?;
+    /// Computes message on the given [Tipset] state, after applying other
+    /// messages and returns the values computed in the VM.
+    pub async fn call_with_gas(
+        self: &Arc<Self>,
+        message: &mut ChainMessage,
+        prior_messages: &[ChainMessage],
+        tipset: Option<Arc<Tipset>>,
+        trace_config: VMTrace,
+    ) -> Result<(InvocResult, ApplyRet), Error> {
+        let ts = tipset.unwrap_or_else(|| self.cs.heaviest_tipset());
+        let chain_index = Arc::clone(&self.chain_store().chain_index);
+        let prior_messages = prior_messages.to_vec();
+        let mut msg = message.clone();
+        let (msg, ret) = self
+            .with_next_tipset_vm(&ts, chain_index, trace_config, move |vm| {
+                for msg in &prior_messages {
+                    vm.apply_message(msg)?;
+                }
+                let from_actor = vm
+                    .get_actor(&msg.from())
+                    .map_err(|e| anyhow::anyhow!("Could not get actor from state: {e}"))?
+                    .context("cant find actor in state tree")?;
+                msg.set_sequence(from_actor.sequence);
+
+                let (ret, _) = vm.apply_message(&msg)?;
+                Ok((msg, ret))
+            })
+            .await?;
+        *message = msg;
 
         Ok((InvocResult::new(message.message().clone(), &ret), ret))
     }
 
+    /// Applies the given messages in order on top of the [Tipset] state, after
+    /// `prior_messages`, as if they were included in the next tipset. The sequence of each
+    /// message is set from the state, so that each message sees the changes made by the
+    /// previous ones. Prior messages that can't be applied are skipped. Nothing computed here is
+    /// persisted.
+    pub async fn simulate(
+        self: &Arc<Self>,
+        messages: Vec<ChainMessage>,
+        prior_messages: Vec<ChainMessage>,
+        tipset: Option<Arc<Tipset>>,
+    ) -> Result<Vec<(ApiInvocResult, ApplyRet)>, Error> {
+        let ts = tipset.unwrap_or_else(|| self.cs.heaviest_tipset());
+        let store = Arc::new(MemoryOverlay::new(self.blockstore_owned()));
+        let chain_index = Arc::new(ChainIndex::new(store));
+        self.with_next_tipset_vm(&ts, chain_index, VMTrace::Traced, move |vm| {
+            for msg in &prior_messages {
+                if let Err(e) = vm.apply_message(msg) {
+                    warn!("Skipping prior message {}: {e:#}", msg.cid());
+                }
+            }
+            let mut results = Vec::with_capacity(messages.len());
+            for mut msg in messages {
+                let from_actor = vm
+                    .get_actor(&msg.from())?
+                    .with_context(|| format!("actor {} not found", msg.from()))?;
+                msg.set_sequence(from_actor.sequence);
+
+                let (ret, duration) = vm.apply_message(&msg)?;
+                results.push((
+                    ApiInvocResult {
+                        msg: msg.message().clone(),
+                        msg_rct: Some(ret.msg_receipt()),
+                        msg_cid: msg.cid(),
+                        error: ret.failure_info().unwrap_or_default(),
+                        duration: duration.as_nanos().clamp(0, u64::MAX as u128) as u64,
+                        gas_cost: MessageGasCost::new(msg.message(), &ret)?,
+                        execution_trace: structured::parse_events(ret.exec_trace())
+                            .unwrap_or_default(),
+                    },
+                    ret,
+                ));
+            }
+            Ok(results)
+        })
+        .await
+    }
+
     /// Replays the given message and returns the result of executing the
     /// indicated message, assuming it was executed in the indicated tipset.
     pub async fn replay(
@@ -848,6 +981,33 @@ where
         )?)
     }
 
+    /// Returns the state the message `mcid` is applied on when executing `tipset`, that is with the
+    /// changes made by the messages and block rewards that precede it in the tipset.
+    pub async fn state_before_message(
+        self: &Arc<Self>,
+        tipset: Arc<Tipset>,
+        mcid: Cid,
+    ) -> Result<Cid, Error> {
+        let this = Arc::clone(self);
+        tokio::task::spawn_blocking(move || -> anyhow::Result<Cid> {
+            let StateOutput { state_root, .. } = apply_block_messages_until(
+                this.chain_store().genesis_block_header().timestamp,
+                Arc::clone(&this.chain_store().chain_index),
+                Arc::clone(&this.chain_config),
+                this.beacon_schedule().clone(),
+                &this.engine,
+                tipset,
+                NO_CALLBACK,
+                VMTrace::NotTraced,
+                VMEvent::NotPushed,
+                Some(mcid),
+            )?;
+            Ok(state_root)
+        })
+        .await?
+        .map_err(|e| Error::Other(format!("{e:#}")))
+    }
+
     /// Check if tipset had executed the message, by loading the receipt based
     /// on the index of the message in the block.
     fn tipset_executed_message(
@@ -1727,6 +1887,38 @@ where
 /// The `ChainStore` caches recent tipsets to make these scans faster.
 #[allow(clippy::too_many_arguments)]
 pub fn apply_block_messages<DB>(
+    genesis_timestamp: u64,
+    chain_index: Arc<ChainIndex<Arc<DB>>>,
+    chain_config: Arc<ChainConfig>,
+    beacon: Arc<BeaconSchedule>,
+    engine: &crate::shim::machine::MultiEngine,
+    tipset: Arc<Tipset>,
+    callback: Option<impl FnMut(MessageCallbackCtx<'_>) -> anyhow::Result<()>>,
+    enable_tracing: VMTrace,
+    enable_event_pushing: VMEvent,
+) -> Result<StateOutput, anyhow::Error>
+where
+    DB: Blockstore + Send + Sync + 'static,
+{
+    apply_block_messages_until(
+        genesis_timestamp,
+        chain_index,
+        chain_config,
+        beacon,
+        engine,
+        tipset,
+        callback,
+        enable_tracing,
+        enable_event_pushing,
+        None,
+    )
+}
+
+/// Like [`apply_block_messages`], but stops right before the message `stop_before` if any. The
+/// returned state root is then the state the message is applied on, and the receipts and events
+/// are left empty.
+#[allow(clippy::too_many_arguments)]
+fn apply_block_messages_until<DB>(
     genesis_timestamp: u64,
     chain_index: Arc<ChainIndex<Arc<DB>>>,
     chain_config: Arc<ChainConfig>,
@@ -1736,6 +1928,7 @@ pub fn apply_block_messages<DB>(
     mut callback: Option<impl FnMut(MessageCallbackCtx<'_>) -> anyhow::Result<()>>,
     enable_tracing: VMTrace,
     enable_event_pushing: VMEvent,
+    stop_before: Option<Cid>,
 ) -> Result<StateOutput, anyhow::Error>
 where
     DB: Blockstore + Send + Sync + 'static,
@@ -1749,6 +1942,9 @@ where
 
     // step 1: special case for genesis block
     if tipset.epoch() == 0 {
+        if let Some(cid) = stop_before {
+            bail!("message {cid} not found in the genesis tipset");
+        }
         // NB: This is here because the process that executes blocks requires that the
         // block miner reference a valid miner in the state tree. Unless we create some
         // magical genesis miner, this won't work properly, so we short circuit here
@@ -1828,8 +2024,15 @@ where
         let mut vm = create_vm(parent_state, epoch, tipset.min_timestamp())?;
 
         // step 4: apply tipset messages
-        let (receipts, events) =
-            vm.apply_block_messages(&block_messages, epoch, callback, enable_event_pushing)?;
+        let (receipts, events) = match stop_before {
+            Some(cid) => {
+                vm.apply_block_messages_before(&block_messages, epoch, cid)?;
+                (vec![], vec![])
+            }
+            None => {
+                vm.apply_block_messages(&block_messages, epoch, callback, enable_event_pushing)?
+            }
+        };
 
         // step 5: construct receipt root from receipts and flush the state-tree
         let receipt_root = Amt::new_from_iter(&chain_index.db, receipts)?;
@@ -1842,3 +2045,51 @@ where
         })
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_sync::block_producer::tests::SoloDevnet;
+
+    #[tokio::test]
+    async fn state_before_message_includes_preceding_messages() {
+        // Run the test only in CI so that regular test on dev machines don't download the actor
+        // bundle on poor internet connections.
+        if std::env::var("CI").is_err() {
+            return;
+        }
+
+        let devnet = SoloDevnet::with_actor_bundle().await;
+        let messages = [devnet.transfer(0), devnet.transfer(1)];
+        for message in &messages {
+            devnet.ctx.mpool.push(message.clone()).await.unwrap();
+        }
+        let tipset = devnet.producer().produce_tipset(1).await.unwrap();
+        let state_manager = &devnet.ctx.state_manager;
+
+        let sender = |state_root| {
+            StateTree::new_from_root(state_manager.blockstore_owned(), &state_root)
+                .unwrap()
+                .get_actor(&devnet.account.address)
+                .unwrap()
+                .unwrap()
+        };
+        let before_first = state_manager
+            .state_before_message(tipset.clone(), messages[0].cid())
+            .await
+            .unwrap();
+        assert_eq!(sender(before_first).sequence, 0);
+        let before_second = state_manager
+            .state_before_message(tipset.clone(), messages[1].cid())
+            .await
+            .unwrap();
+        assert_eq!(sender(before_second).sequence, 1);
+        assert!(sender(before_second).balance < sender(before_first).balance);
+
+        // Messages of other tipsets are not found
+        state_manager
+            .state_before_message(tipset, devnet.transfer(2).cid())
+            .await
+            .unwrap_err();
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Modifications of the state applied before simulating messages, see [`StateOverride`].
+//!
+//! Balances, nonces and bytecode are written directly to the actor states. Contract storage is a
+//! KAMT owned by the EVM actor, so storage slots are written by temporarily swapping the contract
+//! bytecode for a generated one that stores the requested values, invoking it, and restoring the
+//! bytecode.
+
+use crate::eth::EVMMethod;
+use crate::interpreter::{IMPLICIT_MESSAGE_GAS_LIMIT, VM};
+use crate::shim::actors::{evm, is_evm_actor, system, EVMActorStateLoad as _};
+use crate::shim::address::Address;
+use crate::shim::econ::TokenAmount;
+use crate::shim::machine::{BuiltinActor, BuiltinActorManifest};
+use crate::shim::message::{Message, METHOD_SEND};
+use crate::shim::state_tree::StateTree;
+use crate::utils::db::CborStoreExt as _;
+use crate::utils::multihash::prelude::*;
+use ahash::HashMap;
+use anyhow::Context as _;
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::{BytesSer, IPLD_RAW};
+use std::sync::Arc;
+
+/// A storage slot and its value, both 32-byte big-endian words.
+pub type StorageSlot = ([u8; 32], [u8; 32]);
+
+/// Changes applied to a single actor.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ActorOverride {
+    pub balance: Option<TokenAmount>,
+    /// The actor sequence, or the EVM nonce for contracts.
+    pub nonce: Option<u64>,
+    /// EVM bytecode. Actors that are not contracts are turned into one.
+    pub code: Option<Vec<u8>>,
+    pub storage: Option<StorageOverride>,
+}
+
+/// Changes applied to the storage of a contract.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageOverride {
+    /// Replaces the whole storage with the given slots.
+    Replace(Vec<StorageSlot>),
+    /// Sets the given slots, leaving the others untouched.
+    Patch(Vec<StorageSlot>),
+}
+
+/// Changes applied to the state before simulating messages, keyed by actor address. Actors that do
+/// not exist yet are created if the address is a delegated one.
+pub type StateOverride = HashMap<Address, ActorOverride>;
+
+/// Applies the overrides on top of `state_root` and returns the new state root. `new_vm` creates a
+/// VM on the given state root, writing to `store`.
+pub(super) fn apply_state_override<DB: Blockstore + Send + Sync + 'static>(
+    store: &Arc<DB>,
+    state_root: Cid,
+    state_override: &StateOverride,
+    new_vm: impl Fn(Cid) -> anyhow::Result<VM<DB>>,
+) -> anyhow::Result<Cid> {
+    let state_root = create_missing_actors(store, state_root, state_override, &new_vm)?;
+
+    let mut state = StateTree::new_from_root(Arc::clone(store), &state_root)?;
+    let mut storage_writes = vec![];
+    for (address, actor_override) in state_override {
+        let mut actor = state.get_required_actor(address)?;
+        if let Some(code) = &actor_override.code {
+            let (bytecode, bytecode_hash) = put_bytecode(store, code)?;
+            if is_evm_actor(&actor.code) {
+                let mut evm_state = evm::State::load(store, actor.code, actor.state)?;
+                evm_state.set_bytecode(bytecode, bytecode_hash);
+                actor.state = store.put_cbor_default(&evm_state)?;
+            } else {
+                let system_state: system::State = state.get_actor_state()?;
+                actor.code = BuiltinActorManifest::load_v1_actor_list(
+                    store,
+                    system_state.builtin_actors_cid(),
+                )?
+                .get(BuiltinActor::EVM)?;
+                let evm_state = evm::State::new(
+                    &actor.code,
+                    bytecode,
+                    bytecode_hash,
+                    put_empty_storage(store)?,
+                )?;
+                actor.state = store.put_cbor_default(&evm_state)?;
+            }
+        }
+        if let Some(nonce) = actor_override.nonce {
+            if is_evm_actor(&actor.code) {
+                let mut evm_state = evm::State::load(store, actor.code, actor.state)?;
+                evm_state.set_nonce(nonce);
+                actor.state = store.put_cbor_default(&evm_state)?;
+            } else {
+                actor.sequence = nonce;
+            }
+        }
+        if let Some(balance) = &actor_override.balance {
+            actor.balance = balance.clone().into();
+        }
+        if let Some(storage) = &actor_override.storage {
+            anyhow::ensure!(
+                is_evm_actor(&actor.code),
+                "cannot override the storage of {address}, it is not a contract"
+            );
+            let mut evm_state = evm::State::load(store, actor.code, actor.state)?;
+            let slots = match storage {
+                StorageOverride::Replace(slots) => {
+                    evm_state.set_contract_state(put_empty_storage(store)?);
+                    slots
+                }
+                StorageOverride::Patch(slots) => slots,
+            };
+            // Swap the bytecode for the storage setter, it is restored once the slots are written.
+            storage_writes.push((*address, evm_state.bytecode(), evm_state.bytecode_hash()));
+            let (setter, setter_hash) = put_bytecode(store, &storage_setter(slots))?;
+            evm_state.set_bytecode(setter, setter_hash);
+            actor.state = store.put_cbor_default(&evm_state)?;
+        }
+        state.set_actor(address, actor)?;
+    }
+    let state_root = state.flush()?;
+    if storage_writes.is_empty() {
+        return Ok(state_root);
+    }
+
+    let mut vm = new_vm(state_root)?;
+    for (address, _, _) in &storage_writes {
+        let msg = Message {
+            from: Address::SYSTEM_ACTOR,
+            to: *address,
+            method_num: EVMMethod::InvokeContract as u64,
+            gas_limit: IMPLICIT_MESSAGE_GAS_LIMIT as u64,
+            ..Default::default()
+        };
+        let (ret, _) = vm.apply_implicit_message(&msg)?;
+        anyhow::ensure!(
+            ret.msg_receipt().exit_code().is_success(),
+            "failed to override the storage of {address}: {}",
+            ret.failure_info().unwrap_or_default()
+        );
+    }
+    let state_root = vm.flush()?;
+
+    let mut state = StateTree::new_from_root(Arc::clone(store), &state_root)?;
+    for (address, bytecode, bytecode_hash) in storage_writes {
+        let mut actor = state.get_required_actor(&address)?;
+        let mut evm_state = evm::State::load(store, actor.code, actor.state)?;
+        evm_state.set_bytecode(bytecode, bytecode_hash);
+        actor.state = store.put_cbor_default(&evm_state)?;
+        state.set_actor(&address, actor)?;
+    }
+    state.flush()
+}
+
+/// Creates placeholder actors for the overridden addresses that don't exist yet, by sending them
+/// an empty message.
+fn create_missing_actors<DB: Blockstore + Send + Sync + 'static>(
+    store: &Arc<DB>,
+    state_root: Cid,
+    state_override: &StateOverride,
+    new_vm: impl Fn(Cid) -> anyhow::Result<VM<DB>>,
+) -> anyhow::Result<Cid> {
+    let state = StateTree::new_from_root(Arc::clone(store), &state_root)?;
+    let mut missing = vec![];
+    for address in state_override.keys() {
+        if state.get_actor(address)?.is_none() {
+            missing.push(address);
+        }
+    }
+    if missing.is_empty() {
+        return Ok(state_root);
+    }
+
+    let mut vm = new_vm(state_root)?;
+    for address in missing {
+        let msg = Message {
+            from: Address::SYSTEM_ACTOR,
+            to: *address,
+            method_num: METHOD_SEND,
+            gas_limit: IMPLICIT_MESSAGE_GAS_LIMIT as u64,
+            ..Default::default()
+        };
+        let (ret, _) = vm.apply_implicit_message(&msg)?;
+        anyhow::ensure!(
+            ret.msg_receipt().exit_code().is_success(),
+            "failed to create actor {address}: {}",
+            ret.failure_info().unwrap_or_default()
+        );
+    }
+    vm.flush()
+}
+
+/// Stores the bytecode the way the EVM actor does, returning its CID and Keccak-256 hash.
+fn put_bytecode(store: &impl Blockstore, bytecode: &[u8]) -> anyhow::Result<(Cid, [u8; 32])> {
+    let cid = Cid::new_v1(IPLD_RAW, MultihashCode::Blake2b256.digest(bytecode));
+    store.put_keyed(&cid, bytecode)?;
+    Ok((cid, keccak_hash::keccak(bytecode).0))
+}
+
+/// Stores an empty KAMT node, `[bitfield, pointers]`, to be used as an empty contract storage.
+fn put_empty_storage(store: &impl Blockstore) -> anyhow::Result<Cid> {
+    store
+        .put_cbor_default(&(BytesSer(&[]), Vec::<()>::new()))
+        .context("failed to store empty contract storage")
+}
+
+/// Returns EVM bytecode storing the given slots.
+fn storage_setter(slots: &[StorageSlot]) -> Vec<u8> {
+    const PUSH32: u8 = 0x7f;
+    const SSTORE: u8 = 0x55;
+    const STOP: u8 = 0x00;
+
+    let mut code = Vec::with_capacity(slots.len() * 67 + 1);
+    for (key, value) in slots {
+        code.push(PUSH32);
+        code.extend_from_slice(value);
+        code.push(PUSH32);
+        code.extend_from_slice(key);
+        code.push(SSTORE);
+    }
+    code.push(STOP);
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storage_setter_layout() {
+        let code = storage_setter(&[([1; 32], [2; 32])]);
+        assert_eq!(code.len(), 68);
+        assert_eq!(code.first(), Some(&0x7f));
+        assert_eq!(code.get(1..33), Some([2; 32].as_slice()));
+        assert_eq!(code.get(33), Some(&0x7f));
+        assert_eq!(code.get(34..66), Some([1; 32].as_slice()));
+        assert_eq!(code.get(66..), Some([0x55, 0x00].as_slice()));
+    }
+
+    #[test]
+    fn empty_storage_is_an_empty_kamt() {
+        let store = crate::db::MemoryDB::default();
+        let cid = put_empty_storage(&store).unwrap();
+        assert_eq!(store.get(&cid).unwrap(), Some(vec![0x82, 0x40, 0x80]));
+    }
+}
@@ -0,0 +1,614 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Structured differences between the actors of two state roots, as opposed to the printed
+//! report of [`super::print_state_diff`].
+
+use std::sync::Arc;
+
+use crate::lotus_json::{lotus_json_with_self, HasLotusJson as _, LotusJson};
+use crate::shim::actors::builtin_actor_version;
+use crate::shim::machine::BuiltinActor;
+use crate::shim::{
+    address::Address,
+    econ::TokenAmount,
+    state_tree::{ActorState, StateRoot, StateTree},
+};
+use crate::utils::db::CborStoreExt as _;
+use ahash::HashMap;
+use anyhow::{bail, ensure, Context as _};
+use cid::Cid;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::CborStore as _;
+use fvm_shared4::bigint::bigint_ser::BigIntDe;
+use ipld_core::ipld::Ipld;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Difference of an actor between two state roots.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct ActorDiff {
+    /// The actor at the old state root, `None` if it did not exist yet.
+    #[serde(with = "crate::lotus_json")]
+    #[schemars(with = "LotusJson<Option<ActorState>>")]
+    pub old: Option<ActorState>,
+    /// The actor at the new state root, `None` if it was deleted.
+    #[serde(with = "crate::lotus_json")]
+    #[schemars(with = "LotusJson<Option<ActorState>>")]
+    pub new: Option<ActorState>,
+    /// The fields of the actor state that changed.
+    pub fields: Vec<FieldDiff>,
+}
+
+lotus_json_with_self!(ActorDiff);
+
+/// A changed field of an actor state. Fields of builtin actors are named after the actor types,
+/// fields of other actors are named by their index.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct FieldDiff {
+    pub name: String,
+    /// The old value of the field, `None` if it was added.
+    pub old: Option<serde_json::Value>,
+    /// The new value of the field, `None` if it was removed.
+    pub new: Option<serde_json::Value>,
+}
+
+/// How the value of an actor state field is shown. Unlike the generic [`Ipld`], addresses and
+/// amounts, which are encoded as bytes, are shown in their Lotus JSON.
+#[derive(Debug, Clone, Copy)]
+enum FieldKind {
+    Ipld,
+    Address,
+    TokenAmount,
+    BigInt,
+}
+
+impl FieldKind {
+    fn to_json(self, value: &Ipld) -> anyhow::Result<serde_json::Value> {
+        let typed = || -> anyhow::Result<serde_json::Value> {
+            let bytes = fvm_ipld_encoding::to_vec(value)?;
+            Ok(match self {
+                Self::Ipld => value.clone().into_lotus_json_value()?,
+                Self::Address => {
+                    fvm_ipld_encoding::from_slice::<Address>(&bytes)?.into_lotus_json_value()?
+                }
+                Self::TokenAmount => {
+                    fvm_ipld_encoding::from_slice::<TokenAmount>(&bytes)?.into_lotus_json_value()?
+                }
+                Self::BigInt => fvm_ipld_encoding::from_slice::<BigIntDe>(&bytes)?
+                    .0
+                    .into_lotus_json_value()?,
+            })
+        };
+        // fall back to the generic IPLD if the field does not have the expected type
+        typed().or_else(|_| Ok(value.clone().into_lotus_json_value()?))
+    }
+}
+
+/// Returns the fields of the state of the given builtin actor version, empty if they are unknown.
+///
+/// The states of the actors before version 8 are not covered.
+fn builtin_state_fields(actor: BuiltinActor, version: u64) -> &'static [(&'static str, FieldKind)] {
+    use FieldKind as K;
+    if version < 8 {
+        return &[];
+    }
+    match actor {
+        BuiltinActor::System => &[("builtin_actors", K::Ipld)],
+        BuiltinActor::Init => &[
+            ("address_map", K::Ipld),
+            ("next_id", K::Ipld),
+            ("network_name", K::Ipld),
+        ],
+        BuiltinActor::Cron => &[("entries", K::Ipld)],
+        BuiltinActor::Account => &[("address", K::Address)],
+        BuiltinActor::Power if version >= 15 => &[
+            ("total_raw_byte_power", K::BigInt),
+            ("total_bytes_committed", K::BigInt),
+            ("total_quality_adj_power", K::BigInt),
+            ("total_qa_bytes_committed", K::BigInt),
+            ("total_pledge_collateral", K::TokenAmount),
+            ("this_epoch_raw_byte_power", K::BigInt),
+            ("this_epoch_quality_adj_power", K::BigInt),
+            ("this_epoch_pledge_collateral", K::TokenAmount),
+            ("this_epoch_qa_power_smoothed", K::Ipld),
+            ("miner_count", K::Ipld),
+            ("miner_above_min_power_count", K::Ipld),
+            ("ramp_start_epoch", K::Ipld),
+            ("ramp_duration_epochs", K::Ipld),
+            ("cron_event_queue", K::Ipld),
+            ("first_cron_epoch", K::Ipld),
+            ("claims", K::Ipld),
+            ("proof_validation_batch", K::Ipld),
+        ],
+        BuiltinActor::Power => &[
+            ("total_raw_byte_power", K::BigInt),
+            ("total_bytes_committed", K::BigInt),
+            ("total_quality_adj_power", K::BigInt),
+            ("total_qa_bytes_committed", K::BigInt),
+            ("total_pledge_collateral", K::TokenAmount),
+            ("this_epoch_raw_byte_power", K::BigInt),
+            ("this_epoch_quality_adj_power", K::BigInt),
+            ("this_epoch_pledge_collateral", K::TokenAmount),
+            ("this_epoch_qa_power_smoothed", K::Ipld),
+            ("miner_count", K::Ipld),
+            ("miner_above_min_power_count", K::Ipld),
+            ("cron_event_queue", K::Ipld),
+            ("first_cron_epoch", K::Ipld),
+            ("claims", K::Ipld),
+            ("proof_validation_batch", K::Ipld),
+        ],
+        BuiltinActor::Miner => &[
+            ("info", K::Ipld),
+            ("pre_commit_deposits", K::TokenAmount),
+            ("locked_funds", K::TokenAmount),
+            ("vesting_funds", K::Ipld),
+            ("fee_debt", K::TokenAmount),
+            ("initial_pledge", K::TokenAmount),
+            ("pre_committed_sectors", K::Ipld),
+            ("pre_committed_sectors_cleanup", K::Ipld),
+            ("allocated_sectors", K::Ipld),
+            ("sectors", K::Ipld),
+            ("proving_period_start", K::Ipld),
+            ("current_deadline", K::Ipld),
+            ("deadlines", K::Ipld),
+            ("early_terminations", K::Ipld),
+            ("deadline_cron_active", K::Ipld),
+        ],
+        BuiltinActor::Market => {
+            const FIELDS: &[(&str, FieldKind)] = &[
+                ("proposals", K::Ipld),
+                ("states", K::Ipld),
+                ("pending_proposals", K::Ipld),
+                ("escrow_table", K::Ipld),
+                ("locked_table", K::Ipld),
+                ("next_id", K::Ipld),
+                ("deal_ops_by_epoch", K::Ipld),
+                ("last_cron", K::Ipld),
+                ("total_client_locked_collateral", K::TokenAmount),
+                ("total_provider_locked_collateral", K::TokenAmount),
+                ("total_client_storage_fee", K::TokenAmount),
+                ("pending_deal_allocation_ids", K::Ipld),
+                ("provider_sectors", K::Ipld),
+            ];
+            match version {
+                8 => FIELDS.split_at(11).0,
+                9..=12 => FIELDS.split_at(12).0,
+                _ => FIELDS,
+            }
+        }
+        BuiltinActor::PaymentChannel => &[
+            ("from", K::Address),
+            ("to", K::Address),
+            ("to_send", K::TokenAmount),
+            ("settling_at", K::Ipld),
+            ("min_settle_height", K::Ipld),
+            ("lane_states", K::Ipld),
+        ],
+        BuiltinActor::Multisig => &[
+            ("signers", K::Ipld),
+            ("num_approvals_threshold", K::Ipld),
+            ("next_tx_id", K::Ipld),
+            ("initial_balance", K::TokenAmount),
+            ("start_epoch", K::Ipld),
+            ("unlock_duration", K::Ipld),
+            ("pending_txs", K::Ipld),
+        ],
+        BuiltinActor::Reward => &[
+            ("cumsum_baseline", K::BigInt),
+            ("cumsum_realized", K::BigInt),
+            ("effective_network_time", K::Ipld),
+            ("effective_baseline_power", K::BigInt),
+            ("this_epoch_reward", K::TokenAmount),
+            ("this_epoch_reward_smoothed", K::Ipld),
+            ("this_epoch_baseline_power", K::BigInt),
+            ("epoch", K::Ipld),
+            ("total_storage_power_reward", K::TokenAmount),
+            ("simple_total", K::TokenAmount),
+            ("baseline_total", K::TokenAmount),
+        ],
+        BuiltinActor::VerifiedRegistry if version == 8 => &[
+            ("root_key", K::Address),
+            ("verifiers", K::Ipld),
+            ("verified_clients", K::Ipld),
+            ("remove_data_cap_proposal_ids", K::Ipld),
+        ],
+        BuiltinActor::VerifiedRegistry => &[
+            ("root_key", K::Address),
+            ("verifiers", K::Ipld),
+            ("remove_data_cap_proposal_ids", K::Ipld),
+            ("allocations", K::Ipld),
+            ("next_allocation_id", K::Ipld),
+            ("claims", K::Ipld),
+        ],
+        BuiltinActor::DataCap => &[("governor", K::Address), ("token", K::Ipld)],
+        BuiltinActor::EVM if version >= 16 => &[
+            ("bytecode", K::Ipld),
+            ("bytecode_hash", K::Ipld),
+            ("contract_state", K::Ipld),
+            ("transient_data", K::Ipld),
+            ("nonce", K::Ipld),
+            ("tombstone", K::Ipld),
+        ],
+        BuiltinActor::EVM => &[
+            ("bytecode", K::Ipld),
+            ("bytecode_hash", K::Ipld),
+            ("contract_state", K::Ipld),
+            ("nonce", K::Ipld),
+            ("tombstone", K::Ipld),
+        ],
+        _ => &[],
+    }
+}
+
+/// Returns the fields of the state of an actor in order, with their names and kinds.
+fn state_fields(
+    bs: &impl Blockstore,
+    actor: &ActorState,
+) -> anyhow::Result<Vec<(String, FieldKind, Ipld)>> {
+    let state: Ipld = bs.get_cbor_required(&actor.state)?;
+    let known = builtin_actor_version(&actor.code)
+        .map(|(actor, version)| builtin_state_fields(actor, version))
+        .unwrap_or_default();
+    Ok(match state {
+        Ipld::List(values) => {
+            // don't guess the names of a layout we don't know
+            let known = if known.len() == values.len() {
+                known
+            } else {
+                &[]
+            };
+            values
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| match known.get(i) {
+                    Some((name, kind)) => (name.to_string(), *kind, value),
+                    None => (i.to_string(), FieldKind::Ipld, value),
+                })
+                .collect()
+        }
+        state => vec![("state".into(), FieldKind::Ipld, state)],
+    })
+}
+
+/// Returns the actors that were added or changed at `new_root` compared to `old_root`, with
+/// their state at `new_root`.
+///
+/// Like Lotus, the actor HAMTs of both state roots are walked side by side, skipping the subtrees
+/// with the same CID, so the cost depends on the number of changed actors rather than on the size
+/// of the state trees.
+pub fn changed_actors<BS: Blockstore>(
+    bs: &Arc<BS>,
+    old_root: &Cid,
+    new_root: &Cid,
+) -> anyhow::Result<HashMap<Address, ActorState>> {
+    let mut changed = HashMap::default();
+    if old_root == new_root {
+        return Ok(changed);
+    }
+    let mut changed_keys = vec![];
+    diff_hamt_nodes(
+        bs.as_ref(),
+        &actors_hamt_root(bs.as_ref(), old_root)?,
+        &actors_hamt_root(bs.as_ref(), new_root)?,
+        &mut changed_keys,
+    )?;
+    let new_tree = StateTree::new_from_root(bs.clone(), new_root)?;
+    for key in changed_keys {
+        let address = Address::from_bytes(&key)?;
+        let actor = new_tree
+            .get_actor(&address)?
+            .with_context(|| format!("actor {address} not found at state root {new_root}"))?;
+        changed.insert(address, actor);
+    }
+    Ok(changed)
+}
+
+/// Returns the root of the actors HAMT of a state tree. Unlike later versions, state trees of
+/// version 0 have no [`StateRoot`] and are the HAMT itself.
+fn actors_hamt_root(bs: &impl Blockstore, root: &Cid) -> anyhow::Result<Cid> {
+    match bs.get_cbor::<StateRoot>(root) {
+        Ok(Some(state_root)) => Ok(state_root.actors),
+        Ok(None) => bail!("state root {root} not found"),
+        Err(_) => Ok(*root),
+    }
+}
+
+/// A pointer of a HAMT node, see [`fvm_ipld_hamt`].
+enum HamtPointer {
+    Link(Cid),
+    Values(Vec<(Vec<u8>, Ipld)>),
+}
+
+impl HamtPointer {
+    fn from_ipld(ipld: Ipld) -> anyhow::Result<Self> {
+        Ok(match ipld {
+            Ipld::Link(cid) => Self::Link(cid),
+            Ipld::List(values) => Self::Values(
+                values
+                    .into_iter()
+                    .map(|value| match value {
+                        Ipld::List(kv) => match <[Ipld; 2]>::try_from(kv) {
+                            Ok([Ipld::Bytes(key), value]) => Ok((key, value)),
+                            _ => bail!("invalid HAMT key-value pair"),
+                        },
+                        _ => bail!("invalid HAMT key-value pair"),
+                    })
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            // Pointers of version 0 HAMTs are maps keyed by their kind
+            Ipld::Map(mut map) if map.len() == 1 => match (map.remove("0"), map.remove("1")) {
+                (Some(link @ Ipld::Link(_)), None) | (None, Some(link @ Ipld::List(_))) => {
+                    Self::from_ipld(link)?
+                }
+                _ => bail!("invalid HAMT pointer"),
+            },
+            _ => bail!("invalid HAMT pointer"),
+        })
+    }
+
+    /// Collects all the key-value pairs under this pointer.
+    fn collect_values(
+        self,
+        bs: &impl Blockstore,
+        values: &mut HashMap<Vec<u8>, Ipld>,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Link(cid) => {
+                for (_, pointer) in hamt_node(bs, &cid)? {
+                    pointer.collect_values(bs, values)?;
+                }
+            }
+            Self::Values(pairs) => values.extend(pairs),
+        }
+        Ok(())
+    }
+}
+
+/// Returns the pointers of a HAMT node with the index of their slot, in ascending order.
+fn hamt_node(bs: &impl Blockstore, cid: &Cid) -> anyhow::Result<Vec<(usize, HamtPointer)>> {
+    let Ipld::List(node) = bs.get_cbor_required(cid)? else {
+        bail!("invalid HAMT node {cid}");
+    };
+    let Ok([Ipld::Bytes(bitfield), Ipld::List(pointers)]) = <[Ipld; 2]>::try_from(node) else {
+        bail!("invalid HAMT node {cid}");
+    };
+    // The bitfield is a big-endian integer whose set bits are the occupied slots
+    let slots: Vec<_> = bitfield
+        .iter()
+        .rev()
+        .enumerate()
+        .flat_map(|(i, byte)| {
+            (0..8)
+                .filter(move |bit| byte & (1 << bit) != 0)
+                .map(move |bit| i * 8 + bit)
+        })
+        .collect();
+    ensure!(
+        slots.len() == pointers.len(),
+        "invalid HAMT node {cid}: bitfield doesn't match the pointers"
+    );
+    slots
+        .into_iter()
+        .zip(pointers)
+        .map(|(slot, pointer)| Ok((slot, HamtPointer::from_ipld(pointer)?)))
+        .collect()
+}
+
+/// Pushes to `changed` the keys that were added or changed in the HAMT node `new` compared to the
+/// HAMT node `old`. Linked nodes are only loaded if their CIDs differ.
+fn diff_hamt_nodes(
+    bs: &impl Blockstore,
+    old: &Cid,
+    new: &Cid,
+    changed: &mut Vec<Vec<u8>>,
+) -> anyhow::Result<()> {
+    if old == new {
+        return Ok(());
+    }
+    let mut old_pointers: HashMap<_, _> = hamt_node(bs, old)?.into_iter().collect();
+    for (slot, new_pointer) in hamt_node(bs, new)? {
+        match (old_pointers.remove(&slot), new_pointer) {
+            (Some(HamtPointer::Link(old)), HamtPointer::Link(new)) => {
+                diff_hamt_nodes(bs, &old, &new, changed)?
+            }
+            (old_pointer, new_pointer) => {
+                let mut old_values = HashMap::default();
+                if let Some(old_pointer) = old_pointer {
+                    old_pointer.collect_values(bs, &mut old_values)?;
+                }
+                let mut new_values = HashMap::default();
+                new_pointer.collect_values(bs, &mut new_values)?;
+                changed.extend(
+                    new_values
+                        .into_iter()
+                        .filter(|(key, value)| old_values.get(key) != Some(value))
+                        .map(|(key, _)| key),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the difference of the actor with the given address between `old_root` and
+/// `new_root`, down to the fields of its state. Fields that link to other objects, like the
+/// HAMTs of the builtin actors, are compared by their CID.
+pub fn actor_diff<BS: Blockstore>(
+    bs: &Arc<BS>,
+    address: &Address,
+    old_root: &Cid,
+    new_root: &Cid,
+) -> anyhow::Result<ActorDiff> {
+    let old = StateTree::new_from_root(bs.clone(), old_root)?.get_actor(address)?;
+    let new = StateTree::new_from_root(bs.clone(), new_root)?.get_actor(address)?;
+    ensure!(
+        old.is_some() || new.is_some(),
+        "actor {address} not found at either state root"
+    );
+    let mut fields = vec![];
+    if old.as_ref().map(|it| it.state) != new.as_ref().map(|it| it.state) {
+        let old_fields = match &old {
+            Some(actor) => state_fields(bs.as_ref(), actor)?,
+            None => vec![],
+        };
+        let mut new_fields = match &new {
+            Some(actor) => state_fields(bs.as_ref(), actor)?,
+            None => vec![],
+        };
+        for (name, old_kind, old_value) in old_fields {
+            match new_fields.iter().position(|(it, ..)| *it == name) {
+                Some(i) => {
+                    let (_, new_kind, new_value) = new_fields.remove(i);
+                    if new_value != old_value {
+                        fields.push(FieldDiff {
+                            name,
+                            old: Some(old_kind.to_json(&old_value)?),
+                            new: Some(new_kind.to_json(&new_value)?),
+                        });
+                    }
+                }
+                None => fields.push(FieldDiff {
+                    name,
+                    old: Some(old_kind.to_json(&old_value)?),
+                    new: None,
+                }),
+            }
+        }
+        for (name, new_kind, new_value) in new_fields {
+            fields.push(FieldDiff {
+                name,
+                old: None,
+                new: Some(new_kind.to_json(&new_value)?),
+            });
+        }
+    }
+    Ok(ActorDiff { old, new, fields })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDB;
+    use crate::networks::ACTOR_BUNDLES_METADATA;
+    use crate::shim::state_tree::StateTreeVersion;
+    use fil_actor_account_state::v16::State as AccountState;
+    use serde_json::json;
+
+    fn account_code() -> Cid {
+        ACTOR_BUNDLES_METADATA
+            .values()
+            .find_map(|bundle| {
+                (bundle.actor_major_version().ok()? == 16)
+                    .then(|| bundle.manifest.get(BuiltinActor::Account).ok())?
+            })
+            .unwrap()
+    }
+
+    fn state_root(db: &Arc<MemoryDB>, actors: &[(Address, ActorState)]) -> Cid {
+        let mut tree = StateTree::new(db.clone(), StateTreeVersion::V5).unwrap();
+        for (address, actor) in actors {
+            tree.set_actor(address, actor.clone()).unwrap();
+        }
+        tree.flush().unwrap()
+    }
+
+    fn account(db: &MemoryDB, key: u64, balance: u64) -> ActorState {
+        let state = db
+            .put_cbor_default(&AccountState {
+                address: Address::new_id(key).into(),
+            })
+            .unwrap();
+        ActorState::new(
+            account_code(),
+            state,
+            TokenAmount::from_atto(balance),
+            0,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_changed_actors() {
+        let db = Arc::new(MemoryDB::default());
+        let (unchanged, changed, added) = (
+            Address::new_id(100),
+            Address::new_id(101),
+            Address::new_id(102),
+        );
+        let old_root = state_root(
+            &db,
+            &[
+                (unchanged, account(&db, 1, 10)),
+                (changed, account(&db, 2, 10)),
+            ],
+        );
+        let new_root = state_root(
+            &db,
+            &[
+                (unchanged, account(&db, 1, 10)),
+                (changed, account(&db, 2, 20)),
+                (added, account(&db, 3, 0)),
+            ],
+        );
+
+        let actors = changed_actors(&db, &old_root, &new_root).unwrap();
+        assert_eq!(actors.len(), 2);
+        assert_eq!(actors[&changed], account(&db, 2, 20));
+        assert_eq!(actors[&added], account(&db, 3, 0));
+        assert!(changed_actors(&db, &old_root, &old_root)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_changed_actors_in_deep_trees() {
+        let db = Arc::new(MemoryDB::default());
+        let actors: Vec<_> = (0..1000)
+            .map(|i| (Address::new_id(100 + i), account(&db, i, 10)))
+            .collect();
+        let old_root = state_root(&db, &actors);
+
+        let mut new_actors = actors.clone();
+        new_actors[7].1 = account(&db, 7, 20);
+        new_actors[500].1 = account(&db, 500, 0);
+        new_actors.extend((1000..1100).map(|i| (Address::new_id(100 + i), account(&db, i, 0))));
+        // Deleted actors are not reported
+        new_actors.remove(900);
+        let new_root = state_root(&db, &new_actors);
+
+        let mut expected = HashMap::default();
+        for (address, actor) in &new_actors {
+            if !actors.contains(&(*address, actor.clone())) {
+                expected.insert(*address, actor.clone());
+            }
+        }
+        assert_eq!(expected.len(), 102);
+        assert_eq!(changed_actors(&db, &old_root, &new_root).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_actor_diff() {
+        let db = Arc::new(MemoryDB::default());
+        let address = Address::new_id(100);
+        let old_root = state_root(&db, &[(address, account(&db, 1, 10))]);
+        let new_root = state_root(&db, &[(address, account(&db, 2, 20))]);
+
+        let diff = actor_diff(&db, &address, &old_root, &new_root).unwrap();
+        assert_eq!(diff.old, Some(account(&db, 1, 10)));
+        assert_eq!(diff.new, Some(account(&db, 2, 20)));
+        assert_eq!(
+            diff.fields,
+            vec![FieldDiff {
+                name: "address".into(),
+                old: Some(json!("f01")),
+                new: Some(json!("f02")),
+            }]
+        );
+
+        let diff = actor_diff(&db, &Address::new_id(101), &old_root, &new_root);
+        assert!(diff.is_err());
+    }
+}
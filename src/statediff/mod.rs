@@ -1,8 +1,11 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+mod actor_diff;
 mod resolve;
 
+pub use actor_diff::{actor_diff, changed_actors, ActorDiff};
+
 use std::{
     fmt::Write as FmtWrite,
     io::{stdout, Write},
@@ -34,6 +34,8 @@ where
                 Subcommand::Car(cmd) => cmd.run().await,
                 Subcommand::Api(cmd) => cmd.run().await,
                 Subcommand::Net(cmd) => cmd.run().await,
+                Subcommand::ChainSpec(cmd) => cmd.run(),
+                Subcommand::Genesis(cmd) => cmd.run().await,
                 Subcommand::Shed(cmd) => cmd.run(client).await,
                 Subcommand::Completion(cmd) => cmd.run(&mut std::io::stdout()),
             }
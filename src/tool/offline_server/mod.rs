@@ -2,4 +2,4 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 mod server;
-pub use server::start_offline_server;
+pub use server::{start_offline_server, ForkOptions};
@@ -2,9 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use crate::auth::generate_priv_key;
+use crate::beacon::{mock_beacon::MockBeacon, BeaconPoint, BeaconSchedule};
+use crate::chain::index::ResolveNullTipset;
 use crate::chain::ChainStore;
 use crate::chain_sync::network_context::SyncNetworkContext;
-use crate::chain_sync::SyncStage;
+use crate::chain_sync::{BlockProducer, SyncStage};
 use crate::cli_shared::cli::EventsConfig;
 use crate::cli_shared::snapshot::TrustedVendor;
 use crate::daemon::db_util::populate_eth_mappings;
@@ -14,9 +16,11 @@ use crate::key_management::{KeyStore, KeyStoreConfig};
 use crate::libp2p::PeerManager;
 use crate::message_pool::{MessagePool, MpoolRpcProvider};
 use crate::networks::{ChainConfig, NetworkChain};
+use crate::paychmgr::PaychStore;
 use crate::rpc::eth::filter::EthEventHandler;
 use crate::rpc::{start_rpc, RPCState};
-use crate::shim::address::{CurrentNetwork, Network};
+use crate::shim::address::{Address, CurrentNetwork, Network};
+use crate::shim::clock::ChainEpoch;
 use crate::state_manager::StateManager;
 use crate::utils::net::{download_to, DownloadFileOption};
 use crate::JWT_IDENTIFIER;
@@ -37,6 +41,16 @@ use tokio::{
 };
 use tracing::{info, warn};
 
+/// Options of a local fork of the chain, served by the offline RPC server.
+#[derive(Debug, Clone)]
+pub struct ForkOptions {
+    /// Epoch of the tipset to fork the chain from. A null round resolves to the tipset before it.
+    pub epoch: ChainEpoch,
+    /// Miner of the local tipsets. Defaults to the miner of a block of the forked tipset.
+    pub miner: Option<Address>,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn start_offline_server(
     snapshot_files: Vec<PathBuf>,
     chain: NetworkChain,
@@ -45,6 +59,7 @@ pub async fn start_offline_server(
     height: i64,
     genesis: Option<PathBuf>,
     save_jwt_token: Option<PathBuf>,
+    fork: Option<ForkOptions>,
 ) -> anyhow::Result<()> {
     info!("Configuring Offline RPC Server");
 
@@ -74,22 +89,136 @@ pub async fn start_offline_server(
         chain_config.clone(),
         genesis_header.clone(),
     )?);
-    let state_manager = Arc::new(StateManager::new(chain_store.clone(), chain_config)?);
-    let head_ts = Arc::new(db.heaviest_tipset()?);
+    let mut state_manager = StateManager::new(chain_store.clone(), chain_config.clone())?;
+    if let Some(fork) = &fork {
+        let fork_ts = chain_store.chain_index.tipset_by_height(
+            fork.epoch,
+            Arc::new(db.heaviest_tipset()?),
+            ResolveNullTipset::TakeOlder,
+        )?;
+        state_manager = state_manager.with_beacon_schedule(Arc::new(fork_beacon_schedule(
+            &chain_config,
+            genesis_header.timestamp,
+            fork_ts.epoch(),
+        )));
+    }
+    let state_manager = Arc::new(state_manager);
+
+    let (shutdown, shutdown_recv) = mpsc::channel(1);
+
+    let mut keystore = KeyStore::new(KeyStoreConfig::Memory)?;
+    keystore.put(JWT_IDENTIFIER, generate_priv_key())?;
+    let ki = keystore.get(JWT_IDENTIFIER)?;
+    // Lotus admin tokens do not expire but Forest requires all JWT tokens to
+    // have an expiration date. So we set the expiration date to 100 years in
+    // the future to match user-visible behavior of Lotus.
+    let token_exp = chrono::Duration::days(365 * 100);
+    let token = crate::auth::create_token(
+        crate::auth::ADMIN.iter().map(ToString::to_string).collect(),
+        ki.private_key(),
+        token_exp,
+    )?;
+    info!("Admin token: {token}");
+    if let Some(path) = save_jwt_token {
+        std::fs::write(path, token)?;
+    }
+
+    let mut services = JoinSet::new();
+    let rpc_state = offline_rpc_state(
+        state_manager,
+        height,
+        fork,
+        keystore,
+        &events_config,
+        shutdown,
+        &mut services,
+    )?;
+    start_offline_rpc(rpc_state, rpc_port, shutdown_recv).await?;
+
+    Ok(())
+}
+
+/// Beacon schedule of a fork from the tipset at `fork_epoch`. The tipsets produced on the fork get
+/// their beacon entries from a mock beacon, so that the server does not depend on `drand`, while
+/// the tipsets of the snapshots keep being checked against the beacons of the chain.
+fn fork_beacon_schedule(
+    chain_config: &ChainConfig,
+    genesis_timestamp: u64,
+    fork_epoch: ChainEpoch,
+) -> BeaconSchedule {
+    let mut schedule = chain_config.get_beacon_schedule(genesis_timestamp);
+    schedule.0.retain(|point| point.height <= fork_epoch);
+    schedule.0.push(BeaconPoint {
+        height: fork_epoch + 1,
+        beacon: Box::<MockBeacon>::default(),
+    });
+    schedule
+}
+
+/// Builds the state of the offline RPC server, whose chain is the heaviest tipset of the snapshots
+/// or a fork of it. The background tasks of the message pool are spawned in `services`.
+fn offline_rpc_state(
+    state_manager: Arc<StateManager<ManyCar>>,
+    height: i64,
+    fork: Option<ForkOptions>,
+    keystore: KeyStore,
+    events_config: &EventsConfig,
+    shutdown: mpsc::Sender<()>,
+    services: &mut JoinSet<anyhow::Result<()>>,
+) -> anyhow::Result<RPCState<ManyCar>> {
+    let db = state_manager.blockstore_owned();
+    let chain_store = state_manager.chain_store();
+    let mut head_ts = Arc::new(db.heaviest_tipset()?);
+    if let Some(fork) = &fork {
+        // New tipsets and messages are written to the in-memory store on top of the snapshots,
+        // so forking leaves the snapshot files untouched.
+        head_ts = chain_store.chain_index.tipset_by_height(
+            fork.epoch,
+            head_ts,
+            ResolveNullTipset::TakeOlder,
+        )?;
+        chain_store.set_heaviest_tipset(head_ts.clone())?;
+        info!(
+            "Forking the chain from tipset {} at epoch {}",
+            head_ts.key(),
+            head_ts.epoch()
+        );
+    }
 
     populate_eth_mappings(&state_manager, &head_ts)?;
 
-    let (network_send, _) = flume::bounded(5);
+    // There are no peers, so the messages published by the message pool are dropped. The
+    // receiver is kept alive, as publishing fails once it is dropped.
+    let (network_send, network_rx) = flume::bounded(5);
+    services.spawn(async move {
+        while network_rx.recv_async().await.is_ok() {}
+        Ok(())
+    });
     let (tipset_send, _) = flume::bounded(5);
     let network_name = state_manager.get_network_name_from_genesis()?;
-    let message_pool: MessagePool<MpoolRpcProvider<ManyCar>> = MessagePool::new(
+    let message_pool: Arc<MessagePool<MpoolRpcProvider<ManyCar>>> = Arc::new(MessagePool::new(
         MpoolRpcProvider::new(chain_store.publisher().clone(), state_manager.clone()),
         network_name.clone(),
         network_send.clone(),
         Default::default(),
         state_manager.chain_config().clone(),
-        &mut JoinSet::new(),
-    )?;
+        services,
+    )?);
+    let block_producer = match fork {
+        Some(ForkOptions { miner, .. }) => {
+            let miner = match miner {
+                Some(miner) => miner,
+                None => head_ts.min_ticket_block().miner_address,
+            };
+            info!("Producing local tipsets mined by {miner}");
+            Some(Arc::new(BlockProducer::new(
+                state_manager.clone(),
+                message_pool.clone(),
+                miner,
+            )?))
+        }
+        None => None,
+    };
 
     // Validate tipsets since the {height} EPOCH when `height >= 0`,
     // or valiadte the last {-height} EPOCH(s) when `height < 0`
@@ -102,25 +231,6 @@ pub async fn start_offline_server(
         state_manager.validate_tipsets(head_ts.chain_arc(&db).take(n_ts_to_validate))?;
     }
 
-    let (shutdown, shutdown_recv) = mpsc::channel(1);
-
-    let mut keystore = KeyStore::new(KeyStoreConfig::Memory)?;
-    keystore.put(JWT_IDENTIFIER, generate_priv_key())?;
-    let ki = keystore.get(JWT_IDENTIFIER)?;
-    // Lotus admin tokens do not expire but Forest requires all JWT tokens to
-    // have an expiration date. So we set the expiration date to 100 years in
-    // the future to match user-visible behavior of Lotus.
-    let token_exp = chrono::Duration::days(365 * 100);
-    let token = crate::auth::create_token(
-        crate::auth::ADMIN.iter().map(ToString::to_string).collect(),
-        ki.private_key(),
-        token_exp,
-    )?;
-    info!("Admin token: {token}");
-    if let Some(path) = save_jwt_token {
-        std::fs::write(path, token)?;
-    }
-
     let peer_manager = Arc::new(PeerManager::default());
     let sync_network_context =
         SyncNetworkContext::new(network_send, peer_manager, state_manager.blockstore_owned());
@@ -128,11 +238,15 @@ pub async fn start_offline_server(
     let rpc_state = RPCState {
         state_manager,
         keystore: Arc::new(RwLock::new(keystore)),
-        mpool: Arc::new(message_pool),
+        mpool: message_pool,
         bad_blocks: Default::default(),
         msgs_in_tipset: Default::default(),
         sync_states: Arc::new(parking_lot::RwLock::new(nunny::vec![Default::default()])),
-        eth_event_handler: Arc::new(EthEventHandler::from_config(&events_config)),
+        eth_event_handler: Arc::new(EthEventHandler::from_config(events_config)),
+        paych_store: Arc::new(PaychStore::new(db.clone())),
+        settings_store: db,
+        chain_pruner: None,
+        block_producer,
         sync_network_context,
         network_name,
         start_time: chrono::Utc::now(),
@@ -145,9 +259,7 @@ pub async fn start_offline_server(
         .write()
         .first_mut()
         .set_stage(SyncStage::Idle);
-    start_offline_rpc(rpc_state, rpc_port, shutdown_recv).await?;
-
-    Ok(())
+    Ok(rpc_state)
 }
 
 async fn start_offline_rpc<DB>(
@@ -163,7 +275,7 @@ where
     let mut terminate = signal(SignalKind::terminate())?;
 
     let result = tokio::select! {
-        ret = start_rpc(state, rpc_address, None) => ret,
+        ret = start_rpc(state, rpc_address, None, None, None, Default::default()) => ret,
         _ = ctrl_c() => {
             info!("Keyboard interrupt.");
             Ok(())
@@ -240,3 +352,91 @@ fn handle_chain_config(chain: &NetworkChain) -> anyhow::Result<ChainConfig> {
     }
     Ok(chain_config)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_sync::block_producer::tests::sign_transfer;
+    use crate::db::car::AnyCar;
+    use crate::genesis::{
+        export_genesis,
+        test_utils::{solo_genesis, SOLO_MINER},
+    };
+    use crate::key_management::generate_key;
+    use crate::rpc::{chain::ChainProduceTipset, mpool::MpoolPush, RpcMethod as _};
+    use crate::shim::crypto::SignatureType;
+
+    #[tokio::test]
+    async fn fork_includes_pushed_messages() {
+        let chain_config = Arc::new(ChainConfig::devnet());
+        let owner = generate_key(SignatureType::Bls).unwrap();
+        let account = generate_key(SignatureType::Secp256k1).unwrap();
+        let genesis_db = Arc::new(MemoryDB::default());
+        let genesis = solo_genesis(&genesis_db, &chain_config, &owner.address, &account.address);
+        let genesis_timestamp = genesis.timestamp;
+        let mut car = vec![];
+        export_genesis(genesis_db, &genesis, &mut car)
+            .await
+            .unwrap();
+
+        let db = Arc::new(
+            ManyCar::new(MemoryDB::default())
+                .with_read_only(AnyCar::new(car).unwrap())
+                .unwrap(),
+        );
+        let chain_store = Arc::new(
+            ChainStore::new(db.clone(), db.clone(), db, chain_config.clone(), genesis).unwrap(),
+        );
+        let state_manager = Arc::new(
+            StateManager::new(chain_store, chain_config.clone())
+                .unwrap()
+                .with_beacon_schedule(Arc::new(fork_beacon_schedule(
+                    &chain_config,
+                    genesis_timestamp,
+                    0,
+                ))),
+        );
+        let mut services = JoinSet::new();
+        let ctx = Arc::new(
+            offline_rpc_state(
+                state_manager,
+                0,
+                Some(ForkOptions {
+                    epoch: 0,
+                    miner: Some(SOLO_MINER),
+                }),
+                KeyStore::new(KeyStoreConfig::Memory).unwrap(),
+                &EventsConfig::default(),
+                mpsc::channel(1).0,
+                &mut services,
+            )
+            .unwrap(),
+        );
+
+        let message = sign_transfer(&account, owner.address, 0);
+        MpoolPush::handle(ctx.clone(), (message.clone(),))
+            .await
+            .unwrap();
+        let tipset = ChainProduceTipset::handle(ctx.clone(), (None,))
+            .await
+            .unwrap();
+        assert_eq!(tipset.epoch(), 1);
+        let messages = ctx.chain_store().messages_for_tipset(&tipset).unwrap();
+        assert_eq!(
+            messages.iter().map(|m| m.cid()).collect::<Vec<_>>(),
+            vec![message.cid()]
+        );
+    }
+
+    #[test]
+    fn fork_beacon_schedule_mocks_the_produced_tipsets() {
+        let chain_config = ChainConfig::calibnet();
+        // Timestamp of the calibnet genesis
+        let schedule = fork_beacon_schedule(&chain_config, 1_667_326_380, 100);
+        let height = |epoch| schedule.beacon_for_epoch(epoch).unwrap().0;
+        assert_eq!(height(100), 0);
+        assert_eq!(height(101), 101);
+        // Later beacons of the chain are replaced too
+        assert_eq!(height(ChainEpoch::MAX), 101);
+    }
+}
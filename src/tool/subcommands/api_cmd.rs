@@ -15,7 +15,8 @@ use crate::rpc;
 use crate::rpc::eth::types::*;
 use crate::rpc::prelude::*;
 use crate::shim::address::Address;
-use crate::tool::offline_server::start_offline_server;
+use crate::shim::clock::ChainEpoch;
+use crate::tool::offline_server::{start_offline_server, ForkOptions};
 use crate::tool::subcommands::api_cmd::test_snapshot::{Index, Payload};
 use crate::utils::UrlFromMultiAddr;
 use anyhow::{bail, ensure, Context as _};
@@ -63,6 +64,14 @@ pub enum ApiCommands {
         /// If provided, indicates the file to which to save the admin token.
         #[arg(long)]
         save_token: Option<PathBuf>,
+        /// Fork the chain at the tipset of the given EPOCH. Messages pushed to the message pool
+        /// are then included in the local tipsets produced with `Forest.ChainProduceTipset`.
+        #[arg(long)]
+        fork_from: Option<ChainEpoch>,
+        /// Miner of the local tipsets of the fork. Defaults to the miner of a block of the forked
+        /// tipset.
+        #[arg(long, requires = "fork_from")]
+        fork_miner: Option<Address>,
     },
     /// Compare two RPC providers.
     ///
@@ -169,6 +178,8 @@ impl ApiCommands {
                 height,
                 genesis,
                 save_token,
+                fork_from,
+                fork_miner,
             } => {
                 if chain.is_devnet() {
                     ensure!(
@@ -186,6 +197,10 @@ impl ApiCommands {
                     height,
                     genesis,
                     save_token,
+                    fork_from.map(|epoch| ForkOptions {
+                        epoch,
+                        miner: fork_miner,
+                    }),
                 )
                 .await?;
             }
@@ -1236,6 +1236,7 @@ fn eth_tests() -> Vec<RpcTest> {
                             ..EthCallMessage::default()
                         },
                         BlockNumberOrHash::from_predefined(Predefined::Latest),
+                        None,
                     ),
                     use_alias,
                 )
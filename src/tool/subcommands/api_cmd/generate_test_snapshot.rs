@@ -16,6 +16,7 @@ use crate::{
     libp2p_bitswap::{BitswapStoreRead, BitswapStoreReadWrite, Block64},
     message_pool::{MessagePool, MpoolRpcProvider},
     networks::ChainConfig,
+    paychmgr::PaychStore,
     shim::address::CurrentNetwork,
     state_manager::StateManager,
     KeyStore, KeyStoreConfig,
@@ -131,6 +132,10 @@ async fn ctx(
         msgs_in_tipset: Default::default(),
         sync_states: Arc::new(RwLock::new(nunny::vec![Default::default()])),
         eth_event_handler: Arc::new(EthEventHandler::new()),
+        paych_store: Arc::new(PaychStore::new(Arc::new(MemoryDB::default()))),
+        settings_store: Arc::new(MemoryDB::default()),
+        chain_pruner: None,
+        block_producer: None,
         sync_network_context,
         network_name,
         start_time: chrono::Utc::now(),
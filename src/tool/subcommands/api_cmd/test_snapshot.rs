@@ -13,6 +13,7 @@ use crate::{
     lotus_json::HasLotusJson,
     message_pool::{MessagePool, MpoolRpcProvider},
     networks::{ChainConfig, NetworkChain},
+    paychmgr::PaychStore,
     rpc::{
         eth::{filter::EthEventHandler, types::EthHash},
         RPCState, RpcMethod as _, RpcMethodExt as _,
@@ -156,6 +157,10 @@ async fn ctx(
         msgs_in_tipset: Default::default(),
         sync_states: Arc::new(RwLock::new(nunny::vec![Default::default()])),
         eth_event_handler: Arc::new(EthEventHandler::new()),
+        paych_store: Arc::new(PaychStore::new(Arc::new(MemoryDB::default()))),
+        settings_store: Arc::new(MemoryDB::default()),
+        chain_pruner: None,
+        block_producer: None,
         sync_network_context,
         network_name,
         start_time: chrono::Utc::now(),
@@ -29,7 +29,7 @@
 use crate::blocks::Tipset;
 use crate::chain::{
     index::{ChainIndex, ResolveNullTipset},
-    ChainEpochDelta,
+    ChainEpochDelta, ExportFormat,
 };
 use crate::cid_collections::CidHashSet;
 use crate::cli_shared::{snapshot, snapshot::TrustedVendor};
@@ -91,6 +91,9 @@ pub enum ArchiveCommands {
         /// Overwrite output file without prompting.
         #[arg(long, default_value_t = false)]
         force: bool,
+        /// Archive format of the snapshot.
+        #[arg(long, value_enum, default_value_t = ExportFormat::ForestCar)]
+        format: ExportFormat,
     },
     /// Print block headers at 30 day interval for a snapshot file
     Checkpoints {
@@ -146,6 +149,7 @@ impl ArchiveCommands {
                 diff,
                 diff_depth,
                 force,
+                format,
             } => {
                 let store = ManyCar::try_from(snapshot_files)?;
                 let heaviest_tipset = store.heaviest_tipset()?;
@@ -158,6 +162,7 @@ impl ArchiveCommands {
                     diff,
                     diff_depth,
                     force,
+                    format,
                 )
                 .await
             }
@@ -337,6 +342,7 @@ fn build_output_path(
     genesis_timestamp: u64,
     epoch: ChainEpoch,
     output_path: PathBuf,
+    format: ExportFormat,
 ) -> PathBuf {
     match output_path.is_dir() {
         true => output_path.join(snapshot::filename(
@@ -347,7 +353,7 @@ fn build_output_path(
                 .naive_utc()
                 .date(),
             epoch,
-            true,
+            format.is_forest_car(),
         )),
         false => output_path.clone(),
     }
@@ -363,6 +369,7 @@ async fn do_export(
     diff: Option<ChainEpoch>,
     diff_depth: Option<ChainEpochDelta>,
     force: bool,
+    format: ExportFormat,
 ) -> anyhow::Result<()> {
     let ts = Arc::new(root);
 
@@ -404,7 +411,13 @@ async fn do_export(
         CidHashSet::default()
     };
 
-    let output_path = build_output_path(network.to_string(), genesis.timestamp, epoch, output_path);
+    let output_path = build_output_path(
+        network.to_string(),
+        genesis.timestamp,
+        epoch,
+        output_path,
+        format,
+    );
 
     if !force && output_path.exists() {
         let have_permission = Confirm::with_theme(&ColorfulTheme::default())
@@ -444,7 +457,14 @@ async fn do_export(
     pb.enable_steady_tick(std::time::Duration::from_secs_f32(0.1));
     let writer = pb.wrap_async_write(writer);
 
-    crate::chain::export::<Sha256>(store.clone(), &ts, depth, writer, seen, true).await?;
+    match format {
+        ExportFormat::ForestCar => {
+            crate::chain::export::<Sha256>(store.clone(), &ts, depth, writer, seen, true).await?;
+        }
+        ExportFormat::CarV2 => {
+            crate::chain::export_car_v2(store.clone(), &ts, depth, writer, seen).await?;
+        }
+    }
 
     Ok(())
 }
@@ -609,6 +629,7 @@ mod tests {
             None,
             None,
             false,
+            ExportFormat::ForestCar,
         )
         .await
         .unwrap();
@@ -617,12 +638,43 @@ mod tests {
             genesis_timestamp(calibnet::DEFAULT_GENESIS),
             0,
             output_path.path().into(),
+            ExportFormat::ForestCar,
         ))
         .await
         .unwrap();
         CarStream::new(BufReader::new(file)).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn export_car_v2() {
+        let output_path = TempDir::new().unwrap();
+        let store = AnyCar::try_from(calibnet::DEFAULT_GENESIS).unwrap();
+        let heaviest_tipset = store.heaviest_tipset().unwrap();
+        do_export(
+            store.into(),
+            heaviest_tipset.clone(),
+            output_path.path().into(),
+            Some(0),
+            1,
+            None,
+            None,
+            false,
+            ExportFormat::CarV2,
+        )
+        .await
+        .unwrap();
+        let path = build_output_path(
+            NetworkChain::Calibnet.to_string(),
+            genesis_timestamp(calibnet::DEFAULT_GENESIS),
+            0,
+            output_path.path().into(),
+            ExportFormat::CarV2,
+        );
+        let car = AnyCar::try_from(path.as_path()).unwrap();
+        assert_eq!(car.variant(), "CARv2");
+        assert_eq!(car.heaviest_tipset().unwrap(), heaviest_tipset);
+    }
+
     #[test]
     fn archive_info_calibnet() {
         let info = ArchiveInfo::from_store_with(
@@ -14,6 +14,7 @@ use tokio::{
     io::{AsyncWriteExt, BufReader},
 };
 
+use crate::chain::ExportFormat;
 use crate::db::car::ForestCar;
 use crate::utils::db::{
     car_stream::CarStream,
@@ -27,9 +28,12 @@ pub enum CarCommands {
         /// A list of CAR file paths. A CAR file can be a plain CAR, a zstd compressed CAR
         /// or a `.forest.car.zst` file
         car_files: Vec<PathBuf>,
-        /// The output `.forest.car.zst` file path
+        /// The output `.forest.car.zst` or `.car` file path, depending on the format
         #[arg(short, long)]
         output: PathBuf,
+        /// Archive format of the output
+        #[arg(long, value_enum, default_value_t = ExportFormat::ForestCar)]
+        format: ExportFormat,
     },
     /// Check the validity of a CAR archive. For Filecoin-specific checks, see
     /// `forest-tool snapshot validate`.
@@ -48,7 +52,11 @@ pub enum CarCommands {
 impl CarCommands {
     pub async fn run(self) -> anyhow::Result<()> {
         match self {
-            Self::Concat { car_files, output } => {
+            Self::Concat {
+                car_files,
+                output,
+                format,
+            } => {
                 let car_streams: Vec<_> = futures::stream::iter(car_files)
                     .then(tokio::fs::File::open)
                     .map_ok(tokio::io::BufReader::new)
@@ -66,11 +74,21 @@ impl CarCommands {
                 )
                 .map_err(|_| anyhow::Error::msg("car roots cannot be empty"))?;
 
-                let frames = crate::db::car::forest::Encoder::compress_stream_default(
-                    dedup_block_stream(merge_car_streams(car_streams)).map_err(anyhow::Error::from),
-                );
+                let blocks =
+                    dedup_block_stream(merge_car_streams(car_streams)).map_err(anyhow::Error::from);
                 let mut writer = tokio::io::BufWriter::new(tokio::fs::File::create(&output).await?);
-                crate::db::car::forest::Encoder::write(&mut writer, all_roots, frames).await?;
+                match format {
+                    ExportFormat::ForestCar => {
+                        let frames =
+                            crate::db::car::forest::Encoder::compress_stream_default(blocks);
+                        crate::db::car::forest::Encoder::write(&mut writer, all_roots, frames)
+                            .await?;
+                    }
+                    ExportFormat::CarV2 => {
+                        crate::db::car::carv2::Encoder::write(&mut writer, all_roots, blocks)
+                            .await?;
+                    }
+                }
                 writer.flush().await?;
             }
             Self::Validate {
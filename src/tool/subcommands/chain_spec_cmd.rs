@@ -0,0 +1,43 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::path::PathBuf;
+
+use crate::networks::{ChainConfig, ChainSpecFormat, NetworkChain};
+use clap::Subcommand;
+
+#[derive(Debug, Subcommand)]
+pub enum ChainSpecCommands {
+    /// Print the chain specification of a network, which can be edited and loaded with
+    /// `forest --chain-spec`
+    Dump {
+        /// Built-in network chain. The parameters of devnets are read from the environment.
+        #[arg(long, default_value = "mainnet")]
+        chain: NetworkChain,
+        /// Chain specification file to print with all its parameters, instead of a built-in network
+        #[arg(long, conflicts_with = "chain")]
+        chain_spec: Option<PathBuf>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: ChainSpecFormat,
+    },
+}
+
+impl ChainSpecCommands {
+    pub fn run(self) -> anyhow::Result<()> {
+        match self {
+            Self::Dump {
+                chain,
+                chain_spec,
+                format,
+            } => {
+                let config = match chain_spec {
+                    Some(path) => ChainConfig::from_spec_file(&path)?,
+                    None => ChainConfig::from_chain(&chain),
+                };
+                println!("{}", config.to_spec(format)?);
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::mem::discriminant;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::daemon::bundle::load_actor_bundles_from_server;
+use crate::db::MemoryDB;
+use crate::genesis::{export_genesis, GenesisTemplate, GENESIS_ACTORS_VERSION};
+use crate::networks::{ChainConfig, NetworkChain, ACTOR_BUNDLES, ACTOR_BUNDLES_METADATA};
+use crate::shim::machine::BuiltinActorManifest;
+use crate::shim::version::NetworkVersion;
+use anyhow::{bail, ensure, Context as _};
+use clap::Subcommand;
+
+#[derive(Debug, Subcommand)]
+pub enum GenesisCommands {
+    /// Create a genesis CAR file from a template, to be loaded with `forest --genesis`
+    Create {
+        /// Genesis template, in TOML or JSON format
+        #[arg(long)]
+        template: PathBuf,
+        /// Output CAR file
+        #[arg(long)]
+        out: PathBuf,
+        /// Devnet the genesis is created for. Its parameters are read from the environment.
+        #[arg(long, default_value = "devnet")]
+        chain: NetworkChain,
+        /// Chain specification of the network, instead of the parameters of a devnet
+        #[arg(long, conflicts_with = "chain")]
+        chain_spec: Option<PathBuf>,
+    },
+}
+
+impl GenesisCommands {
+    pub async fn run(self) -> anyhow::Result<()> {
+        match self {
+            Self::Create {
+                template,
+                out,
+                chain,
+                chain_spec,
+            } => {
+                let chain_config = match chain_spec {
+                    Some(path) => ChainConfig::from_spec_file(&path)?,
+                    None => ChainConfig::from_chain(&chain),
+                };
+                let template = GenesisTemplate::from_file(&template)?;
+                let network_version = chain_config.network_version(0);
+                ensure!(
+                    template.network_version(&chain_config) == network_version,
+                    "network version {} of the template does not match the network version {} of {} at genesis",
+                    template.network_version(&chain_config),
+                    network_version,
+                    chain_config.network
+                );
+                let chain_actors_version = chain_config
+                    .actors_bundle(network_version)
+                    .and_then(|cid| {
+                        ACTOR_BUNDLES_METADATA
+                            .values()
+                            .find(|bundle| bundle.bundle_cid == cid)
+                    })
+                    .map(|bundle| bundle.actor_major_version())
+                    .transpose()?;
+                ensure!(
+                    chain_actors_version == Some(GENESIS_ACTORS_VERSION),
+                    "{} must run actors v{GENESIS_ACTORS_VERSION} at genesis, not {} at network version {network_version}",
+                    chain_config.network,
+                    chain_actors_version.map_or("unknown actors".into(), |v| format!("actors v{v}")),
+                );
+                // Migrations of later upgrades would run on a state they were not written for
+                if let Some((height, info)) =
+                    chain_config.height_infos.iter().find(|(height, info)| {
+                        info.epoch >= 0 && NetworkVersion::from(**height) <= network_version
+                    })
+                {
+                    bail!(
+                        "upgrade {height} to network version {} of {} is scheduled at epoch {}, after genesis",
+                        NetworkVersion::from(*height),
+                        chain_config.network,
+                        info.epoch
+                    );
+                }
+
+                let bundle = ACTOR_BUNDLES_METADATA
+                    .values()
+                    .find(|bundle| {
+                        discriminant(&bundle.network) == discriminant(&chain_config.network)
+                            && bundle.version == template.actors_version
+                    })
+                    .with_context(|| {
+                        format!(
+                            "no actor bundle {} for {}",
+                            template.actors_version, chain_config.network
+                        )
+                    })?;
+                ensure!(
+                    bundle.actor_major_version()? == GENESIS_ACTORS_VERSION,
+                    "only actors v{GENESIS_ACTORS_VERSION} are supported at genesis"
+                );
+                let bundle_info = ACTOR_BUNDLES
+                    .iter()
+                    .find(|info| info.manifest == bundle.bundle_cid)
+                    .context("actor bundle not found")?;
+                let db = Arc::new(MemoryDB::default());
+                load_actor_bundles_from_server(
+                    &db,
+                    &chain_config.network,
+                    std::slice::from_ref(bundle_info),
+                )
+                .await?;
+                let manifest = BuiltinActorManifest::load_manifest(&db, &bundle.bundle_cid)?;
+
+                let genesis = template.build(&db, &manifest, &chain_config)?;
+                export_genesis(db, &genesis, tokio::fs::File::create(&out).await?).await?;
+                println!("Genesis {} written to {}", genesis.cid(), out.display());
+                Ok(())
+            }
+        }
+    }
+}
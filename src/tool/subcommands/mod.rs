@@ -6,8 +6,10 @@ mod archive_cmd;
 mod backup_cmd;
 mod benchmark_cmd;
 mod car_cmd;
+mod chain_spec_cmd;
 mod db_cmd;
 mod fetch_params_cmd;
+mod genesis_cmd;
 mod net_cmd;
 mod shed_cmd;
 mod snapshot_cmd;
@@ -72,6 +74,14 @@ pub enum Subcommand {
     #[command(subcommand)]
     Net(net_cmd::NetCommands),
 
+    /// Chain specifications of networks
+    #[command(subcommand)]
+    ChainSpec(chain_spec_cmd::ChainSpecCommands),
+
+    /// Create genesis blocks of private networks
+    #[command(subcommand)]
+    Genesis(genesis_cmd::GenesisCommands),
+
     /// Miscellaneous, semver-exempt commands for developer use.
     #[command(subcommand)]
     Shed(shed_cmd::ShedCommands),
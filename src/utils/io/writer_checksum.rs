@@ -4,7 +4,7 @@ use std::{pin::Pin, task::Poll};
 
 use digest::{Digest, Output};
 use pin_project_lite::pin_project;
-use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
+use tokio::io::{AsyncSeek, AsyncWrite, AsyncWriteExt, BufWriter};
 
 pin_project! {
     /// Wrapper `AsyncWriter` implementation that calculates the optional checksum on the fly.
@@ -110,6 +110,19 @@ impl AsyncWrite for VoidAsyncWriter {
     }
 }
 
+impl AsyncSeek for VoidAsyncWriter {
+    fn start_seek(self: Pin<&mut Self>, _position: std::io::SeekFrom) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn poll_complete(
+        self: Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> Poll<std::io::Result<u64>> {
+        std::task::Poll::Ready(Ok(0))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use rand::RngCore;
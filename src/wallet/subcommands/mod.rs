@@ -1,6 +1,7 @@
 // Copyright 2019-2025 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+pub mod msig_cmd;
 pub mod wallet_cmd;
 
 use crate::cli_shared::cli::{CliRpcOpts, HELP_MESSAGE};
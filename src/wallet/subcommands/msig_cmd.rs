@@ -0,0 +1,306 @@
+// Copyright 2019-2025 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::str::FromStr as _;
+use std::time::Duration;
+
+use super::wallet_cmd::WalletBackend;
+use crate::cli::humantoken;
+use crate::rpc::{prelude::*, types::MessageLookup};
+use crate::shim::address::{Address, StrictAddress};
+use crate::shim::clock::ChainEpoch;
+use crate::shim::econ::TokenAmount;
+use crate::shim::message::{Message, MethodNum};
+use anyhow::{ensure, Context as _};
+use cid::Cid;
+use clap::Subcommand;
+use fil_actor_init_state::v16::ExecReturn;
+use fil_actor_multisig_state::v16::ProposeReturn;
+use num::Zero as _;
+
+/// Number of epochs a message must be buried under before its receipt is reported.
+const MESSAGE_CONFIDENCE: i64 = 5;
+
+#[derive(Debug, Subcommand)]
+pub enum MsigCommands {
+    /// Create a multisig wallet
+    Create {
+        /// Addresses of the signers
+        #[arg(num_args = 1.., required = true)]
+        signers: Vec<String>,
+        /// Number of approvals required to execute a transaction, defaults to all the signers
+        #[arg(long, default_value_t = 0)]
+        required: u64,
+        /// Number of epochs over which the initial balance vests
+        #[arg(long, default_value_t = 0)]
+        duration: ChainEpoch,
+        /// Initial balance of the wallet
+        #[arg(long, value_parser = humantoken::parse, default_value_t = TokenAmount::zero())]
+        value: TokenAmount,
+        /// The account sending the message, defaults to the default wallet address
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// Propose a multisig transaction
+    Propose {
+        /// Address of the multisig wallet
+        msig: String,
+        /// Recipient of the transaction
+        to: String,
+        #[arg(value_parser = humantoken::parse)]
+        value: TokenAmount,
+        /// Method to invoke on the recipient
+        #[arg(long, default_value_t = 0)]
+        method: MethodNum,
+        /// Hex encoded parameters of the method
+        #[arg(long, default_value = "")]
+        params: String,
+        /// The signer proposing the transaction, defaults to the default wallet address
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// Approve a pending multisig transaction
+    Approve {
+        /// Address of the multisig wallet
+        msig: String,
+        /// ID of the pending transaction
+        txn_id: u64,
+        /// The signer approving the transaction, defaults to the default wallet address
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// Cancel a pending multisig transaction
+    Cancel {
+        /// Address of the multisig wallet
+        msig: String,
+        /// ID of the pending transaction
+        txn_id: u64,
+        /// The signer who proposed the transaction, defaults to the default wallet address
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// Propose to replace a signer of a multisig wallet
+    SwapPropose {
+        /// Address of the multisig wallet
+        msig: String,
+        /// The signer to remove
+        old_signer: String,
+        /// The signer to add
+        new_signer: String,
+        /// The signer proposing the swap, defaults to the default wallet address
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// Approve a pending signer swap
+    SwapApprove {
+        /// Address of the multisig wallet
+        msig: String,
+        /// The signer who proposed the swap
+        proposer: String,
+        /// ID of the pending transaction
+        txn_id: u64,
+        /// The signer to remove
+        old_signer: String,
+        /// The signer to add
+        new_signer: String,
+        /// The signer approving the swap, defaults to the default wallet address
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// Cancel a pending signer swap
+    SwapCancel {
+        /// Address of the multisig wallet
+        msig: String,
+        /// ID of the pending transaction
+        txn_id: u64,
+        /// The signer to remove
+        old_signer: String,
+        /// The signer to add
+        new_signer: String,
+        /// The signer who proposed the swap, defaults to the default wallet address
+        #[arg(long)]
+        from: Option<String>,
+    },
+}
+
+impl MsigCommands {
+    pub(super) async fn run(self, backend: &WalletBackend) -> anyhow::Result<()> {
+        match self {
+            Self::Create {
+                signers,
+                required,
+                duration,
+                value,
+                from,
+            } => {
+                let from = backend.address_or_default(from.as_deref()).await?;
+                let signers = signers
+                    .iter()
+                    .map(|signer| parse_address(signer))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let prototype = MsigCreate::call(
+                    &backend.remote,
+                    (
+                        required,
+                        signers,
+                        duration,
+                        value,
+                        from,
+                        TokenAmount::zero(),
+                    ),
+                )
+                .await?;
+                let lookup = push_and_wait(backend, prototype.message).await?;
+                let ret: ExecReturn = lookup.receipt.return_data().deserialize()?;
+                println!(
+                    "Created new multisig: {} {}",
+                    Address::from(ret.id_address),
+                    Address::from(ret.robust_address)
+                );
+                Ok(())
+            }
+            Self::Propose {
+                msig,
+                to,
+                value,
+                method,
+                params,
+                from,
+            } => {
+                let from = backend.address_or_default(from.as_deref()).await?;
+                let params = hex::decode(params).context("Params have to be a hex string")?;
+                let prototype = MsigPropose::call(
+                    &backend.remote,
+                    (
+                        parse_address(&msig)?,
+                        parse_address(&to)?,
+                        value,
+                        from,
+                        method,
+                        params,
+                    ),
+                )
+                .await?;
+                print_proposal(push_and_wait(backend, prototype.message).await?)
+            }
+            Self::Approve { msig, txn_id, from } => {
+                let from = backend.address_or_default(from.as_deref()).await?;
+                let prototype =
+                    MsigApprove::call(&backend.remote, (parse_address(&msig)?, txn_id, from))
+                        .await?;
+                push_and_wait(backend, prototype.message).await?;
+                Ok(())
+            }
+            Self::Cancel { msig, txn_id, from } => {
+                let from = backend.address_or_default(from.as_deref()).await?;
+                let prototype =
+                    MsigCancel::call(&backend.remote, (parse_address(&msig)?, txn_id, from))
+                        .await?;
+                push_and_wait(backend, prototype.message).await?;
+                Ok(())
+            }
+            Self::SwapPropose {
+                msig,
+                old_signer,
+                new_signer,
+                from,
+            } => {
+                let from = backend.address_or_default(from.as_deref()).await?;
+                let prototype = MsigSwapPropose::call(
+                    &backend.remote,
+                    (
+                        parse_address(&msig)?,
+                        from,
+                        parse_address(&old_signer)?,
+                        parse_address(&new_signer)?,
+                    ),
+                )
+                .await?;
+                print_proposal(push_and_wait(backend, prototype.message).await?)
+            }
+            Self::SwapApprove {
+                msig,
+                proposer,
+                txn_id,
+                old_signer,
+                new_signer,
+                from,
+            } => {
+                let from = backend.address_or_default(from.as_deref()).await?;
+                let prototype = MsigSwapApprove::call(
+                    &backend.remote,
+                    (
+                        parse_address(&msig)?,
+                        from,
+                        txn_id,
+                        parse_address(&proposer)?,
+                        parse_address(&old_signer)?,
+                        parse_address(&new_signer)?,
+                    ),
+                )
+                .await?;
+                push_and_wait(backend, prototype.message).await?;
+                Ok(())
+            }
+            Self::SwapCancel {
+                msig,
+                txn_id,
+                old_signer,
+                new_signer,
+                from,
+            } => {
+                let from = backend.address_or_default(from.as_deref()).await?;
+                let prototype = MsigSwapCancel::call(
+                    &backend.remote,
+                    (
+                        parse_address(&msig)?,
+                        from,
+                        txn_id,
+                        parse_address(&old_signer)?,
+                        parse_address(&new_signer)?,
+                    ),
+                )
+                .await?;
+                push_and_wait(backend, prototype.message).await?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Signs and pushes the message, then waits for it to be executed successfully.
+async fn push_and_wait(backend: &WalletBackend, message: Message) -> anyhow::Result<MessageLookup> {
+    let smsg = backend.push_message(message).await?;
+    let cid: Cid = smsg.cid();
+    println!("sent message {cid}");
+    let lookup = backend
+        .remote
+        .call(
+            StateWaitMsg::request((cid, MESSAGE_CONFIDENCE, -1, true))?.with_timeout(Duration::MAX),
+        )
+        .await?;
+    ensure!(
+        lookup.receipt.exit_code().is_success(),
+        "message {cid} failed with exit code {}",
+        lookup.receipt.exit_code()
+    );
+    Ok(lookup)
+}
+
+fn print_proposal(lookup: MessageLookup) -> anyhow::Result<()> {
+    let ret: ProposeReturn = lookup.receipt.return_data().deserialize()?;
+    println!("Transaction ID: {}", ret.txn_id);
+    if ret.applied {
+        println!(
+            "Transaction was executed during propose, exit code: {}",
+            ret.code
+        );
+    }
+    Ok(())
+}
+
+fn parse_address(s: &str) -> anyhow::Result<Address> {
+    Ok(StrictAddress::from_str(s)
+        .with_context(|| format!("Invalid address: {s}"))?
+        .into())
+}
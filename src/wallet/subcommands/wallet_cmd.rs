@@ -7,6 +7,7 @@ use std::{
     str::{self, FromStr},
 };
 
+use super::msig_cmd::MsigCommands;
 use crate::cli::humantoken::TokenAmountPretty as _;
 use crate::key_management::{Key, KeyInfo};
 use crate::{
@@ -44,7 +45,7 @@ use num::Zero as _;
 // node is always required for balance queries and for sending messages. When a
 // local wallet is available, no sensitive information will be sent to the
 // remote Filecoin node.
-struct WalletBackend {
+pub(super) struct WalletBackend {
     pub remote: rpc::Client,
     pub local: Option<KeyStore>,
 }
@@ -181,6 +182,52 @@ impl WalletBackend {
         }
     }
 
+    /// Estimates gas for the message, then signs and pushes it to the message pool. Local
+    /// wallets sign the message themselves, remote ones let the node do it.
+    pub(super) async fn push_message(&self, message: Message) -> anyhow::Result<SignedMessage> {
+        if let Some(keystore) = &self.local {
+            let spec = None;
+            let mut message =
+                GasEstimateMessageGas::call(&self.remote, (message, spec, ApiTipsetKey(None)))
+                    .await?;
+
+            if message.gas_premium > message.gas_fee_cap {
+                anyhow::bail!("After estimation, gas premium is greater than gas fee cap")
+            }
+
+            message.sequence = MpoolGetNonce::call(&self.remote, (message.from,)).await?;
+
+            let key = crate::key_management::find_key(&message.from, keystore)?;
+            let sig = crate::key_management::sign(
+                *key.key_info.key_type(),
+                key.key_info.private_key(),
+                message.cid().to_bytes().as_slice(),
+            )?;
+
+            let smsg = SignedMessage::new_from_parts(message, sig)?;
+
+            MpoolPush::call(&self.remote, (smsg.clone(),)).await?;
+            Ok(smsg)
+        } else {
+            Ok(MpoolPushMessage::call(&self.remote, (message, None)).await?)
+        }
+    }
+
+    /// Returns the given address, or the default wallet address if none is given.
+    pub(super) async fn address_or_default(
+        &self,
+        address: Option<&str>,
+    ) -> anyhow::Result<Address> {
+        let address = match address {
+            Some(address) => address.to_owned(),
+            None => self
+                .wallet_default_address()
+                .await?
+                .context("No default wallet address selected. Please set a default address.")?,
+        };
+        Ok(StrictAddress::from_str(&address)?.into())
+    }
+
     async fn wallet_verify(
         &self,
         address: Address,
@@ -301,6 +348,9 @@ pub enum WalletCommands {
         #[arg(long, value_parser = humantoken::parse, default_value_t = TokenAmount::zero())]
         gas_premium: TokenAmount,
     },
+    /// Interact with multisig wallets
+    #[command(subcommand)]
+    Msig(MsigCommands),
 }
 impl WalletCommands {
     pub async fn run(
@@ -490,14 +540,7 @@ impl WalletCommands {
                 gas_limit,
                 gas_premium,
             } => {
-                let from: Address = if let Some(from) = from {
-                    StrictAddress::from_str(&from)?.into()
-                } else {
-                    StrictAddress::from_str(&backend.wallet_default_address().await?.context(
-                        "No default wallet address selected. Please set a default address.",
-                    )?)?
-                    .into()
-                };
+                let from = backend.address_or_default(from.as_deref()).await?;
 
                 let message = Message {
                     from,
@@ -510,39 +553,13 @@ impl WalletCommands {
                     ..Default::default()
                 };
 
-                let signed_msg = if let Some(keystore) = &backend.local {
-                    let spec = None;
-                    let mut message = GasEstimateMessageGas::call(
-                        &backend.remote,
-                        (message, spec, ApiTipsetKey(None)),
-                    )
-                    .await?;
-
-                    if message.gas_premium > message.gas_fee_cap {
-                        anyhow::bail!("After estimation, gas premium is greater than gas fee cap")
-                    }
-
-                    message.sequence = MpoolGetNonce::call(&backend.remote, (from,)).await?;
-
-                    let key = crate::key_management::find_key(&from, keystore)?;
-                    let sig = crate::key_management::sign(
-                        *key.key_info.key_type(),
-                        key.key_info.private_key(),
-                        message.cid().to_bytes().as_slice(),
-                    )?;
-
-                    let smsg = SignedMessage::new_from_parts(message, sig)?;
-
-                    MpoolPush::call(&backend.remote, (smsg.clone(),)).await?;
-                    smsg
-                } else {
-                    MpoolPushMessage::call(&backend.remote, (message, None)).await?
-                };
+                let signed_msg = backend.push_message(message).await?;
 
                 println!("{}", signed_msg.cid());
 
                 Ok(())
             }
+            Self::Msig(cmd) => cmd.run(&backend).await,
         }
     }
 }